@@ -1,12 +1,24 @@
+use std::collections::BTreeMap;
 use std::path::Path;
 
 // 导入模块
 mod modules;
+use modules::command_template;
 use modules::editor;
+use modules::env_profile;
+use modules::error::{AppError, AppResult, ErrorCode};
+use modules::i18n;
+use modules::install_preflight;
 use modules::knowledge;
 use modules::nvm_manager;
 use modules::platform;
+use modules::ports;
+use modules::problem_matcher;
+use modules::process_runner::{Operation, OperationKind, RunError};
 use modules::project_scanner;
+use modules::scheduler;
+use modules::storage;
+use tauri::Manager;
 use tauri_plugin_mcp::Builder as McpBuilder;
 
 #[tauri::command]
@@ -15,7 +27,7 @@ fn greet(name: &str) -> String {
 }
 
 #[tauri::command]
-fn add_workspace(path: String) -> Result<project_scanner::Workspace, String> {
+fn add_workspace(path: String) -> AppResult<project_scanner::Workspace> {
     let workspace_name = Path::new(&path)
         .file_name()
         .unwrap()
@@ -33,104 +45,256 @@ fn add_workspace(path: String) -> Result<project_scanner::Workspace, String> {
 }
 
 #[tauri::command]
-fn scan_workspace_projects(
-    workspace_path: String,
-) -> Result<Vec<project_scanner::Project>, String> {
+fn scan_workspace_projects(workspace_path: String) -> AppResult<Vec<project_scanner::Project>> {
     project_scanner::scan_workspace(&workspace_path)
 }
 
-#[tauri::command]
-fn get_nvm_status() -> Result<serde_json::Value, String> {
+#[tauri::command(async)]
+fn get_nvm_status() -> AppResult<serde_json::Value> {
     nvm_manager::get_nvm_status()
 }
 
-#[tauri::command]
-fn ensure_node_version(version: String) -> Result<String, String> {
-    nvm_manager::ensure_node_version(version)
+#[tauri::command(async)]
+fn ensure_node_version(version: String, operation_id: Option<String>) -> AppResult<String> {
+    let operation = Operation::begin(operation_id, OperationKind::NodeManager);
+    nvm_manager::ensure_node_version(version, &operation)
 }
 
-#[tauri::command]
-fn switch_to_highest_version(versions: Vec<String>) -> Result<String, String> {
-    nvm_manager::switch_to_highest_version(versions)
+#[tauri::command(async)]
+fn switch_to_highest_version(
+    versions: Vec<String>,
+    operation_id: Option<String>,
+) -> AppResult<String> {
+    let operation = Operation::begin(operation_id, OperationKind::NodeManager);
+    nvm_manager::switch_to_highest_version(versions, &operation)
 }
 
-#[tauri::command]
+// 以 command_id 作为操作 id，可通过 cancel_operation 取消
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
 fn execute_project_command(
-    _command_id: String,
+    app: tauri::AppHandle,
+    command_id: String,
     working_dir: String,
     command: String,
     node_version: Option<String>,
+    command_name: Option<String>,
+    env_profile: Option<String>,
+    arg_values: Option<BTreeMap<String, serde_json::Value>>,
+    install_dependencies: Option<bool>,
 ) -> Result<String, String> {
     let mut result_output = String::new();
+    let operation = Operation::begin(Some(command_id.clone()), OperationKind::Command);
+
+    i18n::push_line(
+        &mut result_output,
+        "exec.working_dir",
+        &[("dir", &working_dir)],
+    );
+    i18n::push_line(&mut result_output, "exec.command", &[("command", &command)]);
 
-    result_output.push_str(&format!("📁 工作目录: {}\n", working_dir));
-    result_output.push_str(&format!("🔧 执行命令: {}\n", command));
+    let env_vars = match env_profile::resolve_env_vars(
+        &working_dir,
+        command_name.as_deref(),
+        env_profile.as_deref(),
+    ) {
+        Ok(vars) => vars,
+        Err(e) => {
+            i18n::push_line(&mut result_output, "exec.env_failed", &[("error", &e)]);
+            return Err(result_output);
+        }
+    };
+    if let Err(e) = ports::ensure_port_free(&working_dir, command_name.as_deref()) {
+        i18n::push_line(&mut result_output, "exec.port_in_use", &[("error", &e)]);
+        return Err(result_output);
+    }
+
+    if !env_vars.is_empty() {
+        i18n::push_line(
+            &mut result_output,
+            "exec.env_injected",
+            &[("count", &env_vars.len())],
+        );
+    }
 
     if let Some(version) = node_version {
-        result_output.push_str(&format!("📋 使用Node版本: {}\n", version));
+        i18n::push_line(
+            &mut result_output,
+            "exec.node_version",
+            &[("version", &version)],
+        );
 
-        if let Err(e) = nvm_manager::ensure_node_version(version.clone()) {
-            result_output.push_str(&format!("❌ 切换Node版本失败: {}\n", e));
+        if let Err(e) = nvm_manager::ensure_node_version(version.clone(), &operation) {
+            i18n::push_line(
+                &mut result_output,
+                "exec.node_switch_failed",
+                &[("error", &e)],
+            );
             return Err(result_output);
         }
-        result_output.push_str("✅ Node版本切换成功\n");
+        i18n::push_line(&mut result_output, "exec.node_switched", &[]);
+    }
+
+    let extra_args = match &command_name {
+        Some(name) => {
+            let package_manager = project_scanner::detect_package_manager(Path::new(&working_dir));
+            match command_template::render_for_execution(
+                &working_dir,
+                name,
+                &package_manager,
+                arg_values.as_ref(),
+            ) {
+                Ok(args) => args,
+                Err(e) => {
+                    i18n::push_line(&mut result_output, "exec.args_invalid", &[("error", &e)]);
+                    return Err(result_output);
+                }
+            }
+        }
+        None => String::new(),
+    };
+    if !extra_args.is_empty() {
+        i18n::push_line(
+            &mut result_output,
+            "exec.extra_args",
+            &[("args", &extra_args)],
+        );
+    }
+
+    let preflight =
+        match install_preflight::preflight(&working_dir, None, install_dependencies, false) {
+            Ok(preflight) => preflight,
+            Err(e) => {
+                i18n::push_line(&mut result_output, "exec.preflight_failed", &[("error", &e)]);
+                return Err(result_output);
+            }
+        };
+    preflight.report(&mut result_output);
+    if let Some(install) = &preflight.install_command {
+        let mut install_cmd = platform::build_shell_command(install);
+        install_cmd.current_dir(&working_dir).envs(env_vars.clone());
+        match operation.run(install_cmd, OperationKind::Command) {
+            Ok(output) if output.status.success() => {
+                i18n::push_line(&mut result_output, "exec.install_succeeded", &[]);
+            }
+            Ok(output) => {
+                result_output.push_str(&String::from_utf8_lossy(&output.stderr));
+                i18n::push_line(
+                    &mut result_output,
+                    "exec.install_failed",
+                    &[("status", &output.status)],
+                );
+                return Err(result_output);
+            }
+            Err(e) => {
+                push_run_error(&mut result_output, e);
+                return Err(result_output);
+            }
+        }
     }
 
-    result_output.push_str("\n🚀 开始执行命令...\n\n");
+    result_output.push('\n');
+    i18n::push_line(&mut result_output, "exec.starting", &[]);
+    result_output.push('\n');
 
-    let mut cmd = platform::build_shell_command(&command);
-    match cmd.current_dir(&working_dir).output() {
+    let mut cmd = platform::build_shell_command(&format!("{}{}", command, extra_args));
+    cmd.current_dir(&working_dir).envs(env_vars);
+    match operation.run(cmd, OperationKind::Command) {
         Ok(output) => {
             let stdout = String::from_utf8_lossy(&output.stdout);
             let stderr = String::from_utf8_lossy(&output.stderr);
 
+            let diagnostics = problem_matcher::parse_output(
+                &format!("{}\n{}", stdout, stderr),
+                Some(&working_dir),
+                None,
+            );
+            problem_matcher::clear_diagnostics(&command_id);
+            problem_matcher::publish_diagnostics(&app, &command_id, &diagnostics);
+
             if !stdout.is_empty() {
-                result_output.push_str("📤 标准输出:\n");
+                i18n::push_line(&mut result_output, "exec.stdout", &[]);
                 result_output.push_str(&stdout);
                 result_output.push_str("\n");
             }
 
             if !stderr.is_empty() {
-                result_output.push_str("📤 错误输出:\n");
+                i18n::push_line(&mut result_output, "exec.stderr", &[]);
                 result_output.push_str(&stderr);
                 result_output.push_str("\n");
             }
 
-            result_output.push_str(&format!("\n🔚 命令执行完成 (退出码: {})\n", output.status));
+            result_output.push('\n');
+            i18n::push_line(
+                &mut result_output,
+                "exec.finished",
+                &[("status", &output.status)],
+            );
 
             if output.status.success() {
-                result_output.push_str("✅ 命令执行成功\n");
+                i18n::push_line(&mut result_output, "exec.succeeded", &[]);
                 Ok(result_output)
             } else {
-                result_output.push_str("❌ 命令执行失败\n");
+                i18n::push_line(&mut result_output, "exec.failed", &[]);
                 Err(result_output)
             }
         }
         Err(e) => {
-            result_output.push_str(&format!("❌ 执行命令失败: {}\n", e));
+            push_run_error(&mut result_output, e);
             Err(result_output)
         }
     }
 }
 
+fn push_run_error(output: &mut String, error: RunError) {
+    match error {
+        RunError::Spawn(e) => i18n::push_line(output, "exec.spawn_failed", &[("error", &e)]),
+        RunError::TimedOut(timeout) => i18n::push_line(
+            output,
+            "exec.timed_out",
+            &[("seconds", &timeout.as_secs())],
+        ),
+        RunError::Cancelled => i18n::push_line(output, "exec.cancelled", &[]),
+    }
+}
+
 #[tauri::command]
-fn get_available_editors() -> Result<Vec<editor::Editor>, String> {
+fn get_available_editors() -> AppResult<Vec<editor::Editor>> {
     editor::get_available_editors()
 }
 
+#[tauri::command(async)]
+fn open_project_in_editor(
+    editor_id: String,
+    project_path: String,
+    operation_id: Option<String>,
+) -> AppResult<String> {
+    let editors = editor::get_available_editors()?;
+    let editor = editors.iter().find(|e| e.id == editor_id).ok_or_else(|| {
+        AppError::new(ErrorCode::EditorNotFound, "editor.not_found").arg("id", &editor_id)
+    })?;
+
+    let operation = Operation::begin(operation_id, OperationKind::Editor);
+    editor::open_project_in_editor(&editor.id, &editor.command, &project_path, &operation)
+}
+
 #[tauri::command]
-fn open_project_in_editor(editor_id: String, project_path: String) -> Result<String, String> {
+fn open_file_in_editor(
+    editor_id: String,
+    file: String,
+    line: Option<u32>,
+    column: Option<u32>,
+) -> AppResult<String> {
     let editors = editor::get_available_editors()?;
-    let editor = editors
-        .iter()
-        .find(|e| e.id == editor_id)
-        .ok_or_else(|| format!("Editor not found: {}", editor_id))?;
+    let editor = editors.iter().find(|e| e.id == editor_id).ok_or_else(|| {
+        AppError::new(ErrorCode::EditorNotFound, "editor.not_found").arg("id", &editor_id)
+    })?;
 
-    editor::open_project_in_editor(&editor.id, &editor.command, &project_path)
+    editor::open_file_at_location(&editor.id, &editor.command, &file, line, column)
 }
 
 #[tauri::command]
-fn open_in_finder(path: String) -> Result<String, String> {
+fn open_in_finder(path: String) -> AppResult<String> {
     platform::open_path(&path)
 }
 
@@ -139,17 +303,46 @@ fn build_execution_command(
     command: String,
     node_version: Option<String>,
     package_manager: String,
-) -> Result<String, String> {
+    project_path: Option<String>,
+    arg_values: Option<BTreeMap<String, serde_json::Value>>,
+    install_dependencies: Option<bool>,
+) -> AppResult<String> {
+    let extra_args = match &project_path {
+        Some(path) => command_template::render_for_execution(
+            path,
+            &command,
+            &package_manager,
+            arg_values.as_ref(),
+        )
+        .map_err(|e| AppError::plain(ErrorCode::InvalidArgument, e))?,
+        None => String::new(),
+    };
+    let install_command = match &project_path {
+        Some(path) => {
+            install_preflight::preflight(path, Some(&package_manager), install_dependencies, false)?
+                .install_command
+        }
+        None => None,
+    };
+
     modules::kitty::executor::build_execution_command(
         &command,
         node_version.as_deref(),
         &package_manager,
+        Some(&extra_args),
+        install_command.as_deref(),
     )
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        .setup(|app| {
+            storage::init(app.path().app_data_dir()?);
+            scheduler::start(app.handle().clone());
+            modules::kitty::status::start(app.handle().clone());
+            Ok(())
+        })
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(McpBuilder::default().build())
@@ -163,6 +356,7 @@ pub fn run() {
             execute_project_command,
             get_available_editors,
             open_project_in_editor,
+            open_file_in_editor,
             open_in_finder,
             build_execution_command,
             modules::webview::create_child_webview,
@@ -175,6 +369,31 @@ pub fn run() {
             modules::kitty::executor::execute_command_with_kitten,
             modules::kitty::process::terminate_command,
             modules::kitty::process::get_running_processes,
+            modules::kitty::capture::capture_kitty_output,
+            modules::kitty::config::get_kitty_config,
+            modules::kitty::config::set_kitty_config,
+            modules::kitty::grouping::get_kitty_grouping_settings,
+            modules::kitty::grouping::set_kitty_grouping_settings,
+            modules::kitty::session::export_kitty_session,
+            modules::kitty::session::import_kitty_session,
+            modules::kitty::input::send_text_to_task,
+            modules::kitty::input::send_key_to_task,
+            modules::kitty::input::list_task_actions,
+            modules::kitty::input::run_task_action,
+            modules::recovery::scan_orphans,
+            modules::recovery::adopt_orphan_instances,
+            modules::recovery::kill_orphans,
+            modules::task_group::list_task_groups,
+            modules::task_group::save_task_group,
+            modules::task_group::delete_task_group,
+            modules::terminal_backend::list_terminal_backends,
+            modules::terminal_backend::get_terminal_backend_settings,
+            modules::terminal_backend::set_terminal_backend_settings,
+            modules::terminal_backend::execute_command_in_terminal,
+            modules::terminal_backend::list_terminal_tabs,
+            modules::terminal_backend::close_terminal_tab,
+            modules::terminal_backend::send_terminal_text,
+            modules::terminal_backend::capture_terminal_text,
             modules::git::list_branches,
             modules::git::switch_branch,
             modules::git::list_worktrees,
@@ -182,14 +401,81 @@ pub fn run() {
             modules::git::remove_worktree,
             modules::terminal::pty_manager::create_terminal_session,
             modules::terminal::pty_manager::write_to_terminal,
+            modules::terminal::pty_manager::run_in_terminal_session,
             modules::terminal::pty_manager::resize_terminal,
             modules::terminal::pty_manager::close_terminal_session,
-            modules::terminal::pty_manager::get_terminal_buffer,
+            modules::terminal::pty_manager::get_terminal_snapshot,
+            modules::terminal::pty_manager::get_terminal_text,
+            modules::terminal::pty_manager::search_terminal_scrollback,
             modules::terminal::pty_manager::ping_terminal_session,
             modules::knowledge::list_md_files,
             modules::knowledge::read_md_file,
-            modules::knowledge::write_md_file
+            modules::knowledge::write_md_file,
+            modules::ports::list_listening_ports,
+            modules::ports::check_port_conflict,
+            modules::ports::free_port,
+            modules::problem_matcher::list_problem_matchers,
+            modules::problem_matcher::set_problem_matchers,
+            modules::problem_matcher::parse_task_output,
+            modules::problem_matcher::get_run_diagnostics,
+            modules::problem_matcher::clear_run_diagnostics,
+            modules::env_profile::get_env_config,
+            modules::env_profile::set_env_config,
+            modules::env_profile::preview_environment,
+            modules::command_template::get_command_template,
+            modules::command_template::set_command_template,
+            modules::command_template::render_command_arguments,
+            modules::command_template::get_argument_history,
+            modules::command_template::clear_argument_history,
+            modules::task_runner::list_task_runs,
+            modules::task_runner::get_task_run,
+            modules::task_runner::search_task_output,
+            modules::task_runner::cancel_task_run,
+            modules::task_runner::queue_task_run,
+            modules::task_runner::list_task_queue,
+            modules::task_runner::move_queued_run,
+            modules::task_runner::set_queued_run_priority,
+            modules::task_runner::get_queue_settings,
+            modules::task_runner::set_queue_settings,
+            modules::hooks::list_hooks,
+            modules::hooks::save_hook,
+            modules::hooks::delete_hook,
+            modules::hooks::get_hook_results,
+            modules::hooks::list_readiness_probes,
+            modules::hooks::set_readiness_probe,
+            modules::hooks::delete_readiness_probe,
+            modules::benchmark::run_benchmark,
+            modules::benchmark::list_benchmarks,
+            modules::benchmark::delete_benchmark,
+            modules::benchmark::export_benchmarks,
+            modules::watcher::start_watch,
+            modules::watcher::stop_watch,
+            modules::watcher::list_watches,
+            modules::watcher::trigger_watch,
+            modules::scheduler::list_schedules,
+            modules::scheduler::save_schedule,
+            modules::scheduler::delete_schedule,
+            modules::scheduler::set_schedule_enabled,
+            modules::scheduler::run_schedule_now,
+            modules::scheduler::get_schedule_runs,
+            modules::scheduler::preview_cron,
+            modules::i18n::get_locale,
+            modules::i18n::set_locale,
+            modules::preview::preview_execution,
+            modules::install_preflight::check_dependencies,
+            modules::install_preflight::get_install_policies,
+            modules::install_preflight::set_install_policy,
+            modules::process_runner::cancel_operation,
+            modules::process_runner::list_operations,
+            modules::process_runner::get_timeout_settings,
+            modules::process_runner::set_timeout_settings
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            // 退出时关闭 kitty 实例与内置终端会话，避免留下孤儿进程
+            if let tauri::RunEvent::Exit = event {
+                modules::recovery::shutdown();
+            }
+        });
 }
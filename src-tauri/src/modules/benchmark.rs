@@ -0,0 +1,611 @@
+// 脚本基准测试模块
+//
+// 参考 hyperfine，把项目脚本重复执行多次并统计耗时，用于比较不同分支、Node 版本下的构建速度：
+// - 正式计时前可以先预热若干次，每次运行前可以执行 prepare 命令（如清理缓存）
+// - 每次运行记录墙钟时间、用户态/内核态 CPU 时间与峰值内存，汇总为均值、中位数、标准差、最小、最大值
+// - 结果附带分支、提交与 Node 版本，持久化在应用数据目录，可导出为 JSON / CSV / Markdown
+// - 进度通过 benchmark-progress 事件推送，传入 operation_id 时可通过 cancel_operation 取消
+use crate::modules::error::{lock_error, AppError, AppResult, ErrorCode};
+use crate::modules::kitty::core::get_current_timestamp;
+use crate::modules::platform;
+use crate::modules::process_runner::{self, Measurement, Operation, OperationKind};
+use crate::modules::storage;
+use crate::modules::task_runner::{self, ManualRunRequest};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+const BENCHMARK_STORE: &str = "benchmarks";
+const MAX_RESULTS: usize = 100;
+const MAX_RUNS: u32 = 1000;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkRequest {
+    pub project_path: String,
+    pub command_name: String,
+    #[serde(default)]
+    pub package_manager: Option<String>,
+    #[serde(default)]
+    pub node_version: Option<String>,
+    #[serde(default)]
+    pub env_profile: Option<String>,
+    #[serde(default)]
+    pub arg_values: Option<BTreeMap<String, serde_json::Value>>,
+    #[serde(default = "default_runs")]
+    pub runs: u32,
+    #[serde(default)]
+    pub warmup: u32,
+    // 每次运行（含预热）前执行的 shell 命令，不计入耗时
+    #[serde(default)]
+    pub prepare: Option<String>,
+    // 导出时显示的名称，为空时使用脚本名、分支与 Node 版本
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+fn default_runs() -> u32 {
+    10
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sample {
+    pub wall_ms: f64,
+    pub user_ms: Option<f64>,
+    pub sys_ms: Option<f64>,
+    pub max_rss_kb: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    pub mean: f64,
+    pub median: f64,
+    pub stddev: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkResult {
+    pub id: String,
+    pub label: Option<String>,
+    pub project_path: String,
+    pub command_name: String,
+    pub command: String,
+    pub branch: Option<String>,
+    pub commit: Option<String>,
+    pub node_version: Option<String>,
+    pub runs: u32,
+    pub warmup: u32,
+    pub prepare: Option<String>,
+    pub samples: Vec<Sample>,
+    // 以下统计单位为毫秒，峰值内存为 KB；平台不支持时为 None
+    pub wall: Stats,
+    pub user: Option<Stats>,
+    pub sys: Option<Stats>,
+    pub max_rss_kb: Option<Stats>,
+    pub started_at: u64,
+    pub finished_at: u64,
+}
+
+impl BenchmarkResult {
+    fn display_name(&self) -> String {
+        if let Some(label) = self.label.as_ref().filter(|l| !l.trim().is_empty()) {
+            return label.clone();
+        }
+        let context: Vec<&str> = [self.branch.as_deref(), self.node_version.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect();
+        if context.is_empty() {
+            self.command_name.clone()
+        } else {
+            format!("{} ({})", self.command_name, context.join(", "))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Markdown,
+}
+
+lazy_static! {
+    // 最新的在前
+    static ref RESULTS: Arc<Mutex<Option<Vec<BenchmarkResult>>>> = Arc::new(Mutex::new(None));
+}
+
+fn with_results<R>(f: impl FnOnce(&mut Vec<BenchmarkResult>) -> R) -> AppResult<R> {
+    let mut guard = RESULTS.lock().map_err(lock_error)?;
+    Ok(f(
+        guard.get_or_insert_with(|| storage::load(BENCHMARK_STORE))
+    ))
+}
+
+fn ms(duration: std::time::Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+// 标准差使用样本标准差（n - 1），与 hyperfine 一致
+pub fn stats(values: &[f64]) -> Option<Stats> {
+    if values.is_empty() {
+        return None;
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mid = sorted.len() / 2;
+    let median = if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    };
+    let stddev = if values.len() > 1 {
+        (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+    } else {
+        0.0
+    };
+    Some(Stats {
+        mean,
+        median,
+        stddev,
+        min: sorted[0],
+        max: sorted[sorted.len() - 1],
+    })
+}
+
+// 所有样本都有该项数据时才统计
+fn optional_stats(samples: &[Sample], f: impl Fn(&Sample) -> Option<f64>) -> Option<Stats> {
+    let values: Option<Vec<f64>> = samples.iter().map(f).collect();
+    stats(&values?)
+}
+
+// 执行并读取第一行输出，失败时返回 None
+fn probe_output(command: Command, kind: OperationKind) -> Option<String> {
+    let output = process_runner::run(command, kind).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+}
+
+fn emit_progress(
+    app: &AppHandle,
+    operation_id: Option<&str>,
+    phase: &str,
+    index: u32,
+    total: u32,
+    sample: Option<&Sample>,
+) {
+    let _ = app.emit(
+        "benchmark-progress",
+        serde_json::json!({
+            "operationId": operation_id,
+            "phase": phase,
+            "index": index,
+            "total": total,
+            "sample": sample,
+        }),
+    );
+}
+
+fn run_benchmark_inner(
+    app: &AppHandle,
+    request: BenchmarkRequest,
+    operation: &Operation,
+    operation_id: Option<&str>,
+) -> AppResult<BenchmarkResult> {
+    if request.runs == 0 || request.runs > MAX_RUNS {
+        return Err(
+            AppError::new(ErrorCode::InvalidArgument, "benchmark.invalid_runs")
+                .arg("max", MAX_RUNS),
+        );
+    }
+
+    // 依赖安装在预热前执行一次，之后每次只计时脚本本身
+    let (resolved, install_command) = task_runner::resolve_script_run_with_install(
+        &ManualRunRequest {
+            project_path: request.project_path.clone(),
+            command_name: request.command_name.clone(),
+            package_manager: request.package_manager.clone(),
+            node_version: request.node_version.clone(),
+            env_profile: request.env_profile.clone(),
+            arg_values: request.arg_values.clone(),
+            priority: 0,
+        },
+        "benchmark",
+    )?;
+    let shell = |script: &str| {
+        let mut command = platform::build_shell_command(script);
+        command
+            .current_dir(&resolved.project_path)
+            .envs(resolved.env.iter().cloned());
+        command
+    };
+    let run_error =
+        |e: process_runner::RunError| e.into_app_error(|e| AppError::io("task.spawn_failed", &e));
+    let git = |args: &[&str]| {
+        let mut command = Command::new("git");
+        command.args(args).current_dir(&request.project_path);
+        probe_output(command, OperationKind::Git)
+    };
+
+    let started_at = get_current_timestamp();
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let branch = git(&["rev-parse", "--abbrev-ref", "HEAD"]);
+    let commit = git(&["rev-parse", "--short", "HEAD"]);
+    // 未指定版本时与被测命令使用同样的 shell 与环境变量（含环境配置中的 PATH）
+    let node_version = request
+        .node_version
+        .clone()
+        .or_else(|| probe_output(shell("node --version"), OperationKind::NodeManager));
+
+    if let Some(install) = install_command {
+        let output = operation
+            .run(shell(&install), OperationKind::Command)
+            .map_err(run_error)?;
+        if !output.status.success() {
+            return Err(AppError::new(
+                ErrorCode::BenchmarkCommandFailed,
+                "benchmark.install_failed",
+            )
+            .arg("code", output.status.code().unwrap_or(-1))
+            .with_output(&output));
+        }
+    }
+
+    let total = request.warmup + request.runs;
+    let mut samples = Vec::with_capacity(request.runs as usize);
+    for index in 0..total {
+        let warmup = index < request.warmup;
+
+        if let Some(prepare) = request.prepare.as_deref().filter(|p| !p.trim().is_empty()) {
+            let output = operation
+                .run(shell(prepare), OperationKind::Command)
+                .map_err(run_error)?;
+            if !output.status.success() {
+                return Err(AppError::new(
+                    ErrorCode::BenchmarkCommandFailed,
+                    "benchmark.prepare_failed",
+                )
+                .arg("code", output.status.code().unwrap_or(-1))
+                .with_output(&output));
+            }
+        }
+
+        let Measurement {
+            status,
+            wall,
+            user,
+            sys,
+            max_rss_kb,
+        } = operation
+            .measure(shell(&resolved.command), OperationKind::Command)
+            .map_err(run_error)?;
+        if !status.success() {
+            return Err(AppError::new(
+                ErrorCode::BenchmarkCommandFailed,
+                "benchmark.command_failed",
+            )
+            .arg("run", index + 1)
+            .arg("code", status.code().unwrap_or(-1))
+            .with_exit_code(status.code()));
+        }
+
+        let sample = Sample {
+            wall_ms: ms(wall),
+            user_ms: user.map(ms),
+            sys_ms: sys.map(ms),
+            max_rss_kb,
+        };
+        if warmup {
+            emit_progress(app, operation_id, "warmup", index + 1, total, None);
+        } else {
+            emit_progress(app, operation_id, "run", index + 1, total, Some(&sample));
+            samples.push(sample);
+        }
+    }
+
+    let wall_values: Vec<f64> = samples.iter().map(|s| s.wall_ms).collect();
+    let result = BenchmarkResult {
+        id: format!("benchmark-{:x}", nanos),
+        label: request.label,
+        project_path: request.project_path,
+        command_name: request.command_name,
+        command: resolved.command,
+        branch,
+        commit,
+        node_version,
+        runs: request.runs,
+        warmup: request.warmup,
+        prepare: request.prepare,
+        wall: stats(&wall_values).unwrap_or_default(),
+        user: optional_stats(&samples, |s| s.user_ms),
+        sys: optional_stats(&samples, |s| s.sys_ms),
+        max_rss_kb: optional_stats(&samples, |s| s.max_rss_kb.map(|kb| kb as f64)),
+        samples,
+        started_at,
+        finished_at: get_current_timestamp(),
+    };
+    Ok(result)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn fmt_opt(value: Option<f64>) -> String {
+    value.map(|v| format!("{:.1}", v)).unwrap_or_default()
+}
+
+pub fn export(results: &[BenchmarkResult], format: ExportFormat) -> AppResult<String> {
+    match format {
+        ExportFormat::Json => serde_json::to_string_pretty(results).map_err(|e| {
+            AppError::new(ErrorCode::Internal, "benchmark.export_failed").arg("error", e)
+        }),
+        ExportFormat::Csv => {
+            let mut out = String::from(
+                "name,project,command,branch,commit,node,runs,mean_ms,median_ms,stddev_ms,min_ms,max_ms,user_mean_ms,sys_mean_ms,max_rss_kb\n",
+            );
+            for result in results {
+                let row = [
+                    csv_field(&result.display_name()),
+                    csv_field(&result.project_path),
+                    csv_field(&result.command_name),
+                    csv_field(result.branch.as_deref().unwrap_or_default()),
+                    csv_field(result.commit.as_deref().unwrap_or_default()),
+                    csv_field(result.node_version.as_deref().unwrap_or_default()),
+                    result.runs.to_string(),
+                    format!("{:.1}", result.wall.mean),
+                    format!("{:.1}", result.wall.median),
+                    format!("{:.1}", result.wall.stddev),
+                    format!("{:.1}", result.wall.min),
+                    format!("{:.1}", result.wall.max),
+                    fmt_opt(result.user.as_ref().map(|s| s.mean)),
+                    fmt_opt(result.sys.as_ref().map(|s| s.mean)),
+                    result
+                        .max_rss_kb
+                        .as_ref()
+                        .map(|s| format!("{:.0}", s.max))
+                        .unwrap_or_default(),
+                ];
+                out.push_str(&row.join(","));
+                out.push('\n');
+            }
+            Ok(out)
+        }
+        ExportFormat::Markdown => {
+            // 与 hyperfine --export-markdown 相同：相对值以最快的一项为基准
+            let fastest = results
+                .iter()
+                .min_by(|a, b| a.wall.mean.total_cmp(&b.wall.mean));
+            let mut out = String::from(
+                "| Command | Mean [ms] | Min [ms] | Max [ms] | Relative |\n|:---|---:|---:|---:|---:|\n",
+            );
+            for result in results {
+                let relative = match fastest {
+                    Some(fastest) if fastest.wall.mean > 0.0 && result.wall.mean > 0.0 => {
+                        let ratio = result.wall.mean / fastest.wall.mean;
+                        if std::ptr::eq(result, fastest) {
+                            "1.00".to_string()
+                        } else {
+                            let error = ratio
+                                * ((result.wall.stddev / result.wall.mean).powi(2)
+                                    + (fastest.wall.stddev / fastest.wall.mean).powi(2))
+                                .sqrt();
+                            format!("{:.2} ± {:.2}", ratio, error)
+                        }
+                    }
+                    _ => String::new(),
+                };
+                out.push_str(&format!(
+                    "| `{}` | {:.1} ± {:.1} | {:.1} | {:.1} | {} |\n",
+                    result.display_name().replace('|', "\\|"),
+                    result.wall.mean,
+                    result.wall.stddev,
+                    result.wall.min,
+                    result.wall.max,
+                    relative
+                ));
+            }
+            Ok(out)
+        }
+    }
+}
+
+// ---------- Tauri 命令 ----------
+
+#[tauri::command(async)]
+pub fn run_benchmark(
+    app: AppHandle,
+    request: BenchmarkRequest,
+    operation_id: Option<String>,
+) -> AppResult<BenchmarkResult> {
+    let operation = Operation::begin(operation_id.clone(), OperationKind::Command);
+    let result = run_benchmark_inner(&app, request, &operation, operation_id.as_deref())?;
+
+    let snapshot = with_results(|results| {
+        results.insert(0, result.clone());
+        results.truncate(MAX_RESULTS);
+        results.clone()
+    })?;
+    storage::save(BENCHMARK_STORE, &snapshot)?;
+    Ok(result)
+}
+
+// 最新的在前，可按项目与脚本过滤
+#[tauri::command]
+pub fn list_benchmarks(
+    project_path: Option<String>,
+    command_name: Option<String>,
+) -> AppResult<Vec<BenchmarkResult>> {
+    with_results(|results| {
+        results
+            .iter()
+            .filter(|r| project_path.as_ref().is_none_or(|p| &r.project_path == p))
+            .filter(|r| command_name.as_ref().is_none_or(|c| &r.command_name == c))
+            .cloned()
+            .collect()
+    })
+}
+
+#[tauri::command]
+pub fn delete_benchmark(benchmark_id: String) -> AppResult<()> {
+    let snapshot = with_results(|results| {
+        results.retain(|r| r.id != benchmark_id);
+        results.clone()
+    })?;
+    storage::save(BENCHMARK_STORE, &snapshot)?;
+    Ok(())
+}
+
+// 按传入顺序导出，返回文件内容，由前端决定保存位置
+#[tauri::command]
+pub fn export_benchmarks(benchmark_ids: Vec<String>, format: ExportFormat) -> AppResult<String> {
+    let selected = with_results(|results| {
+        benchmark_ids
+            .iter()
+            .filter_map(|id| results.iter().find(|r| &r.id == id).cloned())
+            .collect::<Vec<_>>()
+    })?;
+    export(&selected, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(
+        label: Option<&str>,
+        branch: Option<&str>,
+        mean: f64,
+        stddev: f64,
+    ) -> BenchmarkResult {
+        BenchmarkResult {
+            id: "benchmark-1".to_string(),
+            label: label.map(str::to_string),
+            project_path: "/work/app".to_string(),
+            command_name: "build".to_string(),
+            command: "pnpm run build".to_string(),
+            branch: branch.map(str::to_string),
+            commit: None,
+            node_version: Some("v20.11.0".to_string()),
+            runs: 3,
+            warmup: 0,
+            prepare: None,
+            samples: Vec::new(),
+            wall: Stats {
+                mean,
+                median: mean,
+                stddev,
+                min: mean - stddev,
+                max: mean + stddev,
+            },
+            user: None,
+            sys: None,
+            max_rss_kb: None,
+            started_at: 0,
+            finished_at: 0,
+        }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn stats_use_middle_values_and_sample_stddev() {
+        let odd = stats(&[3.0, 1.0, 2.0]).unwrap();
+        assert!(close(odd.median, 2.0));
+        assert!(close(odd.mean, 2.0));
+        assert!(close(odd.stddev, 1.0));
+        assert!(close(odd.min, 1.0) && close(odd.max, 3.0));
+
+        let even = stats(&[4.0, 1.0, 3.0, 2.0]).unwrap();
+        assert!(close(even.median, 2.5));
+        // 样本标准差：sqrt(5 / 3)
+        assert!(close(even.stddev, (5.0f64 / 3.0).sqrt()));
+
+        let single = stats(&[7.0]).unwrap();
+        assert!(close(single.median, 7.0) && close(single.stddev, 0.0));
+        assert!(stats(&[]).is_none());
+    }
+
+    #[test]
+    fn optional_stats_need_every_sample() {
+        let sample = |user_ms| Sample {
+            wall_ms: 1.0,
+            user_ms,
+            sys_ms: None,
+            max_rss_kb: None,
+        };
+        let all = [sample(Some(1.0)), sample(Some(3.0))];
+        assert!(close(
+            optional_stats(&all, |s| s.user_ms).unwrap().mean,
+            2.0
+        ));
+        let partial = [sample(Some(1.0)), sample(None)];
+        assert!(optional_stats(&partial, |s| s.user_ms).is_none());
+    }
+
+    #[test]
+    fn csv_quotes_fields_with_separators() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+
+        let csv = export(
+            &[result(Some("fast, cached"), None, 100.0, 10.0)],
+            ExportFormat::Csv,
+        )
+        .unwrap();
+        let row = csv.lines().nth(1).unwrap();
+        assert!(row.starts_with(
+            "\"fast, cached\",/work/app,build,,,v20.11.0,3,100.0,100.0,10.0,90.0,110.0,,,"
+        ));
+    }
+
+    #[test]
+    fn markdown_reports_relative_speed_with_uncertainty() {
+        let markdown = export(
+            &[
+                result(None, Some("main"), 100.0, 10.0),
+                result(Some("a|b"), None, 200.0, 20.0),
+            ],
+            ExportFormat::Markdown,
+        )
+        .unwrap();
+        let rows: Vec<&str> = markdown.lines().skip(2).collect();
+        assert_eq!(
+            rows[0],
+            "| `build (main, v20.11.0)` | 100.0 ± 10.0 | 90.0 | 110.0 | 1.00 |"
+        );
+        // 2 × sqrt(0.1² + 0.1²) ≈ 0.28
+        assert_eq!(
+            rows[1],
+            "| `a\\|b` | 200.0 ± 20.0 | 180.0 | 220.0 | 2.00 ± 0.28 |"
+        );
+    }
+}
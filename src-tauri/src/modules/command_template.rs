@@ -0,0 +1,581 @@
+// 命令模板模块
+//
+// 为项目命令声明带类型的变量（字符串、枚举、文件路径、布尔），在启动时填写：
+// - 校验变量值并逐个进行 shell 转义，拼接到解析后的命令之后
+// - npm 需要通过 `--` 把参数透传给脚本，yarn/pnpm 直接追加
+// - 按命令保存最近使用过的参数组合
+use crate::modules::i18n;
+use crate::modules::platform::shell_quote;
+use crate::modules::storage;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+const TEMPLATE_STORE: &str = "command_templates";
+const HISTORY_STORE: &str = "command_arg_history";
+const MAX_HISTORY: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum VariableKind {
+    String {
+        #[serde(default)]
+        pattern: Option<String>,
+    },
+    Enum {
+        options: Vec<String>,
+    },
+    FilePath {
+        #[serde(default)]
+        must_exist: bool,
+    },
+    Boolean,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandVariable {
+    pub name: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(flatten)]
+    pub kind: VariableKind,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub default: Option<Value>,
+    // 如 --grep；以 = 结尾时生成 --name=value；为空时作为位置参数
+    #[serde(default)]
+    pub flag: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandTemplate {
+    #[serde(default)]
+    pub variables: Vec<CommandVariable>,
+    // 是否在参数前插入 `--`，未设置时按包管理器决定
+    #[serde(default)]
+    pub separator: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArgHistoryEntry {
+    pub values: BTreeMap<String, Value>,
+    pub args: String,
+    pub used_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderedArguments {
+    // 已转义、可直接拼接的参数串（含前导空格）
+    pub args: String,
+    // 未转义的参数列表，便于界面展示
+    pub tokens: Vec<String>,
+}
+
+// key: 项目路径::命令名
+type ArgHistory = HashMap<String, Vec<ArgHistoryEntry>>;
+
+lazy_static! {
+    static ref TEMPLATES: Arc<Mutex<Option<HashMap<String, CommandTemplate>>>> =
+        Arc::new(Mutex::new(None));
+    static ref HISTORY: Arc<Mutex<Option<ArgHistory>>> = Arc::new(Mutex::new(None));
+}
+
+fn template_key(project_path: &str, command_name: &str) -> String {
+    format!("{}::{}", project_path, command_name)
+}
+
+fn with_templates<R>(
+    f: impl FnOnce(&mut HashMap<String, CommandTemplate>) -> R,
+) -> Result<R, String> {
+    let mut guard = TEMPLATES
+        .lock()
+        .map_err(|e| i18n::tf("common.lock_failed", &[("error", &e)]))?;
+    Ok(f(guard.get_or_insert_with(|| storage::load(TEMPLATE_STORE))))
+}
+
+fn with_history<R>(f: impl FnOnce(&mut ArgHistory) -> R) -> Result<R, String> {
+    let mut guard = HISTORY
+        .lock()
+        .map_err(|e| i18n::tf("common.lock_failed", &[("error", &e)]))?;
+    Ok(f(guard.get_or_insert_with(|| storage::load(HISTORY_STORE))))
+}
+
+fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        other => Some(other.to_string()),
+    }
+}
+
+// 校验单个变量并生成未转义的参数
+fn render_variable(
+    variable: &CommandVariable,
+    value: Option<&Value>,
+    project_path: &str,
+) -> Result<Vec<String>, String> {
+    let value = value.filter(|v| !v.is_null()).or(variable.default.as_ref());
+    let display_name = variable.label.as_deref().unwrap_or(&variable.name);
+
+    if let VariableKind::Boolean = variable.kind {
+        let enabled = match value {
+            Some(Value::Bool(b)) => *b,
+            Some(Value::String(s)) => s == "true",
+            Some(Value::Null) | None => false,
+            Some(_) => return Err(i18n::tf("template.not_boolean", &[("name", &display_name)])),
+        };
+        let flag = variable
+            .flag
+            .as_ref()
+            .ok_or_else(|| i18n::tf("template.bool_missing_flag", &[("name", &display_name)]))?;
+        return Ok(if enabled {
+            vec![flag.trim_end_matches('=').to_string()]
+        } else {
+            Vec::new()
+        });
+    }
+
+    let text = value.and_then(value_to_string).filter(|s| !s.is_empty());
+    let Some(text) = text else {
+        if variable.required {
+            return Err(i18n::tf("template.required", &[("name", &display_name)]));
+        }
+        return Ok(Vec::new());
+    };
+
+    match &variable.kind {
+        VariableKind::String {
+            pattern: Some(pattern),
+        } => {
+            let re = Regex::new(pattern).map_err(|e| {
+                i18n::tf(
+                    "template.invalid_pattern",
+                    &[("name", &display_name), ("error", &e)],
+                )
+            })?;
+            if !re.is_match(&text) {
+                return Err(i18n::tf(
+                    "template.pattern_mismatch",
+                    &[("name", &display_name), ("pattern", &pattern)],
+                ));
+            }
+        }
+        VariableKind::Enum { options } if !options.iter().any(|o| o == &text) => {
+            return Err(i18n::tf(
+                "template.not_in_options",
+                &[("name", &display_name), ("options", &options.join(", "))],
+            ));
+        }
+        VariableKind::FilePath { must_exist: true } => {
+            let path = Path::new(&text);
+            let resolved = if path.is_absolute() {
+                path.to_path_buf()
+            } else {
+                Path::new(project_path).join(path)
+            };
+            if !resolved.exists() {
+                return Err(i18n::tf(
+                    "template.file_not_found",
+                    &[("name", &display_name), ("path", &text)],
+                ));
+            }
+        }
+        _ => {}
+    }
+
+    Ok(match &variable.flag {
+        Some(flag) if flag.ends_with('=') => vec![format!("{}{}", flag, text)],
+        Some(flag) => vec![flag.clone(), text],
+        None => vec![text],
+    })
+}
+
+pub fn render_arguments(
+    template: &CommandTemplate,
+    values: &BTreeMap<String, Value>,
+    project_path: &str,
+    package_manager: &str,
+) -> Result<RenderedArguments, String> {
+    let mut tokens = Vec::new();
+    for variable in &template.variables {
+        tokens.extend(render_variable(
+            variable,
+            values.get(&variable.name),
+            project_path,
+        )?);
+    }
+
+    if tokens.is_empty() {
+        return Ok(RenderedArguments {
+            args: String::new(),
+            tokens,
+        });
+    }
+
+    let separator = template.separator.unwrap_or(package_manager == "npm");
+    let mut args = String::new();
+    if separator {
+        args.push_str(" --");
+    }
+    for token in &tokens {
+        args.push(' ');
+        args.push_str(&shell_quote(token));
+    }
+
+    Ok(RenderedArguments { args, tokens })
+}
+
+pub fn get_template(project_path: &str, command_name: &str) -> Option<CommandTemplate> {
+    with_templates(|templates| {
+        templates
+            .get(&template_key(project_path, command_name))
+            .cloned()
+    })
+    .ok()
+    .flatten()
+}
+
+fn record_history(
+    project_path: &str,
+    command_name: &str,
+    values: &BTreeMap<String, Value>,
+    args: &str,
+) {
+    let key = template_key(project_path, command_name);
+    let entry = ArgHistoryEntry {
+        values: values.clone(),
+        args: args.to_string(),
+        used_at: crate::modules::kitty::core::get_current_timestamp(),
+    };
+    let snapshot = with_history(|history| {
+        let list = history.entry(key).or_default();
+        list.retain(|e| e.args != entry.args);
+        list.insert(0, entry);
+        list.truncate(MAX_HISTORY);
+        history.clone()
+    });
+    if let Ok(snapshot) = snapshot {
+        if let Err(e) = storage::save(HISTORY_STORE, &snapshot) {
+            eprintln!("[TEMPLATE] 保存参数历史失败: {}", e);
+        }
+    }
+}
+
+// 执行前调用：渲染参数并写入历史，返回可直接追加到命令后的参数串
+pub fn render_for_execution(
+    project_path: &str,
+    command_name: &str,
+    package_manager: &str,
+    values: Option<&BTreeMap<String, Value>>,
+) -> Result<String, String> {
+    let Some(template) = get_template(project_path, command_name) else {
+        return Ok(String::new());
+    };
+    let empty = BTreeMap::new();
+    let values = values.unwrap_or(&empty);
+    let rendered = render_arguments(&template, values, project_path, package_manager)?;
+    if !rendered.tokens.is_empty() {
+        record_history(project_path, command_name, values, &rendered.args);
+    }
+    Ok(rendered.args)
+}
+
+// ---------- Tauri 命令 ----------
+
+#[tauri::command]
+pub fn get_command_template(
+    project_path: String,
+    command_name: String,
+) -> Result<Option<CommandTemplate>, String> {
+    Ok(get_template(&project_path, &command_name))
+}
+
+// 传入 None 表示删除模板
+#[tauri::command]
+pub fn set_command_template(
+    project_path: String,
+    command_name: String,
+    template: Option<CommandTemplate>,
+) -> Result<(), String> {
+    if let Some(template) = &template {
+        for variable in &template.variables {
+            if variable.name.trim().is_empty() {
+                return Err(i18n::t("template.name_required"));
+            }
+            if let VariableKind::String { pattern: Some(p) } = &variable.kind {
+                Regex::new(p).map_err(|e| {
+                    i18n::tf(
+                        "template.invalid_pattern",
+                        &[("name", &variable.name), ("error", &e)],
+                    )
+                })?;
+            }
+            if matches!(variable.kind, VariableKind::Boolean) && variable.flag.is_none() {
+                return Err(i18n::tf(
+                    "template.bool_missing_flag",
+                    &[("name", &variable.name)],
+                ));
+            }
+        }
+    }
+
+    let key = template_key(&project_path, &command_name);
+    let snapshot = with_templates(|templates| {
+        match template {
+            Some(t) => {
+                templates.insert(key, t);
+            }
+            None => {
+                templates.remove(&key);
+            }
+        }
+        templates.clone()
+    })?;
+    storage::save(TEMPLATE_STORE, &snapshot)
+}
+
+// 预览渲染结果（不写入历史）
+#[tauri::command]
+pub fn render_command_arguments(
+    project_path: String,
+    command_name: String,
+    package_manager: String,
+    values: BTreeMap<String, Value>,
+) -> Result<RenderedArguments, String> {
+    let template = get_template(&project_path, &command_name).unwrap_or_default();
+    render_arguments(&template, &values, &project_path, &package_manager)
+}
+
+#[tauri::command]
+pub fn get_argument_history(
+    project_path: String,
+    command_name: String,
+) -> Result<Vec<ArgHistoryEntry>, String> {
+    with_history(|history| {
+        history
+            .get(&template_key(&project_path, &command_name))
+            .cloned()
+            .unwrap_or_default()
+    })
+}
+
+#[tauri::command]
+pub fn clear_argument_history(project_path: String, command_name: String) -> Result<(), String> {
+    let snapshot = with_history(|history| {
+        history.remove(&template_key(&project_path, &command_name));
+        history.clone()
+    })?;
+    storage::save(HISTORY_STORE, &snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn variable(name: &str, kind: VariableKind) -> CommandVariable {
+        CommandVariable {
+            name: name.to_string(),
+            label: None,
+            kind,
+            required: false,
+            default: None,
+            flag: None,
+        }
+    }
+
+    fn render(
+        variables: Vec<CommandVariable>,
+        values: &[(&str, Value)],
+        project_path: &str,
+    ) -> Result<RenderedArguments, String> {
+        let template = CommandTemplate {
+            variables,
+            separator: None,
+        };
+        let values = values
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect();
+        render_arguments(&template, &values, project_path, "pnpm")
+    }
+
+    fn text() -> VariableKind {
+        VariableKind::String { pattern: None }
+    }
+
+    #[test]
+    fn required_values_fall_back_to_defaults() {
+        let mut grep = variable("grep", text());
+        grep.required = true;
+        grep.label = Some("Grep".to_string());
+        grep.flag = Some("--grep".to_string());
+        assert_eq!(
+            render(vec![grep.clone()], &[], "").unwrap_err(),
+            i18n::tf("template.required", &[("name", &"Grep")])
+        );
+        // 空字符串与 null 视为未填写
+        assert!(render(vec![grep.clone()], &[("grep", json!(""))], "").is_err());
+
+        grep.default = Some(json!("smoke"));
+        let rendered = render(vec![grep.clone()], &[("grep", Value::Null)], "").unwrap();
+        assert_eq!(rendered.tokens, vec!["--grep", "smoke"]);
+        let rendered = render(vec![grep], &[("grep", json!(3))], "").unwrap();
+        assert_eq!(rendered.tokens, vec!["--grep", "3"]);
+
+        let optional = variable("filter", text());
+        let rendered = render(vec![optional], &[], "").unwrap();
+        assert!(rendered.tokens.is_empty());
+        assert_eq!(rendered.args, "");
+    }
+
+    #[test]
+    fn enum_values_must_be_one_of_the_options() {
+        let mut mode = variable(
+            "mode",
+            VariableKind::Enum {
+                options: vec!["dev".to_string(), "prod".to_string()],
+            },
+        );
+        mode.flag = Some("--mode=".to_string());
+        let rendered = render(vec![mode.clone()], &[("mode", json!("prod"))], "").unwrap();
+        assert_eq!(rendered.tokens, vec!["--mode=prod"]);
+        assert_eq!(
+            render(vec![mode], &[("mode", json!("staging"))], "").unwrap_err(),
+            i18n::tf(
+                "template.not_in_options",
+                &[("name", &"mode"), ("options", &"dev, prod")]
+            )
+        );
+    }
+
+    #[test]
+    fn string_patterns_are_validated() {
+        let tag = variable(
+            "tag",
+            VariableKind::String {
+                pattern: Some("^v[0-9]+$".to_string()),
+            },
+        );
+        let rendered = render(vec![tag.clone()], &[("tag", json!("v12"))], "").unwrap();
+        assert_eq!(rendered.tokens, vec!["v12"]);
+        assert_eq!(
+            render(vec![tag], &[("tag", json!("latest"))], "").unwrap_err(),
+            i18n::tf(
+                "template.pattern_mismatch",
+                &[("name", &"tag"), ("pattern", &"^v[0-9]+$")]
+            )
+        );
+
+        let broken = variable(
+            "broken",
+            VariableKind::String {
+                pattern: Some("(".to_string()),
+            },
+        );
+        assert!(render(vec![broken], &[("broken", json!("x"))], "").is_err());
+    }
+
+    #[test]
+    fn boolean_flags_are_emitted_only_when_enabled() {
+        let mut watch = variable("watch", VariableKind::Boolean);
+        assert_eq!(
+            render(vec![watch.clone()], &[("watch", json!(true))], "").unwrap_err(),
+            i18n::tf("template.bool_missing_flag", &[("name", &"watch")])
+        );
+
+        watch.flag = Some("--watch=".to_string());
+        let enabled = render(vec![watch.clone()], &[("watch", json!("true"))], "").unwrap();
+        assert_eq!(enabled.tokens, vec!["--watch"]);
+        let disabled = render(vec![watch.clone()], &[("watch", json!(false))], "").unwrap();
+        assert!(disabled.tokens.is_empty());
+        assert!(render(vec![watch.clone()], &[], "")
+            .unwrap()
+            .tokens
+            .is_empty());
+        assert_eq!(
+            render(vec![watch], &[("watch", json!(1))], "").unwrap_err(),
+            i18n::tf("template.not_boolean", &[("name", &"watch")])
+        );
+    }
+
+    #[test]
+    fn file_paths_are_checked_against_the_project() {
+        let project = std::env::temp_dir().join(format!(
+            "runproject-command-template-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::write(project.join("vite.config.ts"), "export default {}\n").unwrap();
+        let project_path = project.to_string_lossy().to_string();
+
+        let config = variable("config", VariableKind::FilePath { must_exist: true });
+        let rendered = render(
+            vec![config.clone()],
+            &[("config", json!("vite.config.ts"))],
+            &project_path,
+        )
+        .unwrap();
+        assert_eq!(rendered.tokens, vec!["vite.config.ts"]);
+        let absolute = project.join("vite.config.ts").to_string_lossy().to_string();
+        assert!(render(vec![config.clone()], &[("config", json!(absolute))], "").is_ok());
+        assert_eq!(
+            render(
+                vec![config],
+                &[("config", json!("missing.ts"))],
+                &project_path
+            )
+            .unwrap_err(),
+            i18n::tf(
+                "template.file_not_found",
+                &[("name", &"config"), ("path", &"missing.ts")]
+            )
+        );
+
+        // 不要求存在时不检查
+        let output = variable("output", VariableKind::FilePath { must_exist: false });
+        assert!(render(
+            vec![output],
+            &[("output", json!("missing.ts"))],
+            &project_path
+        )
+        .is_ok());
+
+        let _ = std::fs::remove_dir_all(&project);
+    }
+
+    #[test]
+    fn npm_arguments_are_passed_after_a_separator() {
+        let template = CommandTemplate {
+            variables: vec![variable("name", text())],
+            separator: None,
+        };
+        let values = BTreeMap::from([("name".to_string(), json!("a"))]);
+        let npm = render_arguments(&template, &values, "", "npm").unwrap();
+        assert!(npm.args.starts_with(" -- "));
+        let pnpm = render_arguments(&template, &values, "", "pnpm").unwrap();
+        assert!(!pnpm.args.contains("--"));
+
+        let forced = CommandTemplate {
+            separator: Some(true),
+            ..template
+        };
+        let pnpm = render_arguments(&forced, &values, "", "pnpm").unwrap();
+        assert!(pnpm.args.starts_with(" -- "));
+    }
+}
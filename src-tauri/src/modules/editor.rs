@@ -1,8 +1,11 @@
+use crate::modules::error::{AppError, AppResult, ErrorCode};
+use crate::modules::i18n;
+use crate::modules::process_runner::{Operation, OperationKind, RunError};
 use serde::{Deserialize, Serialize};
 use std::env;
 use std::fs;
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, Output};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Editor {
@@ -17,7 +20,7 @@ pub struct EditorList {
     pub editors: Vec<Editor>,
 }
 
-pub fn get_available_editors() -> Result<Vec<Editor>, String> {
+pub fn get_available_editors() -> AppResult<Vec<Editor>> {
     let mut editors = vec![
         Editor {
             id: "trae".to_string(),
@@ -189,24 +192,52 @@ fn is_editor_installed(editor_id: &str, command: &str) -> bool {
     false
 }
 
+fn launch_error(key: &'static str, err: std::io::Error) -> AppError {
+    let code = if err.kind() == std::io::ErrorKind::NotFound {
+        ErrorCode::EditorNotFound
+    } else {
+        ErrorCode::EditorLaunchFailed
+    };
+    AppError::new(code, key).arg("error", err)
+}
+
+fn open_failed(output: &Output, project_path: &str) -> AppError {
+    AppError::new(ErrorCode::EditorLaunchFailed, "editor.open_project_failed")
+        .with_output(output)
+        .with_path(project_path)
+}
+
+// 等待编辑器 CLI 返回；超时说明 CLI 仍在前台运行编辑器本身，视为已打开
+// None 表示超时，此时没有输出可供判断
+fn run_editor(
+    operation: &Operation,
+    command: Command,
+    key: &'static str,
+) -> AppResult<Option<Output>> {
+    match operation.run(command, OperationKind::Editor) {
+        Ok(output) => Ok(Some(output)),
+        Err(RunError::TimedOut(_)) => Ok(None),
+        Err(err) => Err(err.into_app_error(|e| launch_error(key, e))),
+    }
+}
+
 pub fn open_project_in_editor(
     editor_id: &str,
     editor_command: &str,
     project_path: &str,
-) -> Result<String, String> {
+    operation: &Operation,
+) -> AppResult<String> {
     let paths = augmented_paths();
     if let Some(cmd_path) = find_in_paths(editor_command, &paths) {
-        let output = Command::new(cmd_path)
-            .arg(project_path)
-            .output()
-            .map_err(|e| format!("Failed to execute editor: {}", e))?;
-
-        if output.status.success() {
-            return Ok(format!("Opened project in {}", editor_command));
-        }
-
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Failed to open project: {}", stderr));
+        let mut command = Command::new(cmd_path);
+        command.arg(project_path);
+        return match run_editor(operation, command, "editor.execute_failed")? {
+            Some(output) if !output.status.success() => Err(open_failed(&output, project_path)),
+            _ => Ok(i18n::tf(
+                "editor.opened_project",
+                &[("editor", &editor_command)],
+            )),
+        };
     }
 
     #[cfg(target_os = "macos")]
@@ -216,29 +247,72 @@ pub fn open_project_in_editor(
                 .map(|p| p.to_string_lossy().to_string())
                 .unwrap_or_else(|| app_name.to_string());
 
-            let output = Command::new("open")
-                .arg("-a")
-                .arg(app_arg)
-                .arg(project_path)
-                .output()
-                .map_err(|e| format!("Failed to open app: {}", e))?;
+            let mut command = Command::new("open");
+            command.arg("-a").arg(app_arg).arg(project_path);
+            let output = run_editor(operation, command, "editor.open_app_failed")?;
 
-            if output.status.success() {
-                return Ok(format!("Opened project in {}", app_name));
+            if output.is_none_or(|output| output.status.success()) {
+                return Ok(i18n::tf("editor.opened_project", &[("editor", &app_name)]));
             }
         }
     }
 
     // 最后兜底：尝试直接执行（可能在运行时 PATH 已经被用户环境补齐）
-    let output = Command::new(editor_command)
-        .arg(project_path)
-        .output()
-        .map_err(|e| format!("Failed to execute editor: {}", e))?;
+    let mut command = Command::new(editor_command);
+    command.arg(project_path);
+    match run_editor(operation, command, "editor.execute_failed")? {
+        Some(output) if !output.status.success() => Err(open_failed(&output, project_path)),
+        _ => Ok(i18n::tf(
+            "editor.opened_project",
+            &[("editor", &editor_command)],
+        )),
+    }
+}
 
-    if output.status.success() {
-        Ok(format!("Opened project in {}", editor_command))
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(format!("Failed to open project: {}", stderr))
+// 根据编辑器生成“打开文件并定位到行列”的参数
+fn location_args(editor_id: &str, file: &str, line: Option<u32>, column: Option<u32>) -> Vec<String> {
+    let Some(line) = line else {
+        return vec![file.to_string()];
+    };
+    let column = column.unwrap_or(1);
+
+    match editor_id {
+        "vscode" | "cursor" | "trae" => vec![
+            "-g".to_string(),
+            format!("{}:{}:{}", file, line, column),
+        ],
+        "webstorm" | "intellij" => vec![
+            "--line".to_string(),
+            line.to_string(),
+            "--column".to_string(),
+            column.to_string(),
+            file.to_string(),
+        ],
+        _ => vec![format!("{}:{}:{}", file, line, column)],
     }
 }
+
+pub fn open_file_at_location(
+    editor_id: &str,
+    editor_command: &str,
+    file: &str,
+    line: Option<u32>,
+    column: Option<u32>,
+) -> AppResult<String> {
+    let args = location_args(editor_id, file, line, column);
+    let paths = augmented_paths();
+    let program = find_in_paths(editor_command, &paths)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| editor_command.to_string());
+
+    // 编辑器 CLI 通常会转交给已运行的实例，这里不等待其退出
+    Command::new(&program)
+        .args(&args)
+        .spawn()
+        .map_err(|e| launch_error("editor.execute_failed", e))?;
+
+    Ok(i18n::tf(
+        "editor.opened_file",
+        &[("file", &file), ("editor", &editor_command)],
+    ))
+}
@@ -0,0 +1,467 @@
+// 环境变量配置模块
+//
+// 为项目/命令提供环境变量配置：
+// - 按顺序加载 .env、.env.local、.env.<mode>、.env.<mode>.local，后加载的覆盖先加载的
+// - 支持 dotenv-expand 风格的 $VAR / ${VAR} / ${VAR:-default} 展开
+// - 支持多套 profile（如 local / staging），以及按命令覆盖的变量
+// - 合并后的环境注入到所有执行后端（原生命令、kitty、PTY）
+use crate::modules::i18n;
+use crate::modules::storage;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+const STORE_NAME: &str = "env_profiles";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvProfile {
+    pub name: String,
+    // 决定加载哪个 .env.<mode> 文件
+    #[serde(default)]
+    pub mode: Option<String>,
+    #[serde(default)]
+    pub vars: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectEnvConfig {
+    #[serde(default)]
+    pub active_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: Vec<EnvProfile>,
+    // 命令名 -> 该命令固定使用的 profile
+    #[serde(default)]
+    pub command_profiles: HashMap<String, String>,
+    // 命令名 -> 该命令额外的变量
+    #[serde(default)]
+    pub command_vars: HashMap<String, BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvEntry {
+    pub key: String,
+    pub value: String,
+    // 变量来源：.env 文件名、profile:<name> 或 command:<name>
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvPreview {
+    pub profile: Option<String>,
+    pub mode: Option<String>,
+    pub files: Vec<String>,
+    pub entries: Vec<EnvEntry>,
+}
+
+lazy_static! {
+    static ref ENV_CONFIGS: Arc<Mutex<Option<HashMap<String, ProjectEnvConfig>>>> =
+        Arc::new(Mutex::new(None));
+}
+
+// ---------- dotenv 解析 ----------
+
+fn is_valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-')
+}
+
+// 展开 $VAR、${VAR}、${VAR:-default}、${VAR-default}，\$ 表示字面量
+fn expand(value: &str, lookup: &dyn Fn(&str) -> Option<String>) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    let mut result = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\\' && chars.get(i + 1) == Some(&'$') {
+            result.push('$');
+            i += 2;
+            continue;
+        }
+        if c != '$' {
+            result.push(c);
+            i += 1;
+            continue;
+        }
+
+        if chars.get(i + 1) == Some(&'{') {
+            // 按嵌套深度匹配闭合括号，支持 ${A:-${B}} 形式的默认值
+            let mut depth = 0usize;
+            let close = chars[i + 2..].iter().position(|c| match c {
+                '{' => {
+                    depth += 1;
+                    false
+                }
+                '}' if depth > 0 => {
+                    depth -= 1;
+                    false
+                }
+                '}' => true,
+                _ => false,
+            });
+            let Some(close) = close else {
+                result.push(c);
+                i += 1;
+                continue;
+            };
+            let inner: String = chars[i + 2..i + 2 + close].iter().collect();
+            let (name, default, empty_counts_as_unset) =
+                if let Some((n, d)) = inner.split_once(":-") {
+                    (n.to_string(), Some(d.to_string()), true)
+                } else if let Some((n, d)) = inner.split_once('-') {
+                    (n.to_string(), Some(d.to_string()), false)
+                } else {
+                    (inner.clone(), None, false)
+                };
+            let resolved = match lookup(&name) {
+                Some(v) if !(empty_counts_as_unset && v.is_empty()) => v,
+                _ => default.map(|d| expand(&d, lookup)).unwrap_or_default(),
+            };
+            result.push_str(&resolved);
+            i += close + 3;
+            continue;
+        }
+
+        let name: String = chars[i + 1..]
+            .iter()
+            .take_while(|c| c.is_ascii_alphanumeric() || **c == '_')
+            .collect();
+        if name.is_empty() {
+            result.push(c);
+            i += 1;
+        } else {
+            result.push_str(&lookup(&name).unwrap_or_default());
+            i += 1 + name.chars().count();
+        }
+    }
+    result
+}
+
+// 解析 dotenv 内容，expand 时先查已解析变量，再查 base（之前文件与进程环境）
+pub fn parse_dotenv(content: &str, base: &dyn Fn(&str) -> Option<String>) -> Vec<(String, String)> {
+    let mut vars: Vec<(String, String)> = Vec::new();
+    let lines: Vec<&str> = content.lines().collect();
+    let mut index = 0;
+
+    while index < lines.len() {
+        let line = lines[index].trim();
+        index += 1;
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, raw)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        if !is_valid_key(key) {
+            continue;
+        }
+        let raw = raw.trim_start();
+
+        let lookup = |name: &str| {
+            vars.iter()
+                .rev()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.clone())
+                .or_else(|| base(name))
+        };
+
+        let value = match raw.chars().next() {
+            Some(quote @ ('"' | '\'' | '`')) => {
+                // 引号值可以跨行，直到遇到未转义的闭合引号
+                let mut body = raw[1..].to_string();
+                let mut closed = find_closing_quote(&body, quote);
+                while closed.is_none() && index < lines.len() {
+                    body.push('\n');
+                    body.push_str(lines[index]);
+                    index += 1;
+                    closed = find_closing_quote(&body, quote);
+                }
+                let body = match closed {
+                    Some(pos) => body[..pos].to_string(),
+                    None => body,
+                };
+                if quote == '"' {
+                    let unescaped = body
+                        .replace("\\n", "\n")
+                        .replace("\\r", "\r")
+                        .replace("\\t", "\t")
+                        .replace("\\\"", "\"");
+                    expand(&unescaped, &lookup)
+                } else {
+                    body
+                }
+            }
+            _ => {
+                let without_comment = match raw.find(" #") {
+                    Some(pos) => &raw[..pos],
+                    None => raw,
+                };
+                expand(without_comment.trim(), &lookup)
+            }
+        };
+
+        vars.push((key.to_string(), value));
+    }
+
+    vars
+}
+
+fn find_closing_quote(body: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (pos, c) in body.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        if c == '\\' && quote == '"' {
+            escaped = true;
+            continue;
+        }
+        if c == quote {
+            return Some(pos);
+        }
+    }
+    None
+}
+
+// ---------- 配置读写 ----------
+
+fn with_configs<R>(
+    f: impl FnOnce(&mut HashMap<String, ProjectEnvConfig>) -> R,
+) -> Result<R, String> {
+    let mut guard = ENV_CONFIGS
+        .lock()
+        .map_err(|e| i18n::tf("common.lock_failed", &[("error", &e)]))?;
+    let configs = guard.get_or_insert_with(|| storage::load(STORE_NAME));
+    Ok(f(configs))
+}
+
+pub fn get_project_config(project_path: &str) -> ProjectEnvConfig {
+    with_configs(|configs| configs.get(project_path).cloned())
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+// ---------- 合并 ----------
+
+fn env_files(mode: Option<&str>) -> Vec<String> {
+    let mut files = vec![".env".to_string(), ".env.local".to_string()];
+    if let Some(mode) = mode.filter(|m| !m.is_empty()) {
+        files.push(format!(".env.{}", mode));
+        files.push(format!(".env.{}.local", mode));
+    }
+    files
+}
+
+// 解析项目（及命令）的有效环境，返回按来源标注的变量
+pub fn resolve_env(
+    project_path: &str,
+    command_name: Option<&str>,
+    profile: Option<&str>,
+) -> Result<EnvPreview, String> {
+    let config = get_project_config(project_path);
+
+    let profile_name = profile
+        .map(|p| p.to_string())
+        .or_else(|| command_name.and_then(|c| config.command_profiles.get(c).cloned()))
+        .or_else(|| config.active_profile.clone());
+    let profile = match &profile_name {
+        Some(name) => Some(
+            config
+                .profiles
+                .iter()
+                .find(|p| &p.name == name)
+                .cloned()
+                .ok_or_else(|| i18n::tf("env.profile_not_found", &[("name", &name)]))?,
+        ),
+        None => None,
+    };
+    let mode = profile.as_ref().and_then(|p| p.mode.clone());
+
+    let mut merged: BTreeMap<String, EnvEntry> = BTreeMap::new();
+    let mut loaded_files = Vec::new();
+
+    for file in env_files(mode.as_deref()) {
+        let Ok(content) = fs::read_to_string(Path::new(project_path).join(&file)) else {
+            continue;
+        };
+        let snapshot = merged.clone();
+        let lookup = move |name: &str| {
+            snapshot
+                .get(name)
+                .map(|e| e.value.clone())
+                .or_else(|| std::env::var(name).ok())
+        };
+        for (key, value) in parse_dotenv(&content, &lookup) {
+            merged.insert(
+                key.clone(),
+                EnvEntry {
+                    key,
+                    value,
+                    source: file.clone(),
+                },
+            );
+        }
+        loaded_files.push(file);
+    }
+
+    let mut overlay = |vars: &BTreeMap<String, String>, source: String| {
+        for (key, raw) in vars {
+            let snapshot = &merged;
+            let value = expand(raw, &|name: &str| {
+                snapshot
+                    .get(name)
+                    .map(|e| e.value.clone())
+                    .or_else(|| std::env::var(name).ok())
+            });
+            merged.insert(
+                key.clone(),
+                EnvEntry {
+                    key: key.clone(),
+                    value,
+                    source: source.clone(),
+                },
+            );
+        }
+    };
+
+    if let Some(profile) = &profile {
+        overlay(&profile.vars, format!("profile:{}", profile.name));
+    }
+    if let Some(vars) = command_name.and_then(|c| config.command_vars.get(c)) {
+        overlay(
+            vars,
+            format!("command:{}", command_name.unwrap_or_default()),
+        );
+    }
+
+    Ok(EnvPreview {
+        profile: profile_name,
+        mode,
+        files: loaded_files,
+        entries: merged.into_values().collect(),
+    })
+}
+
+// 供各执行后端使用的键值列表
+pub fn resolve_env_vars(
+    project_path: &str,
+    command_name: Option<&str>,
+    profile: Option<&str>,
+) -> Result<Vec<(String, String)>, String> {
+    Ok(resolve_env(project_path, command_name, profile)?
+        .entries
+        .into_iter()
+        .map(|e| (e.key, e.value))
+        .collect())
+}
+
+// ---------- Tauri 命令 ----------
+
+#[tauri::command]
+pub fn get_env_config(project_path: String) -> Result<ProjectEnvConfig, String> {
+    Ok(get_project_config(&project_path))
+}
+
+#[tauri::command]
+pub fn set_env_config(project_path: String, config: ProjectEnvConfig) -> Result<(), String> {
+    if let Some(active) = &config.active_profile {
+        if !config.profiles.iter().any(|p| &p.name == active) {
+            return Err(i18n::tf("env.profile_not_found", &[("name", &active)]));
+        }
+    }
+
+    let snapshot = with_configs(|configs| {
+        configs.insert(project_path, config);
+        configs.clone()
+    })?;
+    storage::save(STORE_NAME, &snapshot)
+}
+
+// 预览某个项目/命令最终生效的环境变量
+#[tauri::command]
+pub fn preview_environment(
+    project_path: String,
+    command_name: Option<String>,
+    profile: Option<String>,
+) -> Result<EnvPreview, String> {
+    resolve_env(&project_path, command_name.as_deref(), profile.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_base(_: &str) -> Option<String> {
+        None
+    }
+
+    fn value(vars: &[(String, String)], key: &str) -> Option<String> {
+        vars.iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.clone())
+    }
+
+    #[test]
+    fn parses_plain_exported_and_commented_lines() {
+        let vars = parse_dotenv(
+            "# comment\n\nexport A=1\nB = two words # trailing\n1BAD=x\nC=#not-comment\n",
+            &no_base,
+        );
+        assert_eq!(value(&vars, "A").as_deref(), Some("1"));
+        assert_eq!(value(&vars, "B").as_deref(), Some("two words"));
+        assert_eq!(value(&vars, "C").as_deref(), Some("#not-comment"));
+        assert_eq!(value(&vars, "1BAD"), None);
+    }
+
+    #[test]
+    fn quoted_values_follow_quote_rules() {
+        let vars = parse_dotenv(
+            "A=\"line\\nnext \\\"q\\\"\"\nB='$HOME raw\\n'\nC=\"first\nsecond\"\nD=after\n",
+            &no_base,
+        );
+        assert_eq!(value(&vars, "A").as_deref(), Some("line\nnext \"q\""));
+        assert_eq!(value(&vars, "B").as_deref(), Some("$HOME raw\\n"));
+        assert_eq!(value(&vars, "C").as_deref(), Some("first\nsecond"));
+        assert_eq!(value(&vars, "D").as_deref(), Some("after"));
+    }
+
+    #[test]
+    fn expands_from_earlier_lines_then_base() {
+        let base = |name: &str| (name == "HOME").then(|| "/home/u".to_string());
+        let vars = parse_dotenv(
+            "ROOT=$HOME/app\nBIN=${ROOT}/bin\nLIT=\\$ROOT\nMISSING=[$NOPE]\n",
+            &base,
+        );
+        assert_eq!(value(&vars, "ROOT").as_deref(), Some("/home/u/app"));
+        assert_eq!(value(&vars, "BIN").as_deref(), Some("/home/u/app/bin"));
+        assert_eq!(value(&vars, "LIT").as_deref(), Some("$ROOT"));
+        assert_eq!(value(&vars, "MISSING").as_deref(), Some("[]"));
+    }
+
+    #[test]
+    fn defaults_distinguish_empty_from_unset() {
+        let lookup = |name: &str| match name {
+            "EMPTY" => Some(String::new()),
+            "SET" => Some("v".to_string()),
+            _ => None,
+        };
+        assert_eq!(expand("${EMPTY:-d}", &lookup), "d");
+        assert_eq!(expand("${EMPTY-d}", &lookup), "");
+        assert_eq!(expand("${UNSET-d}", &lookup), "d");
+        assert_eq!(expand("${SET:-d}", &lookup), "v");
+        assert_eq!(expand("${UNSET:-${SET}x}", &lookup), "vx");
+        assert_eq!(expand("cost $ and ${open", &lookup), "cost $ and ${open");
+    }
+}
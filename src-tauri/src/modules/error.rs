@@ -0,0 +1,202 @@
+// 统一错误模型
+//
+// 所有 Tauri 命令返回的错误都序列化为：
+// { code: "git_not_installed", key: "git.not_installed", message: "...", details: { stderr, exitCode, path } }
+// - code 为稳定的机器可读标识，前端据此区分错误类型
+// - key 为文案目录中的 key，message 为按当前语言生成的描述
+// - details 中的字段按需出现
+use crate::modules::i18n;
+use serde::Serialize;
+use std::fmt;
+use std::process::Output;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    // 通用
+    Internal,
+    InvalidArgument,
+    NotFound,
+    NotADirectory,
+    Io,
+    // 外部进程超时或被取消
+    Timeout,
+    Cancelled,
+    // git
+    GitNotInstalled,
+    NotGitRepository,
+    GitBranchNotFound,
+    GitUncommittedChanges,
+    GitConflict,
+    GitWorktreeExists,
+    GitBranchInUse,
+    GitCommandFailed,
+    // Node 版本管理
+    NodeManagerNotFound,
+    NodeVersionNotFound,
+    NodeManagerFailed,
+    // 依赖安装
+    DependenciesOutdated,
+    // kitty
+    KittyNotInstalled,
+    KittyConnectionFailed,
+    KittyLaunchFailed,
+    KittyCommandFailed,
+    KittyProcessNotFound,
+    // 外部终端后端
+    TerminalBackendUnavailable,
+    TerminalBackendFailed,
+    TerminalOperationUnsupported,
+    // 内置终端
+    TerminalSessionNotFound,
+    TerminalSpawnFailed,
+    TerminalIoFailed,
+    // 知识库
+    NotMarkdownFile,
+    // 编辑器
+    EditorNotFound,
+    EditorLaunchFailed,
+    // 项目扫描
+    WorkspaceNotFound,
+    // 生命周期 Hook 执行失败
+    HookFailed,
+    // 基准测试中 prepare 或被测命令退出码非 0
+    BenchmarkCommandFailed,
+    // 启动前检查到脚本使用的端口已被占用
+    PortInUse,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorDetails {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stderr: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+impl ErrorDetails {
+    fn is_empty(&self) -> bool {
+        self.stderr.is_none() && self.exit_code.is_none() && self.path.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppError {
+    pub code: ErrorCode,
+    // 未经文案目录的错误（如其他模块传入的 String）没有 key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<&'static str>,
+    pub message: String,
+    #[serde(skip_serializing_if = "ErrorDetails::is_empty")]
+    pub details: ErrorDetails,
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    // key 为文案目录中的 key，占位符通过 arg 填充
+    pub fn new(code: ErrorCode, key: &'static str) -> Self {
+        Self {
+            code,
+            key: Some(key),
+            message: i18n::t(key),
+            details: ErrorDetails::default(),
+        }
+    }
+
+    // 直接使用已经生成好的文案
+    pub fn plain(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            key: None,
+            message: message.into(),
+            details: ErrorDetails::default(),
+        }
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::plain(ErrorCode::Internal, message)
+    }
+
+    // 文件读写失败，找不到文件时归为 not_found；key 对应的文案需包含 {error}
+    pub fn io(key: &'static str, error: &std::io::Error) -> Self {
+        let code = if error.kind() == std::io::ErrorKind::NotFound {
+            ErrorCode::NotFound
+        } else {
+            ErrorCode::Io
+        };
+        Self::new(code, key).arg("error", error)
+    }
+
+    // 填充文案中的 {name} 占位符，plain 错误没有占位符，不受影响
+    pub fn arg(mut self, name: &str, value: impl fmt::Display) -> Self {
+        if self.key.is_some() {
+            self.message = self
+                .message
+                .replace(&format!("{{{}}}", name), &value.to_string());
+        }
+        self
+    }
+
+    pub fn with_stderr(mut self, stderr: impl Into<String>) -> Self {
+        let stderr = stderr.into();
+        if !stderr.trim().is_empty() {
+            self.details.stderr = Some(stderr.trim_end().to_string());
+        }
+        self
+    }
+
+    pub fn with_exit_code(mut self, exit_code: Option<i32>) -> Self {
+        self.details.exit_code = exit_code;
+        self
+    }
+
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.details.path = Some(path.into());
+        self
+    }
+
+    // 从子进程输出中提取 stderr 与退出码
+    pub fn with_output(self, output: &Output) -> Self {
+        self.with_stderr(String::from_utf8_lossy(&output.stderr))
+            .with_exit_code(output.status.code())
+    }
+
+    pub fn stderr(&self) -> Option<&str> {
+        self.details.stderr.as_deref()
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.details.stderr {
+            Some(stderr) => write!(f, "{}: {}", self.message, stderr),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+// 仍返回 String 的模块可以直接用 ? 调用返回 AppError 的函数
+impl From<AppError> for String {
+    fn from(error: AppError) -> Self {
+        error.to_string()
+    }
+}
+
+// 尚未细分错误类型的 String 错误统一归为 internal
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::internal(message)
+    }
+}
+
+// 锁中毒等内部错误
+pub fn lock_error(error: impl fmt::Display) -> AppError {
+    AppError::new(ErrorCode::Internal, "common.lock_failed").arg("error", error)
+}
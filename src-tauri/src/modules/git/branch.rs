@@ -1,6 +1,10 @@
+use crate::modules::error::{AppError, AppResult, ErrorCode};
+use crate::modules::i18n;
+use crate::modules::process_runner::{Operation, OperationKind};
 use serde::Serialize;
+use std::io::ErrorKind;
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Output};
 
 #[derive(Debug, Serialize)]
 pub struct GitBranch {
@@ -16,55 +20,114 @@ pub struct GitWorktree {
     pub is_detached: bool,
 }
 
-fn ensure_git_repository(path: &str) -> Result<(), String> {
+fn spawn_error(err: std::io::Error) -> AppError {
+    if err.kind() == ErrorKind::NotFound {
+        AppError::new(ErrorCode::GitNotInstalled, "git.not_installed")
+    } else {
+        AppError::new(ErrorCode::GitCommandFailed, "git.command_failed").arg("error", err)
+    }
+}
+
+// 根据 stderr 归类常见的 git 失败原因
+fn classify_git_error(stderr: &str) -> ErrorCode {
+    let lower = stderr.to_lowercase();
+    if lower.contains("not a git repository") {
+        ErrorCode::NotGitRepository
+    } else if lower.contains("did not match any file(s) known to git")
+        || lower.contains("invalid reference")
+        || lower.contains("not a valid branch name")
+    {
+        ErrorCode::GitBranchNotFound
+    } else if lower.contains("would be overwritten")
+        || lower.contains("please commit your changes or stash them")
+        || lower.contains("contains modified or untracked files")
+    {
+        ErrorCode::GitUncommittedChanges
+    } else if lower.contains("conflict")
+        || lower.contains("needs merge")
+        || lower.contains("resolve your current index first")
+    {
+        ErrorCode::GitConflict
+    } else if lower.contains("is already checked out at")
+        || lower.contains("is already used by worktree")
+    {
+        ErrorCode::GitBranchInUse
+    } else if lower.contains("already exists") {
+        ErrorCode::GitWorktreeExists
+    } else {
+        ErrorCode::GitCommandFailed
+    }
+}
+
+fn git_output(operation: &Operation, args: &[&str], working_dir: &str) -> AppResult<Output> {
+    let mut command = Command::new("git");
+    command.args(args).current_dir(working_dir);
+    operation
+        .run(command, OperationKind::Git)
+        .map_err(|err| err.into_app_error(spawn_error).with_path(working_dir))
+}
+
+fn ensure_git_repository(operation: &Operation, path: &str) -> AppResult<()> {
     if !Path::new(path).exists() {
-        return Err(format!("项目路径不存在: {}", path));
+        return Err(AppError::new(ErrorCode::NotFound, "git.project_not_found")
+            .arg("path", path)
+            .with_path(path));
     }
 
     if Path::new(path).join(".git").exists() {
         return Ok(());
     }
 
-    match Command::new("git")
-        .arg("rev-parse")
-        .arg("--git-dir")
-        .current_dir(path)
-        .output()
-    {
-        Ok(output) if output.status.success() => Ok(()),
-        Ok(_) => Err(format!("路径不是 Git 仓库: {}", path)),
-        Err(err) => Err(format!("检查 Git 仓库失败: {}", err)),
+    let output = git_output(operation, &["rev-parse", "--git-dir"], path)?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(
+            AppError::new(ErrorCode::NotGitRepository, "git.not_repository")
+                .arg("path", path)
+                .with_output(&output)
+                .with_path(path),
+        )
     }
 }
 
-fn run_git_command(args: &[&str], working_dir: &str) -> Result<String, String> {
-    match Command::new("git")
-        .args(args)
-        .current_dir(working_dir)
-        .output()
-    {
-        Ok(output) => {
-            if output.status.success() {
-                Ok(String::from_utf8_lossy(&output.stdout).to_string())
-            } else {
-                Err(String::from_utf8_lossy(&output.stderr).to_string())
-            }
-        }
-        Err(err) => Err(format!("执行 git 命令失败: {}", err)),
+// 失败时 message 为调用方给出的文案 key 对应的描述，具体原因放在 details.stderr 中
+// 超时或被取消时返回 timeout / cancelled
+fn run_git_command(
+    operation: &Operation,
+    args: &[&str],
+    working_dir: &str,
+    action: &'static str,
+) -> AppResult<String> {
+    let output = git_output(operation, args, working_dir)?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(AppError::new(classify_git_error(&stderr), action)
+            .with_output(&output)
+            .with_path(working_dir))
     }
 }
 
-#[tauri::command]
-pub fn list_branches(project_path: String) -> Result<Vec<GitBranch>, String> {
-    ensure_git_repository(&project_path)?;
+#[tauri::command(async)]
+pub fn list_branches(
+    project_path: String,
+    operation_id: Option<String>,
+) -> AppResult<Vec<GitBranch>> {
+    let operation = Operation::begin(operation_id, OperationKind::Git);
+    ensure_git_repository(&operation, &project_path)?;
 
     let output = run_git_command(
+        &operation,
         &[
             "for-each-ref",
             "--format=%(refname:short)::%(HEAD)",
             "refs/heads",
         ],
         &project_path,
+        "git.list_branches_failed",
     )?;
 
     let mut branches = Vec::new();
@@ -94,25 +157,45 @@ pub fn list_branches(project_path: String) -> Result<Vec<GitBranch>, String> {
     Ok(branches)
 }
 
-#[tauri::command]
-pub fn switch_branch(project_path: String, branch: String) -> Result<String, String> {
-    ensure_git_repository(&project_path)?;
+#[tauri::command(async)]
+pub fn switch_branch(
+    project_path: String,
+    branch: String,
+    operation_id: Option<String>,
+) -> AppResult<String> {
+    let operation = Operation::begin(operation_id, OperationKind::Git);
+    ensure_git_repository(&operation, &project_path)?;
 
     if branch.trim().is_empty() {
-        return Err("分支名称不能为空".to_string());
+        return Err(AppError::new(
+            ErrorCode::InvalidArgument,
+            "git.branch_required",
+        ));
     }
 
-    match run_git_command(&["checkout", branch.trim()], &project_path) {
-        Ok(_) => Ok(format!("已切换到分支 {}", branch)),
-        Err(err) => Err(format!("切换分支失败: {}", err)),
-    }
+    run_git_command(
+        &operation,
+        &["checkout", branch.trim()],
+        &project_path,
+        "git.switch_failed",
+    )?;
+    Ok(i18n::tf("git.switched", &[("branch", &branch)]))
 }
 
-#[tauri::command]
-pub fn list_worktrees(project_path: String) -> Result<Vec<GitWorktree>, String> {
-    ensure_git_repository(&project_path)?;
+#[tauri::command(async)]
+pub fn list_worktrees(
+    project_path: String,
+    operation_id: Option<String>,
+) -> AppResult<Vec<GitWorktree>> {
+    let operation = Operation::begin(operation_id, OperationKind::Git);
+    ensure_git_repository(&operation, &project_path)?;
 
-    let output = run_git_command(&["worktree", "list", "--porcelain"], &project_path)?;
+    let output = run_git_command(
+        &operation,
+        &["worktree", "list", "--porcelain"],
+        &project_path,
+        "git.list_worktrees_failed",
+    )?;
 
     let mut worktrees = Vec::new();
     let mut current_worktree: Option<GitWorktree> = None;
@@ -156,44 +239,66 @@ pub fn list_worktrees(project_path: String) -> Result<Vec<GitWorktree>, String>
     Ok(worktrees)
 }
 
-#[tauri::command]
+#[tauri::command(async)]
 pub fn create_worktree(
     project_path: String,
     branch: String,
     worktree_path: String,
-) -> Result<String, String> {
-    ensure_git_repository(&project_path)?;
+    operation_id: Option<String>,
+) -> AppResult<String> {
+    let operation = Operation::begin(operation_id, OperationKind::Git);
+    ensure_git_repository(&operation, &project_path)?;
 
     if branch.trim().is_empty() {
-        return Err("分支名称不能为空".to_string());
+        return Err(AppError::new(
+            ErrorCode::InvalidArgument,
+            "git.branch_required",
+        ));
     }
 
     if worktree_path.trim().is_empty() {
-        return Err("Worktree 路径不能为空".to_string());
+        return Err(AppError::new(
+            ErrorCode::InvalidArgument,
+            "git.worktree_path_required",
+        ));
     }
 
-    match run_git_command(
+    run_git_command(
+        &operation,
         &["worktree", "add", worktree_path.trim(), branch.trim()],
         &project_path,
-    ) {
-        Ok(_) => Ok(format!(
-            "已为分支 {} 创建 worktree: {}",
-            branch, worktree_path
-        )),
-        Err(err) => Err(format!("创建 worktree 失败: {}", err)),
-    }
+        "git.create_worktree_failed",
+    )?;
+    Ok(i18n::tf(
+        "git.worktree_created",
+        &[("branch", &branch), ("path", &worktree_path)],
+    ))
 }
 
-#[tauri::command]
-pub fn remove_worktree(project_path: String, worktree_path: String) -> Result<String, String> {
-    ensure_git_repository(&project_path)?;
+#[tauri::command(async)]
+pub fn remove_worktree(
+    project_path: String,
+    worktree_path: String,
+    operation_id: Option<String>,
+) -> AppResult<String> {
+    let operation = Operation::begin(operation_id, OperationKind::Git);
+    ensure_git_repository(&operation, &project_path)?;
 
     if worktree_path.trim().is_empty() {
-        return Err("Worktree 路径不能为空".to_string());
+        return Err(AppError::new(
+            ErrorCode::InvalidArgument,
+            "git.worktree_path_required",
+        ));
     }
 
-    match run_git_command(&["worktree", "remove", worktree_path.trim()], &project_path) {
-        Ok(_) => Ok(format!("已删除 worktree: {}", worktree_path)),
-        Err(err) => Err(format!("删除 worktree 失败: {}", err)),
-    }
+    run_git_command(
+        &operation,
+        &["worktree", "remove", worktree_path.trim()],
+        &project_path,
+        "git.remove_worktree_failed",
+    )?;
+    Ok(i18n::tf(
+        "git.worktree_removed",
+        &[("path", &worktree_path)],
+    ))
 }
@@ -0,0 +1,717 @@
+// 任务生命周期 Hook 模块
+//
+// 后台任务在启动、成功、失败、就绪（就绪探测通过）时执行用户定义的动作：
+// - Hook 可以绑定到某个项目的某条命令，也可以绑定到整个工作区（按路径前缀匹配）
+// - 动作包括：运行另一条项目命令、执行 shell 片段、向知识库笔记追加一行、POST JSON 到 URL
+// - 就绪探测按命令配置：端口可连接、HTTP 可访问或输出匹配正则
+// - Hook 在后台线程中执行，每次执行结果都会记录并通过 hook-result 事件推送
+use crate::modules::error::{lock_error, AppError, AppResult, ErrorCode};
+use crate::modules::i18n;
+use crate::modules::kitty::core::get_current_timestamp;
+use crate::modules::knowledge;
+use crate::modules::platform;
+use crate::modules::process_runner::{self, OperationKind};
+use crate::modules::storage;
+use crate::modules::task_runner::{self, ManualRunRequest, RunRecord};
+use chrono::Local;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+const HOOK_STORE: &str = "hooks";
+const RESULT_STORE: &str = "hook_results";
+const MAX_RESULTS: usize = 200;
+// Hook 触发的命令再触发 Hook 的最大层数
+const MAX_HOOK_DEPTH: u32 = 3;
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+const PROBE_INTERVAL: Duration = Duration::from_millis(500);
+const PROBE_CONNECT_TIMEOUT: Duration = Duration::from_millis(300);
+const DEFAULT_NOTE_TEMPLATE: &str = "- {time} [{event}] {command} @ {project}";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum HookEvent {
+    Started,
+    Succeeded,
+    Failed,
+    Ready,
+}
+
+impl HookEvent {
+    fn as_str(&self) -> &'static str {
+        match self {
+            HookEvent::Started => "started",
+            HookEvent::Succeeded => "succeeded",
+            HookEvent::Failed => "failed",
+            HookEvent::Ready => "ready",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum HookScope {
+    Command {
+        project_path: String,
+        command_name: String,
+    },
+    // 工作区内所有项目的所有后台任务
+    Workspace {
+        workspace_path: String,
+    },
+}
+
+impl HookScope {
+    fn matches(&self, record: &RunRecord) -> bool {
+        match self {
+            HookScope::Command {
+                project_path,
+                command_name,
+            } => {
+                &record.project_path == project_path
+                    && record.command_name.as_ref() == Some(command_name)
+            }
+            HookScope::Workspace { workspace_path } => {
+                Path::new(&record.project_path).starts_with(workspace_path)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum HookAction {
+    // 以后台任务方式运行项目脚本，project_path 为空时使用触发任务所在的项目
+    RunCommand {
+        #[serde(default)]
+        project_path: Option<String>,
+        command_name: String,
+    },
+    // 在触发任务的项目目录中执行，任务信息通过 RUNPROJECT_* 环境变量传入
+    Shell {
+        script: String,
+    },
+    // 向 Markdown 笔记追加一行，模板支持 {time} {event} {project} {command} {status} {exitCode} {runId}
+    AppendNote {
+        note_path: String,
+        #[serde(default)]
+        template: Option<String>,
+    },
+    // 仅支持 http，请求体为 { event, run }
+    Webhook {
+        url: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hook {
+    // 为空时保存时自动生成
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub scope: HookScope,
+    pub events: Vec<HookEvent>,
+    pub action: HookAction,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub created_at: u64,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ProbeCheck {
+    // 本机端口可以建立 TCP 连接
+    Port { port: u16 },
+    // 返回 5xx 以外的任意状态码
+    Http { url: String },
+    // 输出中出现匹配的内容
+    Output { pattern: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadinessProbe {
+    pub project_path: String,
+    pub command_name: String,
+    pub check: ProbeCheck,
+    // 超过该时间仍未就绪则放弃探测
+    #[serde(default = "default_probe_timeout")]
+    pub timeout_secs: u64,
+}
+
+fn default_probe_timeout() -> u64 {
+    120
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct HookStore {
+    hooks: Vec<Hook>,
+    probes: Vec<ReadinessProbe>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookResult {
+    pub hook_id: String,
+    pub hook_name: String,
+    pub run_id: String,
+    pub event: HookEvent,
+    pub ok: bool,
+    pub message: String,
+    pub started_at: u64,
+    pub finished_at: u64,
+}
+
+lazy_static! {
+    static ref STORE: Arc<Mutex<Option<HookStore>>> = Arc::new(Mutex::new(None));
+    // 最新的在前
+    static ref RESULTS: Arc<Mutex<Option<Vec<HookResult>>>> = Arc::new(Mutex::new(None));
+}
+
+fn with_store<R>(f: impl FnOnce(&mut HookStore) -> R) -> AppResult<R> {
+    let mut guard = STORE.lock().map_err(lock_error)?;
+    Ok(f(guard.get_or_insert_with(|| storage::load(HOOK_STORE))))
+}
+
+fn with_results<R>(f: impl FnOnce(&mut Vec<HookResult>) -> R) -> AppResult<R> {
+    let mut guard = RESULTS.lock().map_err(lock_error)?;
+    Ok(f(guard.get_or_insert_with(|| storage::load(RESULT_STORE))))
+}
+
+fn record_result(app: &AppHandle, result: HookResult) {
+    let _ = app.emit("hook-result", &result);
+    let snapshot = with_results(|results| {
+        results.insert(0, result);
+        results.truncate(MAX_RESULTS);
+        results.clone()
+    });
+    if let Ok(snapshot) = snapshot {
+        if let Err(e) = storage::save(RESULT_STORE, &snapshot) {
+            eprintln!("[HOOK] 保存执行记录失败: {}", e);
+        }
+    }
+}
+
+// 任务状态变化时调用，匹配的 Hook 在后台线程中依次执行
+pub fn dispatch(app: &AppHandle, record: &RunRecord, event: HookEvent) {
+    if event == HookEvent::Started {
+        start_probe(app, record);
+    }
+
+    let hooks: Vec<Hook> = with_store(|store| {
+        store
+            .hooks
+            .iter()
+            .filter(|hook| {
+                hook.enabled && hook.events.contains(&event) && hook.scope.matches(record)
+            })
+            .cloned()
+            .collect()
+    })
+    .unwrap_or_default();
+    if hooks.is_empty() {
+        return;
+    }
+
+    let app = app.clone();
+    let record = record.clone();
+    std::thread::spawn(move || {
+        for hook in hooks {
+            let started_at = get_current_timestamp();
+            let (ok, message) = match execute(&app, &hook.action, &record, event) {
+                Ok(message) => (true, message),
+                Err(error) => (false, error.to_string()),
+            };
+            record_result(
+                &app,
+                HookResult {
+                    hook_id: hook.id,
+                    hook_name: hook.name,
+                    run_id: record.run_id.clone(),
+                    event,
+                    ok,
+                    message,
+                    started_at,
+                    finished_at: get_current_timestamp(),
+                },
+            );
+        }
+    });
+}
+
+fn execute(
+    app: &AppHandle,
+    action: &HookAction,
+    record: &RunRecord,
+    event: HookEvent,
+) -> AppResult<String> {
+    match action {
+        HookAction::RunCommand {
+            project_path,
+            command_name,
+        } => run_command(app, record, project_path.as_deref(), command_name),
+        HookAction::Shell { script } => run_shell(record, event, script),
+        HookAction::AppendNote {
+            note_path,
+            template,
+        } => {
+            let template = template.as_deref().unwrap_or(DEFAULT_NOTE_TEMPLATE);
+            knowledge::append_to_note(note_path, &render_note(template, record, event))?;
+            Ok(i18n::tf("hooks.note_appended", &[("path", note_path)]))
+        }
+        HookAction::Webhook { url } => {
+            let body = serde_json::json!({ "event": event, "run": record }).to_string();
+            let status = http_request("POST", url, Some(&body), WEBHOOK_TIMEOUT)?;
+            if (200..300).contains(&status) {
+                Ok(i18n::tf("hooks.webhook_sent", &[("status", &status)]))
+            } else {
+                Err(
+                    AppError::new(ErrorCode::HookFailed, "hooks.webhook_rejected")
+                        .arg("status", status),
+                )
+            }
+        }
+    }
+}
+
+fn run_command(
+    app: &AppHandle,
+    record: &RunRecord,
+    project_path: Option<&str>,
+    command_name: &str,
+) -> AppResult<String> {
+    if record.hook_depth >= MAX_HOOK_DEPTH {
+        return Err(AppError::new(ErrorCode::HookFailed, "hooks.depth_exceeded")
+            .arg("depth", MAX_HOOK_DEPTH));
+    }
+
+    let mut request = task_runner::resolve_script_run(
+        &ManualRunRequest {
+            project_path: project_path.unwrap_or(&record.project_path).to_string(),
+            command_name: command_name.to_string(),
+            package_manager: None,
+            node_version: None,
+            env_profile: None,
+            arg_values: None,
+            priority: 0,
+        },
+        "hook",
+    )?;
+    request.hook_depth = record.hook_depth + 1;
+    let run_id = task_runner::spawn_run(app, request, None)?;
+    Ok(i18n::tf("hooks.run_queued", &[("run", &run_id)]))
+}
+
+fn run_shell(record: &RunRecord, event: HookEvent, script: &str) -> AppResult<String> {
+    let mut command = platform::build_shell_command(script);
+    command.current_dir(&record.project_path).envs([
+        ("RUNPROJECT_EVENT", event.as_str().to_string()),
+        ("RUNPROJECT_RUN_ID", record.run_id.clone()),
+        ("RUNPROJECT_PROJECT_PATH", record.project_path.clone()),
+        (
+            "RUNPROJECT_COMMAND_NAME",
+            record.command_name.clone().unwrap_or_default(),
+        ),
+        (
+            "RUNPROJECT_EXIT_CODE",
+            record.exit_code.map(|c| c.to_string()).unwrap_or_default(),
+        ),
+    ]);
+
+    let output = process_runner::run(command, OperationKind::Hook)
+        .map_err(|e| e.into_app_error(|e| AppError::io("task.spawn_failed", &e)))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() {
+        Ok(stdout)
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        let code = output.status.code().unwrap_or(-1);
+        let error = AppError::new(ErrorCode::HookFailed, "hooks.script_failed")
+            .arg("code", code)
+            .with_exit_code(output.status.code());
+        let detail = if stderr.is_empty() { stdout } else { stderr };
+        Err(if detail.is_empty() {
+            error
+        } else {
+            error.with_stderr(detail)
+        })
+    }
+}
+
+fn render_note(template: &str, record: &RunRecord, event: HookEvent) -> String {
+    let status = serde_json::to_value(&record.status)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default();
+    template
+        .replace(
+            "{time}",
+            &Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        )
+        .replace("{event}", event.as_str())
+        .replace("{project}", &record.project_path)
+        .replace(
+            "{command}",
+            record.command_name.as_deref().unwrap_or(&record.command),
+        )
+        .replace("{status}", &status)
+        .replace(
+            "{exitCode}",
+            &record.exit_code.map(|c| c.to_string()).unwrap_or_default(),
+        )
+        .replace("{runId}", &record.run_id)
+}
+
+fn parse_http_url(url: &str) -> AppResult<url::Url> {
+    let parsed = url.parse::<url::Url>().map_err(|e| {
+        AppError::new(ErrorCode::InvalidArgument, "hooks.invalid_url")
+            .arg("url", url)
+            .arg("error", e)
+    })?;
+    if parsed.scheme() != "http" || parsed.host_str().is_none() {
+        return Err(AppError::new(ErrorCode::InvalidArgument, "hooks.http_only").arg("url", url));
+    }
+    Ok(parsed)
+}
+
+// 最简单的 HTTP/1.1 请求，只关心状态码；目标通常是本机或内网的接收端
+fn http_request(method: &str, url: &str, body: Option<&str>, timeout: Duration) -> AppResult<u16> {
+    let parsed = parse_http_url(url)?;
+    let host = parsed.host_str().unwrap_or_default();
+    let port = parsed.port_or_known_default().unwrap_or(80);
+    let request_failed = |e: &dyn std::fmt::Display| {
+        AppError::new(ErrorCode::HookFailed, "hooks.request_failed")
+            .arg("url", url)
+            .arg("error", e)
+    };
+
+    let addr: SocketAddr = (host, port)
+        .to_socket_addrs()
+        .map_err(|e| request_failed(&e))?
+        .next()
+        .ok_or_else(|| request_failed(&host))?;
+    let mut stream = TcpStream::connect_timeout(&addr, timeout).map_err(|e| request_failed(&e))?;
+    let _ = stream.set_read_timeout(Some(timeout));
+    let _ = stream.set_write_timeout(Some(timeout));
+
+    let body = body.unwrap_or("");
+    let request = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: runproject\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        method,
+        &parsed[url::Position::BeforePath..url::Position::AfterQuery],
+        &parsed[url::Position::BeforeHost..url::Position::AfterPort],
+        body.len(),
+        body
+    );
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| request_failed(&e))?;
+
+    let mut status_line = String::new();
+    BufReader::new(stream)
+        .read_line(&mut status_line)
+        .map_err(|e| request_failed(&e))?;
+    status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| request_failed(&status_line.trim()))
+}
+
+// ---------- 就绪探测 ----------
+
+fn start_probe(app: &AppHandle, record: &RunRecord) {
+    let Some(command_name) = &record.command_name else {
+        return;
+    };
+    let probe = with_store(|store| {
+        store
+            .probes
+            .iter()
+            .find(|p| p.project_path == record.project_path && &p.command_name == command_name)
+            .cloned()
+    })
+    .ok()
+    .flatten();
+    let Some(probe) = probe else {
+        return;
+    };
+
+    let app = app.clone();
+    let run_id = record.run_id.clone();
+    std::thread::spawn(move || {
+        let pattern = match &probe.check {
+            ProbeCheck::Output { pattern } => Regex::new(pattern).ok(),
+            _ => None,
+        };
+        let deadline = Instant::now() + Duration::from_secs(probe.timeout_secs);
+        while Instant::now() < deadline {
+            if !task_runner::is_running(&run_id) {
+                return;
+            }
+            if probe_passes(&probe.check, pattern.as_ref(), &run_id) {
+                if let Some(record) = task_runner::mark_ready(&app, &run_id) {
+                    dispatch(&app, &record, HookEvent::Ready);
+                }
+                return;
+            }
+            std::thread::sleep(PROBE_INTERVAL);
+        }
+        eprintln!("[HOOK] 就绪探测超时: {}", run_id);
+    });
+}
+
+fn probe_passes(check: &ProbeCheck, pattern: Option<&Regex>, run_id: &str) -> bool {
+    match check {
+        ProbeCheck::Port { port } => {
+            let addr = SocketAddr::from(([127, 0, 0, 1], *port));
+            TcpStream::connect_timeout(&addr, PROBE_CONNECT_TIMEOUT).is_ok()
+        }
+        ProbeCheck::Http { url } => {
+            http_request("GET", url, None, PROBE_CONNECT_TIMEOUT).is_ok_and(|status| status < 500)
+        }
+        ProbeCheck::Output { .. } => pattern.is_some_and(|pattern| {
+            task_runner::get_run(run_id).is_some_and(|run| pattern.is_match(&run.output_tail))
+        }),
+    }
+}
+
+fn validate_hook(hook: &Hook) -> AppResult<()> {
+    let invalid = |key| AppError::new(ErrorCode::InvalidArgument, key);
+    if hook.name.trim().is_empty() {
+        return Err(invalid("hooks.name_required"));
+    }
+    if hook.events.is_empty() {
+        return Err(invalid("hooks.events_required"));
+    }
+    let scope_empty = match &hook.scope {
+        HookScope::Command {
+            project_path,
+            command_name,
+        } => project_path.trim().is_empty() || command_name.trim().is_empty(),
+        HookScope::Workspace { workspace_path } => workspace_path.trim().is_empty(),
+    };
+    if scope_empty {
+        return Err(invalid("hooks.scope_required"));
+    }
+    let action_empty = match &hook.action {
+        HookAction::RunCommand { command_name, .. } => command_name.trim().is_empty(),
+        HookAction::Shell { script } => script.trim().is_empty(),
+        HookAction::AppendNote { note_path, .. } => note_path.trim().is_empty(),
+        HookAction::Webhook { url } => {
+            parse_http_url(url)?;
+            false
+        }
+    };
+    if action_empty {
+        return Err(invalid("hooks.action_required"));
+    }
+    Ok(())
+}
+
+// ---------- Tauri 命令 ----------
+
+#[tauri::command]
+pub fn list_hooks() -> AppResult<Vec<Hook>> {
+    with_store(|store| store.hooks.clone())
+}
+
+// 新建或更新 Hook
+#[tauri::command]
+pub fn save_hook(mut hook: Hook) -> AppResult<Hook> {
+    validate_hook(&hook)?;
+    if hook.id.is_empty() {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        hook.id = format!("hook-{:x}", nanos);
+    }
+    if hook.created_at == 0 {
+        hook.created_at = get_current_timestamp();
+    }
+
+    let snapshot = with_store(|store| {
+        match store.hooks.iter_mut().find(|h| h.id == hook.id) {
+            Some(existing) => *existing = hook.clone(),
+            None => store.hooks.push(hook.clone()),
+        }
+        store.clone()
+    })?;
+    storage::save(HOOK_STORE, &snapshot)?;
+    Ok(hook)
+}
+
+#[tauri::command]
+pub fn delete_hook(hook_id: String) -> AppResult<()> {
+    let snapshot = with_store(|store| {
+        store.hooks.retain(|h| h.id != hook_id);
+        store.clone()
+    })?;
+    storage::save(HOOK_STORE, &snapshot)?;
+    Ok(())
+}
+
+// 最新的在前，可按 Hook 或运行过滤
+#[tauri::command]
+pub fn get_hook_results(
+    hook_id: Option<String>,
+    run_id: Option<String>,
+) -> AppResult<Vec<HookResult>> {
+    with_results(|results| {
+        results
+            .iter()
+            .filter(|r| hook_id.as_ref().is_none_or(|id| &r.hook_id == id))
+            .filter(|r| run_id.as_ref().is_none_or(|id| &r.run_id == id))
+            .cloned()
+            .collect()
+    })
+}
+
+#[tauri::command]
+pub fn list_readiness_probes() -> AppResult<Vec<ReadinessProbe>> {
+    with_store(|store| store.probes.clone())
+}
+
+// 每条命令只有一个探测，已存在时替换
+#[tauri::command]
+pub fn set_readiness_probe(probe: ReadinessProbe) -> AppResult<ReadinessProbe> {
+    match &probe.check {
+        ProbeCheck::Http { url } => {
+            parse_http_url(url)?;
+        }
+        ProbeCheck::Output { pattern } => {
+            Regex::new(pattern).map_err(|e| {
+                AppError::new(ErrorCode::InvalidArgument, "hooks.invalid_pattern").arg("error", e)
+            })?;
+        }
+        ProbeCheck::Port { .. } => {}
+    }
+
+    let snapshot = with_store(|store| {
+        store.probes.retain(|p| {
+            p.project_path != probe.project_path || p.command_name != probe.command_name
+        });
+        store.probes.push(probe.clone());
+        store.clone()
+    })?;
+    storage::save(HOOK_STORE, &snapshot)?;
+    Ok(probe)
+}
+
+#[tauri::command]
+pub fn delete_readiness_probe(project_path: String, command_name: String) -> AppResult<()> {
+    let snapshot = with_store(|store| {
+        store
+            .probes
+            .retain(|p| p.project_path != project_path || p.command_name != command_name);
+        store.clone()
+    })?;
+    storage::save(HOOK_STORE, &snapshot)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::task_runner::RunStatus;
+
+    fn record(project_path: &str, command_name: Option<&str>) -> RunRecord {
+        RunRecord {
+            run_id: "app::build#1-0".to_string(),
+            command_id: format!("{}::build", project_path),
+            project_path: project_path.to_string(),
+            command: "pnpm run build".to_string(),
+            command_name: command_name.map(str::to_string),
+            trigger: "manual".to_string(),
+            status: RunStatus::Failed,
+            exit_code: Some(2),
+            priority: 0,
+            long_running: false,
+            queued_at: 0,
+            started_at: Some(0),
+            ready_at: None,
+            finished_at: Some(1),
+            diagnostics: 0,
+            output_tail: String::new(),
+            hook_depth: 0,
+        }
+    }
+
+    #[test]
+    fn note_template_fills_every_placeholder() {
+        let note = render_note(
+            "[{event}] {command} @ {project} -> {status} ({exitCode}) {runId}",
+            &record("/work/app", Some("build")),
+            HookEvent::Failed,
+        );
+        assert_eq!(
+            note,
+            "[failed] build @ /work/app -> failed (2) app::build#1-0"
+        );
+    }
+
+    #[test]
+    fn note_template_falls_back_to_raw_command_and_formats_time() {
+        let mut run = record("/work/app", None);
+        run.exit_code = None;
+        run.status = RunStatus::Running;
+        let note = render_note(
+            "{time}|{command}|{status}|{exitCode}",
+            &run,
+            HookEvent::Started,
+        );
+        let (time, rest) = note.split_once('|').unwrap();
+        assert!(chrono::NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S").is_ok());
+        assert_eq!(rest, "pnpm run build|running|");
+    }
+
+    #[test]
+    fn command_scope_requires_same_project_and_script() {
+        let scope = HookScope::Command {
+            project_path: "/work/app".to_string(),
+            command_name: "build".to_string(),
+        };
+        assert!(scope.matches(&record("/work/app", Some("build"))));
+        assert!(!scope.matches(&record("/work/app", Some("test"))));
+        assert!(!scope.matches(&record("/work/app", None)));
+        assert!(!scope.matches(&record("/work/app2", Some("build"))));
+    }
+
+    #[test]
+    fn workspace_scope_matches_whole_path_components() {
+        let scope = HookScope::Workspace {
+            workspace_path: "/work".to_string(),
+        };
+        assert!(scope.matches(&record("/work/app", Some("build"))));
+        assert!(scope.matches(&record("/work/nested/lib", None)));
+        assert!(scope.matches(&record("/work", None)));
+        assert!(!scope.matches(&record("/workspace/app", Some("build"))));
+        assert!(!scope.matches(&record("/other/app", Some("build"))));
+    }
+}
@@ -0,0 +1,442 @@
+// 恶意输入测试
+//
+// 把带引号、命令替换、换行、以 - 开头等的项目名、路径与脚本名送入各个执行器的参数构建函数，
+// 并在 unix 上实际交给 bash 执行，确认这些值始终作为单个参数原样到达，不会被 shell 解释。
+use crate::modules::command_template::{
+    render_arguments, CommandTemplate, CommandVariable, VariableKind,
+};
+use crate::modules::kitty::connection::build_instance_args;
+use crate::modules::kitty::core::KittyConfig;
+use crate::modules::kitty::executor::{build_execution_command, build_kitty_window_args};
+use crate::modules::kitty::process::build_kill_pattern;
+use crate::modules::kitty::protocol;
+use crate::modules::kitty::session::{
+    is_line_break, parse_session, render_session, split_args, SessionTab,
+};
+use crate::modules::kitty::tabs::{build_launch_request, TAB_SCRIPT};
+use crate::modules::platform::shell_quote;
+use crate::modules::task_group::GroupTask;
+use quickcheck::{QuickCheck, TestResult};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const HOSTILE: &[&str] = &[
+    "it's",
+    "a\"b",
+    "$(touch PWNED)",
+    "`touch PWNED`",
+    "; touch PWNED",
+    "&& touch PWNED",
+    "| touch PWNED",
+    "'; touch PWNED; '",
+    "'\\''",
+    "a b  c",
+    "-rf",
+    "--config=/tmp/evil",
+    "line\nbreak",
+    "tab\there",
+    "back\\slash",
+    "$HOME",
+    "${PATH}",
+    "*",
+    "~",
+    "!!",
+    "#comment",
+    "日本語 プロジェクト",
+    "@selection",
+];
+
+const PROPERTY_TESTS: u64 = 100;
+
+fn valid(input: &str) -> bool {
+    !input.is_empty() && !input.contains('\0')
+}
+
+// 每个测试使用独立的临时目录，PWNED 文件出现在其中即说明发生了注入
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "runproject-hostile-{}-{}",
+        name,
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = test_dir(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    test_dir(name)
+}
+
+fn assert_not_pwned(dir: &Path) {
+    assert!(
+        !dir.join("PWNED").exists(),
+        "检测到命令注入: {:?}",
+        dir.join("PWNED")
+    );
+}
+
+// 用 bash 执行脚本，返回以 NUL 分隔的输出
+#[cfg(unix)]
+fn run_bash(script: &str, cwd: &Path) -> Vec<String> {
+    let output = Command::new("bash")
+        .arg("-c")
+        .arg(script)
+        .current_dir(cwd)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "脚本执行失败: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    stdout
+        .split('\0')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+#[cfg(unix)]
+fn quote_round_trips(input: &str) -> bool {
+    let script = format!("printf '%s\\0' {}", shell_quote(input));
+    run_bash(&script, &test_dir("quote")) == vec![input.to_string()]
+}
+
+#[cfg(unix)]
+#[test]
+fn shell_quote_round_trips_arbitrary_strings() {
+    let dir = scratch_dir("quote");
+    fn property(input: String) -> TestResult {
+        if !valid(&input) {
+            return TestResult::discard();
+        }
+        TestResult::from_bool(quote_round_trips(&input))
+    }
+    QuickCheck::new()
+        .tests(PROPERTY_TESTS)
+        .quickcheck(property as fn(String) -> TestResult);
+    for input in HOSTILE {
+        assert!(quote_round_trips(input), "转义后未原样还原: {:?}", input);
+    }
+    assert_not_pwned(&dir);
+}
+
+// 脚本名经过包管理器前缀拼接后仍是单个参数
+#[cfg(unix)]
+#[test]
+fn script_names_reach_package_manager_verbatim() {
+    let dir = scratch_dir("script");
+    fn check(script_name: &str) -> bool {
+        let command = build_execution_command(script_name, None, "npm", None, None).unwrap();
+        let script = format!("npm() {{ printf '%s\\0' \"$@\"; }}; {}", command);
+        run_bash(&script, &test_dir("script")) == vec!["run".to_string(), script_name.to_string()]
+    }
+
+    fn property(script_name: String) -> TestResult {
+        let lower = script_name.to_lowercase();
+        // 以包管理器开头的输入按原始命令处理，不在此测试范围内
+        if !valid(&script_name)
+            || lower.starts_with("npm ")
+            || lower.starts_with("yarn ")
+            || lower.starts_with("pnpm ")
+        {
+            return TestResult::discard();
+        }
+        TestResult::from_bool(check(&script_name))
+    }
+    QuickCheck::new()
+        .tests(PROPERTY_TESTS)
+        .quickcheck(property as fn(String) -> TestResult);
+    for name in HOSTILE {
+        assert!(check(name), "脚本名未原样传递: {:?}", name);
+    }
+    assert_not_pwned(&dir);
+}
+
+#[test]
+fn tab_launch_requests_keep_values_intact() {
+    for value in HOSTILE {
+        let env = vec![("RUNPROJECT_TEST".to_string(), value.to_string())];
+        let request = build_launch_request(value, value, value, value, value, &env);
+
+        assert_eq!(request.launch_type, "tab");
+        assert_eq!(request.cwd.as_deref(), Some(*value));
+        assert!(request.env.contains(&format!("RUNPROJECT_TEST={}", value)));
+        assert!(request
+            .tab_title
+            .as_deref()
+            .is_some_and(|title| title.contains(value)));
+        // 脚本本身固定不变，命令与目录只作为位置参数出现
+        assert_eq!(
+            request.args,
+            [
+                "bash".to_string(),
+                "-lc".to_string(),
+                TAB_SCRIPT.to_string(),
+                "runproject".to_string(),
+                value.to_string(),
+                value.to_string(),
+            ]
+        );
+
+        // 经协议编码后 JSON 中的取值与原值一致
+        #[cfg(unix)]
+        {
+            let encoded = protocol::encode("launch", &request).unwrap();
+            let body = &encoded[b"\x1bP@kitty-cmd".len()..encoded.len() - 2];
+            let payload = &serde_json::from_slice::<Value>(body).unwrap()["payload"];
+            assert_eq!(payload["cwd"], *value);
+            assert_eq!(payload["args"][4], *value);
+            assert_eq!(payload["args"][5], *value);
+            assert!(payload["env"]
+                .as_array()
+                .unwrap()
+                .contains(&Value::String(format!("RUNPROJECT_TEST={}", value))));
+        }
+    }
+}
+
+// 实际执行标签页脚本：目录名包含恶意内容时 cd 与命令都应按原样处理
+#[cfg(unix)]
+#[test]
+fn tab_script_runs_in_hostile_directories() {
+    let root = scratch_dir("tab");
+    for (index, name) in HOSTILE.iter().enumerate() {
+        // 目录名不能包含 /，以序号区分
+        let project_dir = root.join(format!("{}-{}", index, name.replace('/', "_")));
+        std::fs::create_dir_all(&project_dir).unwrap();
+        let working_dir = project_dir.to_string_lossy().to_string();
+
+        let request = build_launch_request(name, name, name, &working_dir, "pwd -P", &[]);
+        let tail = &request.args[2..];
+        let output = Command::new("bash")
+            .arg("-c")
+            .args(tail)
+            .current_dir(&root)
+            .env("SHELL", "true")
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let expected = project_dir.canonicalize().unwrap();
+        assert!(
+            stdout.contains(&format!("$ pwd -P\n{}\n", expected.to_string_lossy())),
+            "目录未正确切换: {:?} -> {}",
+            name,
+            stdout
+        );
+        assert_not_pwned(&project_dir);
+    }
+    assert_not_pwned(&root);
+}
+
+#[test]
+fn kitty_window_args_keep_values_intact() {
+    fn property(project_name: String, working_dir: String, command: String) -> TestResult {
+        let args = build_kitty_window_args(
+            &project_name,
+            "dev",
+            "unix:@kitty-runproject-test",
+            &working_dir,
+            &command,
+            &KittyConfig::default(),
+        );
+        TestResult::from_bool(
+            args[0] == format!("--title={} - dev", project_name)
+                && args.contains(&format!("--directory={}", working_dir))
+                && args[args.len() - 3..] == ["bash".to_string(), "-c".to_string(), command],
+        )
+    }
+    QuickCheck::new()
+        .tests(PROPERTY_TESTS)
+        .quickcheck(property as fn(String, String, String) -> TestResult);
+}
+
+#[test]
+fn instance_args_keep_values_intact() {
+    let config = KittyConfig::default();
+    for dir in HOSTILE {
+        let args = build_instance_args("unix:@kitty-runproject-test", dir, &config);
+        assert_eq!(args[0], "--listen-on=unix:@kitty-runproject-test");
+        assert!(args.contains(&format!("--directory={}", dir)));
+        assert_eq!(args.last().unwrap(), "--override=allow_remote_control=yes");
+    }
+}
+
+// 反斜杠只能出现在 ERE 元字符之前，转义普通字符在 ERE 中是未定义行为
+fn kill_pattern_escapes_only_metacharacters(command_id: &str) -> bool {
+    let pattern = build_kill_pattern(command_id);
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' && !matches!(chars.next(), Some(next) if ".[]()*+?{}|^$\\".contains(next)) {
+            return false;
+        }
+    }
+    true
+}
+
+// 交给 grep -E -x 按 POSIX ERE 语义整行匹配
+#[cfg(unix)]
+fn ere_matches(pattern: &str, line: &str) -> bool {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let Ok(mut child) = Command::new("grep")
+        .args(["-E", "-x", "-q", "-e", pattern])
+        .env("LC_ALL", "C.UTF-8")
+        .stdin(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return false;
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = writeln!(stdin, "{}", line);
+    }
+    child.wait().map(|status| status.success()).unwrap_or(false)
+}
+
+// 只能匹配替换后的原始字符串本身，前面多一个字符就不再匹配
+#[cfg(unix)]
+fn kill_pattern_matches_literally(command_id: &str) -> bool {
+    let pattern = build_kill_pattern(command_id);
+    let target = command_id.replace('-', "_");
+    ere_matches(&pattern, &target) && !ere_matches(&pattern, &format!("x{}", target))
+}
+
+#[test]
+fn kill_pattern_escapes_only_ere_metacharacters() {
+    assert_eq!(build_kill_pattern("a#b&c-d~e"), "a#b&c_d~e");
+    assert_eq!(build_kill_pattern("x.y*(z)"), "x\\.y\\*\\(z\\)");
+    fn property(command_id: String) -> bool {
+        kill_pattern_escapes_only_metacharacters(&command_id)
+    }
+    QuickCheck::new()
+        .tests(PROPERTY_TESTS)
+        .quickcheck(property as fn(String) -> bool);
+}
+
+#[cfg(unix)]
+#[test]
+fn kill_pattern_matches_only_literal_command_id() {
+    fn property(command_id: String) -> TestResult {
+        // grep 按行匹配，换行无法作为单行内容传入
+        if !valid(&command_id) || command_id.contains('\n') {
+            return TestResult::discard();
+        }
+        TestResult::from_bool(kill_pattern_matches_literally(&command_id))
+    }
+    QuickCheck::new()
+        .tests(PROPERTY_TESTS)
+        .quickcheck(property as fn(String) -> TestResult);
+    for id in HOSTILE.iter().filter(|id| !id.contains('\n')) {
+        assert!(kill_pattern_matches_literally(id), "匹配模式错误: {:?}", id);
+    }
+}
+
+fn hostile_template() -> CommandTemplate {
+    CommandTemplate {
+        variables: vec![
+            CommandVariable {
+                name: "positional".to_string(),
+                label: None,
+                kind: VariableKind::String { pattern: None },
+                required: true,
+                default: None,
+                flag: None,
+            },
+            CommandVariable {
+                name: "grep".to_string(),
+                label: None,
+                kind: VariableKind::String { pattern: None },
+                required: false,
+                default: None,
+                flag: Some("--grep=".to_string()),
+            },
+        ],
+        separator: Some(false),
+    }
+}
+
+#[cfg(unix)]
+fn template_round_trips(value: &str) -> bool {
+    let dir = test_dir("template");
+    let mut values = BTreeMap::new();
+    values.insert("positional".to_string(), Value::String(value.to_string()));
+    values.insert("grep".to_string(), Value::String(value.to_string()));
+    let rendered =
+        render_arguments(&hostile_template(), &values, &dir.to_string_lossy(), "pnpm").unwrap();
+    let script = format!("printf '%s\\0'{}", rendered.args);
+    run_bash(&script, &dir) == vec![value.to_string(), format!("--grep={}", value)]
+}
+
+// 模板参数逐个转义，到达脚本时与输入完全一致
+#[cfg(unix)]
+#[test]
+fn template_arguments_reach_scripts_verbatim() {
+    let dir = scratch_dir("template");
+    fn property(value: String) -> TestResult {
+        if !valid(&value) {
+            return TestResult::discard();
+        }
+        TestResult::from_bool(template_round_trips(&value))
+    }
+    QuickCheck::new()
+        .tests(PROPERTY_TESTS)
+        .quickcheck(property as fn(String) -> TestResult);
+    for value in HOSTILE {
+        assert!(template_round_trips(value), "参数未原样传递: {:?}", value);
+    }
+    assert_not_pwned(&dir);
+}
+
+fn session_round_trips(value: &str) -> bool {
+    let task = GroupTask {
+        title: value.to_string(),
+        cwd: format!("/tmp/{}", value),
+        command: format!("echo {}", shell_quote(value)),
+        env: BTreeMap::from([("RUNPROJECT_VALUE".to_string(), value.to_string())]),
+        tab: None,
+    };
+    let tabs = vec![SessionTab {
+        title: value.to_string(),
+        split: true,
+        windows: vec![task.clone(), task],
+    }];
+    let content = render_session(value, &tabs).unwrap();
+    parse_session(&content) == tabs
+}
+
+// 导出的会话文件按 kitty 的 shlex 规则拆分后，目录、标题、命令与环境变量与导出前一致
+#[cfg(unix)]
+#[test]
+fn session_files_round_trip_verbatim() {
+    fn property(value: String) -> TestResult {
+        if !valid(&value) || value.contains(is_line_break) || value.trim() != value {
+            return TestResult::discard();
+        }
+        TestResult::from_bool(session_round_trips(&value))
+    }
+    QuickCheck::new()
+        .tests(PROPERTY_TESTS)
+        .quickcheck(property as fn(String) -> TestResult);
+    for value in HOSTILE
+        .iter()
+        .filter(|v| !v.contains(['\r', '\n']) && v.trim() == **v)
+    {
+        assert!(
+            session_round_trips(value),
+            "会话文件未原样还原: {:?}",
+            value
+        );
+    }
+    for value in HOSTILE {
+        let quoted = shell_quote(value);
+        assert_eq!(split_args(&quoted), vec![value.to_string()]);
+    }
+}
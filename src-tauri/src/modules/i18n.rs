@@ -0,0 +1,426 @@
+// 后端文案目录
+//
+// 所有面向用户的文案都以稳定的 key 查表得到，提供 zh-CN 与 en 两套：
+// - 语言优先取设置中保存的值，未设置时跟随系统语言
+// - 文案中的 {name} 占位符由调用方按名称填充
+// - 错误同时携带 key 返回给前端，前端可据此判断或自行翻译
+// 仅用于开发排查的 eprintln 日志不经过这里
+use crate::modules::storage;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+
+const LOCALE_STORE: &str = "locale";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Locale {
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "en")]
+    En,
+}
+
+impl Locale {
+    // 根据 BCP 47 / POSIX 语言标签判断，非中文一律使用英文
+    pub fn from_tag(tag: &str) -> Self {
+        if tag.trim().to_lowercase().starts_with("zh") {
+            Locale::ZhCn
+        } else {
+            Locale::En
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LocaleSettings {
+    // None 表示跟随系统
+    locale: Option<Locale>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocaleInfo {
+    // 实际生效的语言
+    pub locale: Locale,
+    pub preference: Option<Locale>,
+    pub system: Locale,
+}
+
+// (key, zh-CN, en)
+#[rustfmt::skip]
+const CATALOG: &[(&str, &str, &str)] = &[
+    // 通用
+    ("common.lock_failed", "获取锁失败: {error}", "Failed to acquire lock: {error}"),
+    ("common.storage_not_initialized", "存储目录尚未初始化", "Storage directory is not initialized"),
+    ("common.serialize_failed", "序列化失败: {error}", "Serialization failed: {error}"),
+    ("common.read_failed", "读取失败: {error}", "Read failed: {error}"),
+    ("common.write_failed", "写入失败: {error}", "Write failed: {error}"),
+    ("common.read_dir_failed", "读取目录失败: {error}", "Failed to read directory: {error}"),
+    ("common.read_file_failed", "读取文件失败: {error}", "Failed to read file: {error}"),
+    ("common.path_not_found", "路径不存在", "Path does not exist"),
+    ("common.not_a_directory", "路径不是目录", "Path is not a directory"),
+    ("common.file_not_found", "文件不存在", "File does not exist"),
+    ("common.decode_failed", "解码失败: {error}", "Decoding failed: {error}"),
+    ("common.command_failed", "执行命令失败: {error}", "Failed to run command: {error}"),
+    ("common.open_folder_failed", "打开文件夹失败: {error}", "Failed to open folder: {error}"),
+    ("common.opened", "已打开: {path}", "Opened: {path}"),
+    // 外部进程
+    ("runner.timed_out", "操作超时（{seconds} 秒）", "Operation timed out after {seconds} seconds"),
+    ("runner.cancelled", "操作已取消", "Operation was cancelled"),
+    // git
+    ("git.not_installed", "未找到 git，请先安装 Git", "git was not found, please install Git first"),
+    ("git.command_failed", "执行 git 命令失败: {error}", "Failed to run git: {error}"),
+    ("git.project_not_found", "项目路径不存在: {path}", "Project path does not exist: {path}"),
+    ("git.not_repository", "路径不是 Git 仓库: {path}", "Path is not a Git repository: {path}"),
+    ("git.list_branches_failed", "获取分支列表失败", "Failed to list branches"),
+    ("git.branch_required", "分支名称不能为空", "Branch name must not be empty"),
+    ("git.switch_failed", "切换分支失败", "Failed to switch branch"),
+    ("git.switched", "已切换到分支 {branch}", "Switched to branch {branch}"),
+    ("git.list_worktrees_failed", "获取 worktree 列表失败", "Failed to list worktrees"),
+    ("git.worktree_path_required", "Worktree 路径不能为空", "Worktree path must not be empty"),
+    ("git.create_worktree_failed", "创建 worktree 失败", "Failed to create worktree"),
+    ("git.worktree_created", "已为分支 {branch} 创建 worktree: {path}", "Created worktree for branch {branch}: {path}"),
+    ("git.remove_worktree_failed", "删除 worktree 失败", "Failed to remove worktree"),
+    ("git.worktree_removed", "已删除 worktree: {path}", "Removed worktree: {path}"),
+    // 知识库
+    ("knowledge.not_markdown", "文件不是 Markdown", "File is not a Markdown file"),
+    // Node 版本管理
+    ("nvm.command_error", "命令执行错误", "The version manager returned an error"),
+    ("nvm.manager_not_found", "未检测到可用的 Node 版本管理器 (nvm 或 fnm)", "No Node version manager (nvm or fnm) was found"),
+    ("nvm.version_not_found", "未找到 Node 版本: {version}", "Node version not found: {version}"),
+    ("nvm.switched", "成功切换到Node {version} (使用{manager})", "Switched to Node {version} (using {manager})"),
+    ("nvm.switched_highest", "已切换到最高版本: {version}", "Switched to the highest version: {version}"),
+    ("nvm.no_versions", "没有找到可用的Node版本", "No usable Node version was found"),
+    // 编辑器
+    ("editor.not_found", "未找到编辑器: {id}", "Editor not found: {id}"),
+    ("editor.execute_failed", "启动编辑器失败: {error}", "Failed to execute editor: {error}"),
+    ("editor.open_app_failed", "打开应用失败: {error}", "Failed to open app: {error}"),
+    ("editor.open_project_failed", "打开项目失败", "Failed to open project"),
+    ("editor.opened_project", "已在 {editor} 中打开项目", "Opened project in {editor}"),
+    ("editor.opened_file", "已在 {editor} 中打开 {file}", "Opened {file} in {editor}"),
+    // 项目扫描
+    ("scanner.workspace_not_found", "Workspace路径不存在: {path}", "Workspace path does not exist: {path}"),
+    ("scanner.not_a_directory", "路径不是目录: {path}", "Path is not a directory: {path}"),
+    // 内置终端
+    ("terminal.session_not_found", "会话不存在: {id}", "Terminal session not found: {id}"),
+    ("terminal.create_pty_failed", "创建 PTY 失败: {error}", "Failed to create PTY: {error}"),
+    ("terminal.spawn_shell_failed", "启动 shell 失败: {error}", "Failed to start shell: {error}"),
+    ("terminal.writer_failed", "获取 writer 失败: {error}", "Failed to get PTY writer: {error}"),
+    ("terminal.write_failed", "写入失败: {error}", "Failed to write to terminal: {error}"),
+    ("terminal.flush_failed", "刷新失败: {error}", "Failed to flush terminal: {error}"),
+    ("terminal.resize_failed", "调整大小失败: {error}", "Failed to resize terminal: {error}"),
+    ("terminal.kill_failed", "终止失败: {error}", "Failed to terminate shell: {error}"),
+    // kitty
+    ("kitty.check_install_failed", "检查kitty安装状态失败: {error}", "Failed to check whether kitty is installed: {error}"),
+    ("kitty.check_command_failed", "检查kitty命令失败: {error}", "Failed to run kitty: {error}"),
+    ("kitty.not_installed", "Kitty未安装。请先安装Kitty终端。", "Kitty is not installed. Please install the Kitty terminal first."),
+    ("kitty.kitten_unavailable", "Kitten不可用。请确保Kitty已正确安装。", "Kitten is unavailable. Please make sure Kitty is installed correctly."),
+    ("kitty.exited_early", "kitty 进程提前退出: {status}", "kitty exited early: {status}"),
+    ("kitty.socket_timeout", "等待kitty socket连接超时 (重试{retries}次)", "Timed out waiting for the kitty socket ({retries} retries)"),
+    ("kitty.start_failed", "启动kitty实例失败: {error}", "Failed to start kitty instance: {error}"),
+    ("kitty.connect_failed", "连接kitty失败 ({socket}): {error}", "Failed to connect to kitty ({socket}): {error}"),
+    ("kitty.unsupported_socket", "不支持的kitty socket地址: {socket}", "Unsupported kitty socket address: {socket}"),
+    ("kitty.protocol_io_failed", "与kitty通信失败: {error}", "Failed to communicate with kitty: {error}"),
+    ("kitty.protocol_parse_failed", "解析kitty响应失败: {error}", "Failed to parse kitty response: {error}"),
+    ("kitty.remote_error", "kitty执行 {command} 失败: {error}", "kitty failed to run {command}: {error}"),
+    ("kitty.tab_manager_unavailable", "无法访问标签页管理器", "Tab manager is unavailable"),
+    ("kitty.tab_not_found", "标签页不存在", "Tab not found"),
+    ("kitty.process_not_found", "未找到运行中的命令: {id}", "No running command found: {id}"),
+    ("kitty.remote_close_failed", "kitty控制失败: {error}", "kitty remote control failed: {error}"),
+    ("kitty.window_not_found", "未找到命令 {id} 所在的kitty窗口", "No kitty window found for command {id}"),
+    ("kitty.kill_window_failed", "结束kitty窗口进程失败: {error}", "Failed to kill the kitty window process: {error}"),
+    ("kitty.force_killed", "已强制终止相关进程", "Related processes were force-killed"),
+    ("kitty.force_kill_failed", "强制终止也失败: {error}", "Force kill also failed: {error}"),
+    ("kitty.force_kill_spawn_failed", "强制终止命令执行失败", "Failed to run force kill"),
+    ("kitty.terminated", "命令 {id} 已终止", "Command {id} terminated"),
+    ("kitty.terminate_failed", "终止命令失败: {error}", "Failed to terminate command: {error}"),
+    ("kitty.invalid_socket_prefix", "socket 前缀只能包含字母、数字、- 与 _", "Socket prefix may only contain letters, digits, - and _"),
+    ("kitty.invalid_retry", "重试次数至少为 1，间隔不少于 {min} 毫秒", "Retries must be at least 1 and the interval at least {min} ms"),
+    ("kitty.invalid_override", "无效的 override（应为 key=value）: {override}", "Invalid override (expected key=value): {override}"),
+    ("kitty.invalid_start_as", "无效的窗口启动状态: {value}", "Invalid window start state: {value}"),
+    ("kitty.keys_required", "至少需要一个按键", "At least one key is required"),
+    ("kitty.unknown_action", "该任务不支持动作 {action}", "Action {action} is not available for this task"),
+    ("task_action.restart", "重启", "Restart"),
+    ("task_action.open", "在浏览器中打开", "Open in browser"),
+    ("task_action.show_url", "显示地址", "Show URL"),
+    ("task_action.clear", "清屏", "Clear"),
+    ("task_action.quit", "退出", "Quit"),
+    ("task_action.reload", "重新加载", "Reload"),
+    ("task_action.open_web", "打开 Web", "Open web"),
+    ("task_action.open_android", "打开 Android", "Open Android"),
+    ("task_action.open_ios", "打开 iOS", "Open iOS"),
+    ("task_action.dev_menu", "开发菜单", "Dev menu"),
+    ("task_action.debugger", "调试器", "Debugger"),
+    ("task_action.run_all", "运行全部测试", "Run all tests"),
+    ("task_action.run_failed", "只运行失败的测试", "Run failed tests"),
+    ("task_action.changed_only", "只运行改动相关的测试", "Run tests for changed files"),
+    ("task_action.update_snapshots", "更新快照", "Update snapshots"),
+    ("task_action.rerun", "重新运行", "Re-run"),
+    ("task_action.interrupt", "中断 (Ctrl+C)", "Interrupt (Ctrl+C)"),
+    ("kitty.session_empty", "会话文件中没有可导入的命令", "The session file contains no commands to import"),
+    ("kitty.session_nothing_running", "没有运行中的标签页可以导出", "No running tabs to export"),
+    ("kitty.session_multiline", "{title} 的目录或命令包含换行，无法写入会话文件", "The directory or command of {title} contains a line break and cannot be written to a session file"),
+    // 外部终端后端
+    ("terminal_backend.unsupported", "{backend} 不支持 {operation}", "{backend} does not support {operation}"),
+    ("terminal_backend.spawn_failed", "无法执行 {program}: {error}", "Failed to run {program}: {error}"),
+    ("terminal_backend.not_available", "{backend} 不可用", "{backend} is not available"),
+    ("terminal_backend.none_available", "没有可用的外部终端", "No external terminal is available"),
+    ("terminal_backend.fallback_builtin", "⚠️ 没有可用的外部终端，改用内置终端", "⚠️ No external terminal available, using the built-in terminal"),
+    ("terminal_backend.tab_opened", "✅ 已在 {backend} 中打开标签页", "✅ Opened a tab in {backend}"),
+    ("terminal_backend.tmux_failed", "tmux 命令执行失败", "tmux command failed"),
+    ("terminal_backend.wezterm_failed", "wezterm cli 命令执行失败", "wezterm cli command failed"),
+    ("terminal_backend.parse_failed", "解析终端输出失败: {error}", "Failed to parse terminal output: {error}"),
+    ("terminal_backend.start_timeout", "等待 {backend} 启动超时", "Timed out waiting for {backend} to start"),
+    // 命令执行输出
+    ("exec.working_dir", "📁 工作目录: {dir}", "📁 Working directory: {dir}"),
+    ("exec.project_name", "📂 项目名称: {name}", "📂 Project: {name}"),
+    ("exec.raw_command", "🔧 原始命令: {command}", "🔧 Original command: {command}"),
+    ("exec.command", "🔧 执行命令: {command}", "🔧 Command: {command}"),
+    ("exec.command_name", "🚀 命令名称: {name}", "🚀 Command name: {name}"),
+    ("exec.package_manager", "📦 包管理器: {manager}", "📦 Package manager: {manager}"),
+    ("exec.final_command", "📝 完整命令: {command}", "📝 Full command: {command}"),
+    ("exec.env_injected", "🌱 注入环境变量: {count} 个", "🌱 Injected environment variables: {count}"),
+    ("exec.env_failed", "❌ 加载环境变量失败: {error}", "❌ Failed to load environment: {error}"),
+    ("exec.port_in_use", "❌ {error}", "❌ {error}"),
+    ("exec.node_version", "📋 使用Node版本: {version}", "📋 Using Node version: {version}"),
+    ("exec.node_switch_failed", "❌ 切换Node版本失败: {error}", "❌ Failed to switch Node version: {error}"),
+    ("exec.node_switched", "✅ Node版本切换成功", "✅ Node version switched"),
+    ("exec.args_invalid", "❌ 参数校验失败: {error}", "❌ Invalid arguments: {error}"),
+    ("exec.extra_args", "🧩 附加参数:{args}", "🧩 Extra arguments:{args}"),
+    ("exec.starting", "🚀 开始执行命令...", "🚀 Running command..."),
+    ("exec.stdout", "📤 标准输出:", "📤 Standard output:"),
+    ("exec.stderr", "📤 错误输出:", "📤 Standard error:"),
+    ("exec.finished", "🔚 命令执行完成 (退出码: {status})", "🔚 Command finished ({status})"),
+    ("exec.succeeded", "✅ 命令执行成功", "✅ Command succeeded"),
+    ("exec.failed", "❌ 命令执行失败", "❌ Command failed"),
+    ("exec.spawn_failed", "❌ 执行命令失败: {error}", "❌ Failed to run command: {error}"),
+    ("exec.timed_out", "⏱️ 命令执行超时 ({seconds} 秒)，已终止", "⏱️ Command timed out after {seconds} seconds and was terminated"),
+    ("exec.cancelled", "🛑 命令已取消", "🛑 Command cancelled"),
+    ("exec.socket", "🔌 控制socket: {socket}", "🔌 Control socket: {socket}"),
+    ("exec.kitty_running", "🖥️ 正在使用kitty终端执行...", "🖥️ Running in kitty..."),
+    ("exec.kitty_started", "✅ kitty终端启动成功", "✅ kitty started"),
+    ("exec.kitty_window_launched", "在kitty终端中成功启动命令: {name}", "Started command in kitty: {name}"),
+    ("exec.kitty_window_failed", "启动kitty终端失败: {error}", "Failed to start kitty: {error}"),
+    ("exec.kitty_connected", "✅ 已连接到现有kitty实例", "✅ Connected to the running kitty instance"),
+    ("exec.kitty_starting", "🔄 未找到kitty实例，正在启动新的kitty...", "🔄 No kitty instance found, starting a new one..."),
+    ("exec.kitty_instance_started", "✅ kitty实例启动成功 (PID: {pid})", "✅ kitty instance started (PID: {pid})"),
+    ("exec.register_tab_failed", "⚠️ 注册标签页失败: {error}", "⚠️ Failed to register tab: {error}"),
+    ("exec.kitty_tab_created", "✅ kitty标签页创建成功", "✅ kitty tab created"),
+    ("exec.kitty_tab_launched", "在kitty标签页中成功启动命令: {name}", "Started command in a kitty tab: {name}"),
+    ("exec.kitty_tab_focused", "↩️ 命令仍在运行，已切换到现有标签页", "↩️ Command is still running, switched to its existing tab"),
+    ("exec.kitty_tab_focused_message", "命令 {name} 仍在运行，已切换到它的标签页", "{name} is still running, switched to its tab"),
+    ("exec.preflight_failed", "❌ 依赖预检失败: {error}", "❌ Dependency preflight failed: {error}"),
+    ("exec.install_succeeded", "✅ 依赖安装完成", "✅ Dependencies installed"),
+    ("exec.install_failed", "❌ 依赖安装失败 ({status})", "❌ Dependency install failed ({status})"),
+    // 命令模板
+    ("template.not_boolean", "参数 {name} 必须是布尔值", "Argument {name} must be a boolean"),
+    ("template.bool_missing_flag", "布尔参数 {name} 缺少 flag", "Boolean argument {name} has no flag"),
+    ("template.required", "参数 {name} 为必填项", "Argument {name} is required"),
+    ("template.invalid_pattern", "参数 {name} 的校验规则无效: {error}", "Validation pattern of argument {name} is invalid: {error}"),
+    ("template.pattern_mismatch", "参数 {name} 不符合格式要求: {pattern}", "Argument {name} does not match the required format: {pattern}"),
+    ("template.not_in_options", "参数 {name} 的值必须是 {options} 之一", "Argument {name} must be one of {options}"),
+    ("template.file_not_found", "参数 {name} 指向的文件不存在: {path}", "File for argument {name} does not exist: {path}"),
+    ("template.name_required", "参数名称不能为空", "Argument name must not be empty"),
+    // 环境配置
+    ("env.profile_not_found", "环境配置不存在: {name}", "Environment profile not found: {name}"),
+    // 端口
+    ("ports.kill_failed", "无法终止进程 {pid}", "Unable to terminate process {pid}"),
+    ("ports.not_in_use", "端口 {port} 未被占用", "Port {port} is not in use"),
+    ("ports.session_closed", "已关闭终端会话 {id}", "Closed terminal session {id}"),
+    ("ports.owner_unknown", "无法确定占用端口 {port} 的进程", "Unable to determine which process uses port {port}"),
+    ("ports.process_killed", "已终止进程 {pid}", "Terminated process {pid}"),
+    ("ports.in_use", "端口 {port} 已被 {owner} 占用，请先释放端口", "Port {port} is already used by {owner}; free it before starting"),
+    // 问题匹配器
+    ("matcher.missing_pattern", "匹配器 {name} 没有 pattern", "Problem matcher {name} has no pattern"),
+    ("matcher.invalid_regex", "匹配器 {name} 正则无效: {error}", "Problem matcher {name} has an invalid regex: {error}"),
+    ("matcher.builtin_conflict", "匹配器名称与内置匹配器冲突: {name}", "Problem matcher name conflicts with a built-in matcher: {name}"),
+    // 定时任务
+    ("schedule.invalid_cron", "无效的 cron 表达式 {expression}: {error}", "Invalid cron expression {expression}: {error}"),
+    ("schedule.command_missing", "定时任务缺少要执行的命令", "Schedule has no command to run"),
+    ("schedule.previous_running", "上一次运行尚未结束: {run}", "Previous run is still in progress: {run}"),
+    ("schedule.name_required", "定时任务名称不能为空", "Schedule name must not be empty"),
+    ("schedule.projects_required", "定时任务至少需要一个项目", "Schedule needs at least one project"),
+    ("schedule.not_found", "定时任务不存在: {id}", "Schedule not found: {id}"),
+    // 任务组
+    ("task_group.name_required", "任务组名称不能为空", "Task group name must not be empty"),
+    ("task_group.tasks_required", "任务组至少需要一个任务", "Task group needs at least one task"),
+    ("task_group.not_found", "任务组不存在: {id}", "Task group not found: {id}"),
+    ("recovery.not_orphan", "{target} 不是遗留的实例或进程", "{target} is not a leftover instance or process"),
+    // 后台任务
+    ("task.spawn_failed", "启动命令失败: {error}", "Failed to start command: {error}"),
+    ("task.queued", "已达到并发上限，命令已加入队列", "Concurrency limit reached; the command was queued"),
+    // 基准测试
+    ("benchmark.invalid_runs", "运行次数需在 1 到 {max} 之间", "Number of runs must be between 1 and {max}"),
+    ("benchmark.prepare_failed", "prepare 命令退出码 {code}", "Prepare command exited with code {code}"),
+    ("benchmark.install_failed", "依赖安装退出码 {code}", "Dependency install exited with code {code}"),
+    ("benchmark.command_failed", "第 {run} 次运行失败，退出码 {code}", "Run {run} failed with exit code {code}"),
+    ("benchmark.export_failed", "导出失败: {error}", "Export failed: {error}"),
+    // 生命周期 Hook
+    ("hooks.name_required", "Hook 名称不能为空", "Hook name must not be empty"),
+    ("hooks.events_required", "Hook 至少需要一个触发事件", "Hook needs at least one event"),
+    ("hooks.scope_required", "Hook 缺少绑定的命令或工作区", "Hook has no command or workspace to attach to"),
+    ("hooks.action_required", "Hook 缺少要执行的动作", "Hook has no action to perform"),
+    ("hooks.invalid_url", "无效的 URL {url}: {error}", "Invalid URL {url}: {error}"),
+    ("hooks.http_only", "仅支持 http:// 地址: {url}", "Only http:// URLs are supported: {url}"),
+    ("hooks.invalid_pattern", "无效的正则表达式: {error}", "Invalid regular expression: {error}"),
+    ("hooks.request_failed", "请求 {url} 失败: {error}", "Request to {url} failed: {error}"),
+    ("hooks.webhook_sent", "已发送，响应状态 {status}", "Delivered with status {status}"),
+    ("hooks.webhook_rejected", "接收端返回状态 {status}", "Receiver responded with status {status}"),
+    ("hooks.note_appended", "已追加到 {path}", "Appended to {path}"),
+    ("hooks.run_queued", "已提交运行 {run}", "Submitted run {run}"),
+    ("hooks.script_failed", "脚本退出码 {code}", "Script exited with code {code}"),
+    ("hooks.depth_exceeded", "Hook 嵌套触发超过 {depth} 层，已跳过", "Hooks nested more than {depth} levels deep, skipped"),
+    // 文件监听
+    ("watch.invalid_glob", "无效的 glob {pattern}: {error}", "Invalid glob {pattern}: {error}"),
+    ("watch.build_glob_failed", "构建 glob 失败: {error}", "Failed to build glob set: {error}"),
+    ("watch.parse_gitignore_failed", "解析 .gitignore 失败: {error}", "Failed to parse .gitignore: {error}"),
+    ("watch.not_found", "监听不存在: {id}", "Watch not found: {id}"),
+    ("watch.invalid_project_path", "项目路径无效: {error}", "Invalid project path: {error}"),
+    ("watch.create_failed", "创建文件监听失败: {error}", "Failed to create file watcher: {error}"),
+    ("watch.watch_dir_failed", "监听目录失败: {error}", "Failed to watch directory: {error}"),
+    // 依赖预检
+    ("deps.node_modules_missing", "node_modules 不存在", "node_modules is missing"),
+    ("deps.lockfile_changed", "{lockfile} 在上次安装后有更新", "{lockfile} changed since the last install"),
+    ("deps.package_manager_changed", "packageManager 已变为 {expected}，安装时为 {installed}", "packageManager changed to {expected}, installed with {installed}"),
+    ("deps.warning", "⚠️ 依赖可能需要重新安装（{reason}），可执行 {command}", "⚠️ Dependencies may need reinstalling ({reason}), run {command}"),
+    ("deps.installing", "📦 依赖需要重新安装（{reason}），先执行 {command}", "📦 Dependencies need reinstalling ({reason}), running {command} first"),
+    ("deps.install_required", "依赖需要重新安装（{reason}），是否先执行 {command}？", "Dependencies need reinstalling ({reason}). Run {command} first?"),
+    // 执行预览
+    ("preview.dependencies_outdated", "依赖需要重新安装（{reason}），请执行 {command}", "Dependencies need to be reinstalled ({reason}), run {command}"),
+    ("preview.install_before_run", "依赖需要重新安装（{reason}），运行前会执行 {command}", "Dependencies need to be reinstalled ({reason}), {command} will run first"),
+    ("preview.package_json_missing", "工作目录中没有 package.json", "No package.json in the working directory"),
+    ("preview.script_not_found", "package.json 中没有脚本 {script}", "package.json has no script named {script}"),
+    ("preview.args_invalid", "参数校验失败: {error}", "Invalid arguments: {error}"),
+    ("preview.node_will_install", "Node {version} 尚未安装，执行前会通过 {manager} 安装", "Node {version} is not installed, it will be installed with {manager} before running"),
+    ("preview.node_not_installed", "Node {version} 尚未安装，{manager} 切换版本会失败", "Node {version} is not installed, switching with {manager} will fail"),
+    ("preview.node_unavailable", "无法切换到 Node {version}: {error}", "Cannot switch to Node {version}: {error}"),
+    ("preview.env_failed", "加载环境变量失败: {error}", "Failed to load environment: {error}"),
+    ("preview.port_in_use", "端口 {port} 已被 {owner} 占用", "Port {port} is already in use by {owner}"),
+    ("preview.port_in_use_unknown", "端口 {port} 已被占用", "Port {port} is already in use"),
+    ("preview.kitty_not_installed", "未找到 kitty，请先安装", "kitty was not found, please install it first"),
+    // 内嵌网页
+    ("webview.invalid_url", "无效的 URL: {error}", "Invalid URL: {error}"),
+    ("webview.not_found", "未找到网页视图: {label}", "Webview not found: {label}"),
+    ("webview.create_failed", "创建网页视图失败: {error}", "Failed to create child webview: {error}"),
+    ("webview.navigate_failed", "页面跳转失败: {error}", "Failed to navigate: {error}"),
+    ("webview.close_failed", "关闭网页视图失败: {error}", "Failed to close webview: {error}"),
+    ("webview.hide_failed", "隐藏网页视图失败: {error}", "Failed to hide webview: {error}"),
+    ("webview.show_failed", "显示网页视图失败: {error}", "Failed to show webview: {error}"),
+    ("webview.set_position_failed", "设置位置失败: {error}", "Failed to set position: {error}"),
+    ("webview.set_size_failed", "设置大小失败: {error}", "Failed to set size: {error}"),
+];
+
+lazy_static! {
+    static ref MESSAGES: HashMap<&'static str, (&'static str, &'static str)> = CATALOG
+        .iter()
+        .map(|(key, zh, en)| (*key, (*zh, *en)))
+        .collect();
+    static ref SETTINGS: Arc<Mutex<Option<LocaleSettings>>> = Arc::new(Mutex::new(None));
+    static ref SYSTEM_LOCALE: Locale = detect_system_locale();
+}
+
+fn with_settings<R>(f: impl FnOnce(&mut LocaleSettings) -> R) -> Result<R, String> {
+    let mut guard = SETTINGS
+        .lock()
+        .map_err(|e| format_message("common.lock_failed", *SYSTEM_LOCALE, &[("error", &e)]))?;
+    Ok(f(guard.get_or_insert_with(|| storage::load(LOCALE_STORE))))
+}
+
+fn detect_system_locale() -> Locale {
+    for var in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+        if let Ok(value) = std::env::var(var) {
+            if !value.is_empty() && value != "C" && value != "POSIX" {
+                return Locale::from_tag(&value);
+            }
+        }
+    }
+
+    // 从 Finder 启动的应用通常没有 LANG，读取系统偏好设置
+    #[cfg(target_os = "macos")]
+    {
+        if let Ok(output) = std::process::Command::new("defaults")
+            .args(["read", "-g", "AppleLocale"])
+            .output()
+        {
+            let value = String::from_utf8_lossy(&output.stdout);
+            if output.status.success() && !value.trim().is_empty() {
+                return Locale::from_tag(&value);
+            }
+        }
+    }
+
+    // 无法判断时保持原先的中文文案
+    Locale::ZhCn
+}
+
+pub fn current_locale() -> Locale {
+    with_settings(|settings| settings.locale)
+        .ok()
+        .flatten()
+        .unwrap_or(*SYSTEM_LOCALE)
+}
+
+fn lookup(key: &str, locale: Locale) -> &str {
+    match MESSAGES.get(key) {
+        Some((zh, en)) => match locale {
+            Locale::ZhCn => zh,
+            Locale::En => en,
+        },
+        // 缺失的 key 原样返回，便于发现遗漏
+        None => key,
+    }
+}
+
+pub fn format_message(key: &str, locale: Locale, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = lookup(key, locale).to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    text
+}
+
+// 按当前语言取文案
+pub fn t(key: &str) -> String {
+    format_message(key, current_locale(), &[])
+}
+
+// 按当前语言取文案并填充占位符
+pub fn tf(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    format_message(key, current_locale(), args)
+}
+
+// 追加一行文案，用于拼接命令执行过程的输出
+pub fn push_line(output: &mut String, key: &str, args: &[(&str, &dyn Display)]) {
+    output.push_str(&tf(key, args));
+    output.push('\n');
+}
+
+fn locale_info() -> Result<LocaleInfo, String> {
+    let preference = with_settings(|settings| settings.locale)?;
+    Ok(LocaleInfo {
+        locale: preference.unwrap_or(*SYSTEM_LOCALE),
+        preference,
+        system: *SYSTEM_LOCALE,
+    })
+}
+
+// ---------- Tauri 命令 ----------
+
+#[tauri::command]
+pub fn get_locale() -> Result<LocaleInfo, String> {
+    locale_info()
+}
+
+// locale 为空时恢复跟随系统
+#[tauri::command]
+pub fn set_locale(locale: Option<Locale>) -> Result<LocaleInfo, String> {
+    let snapshot = with_settings(|settings| {
+        settings.locale = locale;
+        settings.clone()
+    })?;
+    storage::save(LOCALE_STORE, &snapshot)?;
+    locale_info()
+}
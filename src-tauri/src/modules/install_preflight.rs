@@ -0,0 +1,327 @@
+// 依赖安装预检模块
+//
+// 运行脚本前检查依赖是否需要重新安装，避免拉取代码后出现 `command not found: vite`：
+// - node_modules 不存在
+// - lockfile 比安装标记（node_modules/.modules.yaml、.package-lock.json 等）更新
+// - package.json 中的 packageManager 与安装时使用的不一致
+// 按工作区配置的策略处理：仅提示、运行前询问、或自动在脚本前执行安装命令
+use crate::modules::error::{lock_error, AppError, AppResult, ErrorCode};
+use crate::modules::i18n;
+use crate::modules::project_scanner;
+use crate::modules::storage;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+const STORE_NAME: &str = "install_policies";
+
+// 各包管理器写入 node_modules 的安装标记
+const INSTALL_MARKERS: &[(&str, &str)] = &[
+    ("pnpm", ".modules.yaml"),
+    ("npm", ".package-lock.json"),
+    ("yarn", ".yarn-state.yml"),
+    ("yarn", ".yarn-integrity"),
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallPolicy {
+    // 只在输出中提示
+    #[default]
+    Warn,
+    // 返回 dependencies_outdated 错误，由前端询问后带上选择重新执行
+    Prompt,
+    // 自动在脚本前执行安装命令
+    Auto,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum DependencyIssue {
+    NodeModulesMissing,
+    LockfileChanged { lockfile: String },
+    PackageManagerChanged { expected: String, installed: String },
+}
+
+impl DependencyIssue {
+    pub fn describe(&self) -> String {
+        match self {
+            DependencyIssue::NodeModulesMissing => i18n::t("deps.node_modules_missing"),
+            DependencyIssue::LockfileChanged { lockfile } => {
+                i18n::tf("deps.lockfile_changed", &[("lockfile", lockfile)])
+            }
+            DependencyIssue::PackageManagerChanged {
+                expected,
+                installed,
+            } => i18n::tf(
+                "deps.package_manager_changed",
+                &[("expected", expected), ("installed", installed)],
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyCheck {
+    pub project_path: String,
+    pub package_manager: String,
+    pub install_command: String,
+    pub policy: InstallPolicy,
+    pub issues: Vec<DependencyIssue>,
+}
+
+impl DependencyCheck {
+    pub fn summary(&self) -> String {
+        self.issues
+            .iter()
+            .map(|issue| issue.describe())
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+// 预检结果：install_command 为需要在脚本前执行的安装命令
+#[derive(Debug, Clone)]
+pub struct Preflight {
+    pub check: DependencyCheck,
+    pub install_command: Option<String>,
+}
+
+impl Preflight {
+    // 追加到执行输出中的说明
+    pub fn report(&self, output: &mut String) {
+        if self.check.issues.is_empty() {
+            return;
+        }
+        match &self.install_command {
+            Some(command) => i18n::push_line(
+                output,
+                "deps.installing",
+                &[("reason", &self.check.summary()), ("command", command)],
+            ),
+            None => i18n::push_line(
+                output,
+                "deps.warning",
+                &[
+                    ("reason", &self.check.summary()),
+                    ("command", &self.check.install_command),
+                ],
+            ),
+        }
+    }
+}
+
+lazy_static! {
+    static ref POLICIES: Arc<Mutex<Option<HashMap<String, InstallPolicy>>>> =
+        Arc::new(Mutex::new(None));
+}
+
+fn with_policies<R>(f: impl FnOnce(&mut HashMap<String, InstallPolicy>) -> R) -> AppResult<R> {
+    let mut guard = POLICIES.lock().map_err(lock_error)?;
+    let policies = guard.get_or_insert_with(|| storage::load(STORE_NAME));
+    Ok(f(policies))
+}
+
+// 项目所属工作区的策略，取路径前缀最长的一项
+pub fn policy_for(project_path: &str) -> InstallPolicy {
+    let project = Path::new(project_path);
+    with_policies(|policies| {
+        policies
+            .iter()
+            .filter(|(workspace, _)| project.starts_with(workspace))
+            .max_by_key(|(workspace, _)| workspace.len())
+            .map(|(_, policy)| *policy)
+    })
+    .ok()
+    .flatten()
+    .unwrap_or_default()
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn lockfile_for(package_manager: &str) -> &'static str {
+    match package_manager {
+        "pnpm" => "pnpm-lock.yaml",
+        "yarn" => "yarn.lock",
+        _ => "package-lock.json",
+    }
+}
+
+// pnpm 在 .modules.yaml 中记录了安装时的 packageManager
+fn recorded_package_manager(marker: &Path) -> Option<String> {
+    let content = fs::read_to_string(marker).ok()?;
+    content.lines().find_map(|line| {
+        line.trim().strip_prefix("packageManager:").map(|value| {
+            value
+                .trim()
+                .trim_matches(|c| c == '"' || c == '\'')
+                .to_string()
+        })
+    })
+}
+
+pub fn check_project(project_path: &str, package_manager: Option<&str>) -> DependencyCheck {
+    let dir = Path::new(project_path);
+    let package_json = fs::read_to_string(dir.join("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok());
+
+    // packageManager 字段形如 pnpm@8.6.0，优先于 lockfile 推断
+    let declared = package_json
+        .as_ref()
+        .and_then(|p| p.get("packageManager"))
+        .and_then(|v| v.as_str())
+        .map(|v| v.to_string());
+    let declared_name = declared
+        .as_deref()
+        .and_then(|v| v.split('@').next())
+        .filter(|name| ["npm", "pnpm", "yarn"].contains(name))
+        .map(|name| name.to_string());
+    let package_manager = declared_name
+        .clone()
+        .or_else(|| package_manager.map(|pm| pm.to_string()))
+        .unwrap_or_else(|| project_scanner::detect_package_manager(dir));
+
+    let mut check = DependencyCheck {
+        project_path: project_path.to_string(),
+        install_command: format!("{} install", package_manager),
+        package_manager,
+        policy: policy_for(project_path),
+        issues: Vec::new(),
+    };
+
+    let has_dependencies = package_json.as_ref().is_some_and(|p| {
+        ["dependencies", "devDependencies"].iter().any(|key| {
+            p.get(key)
+                .and_then(|deps| deps.as_object())
+                .is_some_and(|deps| !deps.is_empty())
+        })
+    });
+    if !has_dependencies {
+        return check;
+    }
+
+    let node_modules = dir.join("node_modules");
+    if !node_modules.is_dir() {
+        check.issues.push(DependencyIssue::NodeModulesMissing);
+        return check;
+    }
+
+    let installed = INSTALL_MARKERS
+        .iter()
+        .map(|(manager, file)| (*manager, node_modules.join(file)))
+        .find(|(_, marker)| marker.exists());
+    let Some((installed_manager, marker)) = installed else {
+        return check;
+    };
+
+    if let Some(declared) = &declared {
+        let changed = if installed_manager != check.package_manager {
+            true
+        } else {
+            recorded_package_manager(&marker).is_some_and(|recorded| &recorded != declared)
+        };
+        if changed {
+            check.issues.push(DependencyIssue::PackageManagerChanged {
+                expected: declared.clone(),
+                installed: recorded_package_manager(&marker)
+                    .unwrap_or_else(|| installed_manager.to_string()),
+            });
+        }
+    }
+
+    let lockfile = lockfile_for(&check.package_manager);
+    if let (Some(lock_time), Some(marker_time)) = (modified(&dir.join(lockfile)), modified(&marker))
+    {
+        if lock_time > marker_time {
+            check.issues.push(DependencyIssue::LockfileChanged {
+                lockfile: lockfile.to_string(),
+            });
+        }
+    }
+
+    check
+}
+
+// 执行脚本前调用
+// decision 为用户对提示的选择；unattended 表示无人值守（定时任务、文件监听），此时询问策略按提示处理
+pub fn preflight(
+    project_path: &str,
+    package_manager: Option<&str>,
+    decision: Option<bool>,
+    unattended: bool,
+) -> AppResult<Preflight> {
+    let check = check_project(project_path, package_manager);
+    if check.issues.is_empty() {
+        return Ok(Preflight {
+            check,
+            install_command: None,
+        });
+    }
+
+    let install = match (decision, check.policy) {
+        (Some(decision), _) => decision,
+        (None, InstallPolicy::Auto) => true,
+        (None, InstallPolicy::Prompt) if !unattended => {
+            return Err(
+                AppError::new(ErrorCode::DependenciesOutdated, "deps.install_required")
+                    .arg("reason", check.summary())
+                    .arg("command", &check.install_command)
+                    .with_path(project_path),
+            );
+        }
+        _ => false,
+    };
+
+    if !install {
+        eprintln!(
+            "[DEPS] {} 依赖可能需要更新: {}",
+            project_path,
+            check.summary()
+        );
+    }
+    Ok(Preflight {
+        install_command: install.then(|| check.install_command.clone()),
+        check,
+    })
+}
+
+// ---------- Tauri 命令 ----------
+
+#[tauri::command]
+pub fn check_dependencies(
+    project_path: String,
+    package_manager: Option<String>,
+) -> AppResult<DependencyCheck> {
+    Ok(check_project(&project_path, package_manager.as_deref()))
+}
+
+#[tauri::command]
+pub fn get_install_policies() -> AppResult<HashMap<String, InstallPolicy>> {
+    with_policies(|policies| policies.clone())
+}
+
+// policy 为空时恢复默认（仅提示）
+#[tauri::command]
+pub fn set_install_policy(workspace_path: String, policy: Option<InstallPolicy>) -> AppResult<()> {
+    let snapshot = with_policies(|policies| {
+        match policy {
+            Some(policy) => policies.insert(workspace_path, policy),
+            None => policies.remove(&workspace_path),
+        };
+        policies.clone()
+    })?;
+    storage::save(STORE_NAME, &snapshot)?;
+    Ok(())
+}
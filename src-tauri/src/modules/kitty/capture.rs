@@ -0,0 +1,230 @@
+// 标签页输出抓取
+//
+// 通过 get-text 读取 kitty 标签页的文本，与已提交的行对齐得到新增的行，
+// 写入对应的运行记录：与后台任务一样匹配诊断、保存输出末尾，并以 task-output 事件推送
+// - 定期抓取只读可见屏幕，每个标签页只保留最近提交的若干行用于对齐
+// - 最后一行可能尚未输出完整，标签页结束前不提交
+// - 两次抓取间输出超过一屏或被 clear 清屏时，读取含回滚区的全部文本，
+//   按已提交的最后几行最后一次出现的位置重新定位，找不到时全部作为新输出
+use crate::modules::error::{lock_error, AppError, AppResult, ErrorCode};
+use crate::modules::kitty::core::{KittyTab, TabStatus, KITTY_TAB_MANAGER};
+use crate::modules::kitty::protocol::{self, match_window, GetText};
+use crate::modules::task_runner::{self, RunStatus};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::AppHandle;
+
+// 定位时使用的已提交行数
+const ANCHOR_LINES: usize = 3;
+// 每个标签页保留的已提交行数，只用于和下一次抓取的内容对齐
+const MAX_COMMITTED_LINES: usize = 200;
+const SCREEN_EXTENT: &str = "screen";
+// 包括回滚区
+const ALL_EXTENT: &str = "all";
+// 推送给前端的 stream 名称
+const STREAM: &str = "kitty";
+
+lazy_static! {
+    // 按命令 id 保存最近提交的行
+    static ref COMMITTED: Arc<Mutex<HashMap<String, Vec<String>>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+}
+
+// 在可见屏幕中找到新输出的起点，屏幕已经滚过已提交的全部内容时返回 None
+fn screen_delta_start(committed: &[String], screen: &[String]) -> Option<usize> {
+    let count = committed.len();
+    if count == 0 {
+        return Some(0);
+    }
+    // 已提交的内容完整出现在屏幕中：取第一次出现之后，之后再出现的相同内容是新输出
+    if let Some(index) = screen.windows(count).position(|window| window == committed) {
+        return Some(index + count);
+    }
+    // 屏幕已经滚动，开头是已提交内容的末尾
+    (1..=count.min(screen.len()))
+        .rev()
+        .find(|&overlap| screen[..overlap] == committed[count - overlap..])
+}
+
+// 在全部文本中找到新输出的起点：已提交的最后几行最后一次出现之后
+fn delta_start(committed: &[String], current: &[String]) -> usize {
+    if committed.is_empty() {
+        return 0;
+    }
+    let anchor = &committed[committed.len().saturating_sub(ANCHOR_LINES)..];
+    current
+        .windows(anchor.len())
+        .rposition(|window| window == anchor)
+        .map(|index| index + anchor.len())
+        .unwrap_or(0)
+}
+
+// 读取标签页文本，去掉末尾的空行（屏幕下方未使用的部分）
+fn read_lines(tab: &KittyTab, extent: &str) -> AppResult<Vec<String>> {
+    let window_id = tab
+        .window_id
+        .as_deref()
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, "kitty.tab_not_found"))?;
+    let request = GetText {
+        matches: match_window(window_id),
+        extent: extent.to_string(),
+        ansi: false,
+    };
+    let text = protocol::send(&tab.socket_path, &request)?;
+    let mut lines: Vec<String> = text
+        .lines()
+        .map(|line| line.trim_end().to_string())
+        .collect();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    Ok(lines)
+}
+
+// 抓取一次，把新增的行写入运行记录；final_capture 为 true 时连同最后一行一起提交
+pub fn capture(app: &AppHandle, tab: &KittyTab, final_capture: bool) -> AppResult<()> {
+    let Some(run_id) = &tab.run_id else {
+        return Ok(());
+    };
+    let complete_lines = |lines: &[String]| {
+        if final_capture {
+            lines.len()
+        } else {
+            lines.len().saturating_sub(1)
+        }
+    };
+    let committed = COMMITTED
+        .lock()
+        .map_err(lock_error)?
+        .get(&tab.id)
+        .cloned()
+        .unwrap_or_default();
+
+    let screen = read_lines(tab, SCREEN_EXTENT)?;
+    let complete = complete_lines(&screen);
+    let (lines, complete, start) = match screen_delta_start(&committed, &screen[..complete]) {
+        Some(start) => (screen, complete, start),
+        None => {
+            let all = read_lines(tab, ALL_EXTENT)?;
+            let complete = complete_lines(&all);
+            let start = delta_start(&committed, &all[..complete]);
+            (all, complete, start)
+        }
+    };
+    let start = start.min(complete);
+
+    COMMITTED.lock().map_err(lock_error)?.insert(
+        tab.id.clone(),
+        lines[complete.saturating_sub(MAX_COMMITTED_LINES)..complete].to_vec(),
+    );
+
+    let delta = &lines[start..complete];
+    if !delta.is_empty() {
+        let mut text = delta.join("\n");
+        text.push('\n');
+        task_runner::append_external_output(app, run_id, STREAM, &text);
+    }
+    Ok(())
+}
+
+// 为新创建的标签页登记运行记录，返回 run_id
+pub fn track(app: &AppHandle, tab_id: &str) -> Option<String> {
+    let tab = KITTY_TAB_MANAGER.lock().ok()?.get(tab_id).cloned()?;
+    let run_id = task_runner::begin_external_run(
+        app,
+        &tab.id,
+        &tab.working_dir,
+        &tab.command,
+        Some(tab.command_name.clone()),
+        "kitty",
+    );
+    if let Some(tab) = KITTY_TAB_MANAGER.lock().ok()?.get_mut(tab_id) {
+        tab.run_id = Some(run_id.clone());
+    }
+    Some(run_id)
+}
+
+// 标签页已经结束：最后抓取一次（窗口仍在时）并结束运行记录
+pub fn finish(app: &AppHandle, tab: &KittyTab, status: RunStatus) {
+    if tab.window_id.is_some() {
+        let _ = capture(app, tab, true);
+    }
+    if let Ok(mut committed) = COMMITTED.lock() {
+        committed.remove(&tab.id);
+    }
+    if let Some(run_id) = &tab.run_id {
+        task_runner::finish_external_run(app, run_id, status, tab.exit_code);
+    }
+}
+
+pub fn run_status(status: &TabStatus) -> RunStatus {
+    match status {
+        TabStatus::Running => RunStatus::Running,
+        TabStatus::Completed => RunStatus::Succeeded,
+        TabStatus::Error => RunStatus::Failed,
+        TabStatus::Terminated => RunStatus::Cancelled,
+    }
+}
+
+// 抓取所有运行中的标签页，由状态同步线程定期调用
+pub fn capture_running(app: &AppHandle) {
+    let tabs: Vec<KittyTab> = match KITTY_TAB_MANAGER.lock() {
+        Ok(manager) => manager
+            .values()
+            .filter(|tab| tab.status == TabStatus::Running && tab.run_id.is_some())
+            .cloned()
+            .collect(),
+        Err(_) => return,
+    };
+    for tab in tabs {
+        if let Err(err) = capture(app, &tab, false) {
+            eprintln!("[KITTY][输出抓取] {} 失败: {}", tab.id, err);
+        }
+    }
+}
+
+// ---------- Tauri 命令 ----------
+
+// 立即抓取一次，返回标签页当前的完整文本
+#[tauri::command(async)]
+pub fn capture_kitty_output(app: AppHandle, command_id: String) -> AppResult<String> {
+    let tab = KITTY_TAB_MANAGER
+        .lock()
+        .map_err(lock_error)?
+        .get(&command_id)
+        .cloned()
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, "kitty.tab_not_found"))?;
+    capture(&app, &tab, tab.status != TabStatus::Running)?;
+    Ok(read_lines(&tab, ALL_EXTENT)?.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn screen_start_follows_the_first_full_match() {
+        let committed = lines("a b");
+        assert_eq!(screen_delta_start(&committed, &lines("a b c a b")), Some(2));
+        assert_eq!(screen_delta_start(&[], &lines("a")), Some(0));
+    }
+
+    #[test]
+    fn scrolled_screen_starts_with_the_committed_tail() {
+        let committed = lines("a b c d");
+        assert_eq!(screen_delta_start(&committed, &lines("c d e f")), Some(2));
+        assert_eq!(screen_delta_start(&committed, &lines("e f g")), None);
+    }
+
+    #[test]
+    fn fallback_uses_the_last_anchor_occurrence() {
+        let committed = lines("x y z");
+        assert_eq!(delta_start(&committed, &lines("x y z 1 x y z 2")), 7);
+        assert_eq!(delta_start(&committed, &lines("1 2")), 0);
+    }
+}
@@ -0,0 +1,112 @@
+// kitty 实例设置
+//
+// 启动 kitty 实例与独立窗口时使用的 KittyConfig，保存在应用数据目录，所有入口统一通过 load 读取：
+// - 配置文件：默认不加载（NONE），可以改为使用自己的 kitty.conf 或指定文件
+// - 额外的 --override、新窗口的尺寸与启动状态
+// - 等待 socket 就绪的重试次数与间隔，以及 socket 所在目录
+use crate::modules::error::{lock_error, AppError, AppResult, ErrorCode};
+use crate::modules::kitty::core::KittyConfig;
+use crate::modules::storage;
+use lazy_static::lazy_static;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+const STORE_NAME: &str = "kitty_config";
+const START_AS: &[&str] = &["normal", "maximized", "fullscreen", "minimized"];
+// 重试间隔下限，避免过于频繁地连接 socket
+const MIN_RETRY_DELAY_MS: u64 = 50;
+
+lazy_static! {
+    static ref CONFIG: Arc<Mutex<Option<KittyConfig>>> = Arc::new(Mutex::new(None));
+}
+
+fn with_config<R>(f: impl FnOnce(&mut KittyConfig) -> R) -> AppResult<R> {
+    let mut guard = CONFIG.lock().map_err(lock_error)?;
+    Ok(f(guard.get_or_insert_with(|| storage::load(STORE_NAME))))
+}
+
+// 当前配置，读取失败时使用默认配置
+pub fn load() -> KittyConfig {
+    with_config(|config| config.clone()).unwrap_or_default()
+}
+
+fn invalid(key: &'static str) -> AppError {
+    AppError::new(ErrorCode::InvalidArgument, key)
+}
+
+fn validate(config: &KittyConfig) -> AppResult<()> {
+    // socket 前缀也用于从进程命令行中识别应用启动的实例
+    let prefix_valid = !config.socket_prefix.is_empty()
+        && config
+            .socket_prefix
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !prefix_valid {
+        return Err(invalid("kitty.invalid_socket_prefix"));
+    }
+    if config.max_retries == 0 || config.retry_delay_ms < MIN_RETRY_DELAY_MS {
+        return Err(invalid("kitty.invalid_retry").arg("min", MIN_RETRY_DELAY_MS));
+    }
+    if let Some(dir) = &config.socket_dir {
+        if !Path::new(dir).is_dir() {
+            return Err(
+                AppError::new(ErrorCode::NotADirectory, "common.not_a_directory").with_path(dir),
+            );
+        }
+    }
+    if let Some(entry) = config.overrides.iter().find(|entry| {
+        entry
+            .split_once('=')
+            .is_none_or(|(key, _)| key.trim().is_empty())
+    }) {
+        return Err(invalid("kitty.invalid_override").arg("override", entry));
+    }
+    if let Some(start_as) = &config.window.start_as {
+        if !START_AS.contains(&start_as.as_str()) {
+            return Err(invalid("kitty.invalid_start_as").arg("value", start_as));
+        }
+    }
+    Ok(())
+}
+
+// 空字符串视为未设置
+fn normalize(mut config: KittyConfig) -> KittyConfig {
+    fn non_empty(value: Option<String>) -> Option<String> {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+    config.socket_prefix = config.socket_prefix.trim().to_string();
+    config.socket_dir = non_empty(config.socket_dir);
+    config.config_file = non_empty(config.config_file);
+    config.overrides = config
+        .overrides
+        .iter()
+        .map(|entry| entry.trim().to_string())
+        .filter(|entry| !entry.is_empty())
+        .collect();
+    config.window.width = non_empty(config.window.width);
+    config.window.height = non_empty(config.window.height);
+    config.window.start_as = non_empty(config.window.start_as);
+    config
+}
+
+// ---------- Tauri 命令 ----------
+
+#[tauri::command]
+pub fn get_kitty_config() -> AppResult<KittyConfig> {
+    with_config(|config| config.clone())
+}
+
+// 新的配置只影响之后启动的实例，已经运行的实例保持原样
+#[tauri::command]
+pub fn set_kitty_config(config: KittyConfig) -> AppResult<KittyConfig> {
+    let config = normalize(config);
+    validate(&config)?;
+    let snapshot = with_config(|current| {
+        *current = config;
+        current.clone()
+    })?;
+    storage::save(STORE_NAME, &snapshot)?;
+    Ok(snapshot)
+}
@@ -1,11 +1,40 @@
+use crate::modules::error::{AppError, AppResult, ErrorCode};
 use crate::modules::kitty::core::{sanitize_command_id, KittyConfig};
+use crate::modules::kitty::protocol::{self, Action, Ls};
+#[cfg(unix)]
+use std::ffi::CString;
+#[cfg(unix)]
+use std::fs::{File, OpenOptions};
+use std::io;
+#[cfg(unix)]
+use std::io::Read;
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
 use std::path::Path;
-use std::process::{Command, Stdio};
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus};
+use std::time::Duration;
+#[cfg(unix)]
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
-// 获取socket路径（跨平台兼容）
+// 等待就绪通知时每次 poll 的时长，期间同时检查 kitty 是否已经退出
+#[cfg(unix)]
+const READY_POLL_SLICE_MS: i32 = 100;
+
+// 获取socket路径（跨平台兼容），设置了 socket 目录时统一使用该目录下的 socket 文件
 pub fn get_socket_path(command_id: &str, config: &KittyConfig) -> String {
     let sanitized_id = sanitize_command_id(command_id);
 
+    if let Some(dir) = &config.socket_dir {
+        let path = Path::new(dir).join(format!("{}-{}.sock", config.socket_prefix, sanitized_id));
+        return format!("unix:{}", path.display());
+    }
+
     #[cfg(target_os = "linux")]
     {
         format!("unix:@{}-{}", config.socket_prefix, sanitized_id)
@@ -17,74 +46,75 @@ pub fn get_socket_path(command_id: &str, config: &KittyConfig) -> String {
 }
 
 // 检查Kitty是否安装
-pub fn check_kitty_installed() -> Result<bool, String> {
+pub fn check_kitty_installed() -> AppResult<bool> {
     match Command::new("which").arg("kitty").output() {
         Ok(output) => Ok(output.status.success()),
-        Err(e) => Err(format!("检查kitty安装状态失败: {}", e)),
+        Err(e) => {
+            Err(AppError::new(ErrorCode::Internal, "kitty.check_install_failed").arg("error", e))
+        }
     }
 }
 
 // 检查远程控制是否可用（复用kitty命令）
-pub fn check_kitten_available() -> Result<bool, String> {
+pub fn check_kitten_available() -> AppResult<bool> {
     match Command::new("kitty").arg("--version").output() {
         Ok(output) => Ok(output.status.success()),
-        Err(e) => Err(format!("检查kitty命令失败: {}", e)),
+        Err(e) => Err(
+            AppError::new(ErrorCode::KittyNotInstalled, "kitty.check_command_failed")
+                .arg("error", e),
+        ),
     }
 }
 
-// 测试kitty连接
-pub fn test_kitty_connection(socket_path: &str) -> Result<bool, String> {
-    let test_cmd = format!("kitty @ --to '{}' ls", socket_path);
-
-    match Command::new("sh")
-        .arg("-c")
-        .arg(&test_cmd)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output()
-    {
-        Ok(output) => {
-            if output.status.success() {
-                Ok(true)
-            } else {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                eprintln!(
-                    "[KITTY][连接测试] 尚未就绪: socket={} stderr={}",
-                    socket_path,
-                    stderr.trim()
-                );
-                Ok(false)
-            }
-        }
-        Err(e) => Err(format!("连接测试失败: {}", e)),
+// 测试kitty连接：连接不上视为尚未就绪，其他错误原样返回
+pub fn test_kitty_connection(socket_path: &str) -> AppResult<bool> {
+    match protocol::send(socket_path, &Ls::default()) {
+        Ok(_) => Ok(true),
+        Err(err) if err.code == ErrorCode::KittyConnectionFailed => Ok(false),
+        Err(err) => Err(err),
     }
 }
 
-// 获取Kitty实例信息
-pub fn get_kitty_instance_info(socket_path: &str) -> Result<serde_json::Value, String> {
-    let cmd = format!("kitty @ --to '{}' ls --json", socket_path);
-
-    match Command::new("sh")
-        .arg("-c")
-        .arg(&cmd)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output()
-    {
-        Ok(output) => {
-            if output.status.success() {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                match serde_json::from_str::<serde_json::Value>(&stdout) {
-                    Ok(json) => Ok(json),
-                    Err(e) => Err(format!("解析kitty信息失败: {}", e)),
-                }
-            } else {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                Err(format!("获取kitty信息失败: {}", stderr))
-            }
-        }
-        Err(e) => Err(format!("执行命令失败: {}", e)),
+// 配置文件、自定义 override 与窗口尺寸，启动实例与独立窗口共用
+pub fn build_config_args(config: &KittyConfig) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(config_file) = &config.config_file {
+        args.push(format!("--config={}", config_file));
+    }
+    args.extend(
+        config
+            .overrides
+            .iter()
+            .map(|entry| format!("--override={}", entry)),
+    );
+    let window = &config.window;
+    if let Some(width) = &window.width {
+        args.push(format!("--override=initial_window_width={}", width));
+    }
+    if let Some(height) = &window.height {
+        args.push(format!("--override=initial_window_height={}", height));
+    }
+    // 指定了尺寸时不再沿用上次关闭时的窗口大小
+    if window.width.is_some() || window.height.is_some() {
+        args.push("--override=remember_window_size=no".to_string());
     }
+    if let Some(start_as) = &window.start_as {
+        args.push(format!("--start-as={}", start_as));
+    }
+    args
+}
+
+// 构建启动带远程控制的kitty实例所需的参数，远程控制的 override 放在最后，不会被自定义 override 覆盖
+pub fn build_instance_args(
+    socket_path: &str,
+    working_dir: &str,
+    config: &KittyConfig,
+) -> Vec<String> {
+    let mut args = vec![format!("--listen-on={}", socket_path)];
+    args.extend(build_config_args(config));
+    args.push(format!("--directory={}", working_dir));
+    args.push("--override=allow_remote_control=yes".to_string());
+    args
 }
 
 // 启动kitty实例
@@ -92,108 +122,230 @@ pub fn start_kitty_instance(
     socket_path: &str,
     working_dir: &str,
     config: &KittyConfig,
-) -> Result<u32, String> {
+) -> AppResult<u32> {
     // 检查kitty是否已安装
     if !check_kitty_installed()? {
-        return Err("Kitty未安装。请先安装Kitty终端。".to_string());
+        return Err(AppError::new(
+            ErrorCode::KittyNotInstalled,
+            "kitty.not_installed",
+        ));
     }
 
     // 检查kitty命令是否可用
     if !check_kitten_available()? {
-        return Err("Kitten不可用。请确保Kitty已正确安装。".to_string());
+        return Err(AppError::new(
+            ErrorCode::KittyNotInstalled,
+            "kitty.kitten_unavailable",
+        ));
     }
 
-    let config_arg = config
-        .config_file
-        .as_ref()
-        .map(|f| format!("--config {}", f))
-        .unwrap_or_default();
-
     cleanup_stale_socket(socket_path);
 
-    let escaped_dir = working_dir.replace('\'', "'\\''");
-    let start_cmd = format!(
-        "kitty --listen-on '{}' {} --directory '{}' --override allow_remote_control=yes",
-        socket_path, config_arg, escaped_dir
-    );
-    eprintln!("[KITTY] 启动命令: {}", start_cmd);
+    let args = build_instance_args(socket_path, working_dir, config);
+    eprintln!("[KITTY] 启动命令: kitty {:?}", args);
 
-    let mut process = Command::new("sh");
-    process.env("KITTY_SINGLE_INSTANCE", "no");
+    // 首个窗口中的 shell 启动时写入就绪 FIFO，创建失败时退回轮询
+    let mut ready_fifo = ReadyFifo::create()
+        .map_err(|e| eprintln!("[KITTY] 创建就绪通知失败，改为轮询: {}", e))
+        .ok();
 
-    match process.arg("-c").arg(&start_cmd).spawn() {
-        Ok(mut child) => {
-            let pid = child.id();
+    let mut command = Command::new("kitty");
+    command.args(&args).env("KITTY_SINGLE_INSTANCE", "no");
+    if let Some(fifo) = &ready_fifo {
+        command.args(fifo.window_args());
+    }
+    let mut child = command.spawn().map_err(|e| {
+        AppError::new(ErrorCode::KittyLaunchFailed, "kitty.start_failed").arg("error", e)
+    })?;
+    let pid = child.id();
 
-            // 等待并验证连接
-            let mut retries = 0;
+    let exited_early = |status: ExitStatus| {
+        AppError::new(ErrorCode::KittyLaunchFailed, "kitty.exited_early")
+            .arg("status", status)
+            .with_exit_code(status.code())
+    };
+    let socket_timeout = || {
+        AppError::new(ErrorCode::KittyConnectionFailed, "kitty.socket_timeout")
+            .arg("retries", config.max_retries)
+    };
 
-            while retries < config.max_retries {
-                if let Ok(Some(status)) = child.try_wait() {
-                    return Err(format!("kitty 进程提前退出: {}", status));
+    if let Some(fifo) = ready_fifo.as_mut() {
+        let timeout = Duration::from_millis(config.retry_delay_ms * u64::from(config.max_retries));
+        match fifo.wait(&mut child, timeout) {
+            ReadyState::Ready => {
+                if test_kitty_connection(socket_path)? {
+                    eprintln!("[KITTY] socket就绪: {}", socket_path);
+                    return Ok(pid);
                 }
+                // 窗口已启动但 socket 仍不可用，继续按轮询等待
+            }
+            ReadyState::Exited(status) => return Err(exited_early(status)),
+            ReadyState::TimedOut => return Err(socket_timeout()),
+        }
+    }
 
-                std::thread::sleep(std::time::Duration::from_millis(config.retry_delay_ms));
-
-                match test_kitty_connection(socket_path) {
-                    Ok(true) => {
-                        eprintln!(
-                            "[KITTY] socket就绪: {} (尝试{}次)",
-                            socket_path,
-                            retries + 1
-                        );
-                        return Ok(pid);
-                    }
-                    Ok(false) => {
-                        eprintln!(
-                            "[KITTY] socket未就绪，等待重试 {}/{}",
-                            retries + 1,
-                            config.max_retries
-                        );
-                    }
-                    Err(err) => {
-                        eprintln!("[KITTY] 连接测试失败: {}", err);
-                    }
-                }
+    // 等待并验证连接
+    for retry in 0..config.max_retries {
+        if let Ok(Some(status)) = child.try_wait() {
+            return Err(exited_early(status));
+        }
 
-                retries += 1;
-            }
+        std::thread::sleep(Duration::from_millis(config.retry_delay_ms));
 
-            Err(format!(
-                "等待kitty socket连接超时 (重试{}次)",
-                config.max_retries
-            ))
+        match test_kitty_connection(socket_path) {
+            Ok(true) => {
+                eprintln!("[KITTY] socket就绪: {} (尝试{}次)", socket_path, retry + 1);
+                return Ok(pid);
+            }
+            Ok(false) => {
+                eprintln!(
+                    "[KITTY] socket未就绪，等待重试 {}/{}",
+                    retry + 1,
+                    config.max_retries
+                );
+            }
+            Err(err) => {
+                eprintln!("[KITTY] 连接测试失败: {}", err);
+            }
         }
-        Err(e) => Err(format!("启动kitty实例失败: {}", e)),
     }
+
+    Err(socket_timeout())
 }
 
-fn cleanup_stale_socket(socket_path: &str) {
-    #[cfg(target_os = "macos")]
-    {
-        if let Some(path) = socket_path.strip_prefix("unix:") {
-            let path = Path::new(path);
-            if path.exists() {
-                let _ = std::fs::remove_file(path);
+enum ReadyState {
+    Ready,
+    Exited(ExitStatus),
+    TimedOut,
+}
+
+// 就绪通知：kitty 先监听 socket 再创建窗口，窗口中的 shell 能写入 FIFO 时 socket 已经可用
+#[cfg(unix)]
+struct ReadyFifo {
+    path: PathBuf,
+    file: File,
+}
+
+#[cfg(unix)]
+impl ReadyFifo {
+    fn create() -> io::Result<Self> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let path = std::env::temp_dir().join(format!(
+            "runproject-kitty-ready-{}-{}",
+            std::process::id(),
+            nanos
+        ));
+        let c_path = CString::new(path.as_os_str().as_bytes())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // 非阻塞打开读端，否则会一直等到有写入方
+        let file = match OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&path)
+        {
+            Ok(file) => file,
+            Err(err) => {
+                let _ = std::fs::remove_file(&path);
+                return Err(err);
+            }
+        };
+        Ok(Self { path, file })
+    }
+
+    // 首个窗口执行的命令：写入 FIFO 后换成用户的登录 shell，FIFO 路径作为 $0 传入
+    fn window_args(&self) -> Vec<String> {
+        vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            r#"printf ready > "$0"; exec "${SHELL:-sh}" -l"#.to_string(),
+            self.path.to_string_lossy().to_string(),
+        ]
+    }
+
+    fn wait(&mut self, child: &mut Child, timeout: Duration) -> ReadyState {
+        let deadline = Instant::now() + timeout;
+        let mut buffer = [0u8; 16];
+        while Instant::now() < deadline {
+            if let Ok(Some(status)) = child.try_wait() {
+                return ReadyState::Exited(status);
+            }
+            let mut fds = libc::pollfd {
+                fd: self.file.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            let polled = unsafe { libc::poll(&mut fds, 1, READY_POLL_SLICE_MS) };
+            if polled > 0 {
+                if matches!(self.file.read(&mut buffer), Ok(read) if read > 0) {
+                    return ReadyState::Ready;
+                }
+                // 部分平台在没有写入方时也会报告可读，避免空转
+                std::thread::sleep(Duration::from_millis(READY_POLL_SLICE_MS as u64));
             }
         }
+        ReadyState::TimedOut
     }
 }
 
-// 关闭kitty实例
-pub fn stop_kitty_instance(socket_path: &str) -> Result<(), String> {
-    let cmd = format!("kitty @ --to '{}' quit", socket_path);
-
-    match Command::new("sh").arg("-c").arg(&cmd).output() {
-        Ok(_) => {
-            // 清理socket文件（仅macOS）
-            #[cfg(target_os = "macos")]
-            if socket_path.starts_with("unix:/tmp/") {
-                let file_path = socket_path.trim_start_matches("unix:");
-                let _ = std::fs::remove_file(file_path);
-            }
-            Ok(())
+#[cfg(unix)]
+impl Drop for ReadyFifo {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+// 非 Unix 平台没有 FIFO，创建失败后退回轮询
+#[cfg(not(unix))]
+struct ReadyFifo;
+
+#[cfg(not(unix))]
+impl ReadyFifo {
+    fn create() -> io::Result<Self> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    fn window_args(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn wait(&mut self, _child: &mut Child, _timeout: Duration) -> ReadyState {
+        ReadyState::TimedOut
+    }
+}
+
+// socket 文件路径，Linux 的抽象 socket（unix:@name）没有文件
+fn socket_file(socket_path: &str) -> Option<&Path> {
+    socket_path
+        .strip_prefix("unix:")
+        .filter(|path| !path.starts_with('@'))
+        .map(Path::new)
+}
+
+pub fn cleanup_stale_socket(socket_path: &str) {
+    if let Some(path) = socket_file(socket_path) {
+        if path.exists() {
+            let _ = std::fs::remove_file(path);
         }
-        Err(e) => Err(format!("关闭kitty实例失败: {}", e)),
     }
 }
+
+// 关闭kitty实例
+pub fn stop_kitty_instance(socket_path: &str) -> AppResult<()> {
+    let quit = Action {
+        action: "quit".to_string(),
+    };
+    // kitty 处理 quit 时可能来不及回复，实例已经退出时也连接不上，与之前一样忽略结果
+    let _ = protocol::send(socket_path, &quit);
+
+    // 清理socket文件
+    if let Some(path) = socket_file(socket_path) {
+        let _ = std::fs::remove_file(path);
+    }
+    Ok(())
+}
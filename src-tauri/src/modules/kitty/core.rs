@@ -35,28 +35,65 @@ pub struct KittyTab {
     pub status: TabStatus,
     pub created_at: u64,
     pub pid: Option<u32>,
+    // kitty @ launch 返回的窗口 id，用于精确关闭该标签页
+    #[serde(default)]
+    pub window_id: Option<String>,
+    // 以下由状态同步线程根据 kitty ls 填写
+    #[serde(default)]
+    pub foreground_pids: Vec<u32>,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    #[serde(default)]
+    pub finished_at: Option<u64>,
+    // 对应的运行记录，标签页输出会抓取到该记录中
+    #[serde(default)]
+    pub run_id: Option<String>,
+    // 启动时使用的环境配置，导出会话时按同一配置解析环境变量
+    #[serde(default)]
+    pub env_profile: Option<String>,
 }
 
-// Kitty实例配置
-#[derive(Debug, Clone)]
+// Kitty实例配置，保存在设置中，由 config 模块读取
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct KittyConfig {
     pub socket_prefix: String,
+    // socket 文件所在目录；为空时 Linux 使用抽象 socket，macOS 放在 /tmp
+    pub socket_dir: Option<String>,
     pub max_retries: u32,
     pub retry_delay_ms: u64,
+    // --config 的取值：None 使用自己的 kitty.conf，"NONE" 不加载任何配置文件
     pub config_file: Option<String>,
+    // 额外的 --override，每项为 key=value
+    pub overrides: Vec<String>,
+    pub window: WindowGeometry,
 }
 
 impl Default for KittyConfig {
     fn default() -> Self {
         Self {
             socket_prefix: "kitty-runproject".to_string(),
+            socket_dir: None,
             max_retries: 20,
             retry_delay_ms: 500,
             config_file: Some("NONE".to_string()),
+            overrides: Vec::new(),
+            window: WindowGeometry::default(),
         }
     }
 }
 
+// 新建 OS 窗口的尺寸与状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WindowGeometry {
+    // initial_window_width / initial_window_height 的取值，如 1200 或 120c（按字符）
+    pub width: Option<String>,
+    pub height: Option<String>,
+    // --start-as：normal、maximized、fullscreen、minimized
+    pub start_as: Option<String>,
+}
+
 // 全局Kitty管理器状态
 lazy_static::lazy_static! {
     pub static ref KITTY_TAB_MANAGER: Arc<Mutex<HashMap<String, KittyTab>>> = Arc::new(Mutex::new(HashMap::new()));
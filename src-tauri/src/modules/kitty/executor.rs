@@ -1,12 +1,31 @@
+use crate::modules::command_template;
+use crate::modules::env_profile;
+use crate::modules::error::{AppError, AppResult, ErrorCode};
+use crate::modules::i18n;
+use crate::modules::install_preflight;
+use crate::modules::kitty::config;
 use crate::modules::kitty::connection::{
-    get_socket_path, start_kitty_instance, test_kitty_connection,
+    build_config_args, get_socket_path, start_kitty_instance, test_kitty_connection,
+};
+use crate::modules::kitty::core::{
+    get_current_timestamp, KittyConfig, KittyTab, TabStatus, PROCESS_MANAGER,
+};
+use crate::modules::kitty::grouping::{self, GroupingSettings};
+use crate::modules::kitty::protocol;
+use crate::modules::kitty::tabs::{
+    register_kitty_tab, set_tab_window_id, unregister_kitty_tab, COMMAND_ID_ENV,
 };
-use crate::modules::kitty::core::{get_current_timestamp, KittyTab, TabStatus, PROCESS_MANAGER};
-use crate::modules::kitty::tabs::{create_kitty_tab, register_kitty_tab};
 use crate::modules::nvm_manager;
+use crate::modules::platform::{chain_commands, shell_quote};
+use crate::modules::recovery;
+use crate::modules::task_runner::{self, RunRequest};
+use crate::modules::terminal_backend::kitty::KittyBackend;
+use crate::modules::terminal_backend::LaunchSpec;
+use std::collections::BTreeMap;
+use tauri::AppHandle;
 
 // 生成包管理器特定的命令前缀
-fn get_package_manager_prefix(package_manager: &str, command: &str) -> String {
+pub fn get_package_manager_prefix(package_manager: &str, command: &str) -> String {
     // 分析命令类型，生成合适的包管理器前缀
     let cmd_lower = command.to_lowercase();
 
@@ -51,14 +70,34 @@ fn get_package_manager_prefix(package_manager: &str, command: &str) -> String {
     }
 }
 
-// 构建完整的执行命令
+// 拼接包管理器前缀与参数，不含 Node 版本切换
+pub fn build_script_command(
+    command: &str,
+    package_manager: &str,
+    extra_args: Option<&str>,
+) -> String {
+    let pm_prefix = get_package_manager_prefix(package_manager, command);
+    // 需要加前缀时 command 是脚本名，整体转义，避免脚本名中的特殊字符被 shell 解释
+    let script = if pm_prefix.is_empty() {
+        command.to_string()
+    } else {
+        shell_quote(command)
+    };
+    format!("{}{}{}", pm_prefix, script, extra_args.unwrap_or(""))
+}
+
+// 构建完整的执行命令，install_command 为需要先执行的依赖安装命令，与脚本使用同一 Node 版本
 pub fn build_execution_command(
     command: &str,
     node_version: Option<&str>,
     package_manager: &str,
-) -> Result<String, String> {
-    let pm_prefix = get_package_manager_prefix(package_manager, command);
-    let mut final_command = format!("{}{}", pm_prefix, command);
+    extra_args: Option<&str>,
+    install_command: Option<&str>,
+) -> AppResult<String> {
+    let mut final_command = build_script_command(command, package_manager, extra_args);
+    if let Some(install) = install_command {
+        final_command = chain_commands(install, &final_command);
+    }
 
     if let Some(version) = node_version {
         final_command = nvm_manager::wrap_command_with_node(version, &final_command)?;
@@ -67,9 +106,35 @@ pub fn build_execution_command(
     Ok(final_command)
 }
 
+// 构建独立kitty窗口的启动参数，完整命令作为单个参数交给 bash -c
+pub fn build_kitty_window_args(
+    project_name: &str,
+    command_name: &str,
+    socket_path: &str,
+    working_dir: &str,
+    final_command: &str,
+    config: &KittyConfig,
+) -> Vec<String> {
+    let mut args = vec![
+        format!("--title={} - {}", project_name, command_name),
+        format!("--listen-on={}", socket_path),
+    ];
+    args.extend(build_config_args(config));
+    args.extend([
+        format!("--directory={}", working_dir),
+        "--hold".to_string(),
+        "bash".to_string(),
+        "-c".to_string(),
+        final_command.to_string(),
+    ]);
+    args
+}
+
 // 在kitty终端中执行命令（传统方式）
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn execute_command_in_kitty(
+    app: AppHandle,
     command_id: String,
     working_dir: String,
     command: String,
@@ -77,87 +142,234 @@ pub fn execute_command_in_kitty(
     project_name: String,
     command_name: String,
     package_manager: String,
-) -> Result<serde_json::Value, String> {
-    let mut result_output = String::new();
-
-    // 显示执行的详细信息
-    result_output.push_str(&format!("📁 工作目录: {}\n", working_dir));
-    result_output.push_str(&format!("📂 项目名称: {}\n", project_name));
-    result_output.push_str(&format!("🔧 原始命令: {}\n", command));
-    result_output.push_str(&format!("🚀 命令名称: {}\n", command_name));
-    result_output.push_str(&format!("📦 包管理器: {}\n", package_manager));
-
-    // 构建完整的执行命令
-    let final_command =
-        build_execution_command(&command, node_version.as_deref(), &package_manager)?;
-
-    result_output.push_str(&format!("📝 完整命令: {}\n", final_command));
-
-    // 添加调试日志
-    eprintln!("[DEBUG] 构建的执行命令: {}", final_command);
-    eprintln!("[DEBUG] 原始命令: {}", command);
-    eprintln!("[DEBUG] Node版本: {:?}", node_version);
-    eprintln!("[DEBUG] 包管理器: {}", package_manager);
+    env_profile: Option<String>,
+    arg_values: Option<BTreeMap<String, serde_json::Value>>,
+    install_dependencies: Option<bool>,
+) -> AppResult<serde_json::Value> {
+    let PreparedCommand {
+        final_command,
+        env: env_vars,
+        output: mut result_output,
+        ..
+    } = prepare_terminal_command(
+        &working_dir,
+        &command,
+        node_version.as_deref(),
+        &project_name,
+        &command_name,
+        &package_manager,
+        env_profile.as_deref(),
+        arg_values.as_ref(),
+        install_dependencies,
+    )?;
 
-    let socket_path = get_socket_path(
-        &command_id,
-        &crate::modules::kitty::core::KittyConfig::default(),
+    let kitty_config = config::load();
+    let socket_path = get_socket_path(&command_id, &kitty_config);
+    i18n::push_line(
+        &mut result_output,
+        "exec.socket",
+        &[("socket", &socket_path)],
     );
-    result_output.push_str(&format!("🔌 控制socket: {}\n", socket_path));
 
-    // 使用kitty终端执行命令，开启远程控制功能
-    let kitty_command = format!(
-        "kitty --title '{} - {}' --listen-on '{}' --config NONE --directory '{}' --hold bash -c '{}'",
-        project_name,
-        command_name,
-        socket_path,
-        working_dir,
-        final_command.replace("'", "'\\''") // 转义单引号
-    );
+    let request = RunRequest {
+        command_id: command_id.clone(),
+        project_path: working_dir.clone(),
+        command: final_command.clone(),
+        command_name: Some(command_name.clone()),
+        env: Vec::new(),
+        trigger: "kitty".to_string(),
+        hook_depth: 0,
+        priority: 0,
+        long_running: task_runner::is_long_running_script(&command_name),
+    };
+    let launch_app = app.clone();
+    let launch_command_id = command_id.clone();
+    let admission = task_runner::admit_interactive(
+        &app,
+        request,
+        Box::new(move |run_id| {
+            let command_id = launch_command_id;
+            // 使用kitty终端执行命令，开启远程控制功能
+            let kitty_args = build_kitty_window_args(
+                &project_name,
+                &command_name,
+                &socket_path,
+                &working_dir,
+                &with_exit_file(&final_command),
+                &kitty_config,
+            );
 
-    result_output.push_str("\n🖥️ 正在使用kitty终端执行...\n");
+            result_output.push('\n');
+            i18n::push_line(&mut result_output, "exec.kitty_running", &[]);
+
+            // 使用spawn()而不是output()来避免等待kitty进程完成
+            // 与标签页一样带上所属标记，便于重启后回收孤儿窗口
+            let (owner_key, owner_pid) = recovery::owner_tag();
+            let child = std::process::Command::new("kitty")
+                .args(&kitty_args)
+                .envs(env_vars)
+                .env(COMMAND_ID_ENV, &command_id)
+                .env(owner_key, owner_pid)
+                .env(task_runner::EXIT_FILE_ENV, task_runner::exit_file(run_id))
+                .spawn()
+                .map_err(|e| {
+                    let code = if e.kind() == std::io::ErrorKind::NotFound {
+                        ErrorCode::KittyNotInstalled
+                    } else {
+                        ErrorCode::KittyLaunchFailed
+                    };
+                    AppError::new(code, "exec.kitty_window_failed")
+                        .arg("error", e)
+                        .with_path(working_dir.clone())
+                })?;
 
-    // 使用spawn()而不是output()来避免等待kitty进程完成
-    match std::process::Command::new("bash")
-        .arg("-c")
-        .arg(&kitty_command)
-        .spawn()
-    {
-        Ok(child) => {
             // 保存进程句柄到全局管理器
             if let Ok(mut manager) = PROCESS_MANAGER.lock() {
                 let manager: &mut std::collections::HashMap<String, std::process::Child> =
                     &mut manager;
                 manager.insert(command_id.clone(), child);
             }
+            // 窗口关闭或被终止后视为结束
+            let tracked_id = command_id.clone();
+            task_runner::watch_exit_file(&launch_app, run_id, move || {
+                PROCESS_MANAGER
+                    .lock()
+                    .ok()
+                    .and_then(|mut manager| {
+                        manager
+                            .get_mut(&tracked_id)
+                            .map(|child| matches!(child.try_wait(), Ok(None)))
+                    })
+                    .unwrap_or(false)
+            });
 
-            result_output.push_str("✅ kitty终端启动成功\n");
+            i18n::push_line(&mut result_output, "exec.kitty_started", &[]);
             Ok(serde_json::json!({
                 "success": true,
-                "message": format!("在kitty终端中成功启动命令: {}", command_name),
+                "message": i18n::tf("exec.kitty_window_launched", &[("name", &command_name)]),
                 "output": result_output,
                 "project": project_name,
                 "command": command_name,
-                "command_id": command_id
+                "command_id": command_id,
+                "run_id": run_id
             }))
-        }
-        Err(e) => {
-            result_output.push_str(&format!("❌ 启动kitty终端失败: {}\n", e));
+        }),
+    )?;
+    Ok(task_runner::admission_result(admission, &command_id))
+}
 
-            Ok(serde_json::json!({
-                "success": false,
-                "error": format!("启动kitty终端失败: {}", e),
-                "output": result_output,
-                "project": project_name,
-                "command": command_name
-            }))
-        }
+// 窗口中的命令结束后把退出码写入退出码文件，换行分隔以免命令末尾的注释吞掉后续内容
+fn with_exit_file(final_command: &str) -> String {
+    format!(
+        "{}\ns=$?; [ -z \"${env}\" ] || printf %s \"$s\" > \"${env}\"; exit $s",
+        final_command,
+        env = task_runner::EXIT_FILE_ENV
+    )
+}
+
+// 外部终端执行前的准备结果
+pub struct PreparedCommand {
+    pub final_command: String,
+    pub env: Vec<(String, String)>,
+    // 解析环境变量时使用的环境配置
+    pub env_profile: Option<String>,
+    // 展示给用户的执行详情
+    pub output: String,
+}
+
+// 解析参数模板、依赖预检、Node 版本与环境变量，得到标签页中要执行的命令
+#[allow(clippy::too_many_arguments)]
+pub fn prepare_terminal_command(
+    working_dir: &str,
+    command: &str,
+    node_version: Option<&str>,
+    project_name: &str,
+    command_name: &str,
+    package_manager: &str,
+    env_profile: Option<&str>,
+    arg_values: Option<&BTreeMap<String, serde_json::Value>>,
+    install_dependencies: Option<bool>,
+) -> AppResult<PreparedCommand> {
+    let mut result_output = String::new();
+
+    // 显示执行的详细信息
+    i18n::push_line(
+        &mut result_output,
+        "exec.working_dir",
+        &[("dir", &working_dir)],
+    );
+    i18n::push_line(
+        &mut result_output,
+        "exec.project_name",
+        &[("name", &project_name)],
+    );
+    i18n::push_line(
+        &mut result_output,
+        "exec.raw_command",
+        &[("command", &command)],
+    );
+    i18n::push_line(
+        &mut result_output,
+        "exec.command_name",
+        &[("name", &command_name)],
+    );
+    i18n::push_line(
+        &mut result_output,
+        "exec.package_manager",
+        &[("manager", &package_manager)],
+    );
+
+    // 构建完整的执行命令
+    let extra_args = command_template::render_for_execution(
+        working_dir,
+        command_name,
+        package_manager,
+        arg_values,
+    )
+    .map_err(|e| AppError::plain(ErrorCode::InvalidArgument, e))?;
+    let preflight = install_preflight::preflight(
+        working_dir,
+        Some(package_manager),
+        install_dependencies,
+        false,
+    )?;
+    preflight.report(&mut result_output);
+    let final_command = build_execution_command(
+        command,
+        node_version,
+        package_manager,
+        Some(&extra_args),
+        preflight.install_command.as_deref(),
+    )?;
+
+    i18n::push_line(
+        &mut result_output,
+        "exec.final_command",
+        &[("command", &final_command)],
+    );
+
+    let env = env_profile::resolve_env_vars(working_dir, Some(command_name), env_profile)?;
+    if !env.is_empty() {
+        i18n::push_line(
+            &mut result_output,
+            "exec.env_injected",
+            &[("count", &env.len())],
+        );
     }
+
+    Ok(PreparedCommand {
+        final_command,
+        env,
+        env_profile: env_profile.map(str::to_string),
+        output: result_output,
+    })
 }
 
 // 使用kitty远程控制执行命令
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn execute_command_with_kitten(
+    app: AppHandle,
     command_id: String,
     working_dir: String,
     command: String,
@@ -165,91 +377,179 @@ pub fn execute_command_with_kitten(
     project_name: String,
     command_name: String,
     package_manager: String,
-) -> Result<serde_json::Value, String> {
-    let mut result_output = String::new();
-
-    // 显示执行的详细信息
-    result_output.push_str(&format!("📁 工作目录: {}\n", working_dir));
-    result_output.push_str(&format!("📂 项目名称: {}\n", project_name));
-    result_output.push_str(&format!("🔧 原始命令: {}\n", command));
-    result_output.push_str(&format!("🚀 命令名称: {}\n", command_name));
-    result_output.push_str(&format!("📦 包管理器: {}\n", package_manager));
-
-    // 构建完整的执行命令
-    let final_command =
-        build_execution_command(&command, node_version.as_deref(), &package_manager)?;
+    env_profile: Option<String>,
+    arg_values: Option<BTreeMap<String, serde_json::Value>>,
+    install_dependencies: Option<bool>,
+    workspace_path: Option<String>,
+) -> AppResult<serde_json::Value> {
+    let prepared = prepare_terminal_command(
+        &working_dir,
+        &command,
+        node_version.as_deref(),
+        &project_name,
+        &command_name,
+        &package_manager,
+        env_profile.as_deref(),
+        arg_values.as_ref(),
+        install_dependencies,
+    )?;
+    launch_with_kitten(
+        &app,
+        command_id,
+        project_name,
+        command_name,
+        working_dir,
+        workspace_path.as_deref(),
+        prepared,
+    )
+}
 
-    result_output.push_str(&format!("📝 完整命令: {}\n", final_command));
+// 按分组设置在 kitty 实例中打开标签页执行准备好的命令，外部终端自动选择到 kitty 时也走这里
+pub fn launch_with_kitten(
+    app: &AppHandle,
+    command_id: String,
+    project_name: String,
+    command_name: String,
+    working_dir: String,
+    workspace_path: Option<&str>,
+    prepared: PreparedCommand,
+) -> AppResult<serde_json::Value> {
+    let PreparedCommand {
+        final_command,
+        env,
+        env_profile,
+        output: mut result_output,
+    } = prepared;
 
-    let config = crate::modules::kitty::core::KittyConfig::default();
-    let socket_path = get_socket_path(&command_id, &config);
-    result_output.push_str(&format!("🔌 控制socket: {}\n", socket_path));
+    let backend = KittyBackend {
+        config: config::load(),
+    };
+    let grouping = grouping::settings()?;
+    let instance = grouping.instance_key(&command_id, &working_dir, workspace_path);
+    let socket_path = backend.socket_path(&instance);
+    i18n::push_line(
+        &mut result_output,
+        "exec.socket",
+        &[("socket", &socket_path)],
+    );
 
-    // 测试连接，如果不存在则启动kitty实例
-    match test_kitty_connection(&socket_path) {
-        Ok(true) => {
-            result_output.push_str("✅ 已连接到现有kitty实例\n");
-        }
-        Ok(false) => {
-            result_output.push_str("🔄 未找到kitty实例，正在启动新的kitty...\n");
-
-            match start_kitty_instance(&socket_path, &working_dir, &config) {
-                Ok(pid) => {
-                    result_output.push_str(&format!("✅ kitty实例启动成功 (PID: {})\n", pid));
-                }
-                Err(e) => {
-                    return Ok(serde_json::json!({
-                        "success": false,
-                        "error": format!("启动kitty实例失败: {}", e),
-                        "output": result_output,
-                        "project": project_name,
-                        "command": command_name
-                    }));
-                }
-            }
-        }
-        Err(e) => {
+    // 共用实例时，同一命令仍在运行则切换到已有标签页
+    let connected = test_kitty_connection(&socket_path)?;
+    if connected && grouping.is_shared() {
+        if let Some(existing) = grouping::reuse_existing(&socket_path, &working_dir, &command_name)
+        {
+            i18n::push_line(&mut result_output, "exec.kitty_tab_focused", &[]);
             return Ok(serde_json::json!({
-                "success": false,
-                "error": format!("连接测试失败: {}", e),
+                "success": true,
+                "reused": true,
+                "message": i18n::tf("exec.kitty_tab_focused_message", &[("name", &command_name)]),
                 "output": result_output,
                 "project": project_name,
-                "command": command_name
+                "command": command_name,
+                "command_id": existing.id,
+                "socket_path": socket_path
             }));
         }
     }
 
-    // 创建新的标签页记录
-    let tab_title = format!("{} - {} ({})", project_name, command_name, command_id);
-    let new_tab = KittyTab {
-        id: command_id.clone(),
-        title: tab_title,
+    let request = RunRequest {
+        command_id: command_id.clone(),
+        project_path: working_dir.clone(),
+        command: final_command.clone(),
+        command_name: Some(command_name.clone()),
+        env: Vec::new(),
+        trigger: "kitty".to_string(),
+        hook_depth: 0,
+        priority: 0,
+        long_running: task_runner::is_long_running_script(&command_name),
+    };
+    let spec = LaunchSpec {
+        command_id: command_id.clone(),
         project_name: project_name.clone(),
         command_name: command_name.clone(),
         working_dir: working_dir.clone(),
-        command: final_command.clone(),
-        socket_path: socket_path.clone(),
+        command: final_command,
+        env,
+    };
+    let admission = task_runner::admit_interactive(
+        app,
+        request,
+        Box::new(move |run_id| {
+            // 排队期间实例可能已被关闭，打开前重新检查
+            if connected && test_kitty_connection(&socket_path)? {
+                i18n::push_line(&mut result_output, "exec.kitty_connected", &[]);
+            } else {
+                i18n::push_line(&mut result_output, "exec.kitty_starting", &[]);
+                let pid = start_kitty_instance(&socket_path, &spec.working_dir, &backend.config)?;
+                i18n::push_line(
+                    &mut result_output,
+                    "exec.kitty_instance_started",
+                    &[("pid", &pid)],
+                );
+            }
+            open_kitty_tab(
+                &grouping,
+                &socket_path,
+                &spec,
+                env_profile.as_deref(),
+                run_id,
+                result_output,
+            )
+        }),
+    )?;
+    Ok(task_runner::admission_result(admission, &command_id))
+}
+
+// 在 kitty 实例中创建标签页并登记，标签页结束时由状态同步线程结束运行记录
+fn open_kitty_tab(
+    grouping: &GroupingSettings,
+    socket_path: &str,
+    spec: &LaunchSpec,
+    env_profile: Option<&str>,
+    run_id: &str,
+    mut result_output: String,
+) -> AppResult<serde_json::Value> {
+    let command_id = &spec.command_id;
+
+    // 创建新的标签页记录
+    let new_tab = KittyTab {
+        id: command_id.clone(),
+        title: spec.title(),
+        project_name: spec.project_name.clone(),
+        command_name: spec.command_name.clone(),
+        working_dir: spec.working_dir.clone(),
+        command: spec.command.clone(),
+        socket_path: socket_path.to_string(),
         status: TabStatus::Running,
         created_at: get_current_timestamp(),
         pid: None,
+        window_id: None,
+        foreground_pids: Vec::new(),
+        exit_code: None,
+        finished_at: None,
+        run_id: Some(run_id.to_string()),
+        env_profile: env_profile.map(str::to_string),
     };
 
     // 注册标签页
     if let Err(e) = register_kitty_tab(new_tab) {
-        result_output.push_str(&format!("⚠️ 注册标签页失败: {}\n", e));
+        i18n::push_line(
+            &mut result_output,
+            "exec.register_tab_failed",
+            &[("error", &e)],
+        );
     }
 
     // 使用kitty远程控制创建标签页并执行命令
-    match create_kitty_tab(
-        &socket_path,
-        &command_id,
-        &project_name,
-        &command_name,
-        &working_dir,
-        &final_command,
-    ) {
-        Ok(_) => {
-            result_output.push_str("✅ kitty标签页创建成功\n");
+    let opened = protocol::send(
+        socket_path,
+        &grouping::tab_request(grouping, socket_path, spec),
+    )
+    .map(|window_id| window_id.trim().to_string());
+    match opened {
+        Ok(window_id) => {
+            i18n::push_line(&mut result_output, "exec.kitty_tab_created", &[]);
+            set_tab_window_id(command_id, &window_id);
 
             // 保存命令信息到全局管理器
             if let Ok(mut manager) = PROCESS_MANAGER.lock() {
@@ -265,24 +565,20 @@ pub fn execute_command_with_kitten(
 
             Ok(serde_json::json!({
                 "success": true,
-                "message": format!("在kitty标签页中成功启动命令: {}", command_name),
+                "message": i18n::tf("exec.kitty_tab_launched", &[("name", &spec.command_name)]),
                 "output": result_output,
-                "project": project_name,
-                "command": command_name,
+                "project": spec.project_name,
+                "command": spec.command_name,
                 "command_id": command_id,
-                "socket_path": socket_path
+                "socket_path": socket_path,
+                "run_id": run_id,
+                "reused": false
             }))
         }
         Err(e) => {
-            result_output.push_str(&format!("❌ 创建kitty标签页失败: {}\n", e));
-
-            Ok(serde_json::json!({
-                "success": false,
-                "error": format!("创建kitty标签页失败: {}", e),
-                "output": result_output,
-                "project": project_name,
-                "command": command_name
-            }))
+            // 标签页未创建成功，移除刚注册的记录
+            let _ = unregister_kitty_tab(command_id);
+            Err(e)
         }
     }
 }
@@ -0,0 +1,263 @@
+// 实例分组
+//
+// 决定命令在哪个 kitty 实例（socket）中打开：
+// - PerCommand：每条命令一个实例，即每条命令一个 OS 窗口
+// - PerProject / PerWorkspace：同一项目或工作区共用一个实例，命令作为其中的标签页或分屏
+// 共用实例时标签页标题固定为 "项目 - 命令"，再次执行仍在运行的命令会切换到已有标签页
+use crate::modules::error::{lock_error, AppResult};
+use crate::modules::kitty::core::{KittyTab, TabStatus, KITTY_TAB_MANAGER};
+use crate::modules::kitty::protocol::{self, CloseWindow, FocusTab, Launch};
+use crate::modules::kitty::tabs::{build_launch_request, unregister_kitty_tab};
+use crate::modules::storage;
+use crate::modules::terminal_backend::LaunchSpec;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+const STORE_NAME: &str = "kitty_grouping";
+// 分屏模式下新窗口相对已有窗口的位置
+const SPLIT_LOCATION: &str = "vsplit";
+// 32 位 FNV-1a
+const FNV_OFFSET_BASIS: u32 = 0x811c_9dc5;
+const FNV_PRIME: u32 = 0x0100_0193;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Grouping {
+    PerCommand,
+    PerProject,
+    PerWorkspace,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Layout {
+    #[default]
+    Tabs,
+    Splits,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GroupingSettings {
+    pub grouping: Grouping,
+    pub layout: Layout,
+    // 按项目路径覆盖 layout
+    pub project_layouts: HashMap<String, Layout>,
+}
+
+impl Default for GroupingSettings {
+    fn default() -> Self {
+        Self {
+            grouping: Grouping::PerCommand,
+            layout: Layout::Tabs,
+            project_layouts: HashMap::new(),
+        }
+    }
+}
+
+impl GroupingSettings {
+    pub fn is_shared(&self) -> bool {
+        self.grouping != Grouping::PerCommand
+    }
+
+    pub fn layout_for(&self, project_path: &str) -> Layout {
+        self.project_layouts
+            .get(project_path)
+            .copied()
+            .unwrap_or(self.layout)
+    }
+
+    // 用于生成 socket 路径的实例名；未传工作区时取项目的上级目录
+    pub fn instance_key(
+        &self,
+        command_id: &str,
+        project_path: &str,
+        workspace_path: Option<&str>,
+    ) -> String {
+        match self.grouping {
+            Grouping::PerCommand => command_id.to_string(),
+            Grouping::PerProject => path_key("project", project_path),
+            Grouping::PerWorkspace => {
+                let workspace = workspace_path.map(str::to_string).unwrap_or_else(|| {
+                    Path::new(project_path)
+                        .parent()
+                        .map(|parent| parent.to_string_lossy().to_string())
+                        .unwrap_or_else(|| project_path.to_string())
+                });
+                path_key("workspace", &workspace)
+            }
+        }
+    }
+}
+
+lazy_static! {
+    static ref SETTINGS: Arc<Mutex<Option<GroupingSettings>>> = Arc::new(Mutex::new(None));
+}
+
+fn with_settings<R>(f: impl FnOnce(&mut GroupingSettings) -> R) -> AppResult<R> {
+    let mut guard = SETTINGS.lock().map_err(lock_error)?;
+    Ok(f(guard.get_or_insert_with(|| storage::load(STORE_NAME))))
+}
+
+pub fn settings() -> AppResult<GroupingSettings> {
+    with_settings(|settings| settings.clone())
+}
+
+// 路径哈希写进 socket 名，重启应用后仍要连回同一实例，不能用每个版本可能不同的 DefaultHasher
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u32::from(*byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+// 目录名便于辨认，哈希区分同名目录；socket 名有长度限制，目录名只保留前 24 个字符
+fn path_key(kind: &str, path: &str) -> String {
+    let name: String = Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '_')
+        .take(24)
+        .collect();
+    format!("{}_{}_{:08x}", kind, name, fnv1a(path.as_bytes()))
+}
+
+fn find_tab(predicate: impl Fn(&KittyTab) -> bool) -> Option<KittyTab> {
+    KITTY_TAB_MANAGER
+        .lock()
+        .ok()
+        .and_then(|manager| manager.values().find(|tab| predicate(tab)).cloned())
+}
+
+// 同一实例中已有该命令的标签页时：仍在运行则切换过去并返回它，
+// 已经结束（或窗口已不在）则关闭旧标签页、移除记录，由调用方重新启动
+pub fn reuse_existing(
+    socket_path: &str,
+    working_dir: &str,
+    command_name: &str,
+) -> Option<KittyTab> {
+    let existing = find_tab(|tab| {
+        tab.socket_path == socket_path
+            && tab.working_dir == working_dir
+            && tab.command_name == command_name
+    })?;
+
+    if let (TabStatus::Running, Some(window_id)) = (&existing.status, &existing.window_id) {
+        let focus = FocusTab {
+            matches: Some(format!("window_id:{}", window_id)),
+        };
+        if protocol::send(socket_path, &focus).is_ok() {
+            return Some(existing);
+        }
+    }
+
+    if let Some(window_id) = &existing.window_id {
+        let close = CloseWindow {
+            matches: protocol::match_window(window_id),
+            ignore_no_match: true,
+        };
+        let _ = protocol::send(socket_path, &close);
+    }
+    let _ = unregister_kitty_tab(&existing.id);
+    None
+}
+
+// 共用实例时的 launch 请求：标签页模式每条命令一个标签页，
+// 分屏模式同一项目的命令放在同一个标签页中，标签页标题为项目名
+pub fn launch_request(settings: &GroupingSettings, socket_path: &str, spec: &LaunchSpec) -> Launch {
+    let mut request = build_launch_request(
+        &spec.command_id,
+        &spec.project_name,
+        &spec.command_name,
+        &spec.working_dir,
+        &spec.command,
+        &spec.env,
+    );
+    let title = format!("{} - {}", spec.project_name, spec.command_name);
+    request.window_title = Some(title.clone());
+    request.tab_title = Some(title);
+
+    if settings.layout_for(&spec.working_dir) == Layout::Splits {
+        let sibling = find_tab(|tab| {
+            tab.socket_path == socket_path
+                && tab.working_dir == spec.working_dir
+                && tab.id != spec.command_id
+                && tab.window_id.is_some()
+        });
+        match sibling.and_then(|tab| tab.window_id) {
+            Some(window_id) => {
+                request.launch_type = "window".to_string();
+                request.matches = Some(format!("window_id:{}", window_id));
+                request.location = Some(SPLIT_LOCATION.to_string());
+                request.tab_title = None;
+            }
+            None => request.tab_title = Some(spec.project_name.clone()),
+        }
+    }
+    request
+}
+
+// 打开标签页时发送的 launch 请求，执行与预览共用
+pub fn tab_request(settings: &GroupingSettings, socket_path: &str, spec: &LaunchSpec) -> Launch {
+    if settings.is_shared() {
+        launch_request(settings, socket_path, spec)
+    } else {
+        build_launch_request(
+            &spec.command_id,
+            &spec.project_name,
+            &spec.command_name,
+            &spec.working_dir,
+            &spec.command,
+            &spec.env,
+        )
+    }
+}
+
+// ---------- Tauri 命令 ----------
+
+#[tauri::command]
+pub fn get_kitty_grouping_settings() -> AppResult<GroupingSettings> {
+    settings()
+}
+
+#[tauri::command]
+pub fn set_kitty_grouping_settings(settings: GroupingSettings) -> AppResult<GroupingSettings> {
+    let snapshot = with_settings(|current| {
+        *current = settings;
+        current.clone()
+    })?;
+    storage::save(STORE_NAME, &snapshot)?;
+    Ok(snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fnv1a_matches_reference_values() {
+        assert_eq!(fnv1a(b""), 0x811c_9dc5);
+        assert_eq!(fnv1a(b"a"), 0xe40c_292c);
+        assert_eq!(fnv1a(b"foobar"), 0xbf9c_f968);
+    }
+
+    #[test]
+    fn path_key_is_stable_and_readable() {
+        assert_eq!(
+            path_key("project", "/home/me/my-app"),
+            format!("project_myapp_{:08x}", fnv1a(b"/home/me/my-app"))
+        );
+        assert_ne!(path_key("project", "/a/app"), path_key("project", "/b/app"));
+    }
+
+    #[test]
+    fn per_command_is_the_default() {
+        let settings = GroupingSettings::default();
+        assert!(!settings.is_shared());
+        assert_eq!(settings.instance_key("cmd-1", "/p", None), "cmd-1");
+    }
+}
@@ -0,0 +1,279 @@
+// 向运行中的标签页发送输入
+//
+// 通过 send-text / send-key 把文本或按键发送到命令所在的 kitty 窗口，
+// 开发服务器常用的快捷键按工具预设为命名动作：
+// - 工具根据 package.json 中对应脚本的内容与实际执行的命令识别
+// - Vite 的快捷键需要回车确认，Expo 与 Jest watch 单个按键即可生效
+// - 所有运行中的标签页都带有 interrupt（Ctrl+C）动作
+use crate::modules::error::{lock_error, AppError, AppResult, ErrorCode};
+use crate::modules::i18n;
+use crate::modules::kitty::core::{KittyTab, TabStatus, KITTY_TAB_MANAGER};
+use crate::modules::kitty::protocol::{self, match_window, SendKey, SendText};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DevTool {
+    Vite,
+    Expo,
+    JestWatch,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ActionInput {
+    Text(&'static str),
+    Keys(&'static [&'static str]),
+}
+
+// (工具, 动作名, 输入)，文案 key 为 task_action.<动作名>
+const PRESETS: &[(DevTool, &str, ActionInput)] = &[
+    (DevTool::Vite, "restart", ActionInput::Text("r\r")),
+    (DevTool::Vite, "open", ActionInput::Text("o\r")),
+    (DevTool::Vite, "show_url", ActionInput::Text("u\r")),
+    (DevTool::Vite, "clear", ActionInput::Text("c\r")),
+    (DevTool::Vite, "quit", ActionInput::Text("q\r")),
+    (DevTool::Expo, "reload", ActionInput::Text("r")),
+    (DevTool::Expo, "open_web", ActionInput::Text("w")),
+    (DevTool::Expo, "open_android", ActionInput::Text("a")),
+    (DevTool::Expo, "open_ios", ActionInput::Text("i")),
+    (DevTool::Expo, "dev_menu", ActionInput::Text("m")),
+    (DevTool::Expo, "debugger", ActionInput::Text("j")),
+    (DevTool::JestWatch, "run_all", ActionInput::Text("a")),
+    (DevTool::JestWatch, "run_failed", ActionInput::Text("f")),
+    (DevTool::JestWatch, "changed_only", ActionInput::Text("o")),
+    (
+        DevTool::JestWatch,
+        "update_snapshots",
+        ActionInput::Text("u"),
+    ),
+    (DevTool::JestWatch, "rerun", ActionInput::Keys(&["enter"])),
+    (DevTool::JestWatch, "quit", ActionInput::Text("q")),
+];
+
+const INTERRUPT_ACTION: &str = "interrupt";
+const INTERRUPT_KEYS: &[&str] = &["ctrl+c"];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskAction {
+    pub name: String,
+    pub label: String,
+    // 通用动作为 None
+    pub tool: Option<DevTool>,
+}
+
+fn action_label(name: &str) -> String {
+    i18n::t(&format!("task_action.{}", name))
+}
+
+fn script_body(tab: &KittyTab) -> Option<String> {
+    let content = fs::read_to_string(Path::new(&tab.working_dir).join("package.json")).ok()?;
+    let package_json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    package_json
+        .get("scripts")?
+        .get(&tab.command_name)?
+        .as_str()
+        .map(str::to_string)
+}
+
+// 按脚本内容与执行的命令中出现的程序名识别工具，路径只看最后一段（如 node_modules/.bin/vite）
+pub fn detect_tool(script: &str, command: &str) -> Option<DevTool> {
+    let words: Vec<&str> = script
+        .split_whitespace()
+        .chain(command.split_whitespace())
+        .map(|word| word.rsplit('/').next().unwrap_or(word))
+        .collect();
+    let has = |name: &str| words.contains(&name);
+    // --watchAll=false 等显式关闭监听的写法不算；react-scripts test 默认即为监听模式
+    let watch_flags: Vec<bool> = words
+        .iter()
+        .filter(|word| word.starts_with("--watch"))
+        .map(|word| !word.ends_with("=false"))
+        .collect();
+    let watching = watch_flags.contains(&true);
+    let watch_disabled = watch_flags.contains(&false);
+
+    if (has("jest") && watching) || (has("react-scripts") && has("test") && !watch_disabled) {
+        Some(DevTool::JestWatch)
+    } else if has("expo") {
+        Some(DevTool::Expo)
+    } else if has("vite") {
+        Some(DevTool::Vite)
+    } else {
+        None
+    }
+}
+
+fn tool_for(tab: &KittyTab) -> Option<DevTool> {
+    detect_tool(&script_body(tab).unwrap_or_default(), &tab.command)
+}
+
+// 取运行中的标签页及其窗口匹配条件
+fn running_tab(command_id: &str) -> AppResult<(KittyTab, Option<String>)> {
+    let tab = KITTY_TAB_MANAGER
+        .lock()
+        .map_err(lock_error)?
+        .get(command_id)
+        .cloned()
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, "kitty.tab_not_found"))?;
+    if tab.status != TabStatus::Running {
+        return Err(
+            AppError::new(ErrorCode::KittyProcessNotFound, "kitty.process_not_found")
+                .arg("id", command_id),
+        );
+    }
+    let window = tab.window_id.as_deref().and_then(match_window);
+    if window.is_none() {
+        return Err(AppError::new(ErrorCode::NotFound, "kitty.tab_not_found"));
+    }
+    Ok((tab, window))
+}
+
+fn send_input(command_id: &str, input: ActionInput) -> AppResult<()> {
+    let (tab, window) = running_tab(command_id)?;
+    match input {
+        ActionInput::Text(text) => protocol::send(&tab.socket_path, &SendText::text(window, text)),
+        ActionInput::Keys(keys) => protocol::send(
+            &tab.socket_path,
+            &SendKey {
+                matches: window,
+                keys: keys.iter().map(|key| key.to_string()).collect(),
+            },
+        ),
+    }
+}
+
+// ---------- Tauri 命令 ----------
+
+#[tauri::command(async)]
+pub fn send_text_to_task(command_id: String, text: String) -> AppResult<()> {
+    let (tab, window) = running_tab(&command_id)?;
+    protocol::send(&tab.socket_path, &SendText::text(window, &text))
+}
+
+#[tauri::command(async)]
+pub fn send_key_to_task(command_id: String, keys: Vec<String>) -> AppResult<()> {
+    let keys: Vec<String> = keys
+        .into_iter()
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty())
+        .collect();
+    if keys.is_empty() {
+        return Err(AppError::new(
+            ErrorCode::InvalidArgument,
+            "kitty.keys_required",
+        ));
+    }
+    let (tab, window) = running_tab(&command_id)?;
+    protocol::send(
+        &tab.socket_path,
+        &SendKey {
+            matches: window,
+            keys,
+        },
+    )
+}
+
+// 标签页可用的命名动作：识别出的工具的预设在前，interrupt 在最后
+#[tauri::command]
+pub fn list_task_actions(command_id: String) -> AppResult<Vec<TaskAction>> {
+    let (tab, _) = running_tab(&command_id)?;
+    let tool = tool_for(&tab);
+    let mut actions: Vec<TaskAction> = PRESETS
+        .iter()
+        .filter(|(preset_tool, _, _)| Some(*preset_tool) == tool)
+        .map(|(preset_tool, name, _)| TaskAction {
+            name: name.to_string(),
+            label: action_label(name),
+            tool: Some(*preset_tool),
+        })
+        .collect();
+    actions.push(TaskAction {
+        name: INTERRUPT_ACTION.to_string(),
+        label: action_label(INTERRUPT_ACTION),
+        tool: None,
+    });
+    Ok(actions)
+}
+
+#[tauri::command(async)]
+pub fn run_task_action(command_id: String, action: String) -> AppResult<()> {
+    if action == INTERRUPT_ACTION {
+        return send_input(&command_id, ActionInput::Keys(INTERRUPT_KEYS));
+    }
+    let (tab, _) = running_tab(&command_id)?;
+    let tool = tool_for(&tab);
+    let input = PRESETS
+        .iter()
+        .find(|(preset_tool, name, _)| Some(*preset_tool) == tool && *name == action)
+        .map(|(_, _, input)| *input)
+        .ok_or_else(|| {
+            AppError::new(ErrorCode::InvalidArgument, "kitty.unknown_action").arg("action", &action)
+        })?;
+    send_input(&command_id, input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vite_is_not_confused_with_vitest() {
+        assert_eq!(detect_tool("vite", "pnpm run dev"), Some(DevTool::Vite));
+        assert_eq!(detect_tool("vite --port 3000", ""), Some(DevTool::Vite));
+        assert_eq!(detect_tool("vitest", "pnpm run test"), None);
+        assert_eq!(detect_tool("vitest --watch", ""), None);
+        assert_eq!(detect_tool("vite-node src/main.ts", ""), None);
+    }
+
+    #[test]
+    fn jest_is_detected_only_in_watch_mode() {
+        assert_eq!(detect_tool("jest --watchAll", ""), Some(DevTool::JestWatch));
+        assert_eq!(detect_tool("jest --watch", ""), Some(DevTool::JestWatch));
+        // 监听参数也可能通过 npm run test -- --watch 传入
+        assert_eq!(
+            detect_tool("jest", "npm run test -- --watch"),
+            Some(DevTool::JestWatch)
+        );
+        assert_eq!(detect_tool("jest", "npm run test"), None);
+        assert_eq!(detect_tool("jest --watchAll=false", ""), None);
+    }
+
+    #[test]
+    fn react_scripts_test_watches_by_default() {
+        assert_eq!(
+            detect_tool("react-scripts test", "yarn test"),
+            Some(DevTool::JestWatch)
+        );
+        assert_eq!(detect_tool("react-scripts test --watchAll=false", ""), None);
+        assert_eq!(detect_tool("react-scripts start", ""), None);
+    }
+
+    #[test]
+    fn expo_is_detected() {
+        assert_eq!(
+            detect_tool("expo start", "npm run start"),
+            Some(DevTool::Expo)
+        );
+        assert_eq!(detect_tool("npx expo start --web", ""), Some(DevTool::Expo));
+    }
+
+    #[test]
+    fn bin_paths_use_the_program_name() {
+        assert_eq!(
+            detect_tool("node_modules/.bin/vite", ""),
+            Some(DevTool::Vite)
+        );
+        assert_eq!(
+            detect_tool("", "./node_modules/.bin/jest --watchAll"),
+            Some(DevTool::JestWatch)
+        );
+        assert_eq!(detect_tool("node_modules/.bin/vitest", ""), None);
+        assert_eq!(
+            detect_tool("", "/usr/local/bin/expo start"),
+            Some(DevTool::Expo)
+        );
+    }
+}
@@ -5,24 +5,34 @@
 // - 标签页管理：创建、关闭、列出标签页
 // - 命令执行：在标签页中执行命令
 // - 进程管理：监控和管理运行中的进程
+// - 状态同步：根据 kitty ls 更新标签页的 pid 与退出状态
+// - 输出抓取：把标签页输出写入运行记录
+// - 会话文件：任务组与 kitty --session 文件互相转换
+// - 输入：向运行中的命令发送文本、按键与开发服务器快捷键
 
 // 核心类型和配置
 pub mod core;
 pub use core::{get_current_timestamp, KittyConfig, KittyTab, TabStatus};
 
+// 实例设置：配置文件、override、窗口尺寸与重试
+pub mod config;
+
 // 连接管理
 pub mod connection;
 pub use connection::{
-    check_kitten_available, check_kitty_installed, get_kitty_instance_info, get_socket_path,
-    start_kitty_instance, stop_kitty_instance, test_kitty_connection,
+    check_kitten_available, check_kitty_installed, get_socket_path, start_kitty_instance,
+    stop_kitty_instance, test_kitty_connection,
 };
 
+// 远程控制协议
+pub mod protocol;
+
+// 实例分组：按命令、项目或工作区共用实例
+pub mod grouping;
+
 // 标签页管理
 pub mod tabs;
-pub use tabs::{
-    cleanup_completed_tabs, close_kitty_tab, create_kitty_tab, get_all_tabs, get_tab_status,
-    list_kitty_tabs, register_kitty_tab, unregister_kitty_tab, update_tab_status,
-};
+pub use tabs::{create_kitty_tab, list_kitty_tabs, register_kitty_tab, unregister_kitty_tab};
 
 // 命令执行
 pub mod executor;
@@ -30,6 +40,18 @@ pub use executor::{
     build_execution_command, execute_command_in_kitty, execute_command_with_kitten,
 };
 
+// 状态同步
+pub mod status;
+
+// 输出抓取
+pub mod capture;
+
+// 会话文件导入导出
+pub mod session;
+
+// 向标签页发送输入
+pub mod input;
+
 // 进程管理
 pub mod process;
 pub use process::{
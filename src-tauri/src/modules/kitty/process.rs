@@ -1,63 +1,114 @@
-use crate::modules::kitty::connection::get_socket_path;
-use crate::modules::kitty::core::KittyConfig;
-use crate::modules::kitty::core::{KITTY_TAB_MANAGER, PROCESS_MANAGER};
-use crate::modules::kitty::tabs::unregister_kitty_tab;
+use crate::modules::error::{AppError, AppResult, ErrorCode};
+use crate::modules::i18n;
+use crate::modules::kitty::capture;
+use crate::modules::kitty::core::{KittyTab, TabStatus, KITTY_TAB_MANAGER, PROCESS_MANAGER};
+use crate::modules::kitty::protocol::{self, CloseWindow};
+use crate::modules::kitty::status;
+use crate::modules::kitty::tabs::{list_kitty_tabs, unregister_kitty_tab};
+use crate::modules::task_runner::RunStatus;
 use std::process::Command;
+use tauri::AppHandle;
 
-// 终止指定的进程（使用kitty远程控制）
-#[tauri::command]
-pub fn terminate_command(command_id: String) -> Result<String, String> {
-    let config = KittyConfig::default();
-    let socket_path = get_socket_path(&command_id, &config);
+// POSIX ERE 的元字符，其余字符加反斜杠在 ERE 中是未定义行为
+const ERE_METACHARACTERS: &str = ".[]()*+?{}|^$\\";
 
-    // 检查进程是否存在
-    let mut process_found = false;
-    if let Ok(mut manager) = PROCESS_MANAGER.lock() {
-        if let Some(_child) = manager.remove(&command_id) {
-            process_found = true;
+// 生成 pkill -f 使用的匹配模式（POSIX ERE），只转义 ERE 元字符
+pub fn build_kill_pattern(command_id: &str) -> String {
+    let mut pattern = String::new();
+    for c in command_id.replace('-', "_").chars() {
+        if ERE_METACHARACTERS.contains(c) {
+            pattern.push('\\');
         }
+        pattern.push(c);
     }
+    pattern
+}
 
-    if !process_found {
-        return Err(format!("未找到运行中的命令: {}", command_id));
-    }
+// 标签页所在的窗口：与状态同步相同，按记录的窗口 id 或 RUNPROJECT_COMMAND_ID 查找
+fn tab_window_id(tab: &KittyTab) -> Option<String> {
+    let windows: Vec<_> = list_kitty_tabs(&tab.socket_path)
+        .ok()?
+        .into_iter()
+        .flat_map(|os_window| os_window.tabs)
+        .flat_map(|tab| tab.windows)
+        .collect();
+    status::find_window(tab, &windows).map(|window| window.id.to_string())
+}
+
+// 只关闭匹配到的窗口，找不到时不发送关闭命令，以免关掉实例中的当前活动窗口
+fn close_tab_window(tab: &KittyTab) -> AppResult<()> {
+    let window_id = tab_window_id(tab).ok_or_else(|| {
+        AppError::new(ErrorCode::KittyProcessNotFound, "kitty.window_not_found").arg("id", &tab.id)
+    })?;
+    let close = CloseWindow {
+        matches: protocol::match_window(&window_id),
+        ignore_no_match: false,
+    };
+    protocol::send(&tab.socket_path, &close)
+}
+
+// 终止指定的进程（使用kitty远程控制）
+#[tauri::command]
+pub fn terminate_command(app: AppHandle, command_id: String) -> AppResult<String> {
+    // 标签页记录中有所在实例的 socket；没有记录的是独立窗口模式启动的命令
+    let tab = KITTY_TAB_MANAGER
+        .lock()
+        .ok()
+        .and_then(|manager| manager.get(&command_id).cloned());
+
+    // 检查进程是否存在
+    let child = PROCESS_MANAGER
+        .lock()
+        .ok()
+        .and_then(|mut manager| manager.remove(&command_id));
+    let Some(mut child) = child else {
+        return Err(
+            AppError::new(ErrorCode::KittyProcessNotFound, "kitty.process_not_found")
+                .arg("id", &command_id),
+        );
+    };
 
     // 尝试多种方法终止命令
     let mut success = false;
     let mut error_msg = String::new();
 
-    // 方法1: 尝试使用kitty远程控制
-    let kitty_command = format!("kitty @ --to {} close-window", socket_path);
+    // 仍在运行时，关闭前抓取最后的输出并结束运行记录
+    if let Some(tab) = tab.as_ref().filter(|tab| tab.status == TabStatus::Running) {
+        capture::finish(&app, tab, RunStatus::Cancelled);
+    }
 
-    if let Ok(output) = Command::new("sh").arg("-c").arg(&kitty_command).output() {
-        if output.status.success() {
-            success = true;
-        } else {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            error_msg.push_str(&format!("kitty控制失败: {}", stderr));
+    // 方法1: 标签页通过远程控制关闭所在窗口；独立窗口的实例只属于这条命令，直接结束 kitty 进程
+    let closed = match &tab {
+        Some(tab) => close_tab_window(tab),
+        None => child
+            .kill()
+            .map_err(|e| AppError::io("kitty.kill_window_failed", &e)),
+    };
+    match closed {
+        Ok(()) => success = true,
+        Err(err) => {
+            error_msg.push_str(&i18n::tf("kitty.remote_close_failed", &[("error", &err)]));
         }
-    } else {
-        error_msg.push_str("kitty命令执行失败");
     }
 
     // 方法2: 如果kitty控制失败，尝试直接终止进程树
     if !success {
-        // 使用command_id来匹配相关进程
-        let kill_pattern = command_id.replace('-', "_");
-        let kill_command = format!("pkill -f '{}' || echo '未找到相关进程'", kill_pattern);
+        // 使用command_id来匹配相关进程，pkill 的模式是 ERE，需要转义
+        let kill_pattern = build_kill_pattern(&command_id);
 
-        if let Ok(output) = Command::new("sh").arg("-c").arg(&kill_command).output() {
-            if output.status.success() {
+        if let Ok(output) = Command::new("pkill").arg("-f").arg(&kill_pattern).output() {
+            // 退出码 1 表示没有匹配的进程，与之前的行为一致视为成功
+            if output.status.success() || output.status.code() == Some(1) {
                 success = true;
-                error_msg = format!("已强制终止相关进程");
+                error_msg = i18n::t("kitty.force_killed");
             } else {
-                error_msg.push_str(&format!(
-                    " | 强制终止也失败: {}",
-                    String::from_utf8_lossy(&output.stderr)
-                ));
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                error_msg.push_str(" | ");
+                error_msg.push_str(&i18n::tf("kitty.force_kill_failed", &[("error", &stderr)]));
             }
         } else {
-            error_msg.push_str(" | 强制终止命令执行失败");
+            error_msg.push_str(" | ");
+            error_msg.push_str(&i18n::t("kitty.force_kill_spawn_failed"));
         }
     }
 
@@ -65,26 +116,32 @@ pub fn terminate_command(command_id: String) -> Result<String, String> {
     let _ = unregister_kitty_tab(&command_id);
 
     if success {
-        Ok(format!("命令 {} 已终止", command_id))
+        Ok(i18n::tf("kitty.terminated", &[("id", &command_id)]))
     } else {
-        Err(format!("终止命令失败: {}", error_msg))
+        Err(
+            AppError::new(ErrorCode::KittyCommandFailed, "kitty.terminate_failed")
+                .arg("error", error_msg),
+        )
     }
 }
 
 // 获取所有运行中的进程
 #[tauri::command]
-pub fn get_running_processes() -> Result<Vec<crate::modules::kitty::core::KittyTab>, String> {
+pub fn get_running_processes() -> AppResult<Vec<crate::modules::kitty::core::KittyTab>> {
     if let Ok(manager) = KITTY_TAB_MANAGER.lock() {
         let tabs: Vec<_> = manager.values().cloned().collect();
         Ok(tabs)
     } else {
-        Err("无法访问标签页管理器".to_string())
+        Err(AppError::new(
+            ErrorCode::Internal,
+            "kitty.tab_manager_unavailable",
+        ))
     }
 }
 
 // 清理所有已完成的进程
 #[tauri::command]
-pub fn cleanup_completed_processes() -> Result<usize, String> {
+pub fn cleanup_completed_processes() -> AppResult<usize> {
     if let Ok(mut manager) = KITTY_TAB_MANAGER.lock() {
         let initial_count = manager.len();
         manager.retain(|_, tab| {
@@ -97,12 +154,15 @@ pub fn cleanup_completed_processes() -> Result<usize, String> {
         });
         Ok(initial_count - manager.len())
     } else {
-        Err("无法访问标签页管理器".to_string())
+        Err(AppError::new(
+            ErrorCode::Internal,
+            "kitty.tab_manager_unavailable",
+        ))
     }
 }
 
 // 关闭所有标签页并清理资源
-pub fn shutdown_all_kitty_instances() -> Result<(), String> {
+pub fn shutdown_all_kitty_instances() -> AppResult<()> {
     // 获取所有socket路径
     let socket_paths: Vec<String> = {
         if let Ok(manager) = KITTY_TAB_MANAGER.lock() {
@@ -0,0 +1,486 @@
+// kitty 远程控制协议客户端
+//
+// 不再启动 `kitty @` 子进程，直接连接 --listen-on 指定的 socket 发送命令：
+// - 请求：ESC P @kitty-cmd {"cmd", "version", "no_response", "payload"} ESC \
+// - 响应：同样的包装，内容为 {"ok", "data", "error"}
+// - 每条命令使用一个新连接，kitty 回复后即关闭
+// 通过 allow_remote_control=yes 启动的实例不需要密码，不处理加密
+use crate::modules::error::{AppError, AppResult, ErrorCode};
+#[cfg(unix)]
+use crate::modules::process_runner::{self, OperationKind};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+#[cfg(unix)]
+use std::io::{Read, Write};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+
+#[cfg(unix)]
+const PREFIX: &[u8] = b"\x1bP@kitty-cmd";
+#[cfg(unix)]
+const SUFFIX: &[u8] = b"\x1b\\";
+// 声明的客户端版本，kitty 只用来判断是否支持对应的参数
+#[cfg(unix)]
+const PROTOCOL_VERSION: [u32; 3] = [0, 26, 0];
+
+// 一条远程控制命令及其响应类型
+pub trait RemoteCommand: Serialize {
+    const NAME: &'static str;
+    type Response;
+
+    // data 为响应中的 data 字段，没有时为 None
+    fn parse_response(data: Option<Value>) -> AppResult<Self::Response>;
+}
+
+#[cfg(unix)]
+#[derive(Serialize)]
+struct Request<'a, P: Serialize> {
+    cmd: &'static str,
+    version: [u32; 3],
+    no_response: bool,
+    payload: &'a P,
+}
+
+#[cfg(unix)]
+#[derive(Deserialize)]
+struct Response {
+    ok: bool,
+    #[serde(default)]
+    data: Option<Value>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+fn parse_failed(error: impl std::fmt::Display) -> AppError {
+    AppError::new(ErrorCode::KittyCommandFailed, "kitty.protocol_parse_failed").arg("error", error)
+}
+
+#[cfg(unix)]
+fn io_failed(error: std::io::Error) -> AppError {
+    AppError::new(ErrorCode::KittyCommandFailed, "kitty.protocol_io_failed").arg("error", error)
+}
+
+// 文本类响应：kitty 返回字符串，个别版本返回数字
+fn data_text(data: Option<Value>) -> String {
+    match data {
+        Some(Value::String(text)) => text,
+        Some(Value::Null) | None => String::new(),
+        Some(other) => other.to_string(),
+    }
+}
+
+// 没有返回值的命令
+fn no_data(_: Option<Value>) -> AppResult<()> {
+    Ok(())
+}
+
+// ---------- 命令定义 ----------
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Ls {
+    #[serde(rename = "match", skip_serializing_if = "Option::is_none")]
+    pub matches: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OsWindow {
+    pub id: u64,
+    #[serde(default)]
+    pub is_focused: bool,
+    #[serde(default)]
+    pub tabs: Vec<Tab>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tab {
+    pub id: u64,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub is_focused: bool,
+    #[serde(default)]
+    pub windows: Vec<Window>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Window {
+    pub id: u64,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub pid: Option<u32>,
+    #[serde(default)]
+    pub cwd: String,
+    #[serde(default)]
+    pub cmdline: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    // 通过 OSC 1337 SetUserVar 设置的变量，kitty 已解码
+    #[serde(default)]
+    pub user_vars: HashMap<String, String>,
+    #[serde(default)]
+    pub foreground_processes: Vec<ForegroundProcess>,
+    #[serde(default)]
+    pub is_focused: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForegroundProcess {
+    pub pid: u32,
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub cmdline: Vec<String>,
+}
+
+impl RemoteCommand for Ls {
+    const NAME: &'static str = "ls";
+    type Response = Vec<OsWindow>;
+
+    // ls 的 data 是 JSON 文本，需要再解析一次
+    fn parse_response(data: Option<Value>) -> AppResult<Self::Response> {
+        match data {
+            Some(Value::String(text)) => serde_json::from_str(&text).map_err(parse_failed),
+            Some(value) => serde_json::from_value(value).map_err(parse_failed),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Launch {
+    pub args: Vec<String>,
+    #[serde(rename = "type")]
+    pub launch_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tab_title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    // KEY=VALUE 形式
+    pub env: Vec<String>,
+    pub hold: bool,
+    pub keep_focus: bool,
+    // type 为 window 时新窗口所在的标签页，为空时使用当前活动标签页
+    #[serde(rename = "match", skip_serializing_if = "Option::is_none")]
+    pub matches: Option<String>,
+    // 分屏位置，如 vsplit、hsplit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+impl RemoteCommand for Launch {
+    const NAME: &'static str = "launch";
+    // 新窗口的 id
+    type Response = String;
+
+    fn parse_response(data: Option<Value>) -> AppResult<Self::Response> {
+        Ok(data_text(data))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CloseWindow {
+    #[serde(rename = "match")]
+    pub matches: Option<String>,
+    pub ignore_no_match: bool,
+}
+
+impl RemoteCommand for CloseWindow {
+    const NAME: &'static str = "close-window";
+    type Response = ();
+
+    fn parse_response(data: Option<Value>) -> AppResult<Self::Response> {
+        no_data(data)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SendText {
+    #[serde(rename = "match")]
+    pub matches: Option<String>,
+    // text:<原文> 或 base64:<编码>
+    pub data: String,
+}
+
+impl SendText {
+    pub fn text(matches: Option<String>, text: &str) -> Self {
+        Self {
+            matches,
+            data: format!("text:{}", text),
+        }
+    }
+}
+
+impl RemoteCommand for SendText {
+    const NAME: &'static str = "send-text";
+    type Response = ();
+
+    fn parse_response(data: Option<Value>) -> AppResult<Self::Response> {
+        no_data(data)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SendKey {
+    #[serde(rename = "match")]
+    pub matches: Option<String>,
+    // kitty 按键名，如 ctrl+c、enter、r
+    pub keys: Vec<String>,
+}
+
+impl RemoteCommand for SendKey {
+    const NAME: &'static str = "send-key";
+    type Response = ();
+
+    fn parse_response(data: Option<Value>) -> AppResult<Self::Response> {
+        no_data(data)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GetText {
+    #[serde(rename = "match")]
+    pub matches: Option<String>,
+    // screen、all、selection 等
+    pub extent: String,
+    pub ansi: bool,
+}
+
+impl RemoteCommand for GetText {
+    const NAME: &'static str = "get-text";
+    type Response = String;
+
+    fn parse_response(data: Option<Value>) -> AppResult<Self::Response> {
+        Ok(data_text(data))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FocusTab {
+    #[serde(rename = "match")]
+    pub matches: Option<String>,
+}
+
+impl RemoteCommand for FocusTab {
+    const NAME: &'static str = "focus-tab";
+    type Response = ();
+
+    fn parse_response(data: Option<Value>) -> AppResult<Self::Response> {
+        no_data(data)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SetTabTitle {
+    pub title: String,
+    #[serde(rename = "match")]
+    pub matches: Option<String>,
+}
+
+impl RemoteCommand for SetTabTitle {
+    const NAME: &'static str = "set-tab-title";
+    type Response = ();
+
+    fn parse_response(data: Option<Value>) -> AppResult<Self::Response> {
+        no_data(data)
+    }
+}
+
+// 执行可映射到快捷键的动作，如 quit
+#[derive(Debug, Clone, Serialize)]
+pub struct Action {
+    pub action: String,
+}
+
+impl RemoteCommand for Action {
+    const NAME: &'static str = "action";
+    type Response = ();
+
+    fn parse_response(data: Option<Value>) -> AppResult<Self::Response> {
+        no_data(data)
+    }
+}
+
+// 按窗口 id 匹配
+pub fn match_window(window_id: &str) -> Option<String> {
+    Some(format!("id:{}", window_id))
+}
+
+// ---------- 连接 ----------
+
+#[cfg(unix)]
+fn connect(socket_path: &str) -> AppResult<UnixStream> {
+    let connect_failed = |error: std::io::Error| {
+        AppError::new(ErrorCode::KittyConnectionFailed, "kitty.connect_failed")
+            .arg("socket", socket_path)
+            .arg("error", error)
+    };
+    let Some(address) = socket_path.strip_prefix("unix:") else {
+        return Err(
+            AppError::new(ErrorCode::KittyConnectionFailed, "kitty.unsupported_socket")
+                .arg("socket", socket_path),
+        );
+    };
+
+    // Linux 上 @ 开头的是抽象命名空间 socket
+    #[cfg(target_os = "linux")]
+    if let Some(name) = address.strip_prefix('@') {
+        use std::os::linux::net::SocketAddrExt;
+        let address =
+            std::os::unix::net::SocketAddr::from_abstract_name(name).map_err(connect_failed)?;
+        return UnixStream::connect_addr(&address).map_err(connect_failed);
+    }
+
+    UnixStream::connect(address).map_err(connect_failed)
+}
+
+#[cfg(unix)]
+pub(crate) fn encode<P: Serialize>(name: &'static str, payload: &P) -> AppResult<Vec<u8>> {
+    let request = Request {
+        cmd: name,
+        version: PROTOCOL_VERSION,
+        no_response: false,
+        payload,
+    };
+    let body = serde_json::to_vec(&request).map_err(parse_failed)?;
+    Ok([PREFIX, &body, SUFFIX].concat())
+}
+
+// 读取一条完整的响应；JSON 中的控制字符都会被转义，遇到 ESC \ 即为结尾
+#[cfg(unix)]
+fn read_response(stream: &mut impl Read) -> AppResult<Response> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 8192];
+    loop {
+        let read = stream.read(&mut chunk).map_err(io_failed)?;
+        if read == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+        if buffer.ends_with(SUFFIX) {
+            break;
+        }
+    }
+
+    let start = buffer
+        .windows(PREFIX.len())
+        .position(|window| window == PREFIX)
+        .ok_or_else(|| parse_failed("missing response header"))?;
+    let body = buffer[start + PREFIX.len()..]
+        .strip_suffix(SUFFIX)
+        .ok_or_else(|| parse_failed("truncated response"))?;
+    serde_json::from_slice(body).map_err(parse_failed)
+}
+
+// 发送一条命令并等待响应，读写超时沿用外部终端的超时设置
+#[cfg(unix)]
+pub fn send<C: RemoteCommand>(socket_path: &str, command: &C) -> AppResult<C::Response> {
+    let mut stream = connect(socket_path)?;
+    let timeout = process_runner::timeout_for(OperationKind::Terminal);
+    stream.set_read_timeout(timeout).map_err(io_failed)?;
+    stream.set_write_timeout(timeout).map_err(io_failed)?;
+
+    stream
+        .write_all(&encode(C::NAME, command)?)
+        .map_err(io_failed)?;
+    let response = read_response(&mut stream)?;
+    if response.ok {
+        C::parse_response(response.data)
+    } else {
+        Err(
+            AppError::new(ErrorCode::KittyCommandFailed, "kitty.remote_error")
+                .arg("command", C::NAME)
+                .arg("error", response.error.unwrap_or_default().trim()),
+        )
+    }
+}
+
+// kitty 只通过 Unix socket 提供远程控制
+#[cfg(not(unix))]
+pub fn send<C: RemoteCommand>(socket_path: &str, _command: &C) -> AppResult<C::Response> {
+    Err(
+        AppError::new(ErrorCode::KittyConnectionFailed, "kitty.unsupported_socket")
+            .arg("socket", socket_path),
+    )
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    // 每次 read 只返回一段，模拟响应被拆成多次到达
+    struct Chunks(VecDeque<Vec<u8>>);
+
+    impl Read for Chunks {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let Some(chunk) = self.0.pop_front() else {
+                return Ok(0);
+            };
+            buf[..chunk.len()].copy_from_slice(&chunk);
+            Ok(chunk.len())
+        }
+    }
+
+    fn chunks(parts: &[&[u8]]) -> Chunks {
+        Chunks(parts.iter().map(|part| part.to_vec()).collect())
+    }
+
+    #[test]
+    fn requests_are_wrapped_in_the_kitty_envelope() {
+        let encoded = encode(Ls::NAME, &Ls::default()).unwrap();
+        let body = encoded
+            .strip_prefix(PREFIX)
+            .and_then(|rest| rest.strip_suffix(SUFFIX))
+            .unwrap();
+        let request: Value = serde_json::from_slice(body).unwrap();
+        assert_eq!(request["cmd"], "ls");
+        assert_eq!(request["version"], serde_json::json!([0, 26, 0]));
+        assert_eq!(request["no_response"], false);
+        assert_eq!(request["payload"], serde_json::json!({}));
+    }
+
+    #[test]
+    fn responses_split_across_reads_are_reassembled() {
+        let mut stream = chunks(&[
+            b"\x1bP@kit",
+            b"ty-cmd{\"ok\": true, \"data\": \"4",
+            b"2\"}\x1b",
+            b"\\",
+        ]);
+        let response = read_response(&mut stream).unwrap();
+        assert!(response.ok);
+        assert_eq!(data_text(response.data), "42");
+    }
+
+    #[test]
+    fn bytes_before_the_header_are_skipped() {
+        let mut stream =
+            chunks(&[b"noise\x1bP@kitty-cmd{\"ok\": false, \"error\": \"no match\"}\x1b\\"]);
+        let response = read_response(&mut stream).unwrap();
+        assert!(!response.ok);
+        assert_eq!(response.error.as_deref(), Some("no match"));
+    }
+
+    fn failure(stream: &mut Chunks) -> String {
+        match read_response(stream) {
+            Ok(_) => panic!("response unexpectedly parsed"),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    #[test]
+    fn missing_header_and_truncated_responses_fail() {
+        let missing = failure(&mut chunks(&[b"{\"ok\": true}\x1b\\"]));
+        assert!(missing.contains("missing response header"));
+
+        let truncated = failure(&mut chunks(&[b"\x1bP@kitty-cmd{\"ok\": tr"]));
+        assert!(truncated.contains("truncated response"));
+
+        let empty = failure(&mut chunks(&[]));
+        assert!(empty.contains("missing response header"));
+    }
+}
@@ -0,0 +1,648 @@
+// kitty 会话文件
+//
+// 在任务组、运行中的标签页与 kitty 的 --session 文件之间转换。导出的文件不依赖应用，
+// 可以直接通过 `kitty --session <文件>` 启动整套服务：
+// - 标签页布局每个任务一个 new_tab，分屏布局同一组任务放在 layout splits 的标签页中
+// - 每个窗口与应用内一样通过 TAB_SCRIPT 运行命令，结束后保留交互 shell
+// - 环境变量可能含有密钥，只有显式要求时才写成 launch --env，运行中的标签页按启动时的环境配置解析
+// - 导入时按 new_tab / new_os_window 划分标签页，cd、title 与 launch 的 --cwd、--title、--env 决定目录、标题与环境变量；
+//   含多个窗口的标签页导入为分屏布局，每个分屏标签页保持独立
+use crate::modules::env_profile;
+use crate::modules::error::{lock_error, AppError, AppResult, ErrorCode};
+use crate::modules::kitty::core::{KittyTab, TabStatus, KITTY_TAB_MANAGER};
+use crate::modules::kitty::grouping::{self, Layout};
+use crate::modules::kitty::tabs::{tab_argv, TAB_SCRIPT};
+use crate::modules::platform::shell_quote;
+use crate::modules::task_group::{self, GroupTask, TaskGroup};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+// 分屏布局中后续窗口的位置
+const SPLIT_LOCATION: &str = "vsplit";
+// 导出运行中的标签页且未指定工作区时的会话名
+const DEFAULT_SESSION_NAME: &str = "kitty";
+// 需要取值的 launch 选项，写成 --name value 时下一个参数是它的值
+const LAUNCH_VALUE_OPTIONS: &[&str] = &[
+    "title",
+    "window-title",
+    "tab-title",
+    "type",
+    "cwd",
+    "env",
+    "var",
+    "location",
+    "next-to",
+    "bias",
+    "os-window-title",
+    "os-window-name",
+    "os-window-class",
+    "os-window-state",
+    "stdin-source",
+    "marker",
+    "logo",
+    "logo-position",
+    "logo-alpha",
+    "color",
+    "watcher",
+    "spacing",
+];
+// 导入时可以直接取出 -c 脚本的 shell
+const SHELLS: &[&str] = &["sh", "bash", "zsh", "dash", "fish"];
+
+// 会话中的一个标签页
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionTab {
+    pub title: String,
+    pub split: bool,
+    pub windows: Vec<GroupTask>,
+}
+
+// kitty 按 Python 的 splitlines 拆分会话文件，除 \n 外这些字符也会断行
+pub fn is_line_break(c: char) -> bool {
+    matches!(
+        c,
+        '\n' | '\r'
+            | '\x0b'
+            | '\x0c'
+            | '\x1c'
+            | '\x1d'
+            | '\x1e'
+            | '\u{85}'
+            | '\u{2028}'
+            | '\u{2029}'
+    )
+}
+
+// 标题中的换行替换为空格
+fn single_line(value: &str) -> String {
+    value.replace(is_line_break, " ")
+}
+
+fn expand_home(path: &str) -> String {
+    match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            format!("{}{}", std::env::var("HOME").unwrap_or_default(), rest)
+        }
+        _ => path.to_string(),
+    }
+}
+
+// 按 shlex（POSIX 模式）的规则拆分参数，与 kitty 解析会话文件一致，只有空格、制表符与换行分隔参数
+pub fn split_args(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                for c in chars.by_ref() {
+                    if c == '\'' {
+                        break;
+                    }
+                    current.push(c);
+                }
+            }
+            '"' => {
+                in_word = true;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        // 双引号中的反斜杠只转义 " 与 \
+                        '\\' => match chars.next() {
+                            Some(next @ ('"' | '\\')) => current.push(next),
+                            Some(next) => {
+                                current.push('\\');
+                                current.push(next);
+                            }
+                            None => current.push('\\'),
+                        },
+                        _ => current.push(c),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            ' ' | '\t' | '\r' | '\n' => {
+                if in_word {
+                    args.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            _ => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_word {
+        args.push(current);
+    }
+    args
+}
+
+// 生成会话文件内容；目录或命令中含换行时无法写成单行，返回错误
+pub fn render_session(name: &str, tabs: &[SessionTab]) -> AppResult<String> {
+    let mut content = format!("# {}\n# kitty --session <file>\n", single_line(name));
+    for tab in tabs {
+        content.push_str(&format!("\nnew_tab {}\n", single_line(&tab.title)));
+        if tab.split {
+            content.push_str("layout splits\n");
+        }
+        for (index, task) in tab.windows.iter().enumerate() {
+            if task.cwd.contains(is_line_break)
+                || task.command.contains(is_line_break)
+                || task.env.iter().any(|(key, value)| {
+                    key.contains(is_line_break) || value.contains(is_line_break)
+                })
+            {
+                return Err(
+                    AppError::new(ErrorCode::InvalidArgument, "kitty.session_multiline")
+                        .arg("title", &task.title),
+                );
+            }
+            let mut args = vec![
+                "launch".to_string(),
+                format!("--title={}", single_line(&task.title)),
+                format!("--cwd={}", task.cwd),
+            ];
+            args.extend(
+                task.env
+                    .iter()
+                    .map(|(key, value)| format!("--env={}={}", key, value)),
+            );
+            if tab.split && index > 0 {
+                args.push(format!("--location={}", SPLIT_LOCATION));
+            }
+            args.extend(tab_argv(&task.cwd, &task.command));
+            let line: Vec<String> = args.iter().map(|arg| shell_quote(arg)).collect();
+            content.push_str(&line.join(" "));
+            content.push('\n');
+        }
+    }
+    Ok(content)
+}
+
+// 从 launch 的参数中取出命令与目录：应用生成的 TAB_SCRIPT 直接取位置参数，
+// shell -c 取脚本，其他程序按参数重新拼成命令
+pub fn launch_command(argv: &[String]) -> Option<(String, Option<String>)> {
+    match argv {
+        [] => None,
+        [_, _, script, _, command, cwd, ..] if script == TAB_SCRIPT => {
+            Some((command.clone(), Some(cwd.clone())))
+        }
+        [shell, flag, script, ..]
+            if Path::new(shell)
+                .file_name()
+                .is_some_and(|name| SHELLS.iter().any(|s| name == *s))
+                && flag.starts_with('-')
+                && flag.ends_with('c') =>
+        {
+            Some((script.clone(), None))
+        }
+        _ => {
+            let quoted: Vec<String> = argv.iter().map(|arg| shell_quote(arg)).collect();
+            Some((quoted.join(" "), None))
+        }
+    }
+}
+
+fn parse_launch(
+    args: &[String],
+    default_cwd: Option<&str>,
+    title: Option<String>,
+    tab_title: &str,
+) -> Option<GroupTask> {
+    let mut title = title;
+    let mut cwd = default_cwd.map(str::to_string);
+    let mut env = BTreeMap::new();
+    let mut index = 0;
+    while let Some(option) = args.get(index).and_then(|arg| arg.strip_prefix("--")) {
+        index += 1;
+        // 单独的 -- 之后都是命令
+        if option.is_empty() {
+            break;
+        }
+        let (name, value) = match option.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string())),
+            None if LAUNCH_VALUE_OPTIONS.contains(&option) => {
+                index += 1;
+                (option, args.get(index - 1).cloned())
+            }
+            None => (option, None),
+        };
+        match (name, value) {
+            ("title" | "window-title", Some(value)) => title = Some(value),
+            ("cwd", Some(value)) => cwd = Some(expand_home(&value)),
+            ("env", Some(value)) => {
+                if let Some((key, value)) = value.split_once('=') {
+                    env.insert(key.to_string(), value.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let (command, script_cwd) = launch_command(args.get(index..).unwrap_or_default())?;
+    let title = title
+        .filter(|title| !title.trim().is_empty())
+        .or_else(|| Some(tab_title.to_string()).filter(|title| !title.trim().is_empty()))
+        .unwrap_or_else(|| command.split_whitespace().next().unwrap_or("").to_string());
+    Some(GroupTask {
+        title,
+        cwd: script_cwd.or(cwd).unwrap_or_else(|| expand_home("~")),
+        command,
+        env,
+        tab: None,
+    })
+}
+
+// 解析会话文件，忽略没有命令的标签页与不影响任务内容的指令（focus、os_window_size 等）
+pub fn parse_session(content: &str) -> Vec<SessionTab> {
+    let mut tabs: Vec<SessionTab> = Vec::new();
+    let mut session_cwd: Option<String> = None;
+    let mut tab_cwd: Option<String> = None;
+    let mut next_title: Option<String> = None;
+
+    for line in content.split(is_line_break) {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (command, rest) = line
+            .split_once(char::is_whitespace)
+            .map(|(command, rest)| (command, rest.trim()))
+            .unwrap_or((line, ""));
+        match command {
+            "new_tab" | "new_os_window" => {
+                tabs.push(SessionTab {
+                    title: if command == "new_tab" {
+                        rest.to_string()
+                    } else {
+                        String::new()
+                    },
+                    split: false,
+                    windows: Vec::new(),
+                });
+                tab_cwd = None;
+            }
+            "cd" if tabs.is_empty() => session_cwd = Some(expand_home(rest)),
+            "cd" => tab_cwd = Some(expand_home(rest)),
+            "title" => next_title = Some(rest.to_string()),
+            "launch" => {
+                // 第一个 new_tab 之前的窗口属于隐含的第一个标签页
+                if tabs.is_empty() {
+                    tabs.push(SessionTab {
+                        title: String::new(),
+                        split: false,
+                        windows: Vec::new(),
+                    });
+                }
+                let Some(tab) = tabs.last_mut() else {
+                    continue;
+                };
+                let default_cwd = tab_cwd.as_deref().or(session_cwd.as_deref());
+                if let Some(task) = parse_launch(
+                    &split_args(rest),
+                    default_cwd,
+                    next_title.take(),
+                    &tab.title,
+                ) {
+                    tab.windows.push(task);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    tabs.retain(|tab| !tab.windows.is_empty());
+    for tab in &mut tabs {
+        tab.split = tab.windows.len() > 1;
+    }
+    tabs
+}
+
+// 分屏布局按任务所在的标签页分组，相邻且标签页相同的任务放在同一个标签页中
+fn group_tabs(group: &TaskGroup) -> Vec<SessionTab> {
+    match group.layout {
+        Layout::Splits => {
+            let mut tabs: Vec<(Option<&str>, SessionTab)> = Vec::new();
+            for task in &group.tasks {
+                let key = task.tab.as_deref();
+                match tabs.last_mut() {
+                    Some((last, tab)) if *last == key => tab.windows.push(task.clone()),
+                    _ => tabs.push((
+                        key,
+                        SessionTab {
+                            title: key.unwrap_or(&group.name).to_string(),
+                            split: false,
+                            windows: vec![task.clone()],
+                        },
+                    )),
+                }
+            }
+            tabs.into_iter()
+                .map(|(_, mut tab)| {
+                    tab.split = tab.windows.len() > 1;
+                    tab
+                })
+                .collect()
+        }
+        Layout::Tabs => group
+            .tasks
+            .iter()
+            .map(|task| SessionTab {
+                title: task.title.clone(),
+                split: false,
+                windows: vec![task.clone()],
+            })
+            .collect(),
+    }
+}
+
+// 运行中的标签页按分组设置中各项目的布局排列，分屏布局的项目合并为一个标签页
+fn running_tabs(
+    workspace_path: Option<&str>,
+    include_env: bool,
+) -> AppResult<(String, Vec<SessionTab>)> {
+    let mut running: Vec<KittyTab> = KITTY_TAB_MANAGER
+        .lock()
+        .map_err(lock_error)?
+        .values()
+        .filter(|tab| tab.status == TabStatus::Running)
+        .filter(|tab| {
+            workspace_path
+                .is_none_or(|workspace| Path::new(&tab.working_dir).starts_with(workspace))
+        })
+        .cloned()
+        .collect();
+    if running.is_empty() {
+        return Err(AppError::new(
+            ErrorCode::NotFound,
+            "kitty.session_nothing_running",
+        ));
+    }
+    running.sort_by_key(|tab| tab.created_at);
+
+    let settings = grouping::settings()?;
+    let mut tabs: Vec<(Option<String>, SessionTab)> = Vec::new();
+    for tab in running {
+        // 按标签页启动时的环境配置解析，导出后在应用外启动时同样生效
+        let env = if include_env {
+            env_profile::resolve_env_vars(
+                &tab.working_dir,
+                Some(&tab.command_name),
+                tab.env_profile.as_deref(),
+            )?
+            .into_iter()
+            .collect()
+        } else {
+            BTreeMap::new()
+        };
+        let task = GroupTask {
+            title: format!("{} - {}", tab.project_name, tab.command_name),
+            cwd: tab.working_dir.clone(),
+            command: tab.command.clone(),
+            env,
+            tab: None,
+        };
+        if settings.layout_for(&tab.working_dir) == Layout::Tabs {
+            tabs.push((
+                None,
+                SessionTab {
+                    title: task.title.clone(),
+                    split: false,
+                    windows: vec![task],
+                },
+            ));
+            continue;
+        }
+        match tabs
+            .iter_mut()
+            .find(|(project, _)| project.as_deref() == Some(tab.working_dir.as_str()))
+        {
+            Some((_, session_tab)) => session_tab.windows.push(task),
+            None => tabs.push((
+                Some(tab.working_dir.clone()),
+                SessionTab {
+                    title: tab.project_name.clone(),
+                    split: true,
+                    windows: vec![task],
+                },
+            )),
+        }
+    }
+
+    let name = workspace_path
+        .and_then(|workspace| Path::new(workspace).file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| DEFAULT_SESSION_NAME.to_string());
+    Ok((name, tabs.into_iter().map(|(_, tab)| tab).collect()))
+}
+
+// ---------- Tauri 命令 ----------
+
+// 导出指定任务组，未指定时导出工作区内运行中的标签页；传入 output_path 时同时写入文件
+// include_env 为 true 时才写入环境变量
+#[tauri::command]
+pub fn export_kitty_session(
+    group_id: Option<String>,
+    workspace_path: Option<String>,
+    output_path: Option<String>,
+    include_env: Option<bool>,
+) -> AppResult<String> {
+    let include_env = include_env.unwrap_or(false);
+    let (name, mut tabs) = match group_id {
+        Some(group_id) => {
+            let group = task_group::get_group(&group_id)?;
+            (group.name.clone(), group_tabs(&group))
+        }
+        None => running_tabs(workspace_path.as_deref(), include_env)?,
+    };
+    if !include_env {
+        tabs.iter_mut()
+            .flat_map(|tab| tab.windows.iter_mut())
+            .for_each(|task| task.env.clear());
+    }
+    let content = render_session(&name, &tabs)?;
+    if let Some(path) = output_path {
+        fs::write(&path, &content)
+            .map_err(|e| AppError::io("common.write_failed", &e).with_path(path))?;
+    }
+    Ok(content)
+}
+
+// 分屏布局下把所在标签页记录在任务上，导出时按标签页还原，不合并成一个标签页；
+// 相邻标签页标题相同时加上序号区分
+fn tab_tasks(tabs: Vec<SessionTab>, layout: Layout) -> Vec<GroupTask> {
+    let mut tasks = Vec::new();
+    let mut previous: Option<String> = None;
+    for (index, tab) in tabs.into_iter().enumerate() {
+        let key = (layout == Layout::Splits).then(|| {
+            let title = Some(tab.title.clone())
+                .filter(|title| !title.trim().is_empty())
+                .unwrap_or_else(|| tab.windows[0].title.clone());
+            if previous.as_deref() == Some(title.as_str()) {
+                format!("{} ({})", title, index + 1)
+            } else {
+                title
+            }
+        });
+        previous = key.clone();
+        tasks.extend(tab.windows.into_iter().map(|task| GroupTask {
+            tab: key.clone(),
+            ..task
+        }));
+    }
+    tasks
+}
+
+// 把会话文件导入为任务组，未指定名称时使用文件名
+#[tauri::command]
+pub fn import_kitty_session(
+    path: String,
+    name: Option<String>,
+    workspace_path: Option<String>,
+) -> AppResult<TaskGroup> {
+    let content = fs::read_to_string(&path)
+        .map_err(|e| AppError::io("common.read_failed", &e).with_path(path.clone()))?;
+    let tabs = parse_session(&content);
+    if tabs.is_empty() {
+        return Err(
+            AppError::new(ErrorCode::InvalidArgument, "kitty.session_empty").with_path(path),
+        );
+    }
+
+    let name = name
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| {
+            Path::new(&path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| DEFAULT_SESSION_NAME.to_string())
+        });
+    let layout = if tabs.iter().any(|tab| tab.split) {
+        Layout::Splits
+    } else {
+        Layout::Tabs
+    };
+    task_group::save_group(TaskGroup {
+        id: String::new(),
+        name,
+        workspace_path,
+        layout,
+        tasks: tab_tasks(tabs, layout),
+        created_at: 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imported_split_tabs_export_separately() {
+        let content = "\
+new_tab api
+layout splits
+launch --title=server --env=PORT=3000 sh -c 'npm run dev'
+launch --title=logs sh -c 'tail -f log'
+new_tab web
+layout splits
+launch --title=vite sh -c 'npm run dev'
+launch --title=test sh -c 'npm test'
+new_tab db
+launch --title=db sh -c postgres
+";
+        let tabs = parse_session(content);
+        assert_eq!(
+            tabs[0].windows[0].env,
+            BTreeMap::from([("PORT".to_string(), "3000".to_string())])
+        );
+        let group = TaskGroup {
+            id: String::new(),
+            name: "stack".to_string(),
+            workspace_path: None,
+            layout: Layout::Splits,
+            tasks: tab_tasks(tabs.clone(), Layout::Splits),
+            created_at: 0,
+        };
+        let exported = render_session(&group.name, &group_tabs(&group)).unwrap();
+        assert!(exported.contains("--env=PORT=3000"));
+        assert_eq!(parse_session(&exported), tabs);
+    }
+
+    #[test]
+    fn adjacent_tabs_with_the_same_title_stay_apart() {
+        let content = "\
+new_tab app
+launch --title=a sh -c a
+launch --title=b sh -c b
+new_tab app
+launch --title=c sh -c c
+launch --title=d sh -c d
+";
+        let tasks = tab_tasks(parse_session(content), Layout::Splits);
+        let keys: Vec<_> = tasks.iter().map(|task| task.tab.as_deref()).collect();
+        assert_eq!(
+            keys,
+            [Some("app"), Some("app"), Some("app (2)"), Some("app (2)")]
+        );
+    }
+
+    #[test]
+    fn running_tabs_export_env_only_when_asked() {
+        let project =
+            std::env::temp_dir().join(format!("runproject-session-env-{}", std::process::id()));
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join(".env"), "API_TOKEN=secret\n").unwrap();
+        let working_dir = project.to_string_lossy().to_string();
+        let register = |id: &str, env_profile: Option<&str>| {
+            KITTY_TAB_MANAGER.lock().unwrap().insert(
+                id.to_string(),
+                KittyTab {
+                    id: id.to_string(),
+                    title: id.to_string(),
+                    project_name: "app".to_string(),
+                    command_name: "dev".to_string(),
+                    working_dir: working_dir.clone(),
+                    command: "npm run dev".to_string(),
+                    socket_path: String::new(),
+                    status: TabStatus::Running,
+                    created_at: 0,
+                    pid: None,
+                    window_id: None,
+                    foreground_pids: Vec::new(),
+                    exit_code: None,
+                    finished_at: None,
+                    run_id: None,
+                    env_profile: env_profile.map(str::to_string),
+                },
+            );
+        };
+        let id = format!("session-env-{}", std::process::id());
+        register(&id, None);
+
+        let (_, tabs) = running_tabs(Some(&working_dir), false).unwrap();
+        assert!(tabs[0].windows[0].env.is_empty());
+        let (_, tabs) = running_tabs(Some(&working_dir), true).unwrap();
+        assert_eq!(
+            tabs[0].windows[0].env.get("API_TOKEN").map(String::as_str),
+            Some("secret")
+        );
+
+        // 按启动时记录的环境配置解析，而不是当前默认配置
+        register(&id, Some("staging"));
+        assert!(running_tabs(Some(&working_dir), false).is_ok());
+        assert!(running_tabs(Some(&working_dir), true).is_err());
+
+        KITTY_TAB_MANAGER.lock().unwrap().remove(&id);
+        let _ = fs::remove_dir_all(&project);
+    }
+}
@@ -0,0 +1,180 @@
+// 标签页状态同步
+//
+// 后台线程定期对每个已知的 socket 执行 ls，把窗口对应回命令 id：
+// - 优先按 launch 返回的窗口 id 匹配，其次按标签页环境变量中的 RUNPROJECT_COMMAND_ID
+// - 运行中的标签页填写窗口 pid 与前台进程 pid
+// - TAB_SCRIPT 在命令结束后写入 runproject_exit 变量，据此转为 Completed / Error / Terminated
+// - 窗口或整个实例已经不存在时转为 Terminated
+// 状态有变化的标签页通过 kitty-tab-status 事件推送给前端，运行中的标签页随后抓取一次输出
+use crate::modules::error::ErrorCode;
+use crate::modules::kitty::capture;
+use crate::modules::kitty::core::{get_current_timestamp, KittyTab, TabStatus, KITTY_TAB_MANAGER};
+use crate::modules::kitty::protocol::Window;
+use crate::modules::kitty::tabs::{list_kitty_tabs, COMMAND_ID_ENV, EXIT_USER_VAR};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const RECONCILE_INTERVAL: Duration = Duration::from_secs(2);
+// 注册后还没拿到窗口 id 的标签页可能正在创建，这段时间内找不到窗口不视为已关闭
+const LAUNCH_GRACE_SECS: u64 = 10;
+// 大于 128 的退出码表示被信号终止（如 Ctrl+C 为 130）
+const SIGNAL_EXIT_BASE: i32 = 128;
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+// 窗口在 ls 结果中的状态
+enum Observed<'a> {
+    Window(&'a Window),
+    Missing,
+    // socket 暂时无法访问以外的错误，本轮跳过
+    Unknown,
+}
+
+fn status_for_exit(code: i32) -> TabStatus {
+    match code {
+        0 => TabStatus::Completed,
+        code if code > SIGNAL_EXIT_BASE => TabStatus::Terminated,
+        _ => TabStatus::Error,
+    }
+}
+
+// 按环境变量匹配时跳过已结束仍保留着的窗口，避免读到旧窗口的 runproject_exit，多个时取最新的
+pub fn find_window<'a>(tab: &KittyTab, windows: &'a [Window]) -> Option<&'a Window> {
+    windows
+        .iter()
+        .find(|window| tab.window_id.as_deref() == Some(window.id.to_string().as_str()))
+        .or_else(|| {
+            windows
+                .iter()
+                .filter(|window| window.env.get(COMMAND_ID_ENV) == Some(&tab.id))
+                .filter(|window| !window.user_vars.contains_key(EXIT_USER_VAR))
+                .max_by_key(|window| window.id)
+        })
+}
+
+// 按观察结果更新标签页，返回是否有变化
+fn apply(tab: &mut KittyTab, observed: Observed, now: u64) -> bool {
+    let before = (
+        tab.status.clone(),
+        tab.pid,
+        tab.foreground_pids.clone(),
+        tab.window_id.clone(),
+    );
+
+    match observed {
+        Observed::Window(window) => {
+            tab.window_id = Some(window.id.to_string());
+            tab.pid = window.pid;
+            tab.foreground_pids = window
+                .foreground_processes
+                .iter()
+                .map(|process| process.pid)
+                .collect();
+            if let Some(code) = window
+                .user_vars
+                .get(EXIT_USER_VAR)
+                .and_then(|value| value.trim().parse::<i32>().ok())
+            {
+                tab.status = status_for_exit(code);
+                tab.exit_code = Some(code);
+                tab.finished_at = Some(now);
+            }
+        }
+        Observed::Missing => {
+            let launching =
+                tab.window_id.is_none() && now.saturating_sub(tab.created_at) < LAUNCH_GRACE_SECS;
+            if !launching {
+                tab.status = TabStatus::Terminated;
+                tab.foreground_pids.clear();
+                tab.finished_at = Some(now);
+            }
+        }
+        Observed::Unknown => {}
+    }
+
+    before
+        != (
+            tab.status.clone(),
+            tab.pid,
+            tab.foreground_pids.clone(),
+            tab.window_id.clone(),
+        )
+}
+
+// 同步一轮，返回状态有变化的标签页
+pub fn reconcile() -> Vec<KittyTab> {
+    let sockets: Vec<String> = match KITTY_TAB_MANAGER.lock() {
+        Ok(manager) => {
+            let mut sockets: Vec<String> = manager
+                .values()
+                .filter(|tab| tab.status == TabStatus::Running)
+                .map(|tab| tab.socket_path.clone())
+                .collect();
+            sockets.sort();
+            sockets.dedup();
+            sockets
+        }
+        Err(_) => return Vec::new(),
+    };
+
+    // 在锁外访问 socket，避免阻塞其他命令
+    let listings: HashMap<String, Option<Vec<Window>>> = sockets
+        .into_iter()
+        .map(|socket_path| {
+            let windows = match list_kitty_tabs(&socket_path) {
+                Ok(os_windows) => Some(
+                    os_windows
+                        .into_iter()
+                        .flat_map(|os_window| os_window.tabs)
+                        .flat_map(|tab| tab.windows)
+                        .collect(),
+                ),
+                // 连接不上说明实例已经退出，其中的标签页都视为已关闭
+                Err(err) if err.code == ErrorCode::KittyConnectionFailed => Some(Vec::new()),
+                Err(err) => {
+                    eprintln!("[KITTY][状态同步] 读取 {} 失败: {}", socket_path, err);
+                    None
+                }
+            };
+            (socket_path, windows)
+        })
+        .collect();
+
+    let now = get_current_timestamp();
+    let Ok(mut manager) = KITTY_TAB_MANAGER.lock() else {
+        return Vec::new();
+    };
+    manager
+        .values_mut()
+        .filter(|tab| tab.status == TabStatus::Running)
+        .filter_map(|tab| {
+            let observed = match listings.get(&tab.socket_path) {
+                Some(Some(windows)) => match find_window(tab, windows) {
+                    Some(window) => Observed::Window(window),
+                    None => Observed::Missing,
+                },
+                _ => Observed::Unknown,
+            };
+            apply(tab, observed, now).then(|| tab.clone())
+        })
+        .collect()
+}
+
+// 在 setup 中调用，启动状态同步线程（仅启动一次）
+pub fn start(app: AppHandle) {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(move || loop {
+        for tab in reconcile() {
+            let _ = app.emit("kitty-tab-status", &tab);
+            if tab.status != TabStatus::Running {
+                capture::finish(&app, &tab, capture::run_status(&tab.status));
+            }
+        }
+        capture::capture_running(&app);
+        std::thread::sleep(RECONCILE_INTERVAL);
+    });
+}
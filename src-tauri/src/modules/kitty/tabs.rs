@@ -1,169 +1,139 @@
-use crate::modules::kitty::core::{KittyTab, TabStatus, KITTY_TAB_MANAGER};
-use std::process::Command;
+use crate::modules::error::{AppError, AppResult, ErrorCode};
+use crate::modules::kitty::core::{KittyTab, KITTY_TAB_MANAGER};
+use crate::modules::kitty::protocol::{self, Launch, Ls, OsWindow};
+use crate::modules::recovery;
 
-// 使用kitty远程控制创建标签页
-pub fn create_kitty_tab(
-    socket_path: &str,
+// 标签页中运行的脚本：命令与工作目录通过位置参数 $1、$2 传入，不拼接进脚本本身
+// 命令结束后通过 OSC 1337 SetUserVar 写入退出码（值需 base64 编码），再换成交互 shell 保持标签页
+// 设置了 RUNPROJECT_EXIT_FILE 时同时把退出码写入该文件，供不支持用户变量的终端判断结束
+pub const TAB_SCRIPT: &str = concat!(
+    r#"printf '\n$ %s\n' "$1"; cd -- "$2" && eval "$1"; s=$?; "#,
+    r#"printf '\033]1337;SetUserVar=runproject_exit=%s\007' "$(printf %s "$s" | base64)"; "#,
+    r#"[ -z "$RUNPROJECT_EXIT_FILE" ] || printf %s "$s" > "$RUNPROJECT_EXIT_FILE"; "#,
+    r#"exec "${SHELL:-bash}""#
+);
+
+// 标签页环境变量中的命令 id，用于从 kitty ls 的结果找回对应的标签页
+pub const COMMAND_ID_ENV: &str = "RUNPROJECT_COMMAND_ID";
+// 项目名与命令名，应用重启后接管遗留的标签页时使用
+pub const PROJECT_NAME_ENV: &str = "RUNPROJECT_PROJECT";
+pub const COMMAND_NAME_ENV: &str = "RUNPROJECT_COMMAND";
+// TAB_SCRIPT 写入的退出码变量名
+pub const EXIT_USER_VAR: &str = "runproject_exit";
+
+fn tab_env(
     command_id: &str,
     project_name: &str,
     command_name: &str,
-    working_dir: &str,
-    final_command: &str,
-) -> Result<String, String> {
-    // 转义工作目录中的特殊字符
-    let escaped_working_dir = working_dir.replace("'", "'\\''");
-    let tab_title = format!("{} - {} ({})", project_name, command_name, command_id);
-    let escaped_tab_title = tab_title.replace("'", "'\\''");
-
-    let display_command = final_command.replace('"', "\\\"");
-    let escaped_command = final_command.replace('\'', "'\\''");
-    let shell_script = format!(
-        "printf '\\n$ %s\\n' \"{}\" && cd '{}' && {} ; exec $SHELL",
-        display_command, escaped_working_dir, escaped_command
-    );
-    let escaped_script = shell_script.replace('\'', "'\\''");
-
-    let kitty_cmd = format!(
-        "kitty @ --to '{}' launch --type=tab --tab-title '{}' --cwd '{}' --hold bash -lc '{}'",
-        socket_path, escaped_tab_title, escaped_working_dir, escaped_script
-    );
-
-    // 添加调试日志
-    eprintln!("[DEBUG] 执行kitty命令: {}", kitty_cmd);
-    eprintln!("[DEBUG] 工作目录: {}", working_dir);
-    eprintln!("[DEBUG] 最终命令: {}", final_command);
+    env: &[(String, String)],
+) -> Vec<String> {
+    let (owner_key, owner_pid) = recovery::owner_tag();
+    [
+        (COMMAND_ID_ENV, command_id),
+        (PROJECT_NAME_ENV, project_name),
+        (COMMAND_NAME_ENV, command_name),
+        (owner_key, owner_pid.as_str()),
+    ]
+    .into_iter()
+    .map(|(key, value)| format!("{}={}", key, value))
+    .chain(env.iter().map(|(key, value)| format!("{}={}", key, value)))
+    .collect()
+}
 
-    match Command::new("sh").arg("-c").arg(&kitty_cmd).output() {
-        Ok(output) => {
-            if output.status.success() {
-                eprintln!("[DEBUG] kitty命令执行成功");
-                Ok("标签页创建成功".to_string())
-            } else {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                eprintln!("[DEBUG] kitty命令执行失败 - stderr: {}", stderr);
-                eprintln!("[DEBUG] kitty命令执行失败 - stdout: {}", stdout);
-                Err(format!("kitty命令执行失败: {}", stderr))
-            }
-        }
-        Err(e) => {
-            eprintln!("[DEBUG] 执行kitty命令失败: {}", e);
-            Err(format!("执行kitty命令失败: {}", e))
-        }
-    }
+fn tab_title(command_id: &str, project_name: &str, command_name: &str) -> String {
+    format!("{} - {} ({})", project_name, command_name, command_id)
 }
 
-// 关闭kitty标签页
-pub fn close_kitty_tab(socket_path: &str, tab_id: Option<&str>) -> Result<(), String> {
-    let cmd = if let Some(id) = tab_id {
-        format!("kitty @ --to '{}' close-tab --match id:{}", socket_path, id)
-    } else {
-        format!("kitty @ --to '{}' close-tab", socket_path)
-    };
+// 在标签页中运行命令的参数，导出的会话文件也使用同样的参数
+pub fn tab_argv(working_dir: &str, final_command: &str) -> Vec<String> {
+    vec![
+        "bash".to_string(),
+        "-lc".to_string(),
+        TAB_SCRIPT.to_string(),
+        "runproject".to_string(),
+        final_command.to_string(),
+        working_dir.to_string(),
+    ]
+}
 
-    match Command::new("sh").arg("-c").arg(&cmd).output() {
-        Ok(output) => {
-            if output.status.success() {
-                Ok(())
-            } else {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                Err(format!("关闭标签页失败: {}", stderr))
-            }
-        }
-        Err(e) => Err(format!("执行关闭命令失败: {}", e)),
+// 通过远程控制协议创建标签页的 launch 请求
+pub fn build_launch_request(
+    command_id: &str,
+    project_name: &str,
+    command_name: &str,
+    working_dir: &str,
+    final_command: &str,
+    env: &[(String, String)],
+) -> Launch {
+    Launch {
+        args: tab_argv(working_dir, final_command),
+        launch_type: "tab".to_string(),
+        tab_title: Some(tab_title(command_id, project_name, command_name)),
+        cwd: Some(working_dir.to_string()),
+        env: tab_env(command_id, project_name, command_name, env),
+        hold: true,
+        ..Default::default()
     }
 }
 
-// 获取标签页列表
-pub fn list_kitty_tabs(socket_path: &str) -> Result<Vec<serde_json::Value>, String> {
-    let cmd = format!("kitty @ --to '{}' ls --json", socket_path);
+// 使用kitty远程控制创建标签页，返回新窗口的 id
+pub fn create_kitty_tab(
+    socket_path: &str,
+    command_id: &str,
+    project_name: &str,
+    command_name: &str,
+    working_dir: &str,
+    final_command: &str,
+    env: &[(String, String)],
+) -> AppResult<String> {
+    let request = build_launch_request(
+        command_id,
+        project_name,
+        command_name,
+        working_dir,
+        final_command,
+        env,
+    );
 
-    match Command::new("sh").arg("-c").arg(&cmd).output() {
-        Ok(output) => {
-            if output.status.success() {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                match serde_json::from_str::<serde_json::Value>(&stdout) {
-                    Ok(json) => {
-                        if let Some(tabs) = json.as_array() {
-                            Ok(tabs.clone())
-                        } else {
-                            Ok(Vec::new())
-                        }
-                    }
-                    Err(e) => Err(format!("解析标签页信息失败: {}", e)),
-                }
-            } else {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                Err(format!("获取标签页列表失败: {}", stderr))
-            }
-        }
-        Err(e) => Err(format!("执行命令失败: {}", e)),
-    }
+    let window_id = protocol::send(socket_path, &request)?;
+    Ok(window_id.trim().to_string())
 }
 
-// 获取标签页状态
-pub fn get_tab_status(tab_id: &str) -> Result<Option<KittyTab>, String> {
-    if let Ok(manager) = KITTY_TAB_MANAGER.lock() {
-        Ok(manager.get(tab_id).cloned())
-    } else {
-        Err("无法访问标签页管理器".to_string())
-    }
+// 获取标签页列表（按 OS 窗口分组）
+pub fn list_kitty_tabs(socket_path: &str) -> AppResult<Vec<OsWindow>> {
+    protocol::send(socket_path, &Ls::default())
 }
 
-// 更新标签页状态
-pub fn update_tab_status(tab_id: &str, status: TabStatus) -> Result<(), String> {
+// 记录标签页对应的 kitty 窗口 id
+pub fn set_tab_window_id(tab_id: &str, window_id: &str) {
     if let Ok(mut manager) = KITTY_TAB_MANAGER.lock() {
         if let Some(tab) = manager.get_mut(tab_id) {
-            tab.status = status;
-            Ok(())
-        } else {
-            Err("标签页不存在".to_string())
+            tab.window_id = Some(window_id.to_string()).filter(|id| !id.is_empty());
         }
-    } else {
-        Err("无法访问标签页管理器".to_string())
     }
 }
 
 // 注册新的标签页
-pub fn register_kitty_tab(tab: KittyTab) -> Result<(), String> {
+pub fn register_kitty_tab(tab: KittyTab) -> AppResult<()> {
     if let Ok(mut manager) = KITTY_TAB_MANAGER.lock() {
         manager.insert(tab.id.clone(), tab);
         Ok(())
     } else {
-        Err("无法访问标签页管理器".to_string())
+        Err(AppError::new(
+            ErrorCode::Internal,
+            "kitty.tab_manager_unavailable",
+        ))
     }
 }
 
 // 移除标签页
-pub fn unregister_kitty_tab(tab_id: &str) -> Result<Option<KittyTab>, String> {
+pub fn unregister_kitty_tab(tab_id: &str) -> AppResult<Option<KittyTab>> {
     if let Ok(mut manager) = KITTY_TAB_MANAGER.lock() {
         Ok(manager.remove(tab_id))
     } else {
-        Err("无法访问标签页管理器".to_string())
-    }
-}
-
-// 获取所有标签页
-pub fn get_all_tabs() -> Result<Vec<KittyTab>, String> {
-    if let Ok(manager) = KITTY_TAB_MANAGER.lock() {
-        Ok(manager.values().cloned().collect())
-    } else {
-        Err("无法访问标签页管理器".to_string())
-    }
-}
-
-// 清理已完成的标签页
-pub fn cleanup_completed_tabs() -> Result<usize, String> {
-    if let Ok(mut manager) = KITTY_TAB_MANAGER.lock() {
-        let initial_count = manager.len();
-        manager.retain(|_, tab| {
-            !matches!(
-                tab.status,
-                TabStatus::Completed | TabStatus::Error | TabStatus::Terminated
-            )
-        });
-        Ok(initial_count - manager.len())
-    } else {
-        Err("无法访问标签页管理器".to_string())
+        Err(AppError::new(
+            ErrorCode::Internal,
+            "kitty.tab_manager_unavailable",
+        ))
     }
 }
@@ -1,3 +1,4 @@
+use crate::modules::error::{AppError, AppResult, ErrorCode};
 use serde::Serialize;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -43,17 +44,41 @@ fn title_from_filename(path: &Path) -> String {
         .to_string()
 }
 
+// 读写前确认目标是已存在的 Markdown 文件
+fn ensure_md_file(path: &str) -> AppResult<PathBuf> {
+    let file_path = PathBuf::from(path);
+    if !file_path.is_file() {
+        return Err(AppError::new(ErrorCode::NotFound, "common.file_not_found").with_path(path));
+    }
+    if !is_md_file(&file_path) {
+        return Err(
+            AppError::new(ErrorCode::NotMarkdownFile, "knowledge.not_markdown").with_path(path),
+        );
+    }
+    Ok(file_path)
+}
+
 #[tauri::command]
-pub fn list_md_files(folder_path: String) -> Result<Vec<KnowledgeFile>, String> {
+pub fn list_md_files(folder_path: String) -> AppResult<Vec<KnowledgeFile>> {
     let dir = PathBuf::from(&folder_path);
-    if !dir.exists() || !dir.is_dir() {
-        return Err("路径不存在或不是目录".to_string());
+    if !dir.exists() {
+        return Err(
+            AppError::new(ErrorCode::NotFound, "common.path_not_found").with_path(folder_path)
+        );
+    }
+    if !dir.is_dir() {
+        return Err(
+            AppError::new(ErrorCode::NotADirectory, "common.not_a_directory")
+                .with_path(folder_path),
+        );
     }
 
     let mut files = Vec::new();
-    let entries = fs::read_dir(&dir).map_err(|e| format!("读取目录失败: {}", e))?;
+    let entries = fs::read_dir(&dir)
+        .map_err(|e| AppError::io("common.read_dir_failed", &e).with_path(&folder_path))?;
     for entry in entries {
-        let entry = entry.map_err(|e| format!("读取文件失败: {}", e))?;
+        let entry = entry
+            .map_err(|e| AppError::io("common.read_file_failed", &e).with_path(&folder_path))?;
         let path = entry.path();
         if !path.is_file() || !is_md_file(&path) {
             continue;
@@ -72,19 +97,36 @@ pub fn list_md_files(folder_path: String) -> Result<Vec<KnowledgeFile>, String>
 }
 
 #[tauri::command]
-pub fn read_md_file(path: String) -> Result<String, String> {
-    let file_path = PathBuf::from(&path);
-    if !file_path.exists() || !file_path.is_file() || !is_md_file(&file_path) {
-        return Err("文件不存在或不是 Markdown".to_string());
-    }
-    fs::read_to_string(&file_path).map_err(|e| format!("读取失败: {}", e))
+pub fn read_md_file(path: String) -> AppResult<String> {
+    let file_path = ensure_md_file(&path)?;
+    fs::read_to_string(&file_path)
+        .map_err(|e| AppError::io("common.read_failed", &e).with_path(path))
 }
 
 #[tauri::command]
-pub fn write_md_file(path: String, content: String) -> Result<(), String> {
-    let file_path = PathBuf::from(&path);
-    if !file_path.exists() || !file_path.is_file() || !is_md_file(&file_path) {
-        return Err("文件不存在或不是 Markdown".to_string());
+pub fn write_md_file(path: String, content: String) -> AppResult<()> {
+    let file_path = ensure_md_file(&path)?;
+    fs::write(&file_path, content)
+        .map_err(|e| AppError::io("common.write_failed", &e).with_path(path))
+}
+
+// 在笔记末尾追加内容，笔记不存在时新建（所在目录需已存在）
+pub fn append_to_note(path: &str, text: &str) -> AppResult<()> {
+    let file_path = PathBuf::from(path);
+    if !is_md_file(&file_path) {
+        return Err(
+            AppError::new(ErrorCode::NotMarkdownFile, "knowledge.not_markdown").with_path(path),
+        );
+    }
+
+    let mut content = fs::read_to_string(&file_path).unwrap_or_default();
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(text);
+    if !text.ends_with('\n') {
+        content.push('\n');
     }
-    fs::write(&file_path, content).map_err(|e| format!("写入失败: {}", e))
+    fs::write(&file_path, content)
+        .map_err(|e| AppError::io("common.write_failed", &e).with_path(path))
 }
@@ -1,9 +1,29 @@
+pub mod benchmark;
+pub mod command_template;
 pub mod editor;
+pub mod env_profile;
+pub mod error;
 pub mod git;
+#[cfg(test)]
+mod hostile_input_tests;
+pub mod hooks;
+pub mod i18n;
+pub mod install_preflight;
 pub mod kitty;
 pub mod knowledge;
 pub mod nvm_manager;
 pub mod platform;
+pub mod ports;
+pub mod preview;
+pub mod problem_matcher;
+pub mod process_runner;
 pub mod project_scanner;
+pub mod recovery;
+pub mod scheduler;
+pub mod storage;
+pub mod task_group;
+pub mod task_runner;
 pub mod terminal;
+pub mod terminal_backend;
+pub mod watcher;
 pub mod webview;
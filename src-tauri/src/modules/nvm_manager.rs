@@ -1,4 +1,9 @@
-use std::process::{Command, Output, Stdio};
+use crate::modules::error::{AppError, AppResult, ErrorCode};
+use crate::modules::i18n;
+use crate::modules::platform::shell_quote;
+use crate::modules::process_runner::{Operation, OperationKind};
+use serde::Serialize;
+use std::process::{Command, Output};
 
 #[derive(Clone, Copy)]
 enum NodeVersionManager {
@@ -17,80 +22,94 @@ impl NodeVersionManager {
     }
 }
 
-fn run_command(mut command: Command) -> Result<Output, String> {
-    command.stdout(Stdio::piped()).stderr(Stdio::piped());
-    let output = command
-        .output()
-        .map_err(|e| format!("执行命令失败: {}", e))?;
+fn run_command(operation: &Operation, command: Command) -> AppResult<Output> {
+    let output = operation
+        .run(command, OperationKind::NodeManager)
+        .map_err(|err| {
+            err.into_app_error(|e| {
+                AppError::new(ErrorCode::NodeManagerFailed, "common.command_failed").arg("error", e)
+            })
+        })?;
 
     if output.status.success() {
         Ok(output)
     } else {
-        let error = String::from_utf8_lossy(&output.stderr);
-        Err(format!("命令执行错误: {}", error))
+        Err(AppError::new(ErrorCode::NodeManagerFailed, "nvm.command_error").with_output(&output))
     }
 }
 
-fn execute_manager_command(manager: NodeVersionManager, args: &[&str]) -> Result<Output, String> {
+fn manager_not_found() -> AppError {
+    AppError::new(ErrorCode::NodeManagerNotFound, "nvm.manager_not_found")
+}
+
+fn execute_manager_command(
+    operation: &Operation,
+    manager: NodeVersionManager,
+    args: &[&str],
+) -> AppResult<Output> {
     match manager {
         NodeVersionManager::Nvm => {
-            let script = format!("source ~/.nvm/nvm.sh && nvm {}", args.join(" "));
+            // nvm 是 shell 函数，只能通过 bash 调用；参数以位置参数传入，不拼接进脚本
             let mut command = Command::new("bash");
-            command.arg("-c").arg(script);
-            run_command(command)
+            command
+                .arg("-c")
+                .arg("source ~/.nvm/nvm.sh && nvm \"$@\"")
+                .arg("nvm")
+                .args(args);
+            run_command(operation, command)
         }
         NodeVersionManager::Fnm => {
             let mut command = Command::new("fnm");
             command.args(args);
-            run_command(command)
+            run_command(operation, command)
         }
         NodeVersionManager::NvmWindows => {
             let mut command = Command::new("nvm");
             command.args(args);
-            run_command(command)
+            run_command(operation, command)
         }
     }
 }
 
-fn is_nvm_available() -> bool {
+fn is_nvm_available(operation: &Operation) -> bool {
     #[cfg(target_os = "windows")]
     {
         let mut command = Command::new("nvm");
         command.arg("version");
-        return run_command(command).is_ok();
+        return run_command(operation, command).is_ok();
     }
 
     let mut command = Command::new("bash");
     command
         .arg("-c")
         .arg("source ~/.nvm/nvm.sh && nvm --version");
-    run_command(command).is_ok()
+    run_command(operation, command).is_ok()
 }
 
-fn is_fnm_available() -> bool {
+fn is_fnm_available(operation: &Operation) -> bool {
     let mut command = Command::new("fnm");
     command.arg("--version");
-    run_command(command).is_ok()
+    run_command(operation, command).is_ok()
 }
 
-fn detect_manager() -> Result<NodeVersionManager, String> {
+fn detect_manager(operation: &Operation) -> AppResult<NodeVersionManager> {
     #[cfg(target_os = "windows")]
     {
-        if is_nvm_available() {
+        if is_nvm_available(operation) {
             return Ok(NodeVersionManager::NvmWindows);
         }
-        if is_fnm_available() {
+        if is_fnm_available(operation) {
             return Ok(NodeVersionManager::Fnm);
         }
-        return Err("未检测到可用的 Node 版本管理器 (nvm 或 fnm)".to_string());
+        return Err(manager_not_found());
     }
 
-    if is_nvm_available() {
+    if is_nvm_available(operation) {
         Ok(NodeVersionManager::Nvm)
-    } else if is_fnm_available() {
+    } else if is_fnm_available(operation) {
         Ok(NodeVersionManager::Fnm)
     } else {
-        Err("未检测到可用的 Node 版本管理器 (nvm 或 fnm)".to_string())
+        Err(manager_not_found())
     }
 }
 
@@ -139,47 +158,78 @@ fn parse_versions(output: &str) -> Vec<String> {
     versions
 }
 
-fn get_installed_versions_with(manager: NodeVersionManager) -> Result<Vec<String>, String> {
+fn get_installed_versions_with(
+    operation: &Operation,
+    manager: NodeVersionManager,
+) -> AppResult<Vec<String>> {
     let args: Vec<&str> = match manager {
         NodeVersionManager::Nvm => vec!["list", "--no-colors"],
         NodeVersionManager::Fnm => vec!["list"],
         NodeVersionManager::NvmWindows => vec!["list"],
     };
 
-    let output = execute_manager_command(manager, &args)?;
+    let output = execute_manager_command(operation, manager, &args)?;
     let stdout = String::from_utf8_lossy(&output.stdout);
 
     Ok(parse_versions(&stdout))
 }
 
-fn install_node_version_with(manager: NodeVersionManager, version: &str) -> Result<String, String> {
-    let output = execute_manager_command(manager, &["install", version])?;
+fn install_node_version_with(
+    operation: &Operation,
+    manager: NodeVersionManager,
+    version: &str,
+) -> AppResult<String> {
+    let output =
+        execute_manager_command(operation, manager, &["install", version]).map_err(|err| {
+            // 版本号不存在时各管理器的提示不同，统一归为 node_version_not_found
+            let stderr = err.stderr().unwrap_or_default().to_lowercase();
+            if stderr.contains("not found")
+                || stderr.contains("not available")
+                || stderr.contains("n/a")
+                || stderr.contains("can't find")
+            {
+                let mut error =
+                    AppError::new(ErrorCode::NodeVersionNotFound, "nvm.version_not_found")
+                        .arg("version", version);
+                error.details = err.details;
+                error
+            } else {
+                err
+            }
+        })?;
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
-fn use_node_version_with(manager: NodeVersionManager, version: &str) -> Result<String, String> {
+fn use_node_version_with(
+    operation: &Operation,
+    manager: NodeVersionManager,
+    version: &str,
+) -> AppResult<String> {
     let args: Vec<&str> = match manager {
         NodeVersionManager::Nvm => vec!["use", version],
         NodeVersionManager::Fnm => vec!["default", version],
         NodeVersionManager::NvmWindows => vec!["use", version],
     };
 
-    let output = execute_manager_command(manager, &args)?;
+    let output = execute_manager_command(operation, manager, &args)?;
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
-fn ensure_with_manager(manager: NodeVersionManager, version: &str) -> Result<String, String> {
-    let installed_versions = get_installed_versions_with(manager)?;
+fn ensure_with_manager(
+    operation: &Operation,
+    manager: NodeVersionManager,
+    version: &str,
+) -> AppResult<String> {
+    let installed_versions = get_installed_versions_with(operation, manager)?;
 
     if !installed_versions.iter().any(|item| item == version) {
-        install_node_version_with(manager, version)?;
+        install_node_version_with(operation, manager, version)?;
     }
 
-    use_node_version_with(manager, version)?;
-    Ok(format!(
-        "成功切换到Node {} (使用{})",
-        version,
-        manager.label()
+    use_node_version_with(operation, manager, version)?;
+    Ok(i18n::tf(
+        "nvm.switched",
+        &[("version", &version), ("manager", &manager.label())],
     ))
 }
 
@@ -200,10 +250,11 @@ fn get_highest_version(versions: &[String]) -> Option<String> {
 }
 
 // 获取NVM状态
-pub fn get_nvm_status() -> Result<serde_json::Value, String> {
-    match detect_manager() {
+pub fn get_nvm_status() -> AppResult<serde_json::Value> {
+    let operation = Operation::untracked();
+    match detect_manager(&operation) {
         Ok(manager) => {
-            let installed_versions = get_installed_versions_with(manager)?;
+            let installed_versions = get_installed_versions_with(&operation, manager)?;
             Ok(serde_json::json!({
                 "available": true,
                 "manager": manager.label(),
@@ -218,27 +269,64 @@ pub fn get_nvm_status() -> Result<serde_json::Value, String> {
     }
 }
 
-// 确保Node版本可用
-pub fn ensure_node_version(version: String) -> Result<String, String> {
-    let manager = detect_manager()?;
-    ensure_with_manager(manager, &version)
+// 确保Node版本可用，取消 operation 会终止正在进行的安装或切换
+pub fn ensure_node_version(version: String, operation: &Operation) -> AppResult<String> {
+    let manager = detect_manager(operation)?;
+    ensure_with_manager(operation, manager, &version)
 }
 
 // 切换到最高版本
-pub fn switch_to_highest_version(versions: Vec<String>) -> Result<String, String> {
-    let manager = detect_manager()?;
+pub fn switch_to_highest_version(
+    versions: Vec<String>,
+    operation: &Operation,
+) -> AppResult<String> {
+    let manager = detect_manager(operation)?;
 
     if let Some(highest) = get_highest_version(&versions) {
-        ensure_with_manager(manager, &highest).map(|_| format!("已切换到最高版本: {}", highest))
+        ensure_with_manager(operation, manager, &highest)
+            .map(|_| i18n::tf("nvm.switched_highest", &[("version", &highest)]))
     } else {
-        Err("没有找到可用的Node版本".to_string())
+        Err(AppError::new(
+            ErrorCode::NodeVersionNotFound,
+            "nvm.no_versions",
+        ))
     }
 }
 
+// 执行前预览用的 Node 版本信息
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeVersionPlan {
+    pub version: String,
+    pub manager: &'static str,
+    pub installed: bool,
+    // 带版本切换前缀的完整命令
+    pub command: String,
+}
+
+// 只查询版本管理器与已安装版本，不安装也不切换
+pub fn plan_node_version(version: &str, command: &str) -> AppResult<NodeVersionPlan> {
+    let operation = Operation::untracked();
+    let manager = detect_manager(&operation)?;
+    let installed_versions = get_installed_versions_with(&operation, manager)?;
+    Ok(NodeVersionPlan {
+        version: version.to_string(),
+        manager: manager.label(),
+        installed: installed_versions.iter().any(|item| item == version),
+        command: wrap_with_manager(manager, version, command),
+    })
+}
+
 // 根据可用的 Node 版本管理器构建命令前缀
-pub fn wrap_command_with_node(version: &str, command: &str) -> Result<String, String> {
-    let manager = detect_manager()?;
-    let wrapped = match manager {
+pub fn wrap_command_with_node(version: &str, command: &str) -> AppResult<String> {
+    let manager = detect_manager(&Operation::untracked())?;
+    Ok(wrap_with_manager(manager, version, command))
+}
+
+// 版本号来自前端，需要转义后再拼接
+fn wrap_with_manager(manager: NodeVersionManager, version: &str, command: &str) -> String {
+    let version = shell_quote(version);
+    match manager {
         NodeVersionManager::Nvm => {
             format!("source ~/.nvm/nvm.sh && nvm use {} && {}", version, command)
         }
@@ -250,7 +338,5 @@ pub fn wrap_command_with_node(version: &str, command: &str) -> Result<String, St
             "nvm use {}; if ($LASTEXITCODE -ne 0) {{ exit $LASTEXITCODE }}; {}",
             version, command
         ),
-    };
-
-    Ok(wrapped)
+    }
 }
@@ -1,3 +1,5 @@
+use crate::modules::error::{AppError, AppResult};
+use crate::modules::i18n;
 use std::env;
 use std::path::Path;
 use std::process::Command;
@@ -30,14 +32,48 @@ pub fn build_shell_command(command: &str) -> Command {
     }
 }
 
-pub fn open_path(path: &str) -> Result<String, String> {
+// 依次执行两条命令，前一条失败时不再执行后一条
+pub fn chain_commands(first: &str, second: &str) -> String {
+    if cfg!(target_os = "windows") {
+        format!(
+            "{}; if ($LASTEXITCODE -ne 0) {{ exit $LASTEXITCODE }}; {}",
+            first, second
+        )
+    } else {
+        format!("{} && {}", first, second)
+    }
+}
+
+// 将单个参数转义为可安全拼接到 shell 命令中的形式
+pub fn shell_quote(arg: &str) -> String {
+    #[cfg(target_os = "windows")]
+    {
+        // PowerShell 单引号字符串中只需将 ' 写成 ''
+        return format!("'{}'", arg.replace('\'', "''"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let is_safe = !arg.is_empty()
+            && arg
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c));
+        if is_safe {
+            arg.to_string()
+        } else {
+            format!("'{}'", arg.replace('\'', "'\\''"))
+        }
+    }
+}
+
+pub fn open_path(path: &str) -> AppResult<String> {
     #[cfg(target_os = "windows")]
     {
         Command::new("explorer")
             .arg(path)
             .spawn()
-            .map_err(|e| format!("Failed to open folder: {}", e))?;
-        return Ok(format!("Opened: {}", path));
+            .map_err(|e| AppError::io("common.open_folder_failed", &e).with_path(path))?;
+        return Ok(i18n::tf("common.opened", &[("path", &path)]));
     }
 
     #[cfg(target_os = "macos")]
@@ -45,8 +81,8 @@ pub fn open_path(path: &str) -> Result<String, String> {
         Command::new("open")
             .arg(path)
             .spawn()
-            .map_err(|e| format!("Failed to open folder: {}", e))?;
-        return Ok(format!("Opened: {}", path));
+            .map_err(|e| AppError::io("common.open_folder_failed", &e).with_path(path))?;
+        return Ok(i18n::tf("common.opened", &[("path", &path)]));
     }
 
     #[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
@@ -54,7 +90,7 @@ pub fn open_path(path: &str) -> Result<String, String> {
         Command::new("xdg-open")
             .arg(path)
             .spawn()
-            .map_err(|e| format!("Failed to open folder: {}", e))?;
-        return Ok(format!("Opened: {}", path));
+            .map_err(|e| AppError::io("common.open_folder_failed", &e).with_path(path))?;
+        return Ok(i18n::tf("common.opened", &[("path", &path)]));
     }
 }
@@ -0,0 +1,952 @@
+// 端口登记模块
+//
+// 负责把系统中处于 LISTEN 状态的 TCP 端口映射回我们管理的任务：
+// - Linux 读取 /proc/net/tcp{,6} 并通过 /proc/<pid>/fd 反查 socket 所属进程
+// - 其他平台回退到 lsof
+// - 沿父进程链向上查找，识别属于内置终端会话或 kitty 实例的进程
+// - 启动前根据项目声明/推断的端口做冲突检查，并提供“释放端口”操作
+use crate::modules::error::{AppError, AppResult, ErrorCode};
+use crate::modules::i18n;
+use crate::modules::kitty::connection::get_socket_path;
+use crate::modules::kitty::core::{
+    sanitize_command_id, KittyConfig, KITTY_TAB_MANAGER, PROCESS_MANAGER,
+};
+use crate::modules::kitty::process::terminate_command;
+use crate::modules::task_runner;
+use crate::modules::terminal::pty_manager::{close_terminal_session, session_pids};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tauri::AppHandle;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum PortOwner {
+    Terminal { session_id: String },
+    Kitty { command_id: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListeningPort {
+    pub port: u16,
+    pub address: String,
+    pub protocol: String,
+    pub pid: Option<u32>,
+    pub process_name: Option<String>,
+    pub cwd: Option<String>,
+    pub owner: Option<PortOwner>,
+    pub project_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortCheckResult {
+    pub port: Option<u16>,
+    // explicit: 调用方指定；declared: 脚本参数/.env/配置文件；inferred: 框架默认端口
+    pub source: Option<String>,
+    pub in_use: bool,
+    pub listeners: Vec<ListeningPort>,
+}
+
+#[derive(Debug, Clone)]
+struct ProcessInfo {
+    ppid: Option<u32>,
+    name: Option<String>,
+    cmdline: Vec<String>,
+    cwd: Option<String>,
+}
+
+// 原始监听记录（尚未关联进程）
+struct RawListener {
+    port: u16,
+    address: String,
+    protocol: String,
+    pid: Option<u32>,
+}
+
+// ---------- Linux: /proc 解析 ----------
+
+#[cfg(target_os = "linux")]
+fn parse_hex_ipv4(hex: &str) -> Option<String> {
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    let bytes = value.to_le_bytes();
+    Some(format!(
+        "{}.{}.{}.{}",
+        bytes[0], bytes[1], bytes[2], bytes[3]
+    ))
+}
+
+#[cfg(target_os = "linux")]
+fn parse_hex_ipv6(hex: &str) -> Option<String> {
+    if hex.len() != 32 {
+        return None;
+    }
+    // /proc 中按 4 个小端 u32 存储
+    let mut bytes = Vec::with_capacity(16);
+    for chunk in 0..4 {
+        let word = u32::from_str_radix(&hex[chunk * 8..chunk * 8 + 8], 16).ok()?;
+        bytes.extend_from_slice(&word.to_le_bytes());
+    }
+    let mut octets = [0u8; 16];
+    octets.copy_from_slice(&bytes);
+    Some(std::net::Ipv6Addr::from(octets).to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn read_proc_net(file: &str, protocol: &str) -> Vec<(u16, String, String, u64)> {
+    fs::read_to_string(file)
+        .map(|content| parse_proc_net(&content, protocol))
+        .unwrap_or_default()
+}
+
+// 解析 /proc/net/tcp{,6} 内容，返回 LISTEN 状态的 (端口, 地址, 协议, inode)
+#[cfg(target_os = "linux")]
+fn parse_proc_net(content: &str, protocol: &str) -> Vec<(u16, String, String, u64)> {
+    let mut result = Vec::new();
+    for line in content.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 10 {
+            continue;
+        }
+        // st == 0A 表示 LISTEN
+        if fields[3] != "0A" {
+            continue;
+        }
+        let Some((ip_hex, port_hex)) = fields[1].split_once(':') else {
+            continue;
+        };
+        let Ok(port) = u16::from_str_radix(port_hex, 16) else {
+            continue;
+        };
+        let address = if protocol == "tcp6" {
+            parse_hex_ipv6(ip_hex)
+        } else {
+            parse_hex_ipv4(ip_hex)
+        }
+        .unwrap_or_else(|| ip_hex.to_string());
+        let inode = fields[9].parse::<u64>().unwrap_or(0);
+        result.push((port, address, protocol.to_string(), inode));
+    }
+    result
+}
+
+#[cfg(target_os = "linux")]
+fn list_pids() -> Vec<u32> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn socket_inode_owners(inodes: &HashSet<u64>) -> HashMap<u64, u32> {
+    let mut owners = HashMap::new();
+    for pid in list_pids() {
+        // 无权限读取的进程直接跳过
+        let Ok(fds) = fs::read_dir(format!("/proc/{}/fd", pid)) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let Ok(target) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            let target = target.to_string_lossy();
+            if let Some(inode) = target
+                .strip_prefix("socket:[")
+                .and_then(|s| s.strip_suffix(']'))
+                .and_then(|s| s.parse::<u64>().ok())
+            {
+                if inodes.contains(&inode) {
+                    owners.entry(inode).or_insert(pid);
+                }
+            }
+        }
+    }
+    owners
+}
+
+#[cfg(target_os = "linux")]
+fn collect_listeners() -> Vec<RawListener> {
+    let mut entries = read_proc_net("/proc/net/tcp", "tcp");
+    entries.extend(read_proc_net("/proc/net/tcp6", "tcp6"));
+
+    let inodes: HashSet<u64> = entries.iter().map(|e| e.3).filter(|i| *i != 0).collect();
+    let owners = socket_inode_owners(&inodes);
+
+    entries
+        .into_iter()
+        .map(|(port, address, protocol, inode)| RawListener {
+            port,
+            address,
+            protocol,
+            pid: owners.get(&inode).copied(),
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn process_info(pid: u32) -> Option<ProcessInfo> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // comm 可能包含空格和括号，从最后一个 ')' 之后开始解析
+    let after_comm = &stat[stat.rfind(')')? + 1..];
+    let ppid = after_comm
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse::<u32>().ok())
+        .filter(|p| *p != 0);
+
+    let name = fs::read_to_string(format!("/proc/{}/comm", pid))
+        .ok()
+        .map(|s| s.trim().to_string());
+    let cmdline = fs::read(format!("/proc/{}/cmdline", pid))
+        .map(|raw| {
+            raw.split(|b| *b == 0)
+                .filter(|part| !part.is_empty())
+                .map(|part| String::from_utf8_lossy(part).to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    let cwd = fs::read_link(format!("/proc/{}/cwd", pid))
+        .ok()
+        .map(|p| p.to_string_lossy().to_string());
+
+    Some(ProcessInfo {
+        ppid,
+        name,
+        cmdline,
+        cwd,
+    })
+}
+
+// ---------- 其他平台: lsof / ps ----------
+
+#[cfg(not(target_os = "linux"))]
+fn collect_listeners() -> Vec<RawListener> {
+    let output = match Command::new("lsof")
+        .args(["-nP", "-iTCP", "-sTCP:LISTEN", "-F", "pn"])
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("[PORTS] 执行 lsof 失败: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut result = Vec::new();
+    let mut current_pid: Option<u32> = None;
+    for line in stdout.lines() {
+        if let Some(pid) = line.strip_prefix('p') {
+            current_pid = pid.parse::<u32>().ok();
+        } else if let Some(name) = line.strip_prefix('n') {
+            let Some((address, port)) = name.rsplit_once(':') else {
+                continue;
+            };
+            let Ok(port) = port.parse::<u16>() else {
+                continue;
+            };
+            let protocol = if address.starts_with('[') {
+                "tcp6"
+            } else {
+                "tcp"
+            };
+            result.push(RawListener {
+                port,
+                address: address.trim_matches(|c| c == '[' || c == ']').to_string(),
+                protocol: protocol.to_string(),
+                pid: current_pid,
+            });
+        }
+    }
+    result
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_info(pid: u32) -> Option<ProcessInfo> {
+    let output = Command::new("ps")
+        .args(["-o", "ppid=,comm=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().next()?.trim();
+    let (ppid, name) = line.split_once(char::is_whitespace)?;
+
+    let cmdline = Command::new("ps")
+        .args(["-o", "command=", "-p", &pid.to_string()])
+        .output()
+        .map(|o| {
+            String::from_utf8_lossy(&o.stdout)
+                .split_whitespace()
+                .map(|s| s.to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let cwd = Command::new("lsof")
+        .args(["-a", "-p", &pid.to_string(), "-d", "cwd", "-F", "n"])
+        .output()
+        .ok()
+        .and_then(|o| {
+            String::from_utf8_lossy(&o.stdout)
+                .lines()
+                .find_map(|l| l.strip_prefix('n').map(|s| s.to_string()))
+        });
+
+    Some(ProcessInfo {
+        ppid: ppid.trim().parse::<u32>().ok().filter(|p| *p != 0),
+        name: Some(name.trim().to_string()),
+        cmdline,
+        cwd,
+    })
+}
+
+// ---------- 任务归属 ----------
+
+// 从 kitty 进程命令行中提取 --listen-on 的实例名：独立窗口时是 sanitize 后的 command_id，共用实例时是分组的实例名
+fn kitty_socket_id(cmdline: &[String], config: &KittyConfig) -> Option<String> {
+    let marker = format!("{}-", config.socket_prefix);
+    cmdline.iter().find_map(|arg| {
+        let start = arg.find(&marker)? + marker.len();
+        let rest = &arg[start..];
+        Some(rest.trim_end_matches(".sock").to_string())
+    })
+}
+
+// 将实例名映射回 command_id：实例中只有一个标签页时即为它，独立窗口按 sanitize 后的 id 匹配；
+// 共用实例中有多个标签页时无法确定，由调用方按窗口 pid 判断
+fn resolve_kitty_command_id(instance: &str, config: &KittyConfig) -> Option<String> {
+    let socket_path = get_socket_path(instance, config);
+    if let Ok(manager) = KITTY_TAB_MANAGER.lock() {
+        let mut tabs = manager
+            .values()
+            .filter(|tab| tab.socket_path == socket_path);
+        if let Some(tab) = tabs.next() {
+            return tabs.next().is_none().then(|| tab.id.clone());
+        }
+    }
+    if let Ok(manager) = PROCESS_MANAGER.lock() {
+        if let Some(id) = manager
+            .keys()
+            .find(|id| sanitize_command_id(id) == instance)
+        {
+            return Some(id.clone());
+        }
+    }
+    None
+}
+
+// kitty 标签页中 shell 的 pid 到 command_id，由状态同步线程填写
+fn kitty_window_pids() -> HashMap<u32, String> {
+    KITTY_TAB_MANAGER
+        .lock()
+        .map(|manager| {
+            manager
+                .values()
+                .filter_map(|tab| Some((tab.pid?, tab.id.clone())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+struct OwnerResolver {
+    terminal_pids: HashMap<u32, String>,
+    kitty_window_pids: HashMap<u32, String>,
+    kitty_config: KittyConfig,
+    cache: HashMap<u32, Option<ProcessInfo>>,
+}
+
+impl OwnerResolver {
+    fn new() -> Self {
+        let terminal_pids = session_pids()
+            .into_iter()
+            .map(|(session_id, pid)| (pid, session_id))
+            .collect();
+        Self {
+            terminal_pids,
+            kitty_window_pids: kitty_window_pids(),
+            kitty_config: crate::modules::kitty::config::load(),
+            cache: HashMap::new(),
+        }
+    }
+
+    fn info(&mut self, pid: u32) -> Option<ProcessInfo> {
+        self.cache
+            .entry(pid)
+            .or_insert_with(|| process_info(pid))
+            .clone()
+    }
+
+    // 沿父进程链向上查找所属任务
+    fn resolve(&mut self, pid: u32) -> Option<PortOwner> {
+        let mut current = Some(pid);
+        let mut visited = HashSet::new();
+        while let Some(p) = current {
+            if !visited.insert(p) || p <= 1 {
+                break;
+            }
+            if let Some(session_id) = self.terminal_pids.get(&p) {
+                return Some(PortOwner::Terminal {
+                    session_id: session_id.clone(),
+                });
+            }
+            if let Some(command_id) = self.kitty_window_pids.get(&p) {
+                return Some(PortOwner::Kitty {
+                    command_id: command_id.clone(),
+                });
+            }
+            let info = self.info(p)?;
+            if let Some(instance) = kitty_socket_id(&info.cmdline, &self.kitty_config) {
+                if let Some(command_id) = resolve_kitty_command_id(&instance, &self.kitty_config) {
+                    return Some(PortOwner::Kitty { command_id });
+                }
+            }
+            current = info.ppid;
+        }
+        None
+    }
+}
+
+fn match_project(cwd: Option<&str>, project_paths: &[String]) -> Option<String> {
+    let cwd = Path::new(cwd?);
+    project_paths
+        .iter()
+        .filter(|project| cwd.starts_with(Path::new(project)))
+        // 取最长匹配，兼容嵌套项目
+        .max_by_key(|project| project.len())
+        .cloned()
+}
+
+fn collect_ports(project_paths: &[String]) -> Vec<ListeningPort> {
+    let mut resolver = OwnerResolver::new();
+    let mut seen = HashSet::new();
+    let mut ports = Vec::new();
+
+    for raw in collect_listeners() {
+        // 同一进程在 0.0.0.0 与 :: 上重复监听时只保留一条
+        if !seen.insert((raw.port, raw.address.clone(), raw.pid)) {
+            continue;
+        }
+        let info = raw.pid.and_then(|pid| resolver.info(pid));
+        let owner = raw.pid.and_then(|pid| resolver.resolve(pid));
+        let cwd = info.as_ref().and_then(|i| i.cwd.clone());
+        let mut project_path = match_project(cwd.as_deref(), project_paths);
+        if project_path.is_none() {
+            if let Some(PortOwner::Kitty { command_id }) = &owner {
+                project_path = KITTY_TAB_MANAGER
+                    .lock()
+                    .ok()
+                    .and_then(|m| m.get(command_id).map(|t| t.working_dir.clone()));
+            }
+        }
+
+        ports.push(ListeningPort {
+            port: raw.port,
+            address: raw.address,
+            protocol: raw.protocol,
+            pid: raw.pid,
+            process_name: info.as_ref().and_then(|i| i.name.clone()),
+            cwd,
+            owner,
+            project_path,
+        });
+    }
+
+    ports.sort_by_key(|p| p.port);
+    ports
+}
+
+// ---------- 端口推断 ----------
+
+fn parse_port(value: &str) -> Option<u16> {
+    value
+        .trim()
+        .trim_matches(|c| c == '"' || c == '\'' || c == ',')
+        .parse::<u16>()
+        .ok()
+        .filter(|p| *p != 0)
+}
+
+// 从脚本中解析 --port 3000 / --port=3000 / -p 3000 / PORT=3000
+fn port_from_script(script: &str) -> Option<u16> {
+    let tokens: Vec<&str> = script.split_whitespace().collect();
+    for (i, token) in tokens.iter().enumerate() {
+        if let Some(value) = token.strip_prefix("--port=") {
+            if let Some(port) = parse_port(value) {
+                return Some(port);
+            }
+        }
+        if let Some(value) = token.strip_prefix("PORT=") {
+            if let Some(port) = parse_port(value) {
+                return Some(port);
+            }
+        }
+        if *token == "--port" || *token == "-p" {
+            if let Some(port) = tokens.get(i + 1).and_then(|v| parse_port(v)) {
+                return Some(port);
+            }
+        }
+    }
+    None
+}
+
+fn port_from_env_files(dir: &Path) -> Option<u16> {
+    for name in [".env.local", ".env"] {
+        let Ok(content) = fs::read_to_string(dir.join(name)) else {
+            continue;
+        };
+        for line in content.lines() {
+            let line = line.trim().trim_start_matches("export ");
+            if let Some(value) = line.strip_prefix("PORT=") {
+                if let Some(port) = parse_port(value) {
+                    return Some(port);
+                }
+            }
+        }
+    }
+    None
+}
+
+// 在 vite/webpack 等配置文件中查找 `port: 3000`
+fn port_from_config_files(dir: &Path) -> Option<u16> {
+    const CONFIG_FILES: [&str; 8] = [
+        "vite.config.ts",
+        "vite.config.js",
+        "vite.config.mjs",
+        "webpack.config.js",
+        "vue.config.js",
+        "nuxt.config.ts",
+        "astro.config.mjs",
+        "angular.json",
+    ];
+    for name in CONFIG_FILES {
+        let Ok(content) = fs::read_to_string(dir.join(name)) else {
+            continue;
+        };
+        let mut rest = content.as_str();
+        while let Some(pos) = rest.find("port") {
+            rest = &rest[pos + 4..];
+            let candidate = rest.trim_start_matches(['"', '\'']);
+            let Some(candidate) = candidate.trim_start().strip_prefix(':') else {
+                continue;
+            };
+            let digits: String = candidate
+                .trim_start()
+                .chars()
+                .take_while(|c| c.is_ascii_digit())
+                .collect();
+            if let Some(port) = parse_port(&digits) {
+                return Some(port);
+            }
+        }
+    }
+    None
+}
+
+// 根据依赖推断框架默认端口：(依赖名, 可执行文件名, 默认端口)
+fn port_from_framework(package_json: &serde_json::Value, script: Option<&str>) -> Option<u16> {
+    const DEFAULTS: [(&str, &str, u16); 11] = [
+        ("next", "next", 3000),
+        ("nuxt", "nuxt", 3000),
+        ("react-scripts", "react-scripts", 3000),
+        ("@remix-run/dev", "remix", 3000),
+        ("@angular/cli", "ng", 4200),
+        ("astro", "astro", 4321),
+        ("gatsby", "gatsby", 8000),
+        ("@vue/cli-service", "vue-cli-service", 8080),
+        ("webpack-dev-server", "webpack", 8080),
+        ("expo", "expo", 8081),
+        ("vite", "vite", 5173),
+    ];
+
+    let has_dep = |name: &str| {
+        ["dependencies", "devDependencies"]
+            .iter()
+            .any(|key| package_json.get(key).and_then(|d| d.get(name)).is_some())
+    };
+
+    // 优先匹配脚本中实际调用的工具
+    if let Some(script) = script {
+        if let Some((_, _, port)) = DEFAULTS.iter().find(|(dep, bin, _)| {
+            has_dep(dep) && script.split_whitespace().any(|token| token == *bin)
+        }) {
+            return Some(*port);
+        }
+    }
+
+    DEFAULTS
+        .iter()
+        .find(|(dep, _, _)| has_dep(dep))
+        .map(|(_, _, port)| *port)
+}
+
+// 推断项目（或某个脚本）将使用的端口，返回 (端口, 来源)
+// build、test 等一次性脚本只认脚本参数中写明的端口，配置文件与框架默认端口只用于开发服务器等常驻脚本
+pub fn infer_project_port(project_path: &str, command_name: Option<&str>) -> Option<(u16, String)> {
+    let dir = Path::new(project_path);
+    let package_json = fs::read_to_string(dir.join("package.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+        .unwrap_or(serde_json::Value::Null);
+
+    let script = command_name.and_then(|name| {
+        package_json
+            .get("scripts")
+            .and_then(|s| s.get(name))
+            .and_then(|s| s.as_str())
+    });
+
+    if let Some(port) = script.and_then(port_from_script) {
+        return Some((port, "declared".to_string()));
+    }
+    if command_name.is_some_and(|name| !task_runner::is_long_running_script(name)) {
+        return None;
+    }
+    if let Some(port) = package_json
+        .get("config")
+        .and_then(|c| c.get("port"))
+        .and_then(|p| p.as_u64().or_else(|| p.as_str()?.parse().ok()))
+        .and_then(|p| u16::try_from(p).ok())
+    {
+        return Some((port, "declared".to_string()));
+    }
+    if let Some(port) = port_from_env_files(dir).or_else(|| port_from_config_files(dir)) {
+        return Some((port, "declared".to_string()));
+    }
+
+    port_from_framework(&package_json, script).map(|port| (port, "inferred".to_string()))
+}
+
+// ---------- 终止 ----------
+
+pub fn kill_pid(pid: u32, signal: &str) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        let _ = signal;
+        return Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/T", "/F"])
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        Command::new("kill")
+            .arg(format!("-{}", signal))
+            .arg(pid.to_string())
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+}
+
+pub fn is_pid_alive(pid: u32) -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        Path::new(&format!("/proc/{}", pid)).exists()
+    }
+    // taskkill 不支持信号 0，会直接结束进程树，Windows 上改为按 PID 查询进程列表
+    #[cfg(target_os = "windows")]
+    {
+        Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/NH", "/FO", "CSV"])
+            .output()
+            .map(|o| {
+                String::from_utf8_lossy(&o.stdout)
+                    .lines()
+                    .any(|line| line.split(',').nth(1) == Some(&format!("\"{}\"", pid)))
+            })
+            .unwrap_or(false)
+    }
+    #[cfg(all(unix, not(target_os = "linux")))]
+    {
+        kill_pid(pid, "0")
+    }
+}
+
+// 先 TERM，等待片刻后仍存活则 KILL
+fn terminate_pid(pid: u32) -> Result<(), String> {
+    if !kill_pid(pid, "TERM") && is_pid_alive(pid) {
+        return Err(i18n::tf("ports.kill_failed", &[("pid", &pid)]));
+    }
+    for _ in 0..10 {
+        if !is_pid_alive(pid) {
+            return Ok(());
+        }
+        std::thread::sleep(std::time::Duration::from_millis(200));
+    }
+    kill_pid(pid, "KILL");
+    Ok(())
+}
+
+// ---------- Tauri 命令 ----------
+
+// 列出所有监听中的端口及其归属
+#[tauri::command]
+pub fn list_listening_ports(
+    project_paths: Option<Vec<String>>,
+) -> Result<Vec<ListeningPort>, String> {
+    Ok(collect_ports(&project_paths.unwrap_or_default()))
+}
+
+pub fn check_port(
+    project_path: &str,
+    command_name: Option<&str>,
+    port: Option<u16>,
+) -> PortCheckResult {
+    let resolved = match port {
+        Some(p) => Some((p, "explicit".to_string())),
+        None => infer_project_port(project_path, command_name),
+    };
+
+    let Some((port, source)) = resolved else {
+        return PortCheckResult {
+            port: None,
+            source: None,
+            in_use: false,
+            listeners: Vec::new(),
+        };
+    };
+
+    let listeners: Vec<ListeningPort> = collect_ports(&[project_path.to_string()])
+        .into_iter()
+        .filter(|l| l.port == port)
+        .collect();
+
+    PortCheckResult {
+        port: Some(port),
+        source: Some(source),
+        in_use: !listeners.is_empty(),
+        listeners,
+    }
+}
+
+// 占用端口的进程描述：名称 (PID)
+pub fn describe_listeners(listeners: &[ListeningPort]) -> Option<String> {
+    listeners
+        .iter()
+        .find_map(|l| match (&l.process_name, l.pid) {
+            (Some(name), Some(pid)) => Some(format!("{} ({})", name, pid)),
+            (None, Some(pid)) => Some(pid.to_string()),
+            _ => None,
+        })
+}
+
+// 各启动路径在启动前调用：脚本将使用的端口已被占用时返回 PortInUse
+pub fn ensure_port_free(project_path: &str, command_name: Option<&str>) -> AppResult<()> {
+    let check = check_port(project_path, command_name, None);
+    match check.port {
+        Some(port) if check.in_use => Err(AppError::new(ErrorCode::PortInUse, "ports.in_use")
+            .arg("port", port)
+            .arg(
+                "owner",
+                describe_listeners(&check.listeners).unwrap_or_else(|| "-".to_string()),
+            )
+            .with_path(project_path)),
+        _ => Ok(()),
+    }
+}
+
+// 启动前检查端口冲突
+#[tauri::command]
+pub fn check_port_conflict(
+    project_path: String,
+    command_name: Option<String>,
+    port: Option<u16>,
+) -> Result<PortCheckResult, String> {
+    Ok(check_port(&project_path, command_name.as_deref(), port))
+}
+
+// 释放端口：优先走已有的任务终止逻辑，未托管的进程直接发送信号
+#[tauri::command]
+pub fn free_port(app: AppHandle, port: u16) -> Result<String, String> {
+    let listeners: Vec<ListeningPort> = collect_ports(&[])
+        .into_iter()
+        .filter(|l| l.port == port)
+        .collect();
+
+    if listeners.is_empty() {
+        return Ok(i18n::tf("ports.not_in_use", &[("port", &port)]));
+    }
+
+    let mut handled_owners = HashSet::new();
+    let mut messages = Vec::new();
+    for listener in listeners {
+        match listener.owner {
+            Some(PortOwner::Kitty { command_id }) => {
+                if handled_owners.insert(format!("kitty:{}", command_id)) {
+                    messages.push(terminate_command(app.clone(), command_id)?);
+                }
+            }
+            Some(PortOwner::Terminal { session_id }) => {
+                if handled_owners.insert(format!("terminal:{}", session_id)) {
+                    close_terminal_session(session_id.clone())?;
+                    messages.push(i18n::tf("ports.session_closed", &[("id", &session_id)]));
+                }
+            }
+            None => {
+                let pid = listener
+                    .pid
+                    .ok_or_else(|| i18n::tf("ports.owner_unknown", &[("port", &port)]))?;
+                if handled_owners.insert(format!("pid:{}", pid)) {
+                    terminate_pid(pid)?;
+                    messages.push(i18n::tf("ports.process_killed", &[("pid", &pid)]));
+                }
+            }
+        }
+    }
+
+    Ok(messages.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    const PROC_NET_TCP: &str = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41235 1 0000000000000000 100 0 0 10 0
+   1: 00000000:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41236 1 0000000000000000 100 0 0 10 0
+   2: 0100007F:A1B2 0100007F:0BB8 01 00000000:00000000 00:00000000 00000000  1000        0 41237 1 0000000000000000 20 4 30 10 -1
+   3: garbage
+";
+
+    #[cfg(target_os = "linux")]
+    const PROC_NET_TCP6: &str = "\
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000001000000:1435 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 52001 1 0000000000000000 100 0 0 10 0
+   1: 00000000000000000000000000000000:0050 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 52002 1 0000000000000000 100 0 0 10 0
+";
+
+    fn project_dir(name: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("runproject-ports-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn hex_addresses_are_little_endian() {
+        assert_eq!(parse_hex_ipv4("0100007F").as_deref(), Some("127.0.0.1"));
+        assert_eq!(parse_hex_ipv4("00000000").as_deref(), Some("0.0.0.0"));
+        assert_eq!(parse_hex_ipv4("zz"), None);
+        assert_eq!(
+            parse_hex_ipv6("00000000000000000000000001000000").as_deref(),
+            Some("::1")
+        );
+        assert_eq!(
+            parse_hex_ipv6("000080FE00000000FF565002BD69B1FE").as_deref(),
+            Some("fe80::250:56ff:feb1:69bd")
+        );
+        assert_eq!(parse_hex_ipv6("0100"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn proc_net_keeps_only_listening_sockets() {
+        assert_eq!(
+            parse_proc_net(PROC_NET_TCP, "tcp"),
+            vec![
+                (3000, "127.0.0.1".to_string(), "tcp".to_string(), 41235),
+                (8080, "0.0.0.0".to_string(), "tcp".to_string(), 41236),
+            ]
+        );
+        assert_eq!(
+            parse_proc_net(PROC_NET_TCP6, "tcp6"),
+            vec![
+                (5173, "::1".to_string(), "tcp6".to_string(), 52001),
+                (80, "::".to_string(), "tcp6".to_string(), 52002),
+            ]
+        );
+        assert!(parse_proc_net("", "tcp").is_empty());
+    }
+
+    #[test]
+    fn scripts_declare_ports() {
+        assert_eq!(port_from_script("vite --port 4000"), Some(4000));
+        assert_eq!(port_from_script("next dev --port=3001"), Some(3001));
+        assert_eq!(port_from_script("PORT=8081 node server.js"), Some(8081));
+        assert_eq!(port_from_script("serve -p 5000"), Some(5000));
+        assert_eq!(port_from_script("vite --port 0"), None);
+        assert_eq!(port_from_script("vite --port"), None);
+        assert_eq!(port_from_script("vite build"), None);
+    }
+
+    #[test]
+    fn project_ports_prefer_declared_over_framework_defaults() {
+        let dir = project_dir("declared");
+        fs::write(
+            dir.join("package.json"),
+            r#"{"scripts":{"dev":"vite --port 4100","start":"vite","build":"vite build","test":"vitest --port 5300"},"devDependencies":{"vite":"^5"}}"#,
+        )
+        .unwrap();
+        let path = dir.to_string_lossy().to_string();
+        assert_eq!(
+            infer_project_port(&path, Some("dev")),
+            Some((4100, "declared".to_string()))
+        );
+        assert_eq!(
+            infer_project_port(&path, Some("start")),
+            Some((5173, "inferred".to_string()))
+        );
+
+        // 一次性脚本不套用开发服务器的默认端口，只认脚本中写明的端口
+        assert_eq!(infer_project_port(&path, Some("build")), None);
+        assert_eq!(
+            infer_project_port(&path, Some("test")),
+            Some((5300, "declared".to_string()))
+        );
+
+        fs::write(dir.join(".env"), "export PORT=\"3300\"\n").unwrap();
+        assert_eq!(
+            infer_project_port(&path, Some("start")),
+            Some((3300, "declared".to_string()))
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn project_ports_read_config_files_and_framework_defaults() {
+        let dir = project_dir("config");
+        fs::write(
+            dir.join("package.json"),
+            r#"{"scripts":{"dev":"next dev"},"dependencies":{"next":"14","vite":"5"}}"#,
+        )
+        .unwrap();
+        let path = dir.to_string_lossy().to_string();
+        // 脚本实际调用的工具优先于依赖顺序
+        assert_eq!(
+            infer_project_port(&path, Some("dev")),
+            Some((3000, "inferred".to_string()))
+        );
+
+        fs::write(
+            dir.join("vite.config.ts"),
+            "export default { server: { \"port\": 5200 } }",
+        )
+        .unwrap();
+        assert_eq!(
+            infer_project_port(&path, Some("dev")),
+            Some((5200, "declared".to_string()))
+        );
+
+        let empty = project_dir("empty");
+        assert_eq!(infer_project_port(&empty.to_string_lossy(), None), None);
+        let _ = fs::remove_dir_all(&dir);
+        let _ = fs::remove_dir_all(&empty);
+    }
+}
@@ -0,0 +1,372 @@
+// 执行预览模块
+//
+// 在真正启动前解析一次命令会如何执行，全程不产生副作用：
+// - 包管理器前缀、模板参数（不写入参数历史）、Node 版本切换方式
+// - 合并后的环境变量、工作目录、执行后端以及最终的 argv
+// - 可预见的问题以警告形式返回，例如依赖需要重新安装、Node 版本未安装、端口被占用
+use crate::modules::command_template;
+use crate::modules::env_profile::{self, EnvPreview};
+use crate::modules::error::{AppError, AppResult, ErrorCode};
+use crate::modules::i18n;
+use crate::modules::install_preflight::{self, DependencyCheck, InstallPolicy};
+use crate::modules::kitty::config;
+use crate::modules::kitty::connection::{check_kitty_installed, get_socket_path};
+use crate::modules::kitty::executor::{
+    build_kitty_window_args, build_script_command, get_package_manager_prefix,
+};
+use crate::modules::kitty::grouping;
+use crate::modules::kitty::protocol::Launch;
+use crate::modules::nvm_manager::{self, NodeVersionPlan};
+use crate::modules::platform;
+use crate::modules::ports;
+use crate::modules::project_scanner;
+use crate::modules::terminal::session;
+use crate::modules::terminal_backend::kitty::KittyBackend;
+use crate::modules::terminal_backend::LaunchSpec;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::Path;
+
+// 与前端的执行入口一一对应
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionBackend {
+    // execute_project_command：直接以 shell 运行并收集输出
+    Native,
+    // execute_command_in_kitty：独立的 kitty 窗口
+    Kitty,
+    // execute_command_with_kitten：通过远程控制在 kitty 中新建标签页
+    KittyRemote,
+    // 内置终端：命令作为输入写入交互式 shell
+    Pty,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewWarning {
+    pub key: &'static str,
+    pub message: String,
+}
+
+// 远程控制时发往 kitty 实例的 launch 请求
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteLaunch {
+    pub socket_path: String,
+    pub request: Launch,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionPreview {
+    pub backend: ExecutionBackend,
+    pub working_dir: String,
+    pub package_manager: String,
+    pub package_manager_prefix: String,
+    pub extra_args: String,
+    pub dependencies: DependencyCheck,
+    pub node: Option<NodeVersionPlan>,
+    pub env: Option<EnvPreview>,
+    // 交给 shell 执行的完整命令
+    pub final_command: String,
+    pub argv: Vec<String>,
+    // 内置终端写入 shell 的内容
+    pub input: Option<String>,
+    pub remote: Option<RemoteLaunch>,
+    pub warnings: Vec<PreviewWarning>,
+}
+
+fn warn(warnings: &mut Vec<PreviewWarning>, key: &'static str, args: &[(&str, &dyn Display)]) {
+    warnings.push(PreviewWarning {
+        key,
+        message: i18n::tf(key, args),
+    });
+}
+
+fn read_package_json(dir: &Path) -> Option<serde_json::Value> {
+    let content = std::fs::read_to_string(dir.join("package.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn command_argv(cmd: &std::process::Command) -> Vec<String> {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|arg| arg.to_string_lossy().to_string())
+        .collect()
+}
+
+// 不写入参数历史的模板渲染
+fn preview_extra_args(
+    working_dir: &str,
+    template_name: &str,
+    package_manager: &str,
+    arg_values: Option<&BTreeMap<String, serde_json::Value>>,
+) -> Result<String, String> {
+    let Some(template) = command_template::get_template(working_dir, template_name) else {
+        return Ok(String::new());
+    };
+    let empty = BTreeMap::new();
+    command_template::render_arguments(
+        &template,
+        arg_values.unwrap_or(&empty),
+        working_dir,
+        package_manager,
+    )
+    .map(|rendered| rendered.args)
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn preview_execution(
+    backend: ExecutionBackend,
+    working_dir: String,
+    command: String,
+    node_version: Option<String>,
+    package_manager: Option<String>,
+    project_name: Option<String>,
+    command_name: Option<String>,
+    command_id: Option<String>,
+    env_profile: Option<String>,
+    arg_values: Option<BTreeMap<String, serde_json::Value>>,
+    workspace_path: Option<String>,
+) -> AppResult<ExecutionPreview> {
+    let dir = Path::new(&working_dir);
+    if !dir.is_dir() {
+        return Err(
+            AppError::new(ErrorCode::NotADirectory, "common.not_a_directory")
+                .with_path(&working_dir),
+        );
+    }
+
+    let mut warnings = Vec::new();
+    let package_manager =
+        package_manager.unwrap_or_else(|| project_scanner::detect_package_manager(dir));
+    let command_name = command_name.unwrap_or_else(|| command.clone());
+    let project_name = project_name.unwrap_or_else(|| {
+        dir.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    });
+    let command_id = command_id.unwrap_or_else(|| format!("{}-{}", project_name, command_name));
+
+    // execute_project_command 直接执行原始命令，其余后端按脚本名补全包管理器前缀
+    let package_manager_prefix = if backend == ExecutionBackend::Native {
+        String::new()
+    } else {
+        get_package_manager_prefix(&package_manager, &command)
+    };
+
+    let package_json = read_package_json(dir);
+    match &package_json {
+        Some(package_json) => {
+            let has_script = package_json
+                .get("scripts")
+                .and_then(|scripts| scripts.get(&command))
+                .is_some();
+            if !package_manager_prefix.is_empty() && !has_script {
+                warn(
+                    &mut warnings,
+                    "preview.script_not_found",
+                    &[("script", &command)],
+                );
+            }
+        }
+        None => warn(&mut warnings, "preview.package_json_missing", &[]),
+    }
+
+    let extra_args = preview_extra_args(
+        &working_dir,
+        &command_name,
+        &package_manager,
+        arg_values.as_ref(),
+    )
+    .unwrap_or_else(|e| {
+        warn(&mut warnings, "preview.args_invalid", &[("error", &e)]);
+        String::new()
+    });
+
+    // 依赖预检：自动安装策略下，安装命令会在脚本前执行
+    let dependencies = install_preflight::check_project(&working_dir, Some(&package_manager));
+    let install_command = (!dependencies.issues.is_empty()
+        && dependencies.policy == InstallPolicy::Auto)
+        .then(|| dependencies.install_command.clone());
+    if !dependencies.issues.is_empty() {
+        let reason = dependencies.summary();
+        match &install_command {
+            Some(install) => warn(
+                &mut warnings,
+                "preview.install_before_run",
+                &[("reason", &reason), ("command", install)],
+            ),
+            None => warn(
+                &mut warnings,
+                "preview.dependencies_outdated",
+                &[
+                    ("reason", &reason),
+                    ("command", &dependencies.install_command),
+                ],
+            ),
+        }
+    }
+
+    // execute_project_command 单独执行安装命令，其余后端与脚本串联在同一条命令中
+    let script_command = if backend == ExecutionBackend::Native {
+        format!("{}{}", command, extra_args)
+    } else {
+        let script = build_script_command(&command, &package_manager, Some(&extra_args));
+        match &install_command {
+            Some(install) => platform::chain_commands(install, &script),
+            None => script,
+        }
+    };
+
+    let node = match &node_version {
+        Some(version) => match nvm_manager::plan_node_version(version, &script_command) {
+            Ok(plan) => {
+                if !plan.installed {
+                    // 只有 execute_project_command 会先安装缺失的版本
+                    let key = if backend == ExecutionBackend::Native {
+                        "preview.node_will_install"
+                    } else {
+                        "preview.node_not_installed"
+                    };
+                    warn(
+                        &mut warnings,
+                        key,
+                        &[("version", &version), ("manager", &plan.manager)],
+                    );
+                }
+                Some(plan)
+            }
+            Err(err) => {
+                warn(
+                    &mut warnings,
+                    "preview.node_unavailable",
+                    &[("version", &version), ("error", &err.message)],
+                );
+                None
+            }
+        },
+        None => None,
+    };
+
+    // execute_project_command 先切换默认版本再执行，命令本身不带切换前缀
+    let final_command = match &node {
+        Some(plan) if backend != ExecutionBackend::Native => plan.command.clone(),
+        _ => script_command,
+    };
+
+    let env =
+        match env_profile::resolve_env(&working_dir, Some(&command_name), env_profile.as_deref()) {
+            Ok(env) => Some(env),
+            Err(e) => {
+                warn(&mut warnings, "preview.env_failed", &[("error", &e)]);
+                None
+            }
+        };
+    let env_vars: Vec<(String, String)> = env
+        .as_ref()
+        .map(|env| {
+            env.entries
+                .iter()
+                .map(|e| (e.key.clone(), e.value.clone()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let check = ports::check_port(&working_dir, Some(&command_name), None);
+    if let (Some(port), true) = (check.port, check.in_use) {
+        match ports::describe_listeners(&check.listeners) {
+            Some(owner) => warn(
+                &mut warnings,
+                "preview.port_in_use",
+                &[("port", &port), ("owner", &owner)],
+            ),
+            None => warn(
+                &mut warnings,
+                "preview.port_in_use_unknown",
+                &[("port", &port)],
+            ),
+        }
+    }
+
+    let kitty_config = config::load();
+    let mut remote = None;
+    let (argv, input) = match backend {
+        ExecutionBackend::Native => (
+            command_argv(&platform::build_shell_command(&final_command)),
+            None,
+        ),
+        ExecutionBackend::Kitty => {
+            let socket_path = get_socket_path(&command_id, &kitty_config);
+            let mut argv = vec!["kitty".to_string()];
+            argv.extend(build_kitty_window_args(
+                &project_name,
+                &command_name,
+                &socket_path,
+                &working_dir,
+                &final_command,
+                &kitty_config,
+            ));
+            (argv, None)
+        }
+        ExecutionBackend::KittyRemote => {
+            // 与执行器相同：按分组设置选择实例，共用实例时按布局生成标签页或分屏的请求
+            let settings = grouping::settings()?;
+            let instance =
+                settings.instance_key(&command_id, &working_dir, workspace_path.as_deref());
+            let socket_path = KittyBackend {
+                config: kitty_config.clone(),
+            }
+            .socket_path(&instance);
+            let spec = LaunchSpec {
+                command_id: command_id.clone(),
+                project_name: project_name.clone(),
+                command_name: command_name.clone(),
+                working_dir: working_dir.clone(),
+                command: final_command.clone(),
+                env: env_vars.clone(),
+            };
+            let request = grouping::tab_request(&settings, &socket_path, &spec);
+            remote = Some(RemoteLaunch {
+                socket_path,
+                request: request.clone(),
+            });
+            (request.args, None)
+        }
+        ExecutionBackend::Pty => {
+            let shell = session::build_shell_command()?;
+            let argv = shell
+                .get_argv()
+                .iter()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect();
+            (argv, Some(format!("{}\n", final_command)))
+        }
+    };
+
+    if matches!(
+        backend,
+        ExecutionBackend::Kitty | ExecutionBackend::KittyRemote
+    ) && !check_kitty_installed().unwrap_or(false)
+    {
+        warn(&mut warnings, "preview.kitty_not_installed", &[]);
+    }
+
+    Ok(ExecutionPreview {
+        backend,
+        working_dir,
+        package_manager,
+        package_manager_prefix,
+        extra_args,
+        dependencies,
+        node,
+        env,
+        final_command,
+        argv,
+        input,
+        remote,
+        warnings,
+    })
+}
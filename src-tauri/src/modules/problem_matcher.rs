@@ -0,0 +1,872 @@
+// 问题匹配器模块
+//
+// 参考 VS Code problemMatcher 的设计，把任务输出解析为结构化诊断：
+// - 内置 tsc、eslint (stylish/unix)、vite、webpack、jest、vitest、rustc 匹配器
+// - 支持用户自定义的正则匹配器（单行或多行 pattern，最后一个 pattern 可 loop）
+// - 以流的方式逐行处理 PTY/命令输出，按运行 id 保存诊断结果（只保留最近的运行）
+use crate::modules::{i18n, storage};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+const MATCHER_STORE: &str = "problem_matchers";
+// 保留诊断的运行数量，超出时丢弃最早的运行
+const MAX_TRACKED_RUNS: usize = 100;
+// 每次运行最多保留的诊断条数，长时间 watch 时避免无限增长
+const MAX_RUN_DIAGNOSTICS: usize = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatcherPattern {
+    pub regexp: String,
+    #[serde(default)]
+    pub file: Option<usize>,
+    #[serde(default)]
+    pub line: Option<usize>,
+    #[serde(default)]
+    pub column: Option<usize>,
+    #[serde(default)]
+    pub severity: Option<usize>,
+    #[serde(default)]
+    pub message: Option<usize>,
+    #[serde(default)]
+    pub code: Option<usize>,
+    // 最后一个 pattern 可以重复匹配，每次匹配产出一条诊断
+    #[serde(default, rename = "loop")]
+    pub repeat: bool,
+    // 扩展：匹配到该 pattern 之前最多允许跳过的无关行数（空行总是跳过）
+    #[serde(default)]
+    pub max_gap: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProblemMatcher {
+    pub name: String,
+    #[serde(default)]
+    pub source: Option<String>,
+    // pattern 未捕获 severity 时使用的默认值
+    #[serde(default)]
+    pub severity: Option<String>,
+    // relative | absolute | autoDetect
+    #[serde(default)]
+    pub file_location: Option<String>,
+    pub pattern: Vec<MatcherPattern>,
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub file: String,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub severity: String,
+    pub message: String,
+    pub source: String,
+    pub code: Option<String>,
+}
+
+lazy_static! {
+    static ref ANSI_RE: Regex =
+        Regex::new(r"\x1b\[[0-9;?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[@-Z\\-_]")
+            .unwrap();
+    static ref BUILTIN_MATCHERS: Vec<ProblemMatcher> = builtin_matchers();
+    static ref USER_MATCHERS: Arc<Mutex<Option<Vec<ProblemMatcher>>>> = Arc::new(Mutex::new(None));
+    static ref RUN_DIAGNOSTICS: Arc<Mutex<RunDiagnostics>> =
+        Arc::new(Mutex::new(RunDiagnostics::default()));
+}
+
+// 按运行 id 保存的诊断，order 记录运行出现的先后顺序
+#[derive(Default)]
+struct RunDiagnostics {
+    runs: HashMap<String, Vec<Diagnostic>>,
+    order: VecDeque<String>,
+}
+
+impl RunDiagnostics {
+    fn record(&mut self, run_id: &str, diagnostics: &[Diagnostic]) {
+        if !self.runs.contains_key(run_id) {
+            self.order.push_back(run_id.to_string());
+            while self.order.len() > MAX_TRACKED_RUNS {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.runs.remove(&oldest);
+                }
+            }
+        }
+        let stored = self.runs.entry(run_id.to_string()).or_default();
+        let room = MAX_RUN_DIAGNOSTICS.saturating_sub(stored.len());
+        stored.extend(diagnostics.iter().take(room).cloned());
+    }
+
+    fn remove(&mut self, run_id: &str) {
+        if self.runs.remove(run_id).is_some() {
+            self.order.retain(|id| id != run_id);
+        }
+    }
+}
+
+fn with_user_matchers<R>(f: impl FnOnce(&mut Vec<ProblemMatcher>) -> R) -> Result<R, String> {
+    let mut guard = USER_MATCHERS
+        .lock()
+        .map_err(|e| i18n::tf("common.lock_failed", &[("error", &e)]))?;
+    Ok(f(guard.get_or_insert_with(|| storage::load(MATCHER_STORE))))
+}
+
+// ---------- 内置匹配器 ----------
+
+fn pattern(regexp: &str) -> MatcherPattern {
+    MatcherPattern {
+        regexp: regexp.to_string(),
+        file: None,
+        line: None,
+        column: None,
+        severity: None,
+        message: None,
+        code: None,
+        repeat: false,
+        max_gap: 0,
+    }
+}
+
+fn matcher(name: &str, source: &str, pattern: Vec<MatcherPattern>) -> ProblemMatcher {
+    ProblemMatcher {
+        name: name.to_string(),
+        source: Some(source.to_string()),
+        severity: Some("error".to_string()),
+        file_location: Some("autoDetect".to_string()),
+        pattern,
+        builtin: true,
+    }
+}
+
+fn builtin_matchers() -> Vec<ProblemMatcher> {
+    vec![
+        // src/a.ts(3,7): error TS2322: ... 以及 --pretty 输出 src/a.ts:3:7 - error TS2322: ...
+        matcher(
+            "tsc",
+            "ts",
+            vec![MatcherPattern {
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                code: Some(5),
+                message: Some(6),
+                ..pattern(
+                    r"^([^\s].*?)[(:](\d+)[,:](\d+)(?:\):\s+|\s+-\s+)(error|warning|info)\s+(TS\d+)\s*:\s*(.*)$",
+                )
+            }],
+        ),
+        // 文件路径一行，随后每行一个问题
+        matcher(
+            "eslint-stylish",
+            "eslint",
+            vec![
+                MatcherPattern {
+                    file: Some(1),
+                    ..pattern(r"^([^\s✖].*\.\w+)\s*$")
+                },
+                MatcherPattern {
+                    line: Some(1),
+                    column: Some(2),
+                    severity: Some(3),
+                    message: Some(4),
+                    code: Some(5),
+                    repeat: true,
+                    ..pattern(r"^\s+(\d+):(\d+)\s+(error|warning|info)\s+(.*?)(?:\s\s+(\S+))?\s*$")
+                },
+            ],
+        ),
+        // /abs/file.js:1:10: message [Error/rule]
+        matcher(
+            "eslint-unix",
+            "eslint",
+            vec![MatcherPattern {
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                message: Some(4),
+                severity: Some(5),
+                code: Some(6),
+                ..pattern(r"^(.+?):(\d+):(\d+):\s+(.*?)\s+\[(Error|Warning)(?:/([^\]]+))?\]\s*$")
+            }],
+        ),
+        // esbuild: ✘ [ERROR] message ... src/main.ts:3:6:
+        matcher(
+            "vite-esbuild",
+            "vite",
+            vec![
+                MatcherPattern {
+                    severity: Some(1),
+                    message: Some(2),
+                    ..pattern(r"^\s*[✘▲X]\s+\[(ERROR|WARNING)\]\s+(.*)$")
+                },
+                MatcherPattern {
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    max_gap: 2,
+                    ..pattern(r"^\s+(\S.*?):(\d+):(\d+):\s*$")
+                },
+            ],
+        ),
+        // [vite] Internal server error: ... / 可选的 Plugin: xxx / File: /abs/file:line:col
+        matcher(
+            "vite",
+            "vite",
+            vec![
+                MatcherPattern {
+                    message: Some(1),
+                    ..pattern(r"^.*\[vite\]\s+(?:Internal server error:\s+)?(.*)$")
+                },
+                MatcherPattern {
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    max_gap: 1,
+                    ..pattern(r"^\s+File:\s+(.+?)(?::(\d+):(\d+))?\s*$")
+                },
+            ],
+        ),
+        // ERROR in ./src/index.js 5:2-8 后跟一行说明
+        matcher(
+            "webpack",
+            "webpack",
+            vec![
+                MatcherPattern {
+                    severity: Some(1),
+                    file: Some(2),
+                    line: Some(3),
+                    column: Some(4),
+                    ..pattern(r"^(ERROR|WARNING) in (.+?)(?:[ :](\d+):(\d+)(?:-\d+)?)?\s*$")
+                },
+                MatcherPattern {
+                    message: Some(1),
+                    max_gap: 1,
+                    ..pattern(r"^\s*(\S.*)$")
+                },
+            ],
+        ),
+        // ● Suite › test 之后在调用栈中找到测试文件位置
+        matcher(
+            "jest",
+            "jest",
+            vec![
+                MatcherPattern {
+                    message: Some(1),
+                    ..pattern(r"^\s*●\s+(.+?)\s*$")
+                },
+                MatcherPattern {
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    max_gap: 60,
+                    ..pattern(r"^\s+at\s+(?:.*\()?([^()\s]+?):(\d+):(\d+)\)?\s*$")
+                },
+            ],
+        ),
+        //  FAIL  src/a.test.ts > suite > test 之后的 ❯ src/a.test.ts:5:17
+        matcher(
+            "vitest",
+            "vitest",
+            vec![
+                MatcherPattern {
+                    file: Some(1),
+                    message: Some(2),
+                    ..pattern(r"^\s*FAIL\s+(\S+)\s+>\s+(.+?)\s*$")
+                },
+                MatcherPattern {
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    max_gap: 30,
+                    ..pattern(r"^\s*❯\s+(\S+?):(\d+):(\d+)\s*$")
+                },
+            ],
+        ),
+        // error[E0308]: mismatched types /  --> src/main.rs:4:18
+        matcher(
+            "rustc",
+            "rustc",
+            vec![
+                MatcherPattern {
+                    severity: Some(1),
+                    code: Some(2),
+                    message: Some(3),
+                    ..pattern(r"^(error|warning)(?:\[(\w+)\])?:\s+(.*)$")
+                },
+                MatcherPattern {
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    ..pattern(r"^\s*-->\s+(.+?):(\d+):(\d+)\s*$")
+                },
+            ],
+        ),
+    ]
+}
+
+// ---------- 匹配引擎 ----------
+
+#[derive(Default, Clone)]
+struct PartialMatch {
+    file: Option<String>,
+    line: Option<u32>,
+    column: Option<u32>,
+    severity: Option<String>,
+    message: Option<String>,
+    code: Option<String>,
+}
+
+struct CompiledMatcher {
+    matcher: ProblemMatcher,
+    regexes: Vec<Regex>,
+    // 下一个期望匹配的 pattern 下标
+    index: usize,
+    // 当前 pattern 已跳过的行数
+    gap: usize,
+    data: PartialMatch,
+}
+
+impl CompiledMatcher {
+    fn compile(matcher: ProblemMatcher) -> Result<Self, String> {
+        if matcher.pattern.is_empty() {
+            return Err(i18n::tf(
+                "matcher.missing_pattern",
+                &[("name", &matcher.name)],
+            ));
+        }
+        let regexes = matcher
+            .pattern
+            .iter()
+            .map(|p| {
+                Regex::new(&p.regexp).map_err(|e| {
+                    i18n::tf(
+                        "matcher.invalid_regex",
+                        &[("name", &matcher.name), ("error", &e)],
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            matcher,
+            regexes,
+            index: 0,
+            gap: 0,
+            data: PartialMatch::default(),
+        })
+    }
+
+    fn reset(&mut self) {
+        self.index = 0;
+        self.gap = 0;
+        self.data = PartialMatch::default();
+    }
+
+    fn capture(&mut self, pattern_index: usize, line: &str) -> bool {
+        let Some(caps) = self.regexes[pattern_index].captures(line) else {
+            return false;
+        };
+        let pattern = &self.matcher.pattern[pattern_index];
+        let get = |group: Option<usize>| {
+            group
+                .and_then(|g| caps.get(g))
+                .map(|m| m.as_str().trim().to_string())
+                .filter(|s| !s.is_empty())
+        };
+
+        if let Some(file) = get(pattern.file) {
+            self.data.file = Some(file);
+        }
+        if let Some(line) = get(pattern.line).and_then(|v| v.parse().ok()) {
+            self.data.line = Some(line);
+        }
+        if let Some(column) = get(pattern.column).and_then(|v| v.parse().ok()) {
+            self.data.column = Some(column);
+        }
+        if let Some(severity) = get(pattern.severity) {
+            self.data.severity = Some(severity);
+        }
+        if let Some(message) = get(pattern.message) {
+            self.data.message = Some(message);
+        }
+        if let Some(code) = get(pattern.code) {
+            self.data.code = Some(code);
+        }
+        true
+    }
+
+    fn emit(&self, cwd: Option<&str>) -> Option<Diagnostic> {
+        let file = self.data.file.clone()?;
+        let message = self.data.message.clone().unwrap_or_default();
+        let severity = normalize_severity(
+            self.data
+                .severity
+                .as_deref()
+                .or(self.matcher.severity.as_deref()),
+        );
+        Some(Diagnostic {
+            file: resolve_file(&file, cwd, self.matcher.file_location.as_deref()),
+            line: self.data.line,
+            column: self.data.column,
+            severity,
+            message,
+            source: self
+                .matcher
+                .source
+                .clone()
+                .unwrap_or_else(|| self.matcher.name.clone()),
+            code: self.data.code.clone(),
+        })
+    }
+
+    fn process_line(&mut self, line: &str, cwd: Option<&str>) -> Option<Diagnostic> {
+        let last = self.regexes.len() - 1;
+
+        // 多行匹配过程中跳过空行
+        if self.index > 0 && line.trim().is_empty() {
+            return None;
+        }
+
+        if self.index > 0 {
+            // 处于 loop 状态：最后一个 pattern 已匹配过
+            if self.index > last {
+                let base = self.data.clone();
+                if self.capture(last, line) {
+                    let diagnostic = self.emit(cwd);
+                    self.data = base;
+                    return diagnostic;
+                }
+                self.reset();
+            } else if self.capture(self.index, line) {
+                return self.advance(cwd);
+            } else if self.gap < self.matcher.pattern[self.index].max_gap {
+                self.gap += 1;
+                return None;
+            } else {
+                self.reset();
+            }
+        }
+
+        // 从第一个 pattern 重新开始
+        if self.capture(0, line) {
+            return self.advance(cwd);
+        }
+        None
+    }
+
+    fn advance(&mut self, cwd: Option<&str>) -> Option<Diagnostic> {
+        let last = self.regexes.len() - 1;
+        self.gap = 0;
+        if self.index < last {
+            self.index += 1;
+            return None;
+        }
+
+        let diagnostic = self.emit(cwd);
+        if self.matcher.pattern[last].repeat {
+            // 保留前面 pattern 的数据，清除 loop pattern 自身的数据后继续匹配
+            self.index = last + 1;
+            let loop_pattern = &self.matcher.pattern[last];
+            if loop_pattern.line.is_some() {
+                self.data.line = None;
+            }
+            if loop_pattern.column.is_some() {
+                self.data.column = None;
+            }
+            if loop_pattern.message.is_some() {
+                self.data.message = None;
+            }
+            if loop_pattern.code.is_some() {
+                self.data.code = None;
+            }
+        } else {
+            self.reset();
+        }
+        diagnostic
+    }
+}
+
+fn normalize_severity(severity: Option<&str>) -> String {
+    match severity.map(|s| s.to_lowercase()) {
+        Some(s) if s.starts_with("warn") => "warning".to_string(),
+        Some(s) if s.starts_with("info") || s == "note" || s == "hint" => "info".to_string(),
+        _ => "error".to_string(),
+    }
+}
+
+fn resolve_file(file: &str, cwd: Option<&str>, file_location: Option<&str>) -> String {
+    let trimmed = file.trim_start_matches("file://");
+    let relative = trimmed.strip_prefix("./").unwrap_or(trimmed);
+    let is_absolute = Path::new(relative).is_absolute();
+    match (file_location.unwrap_or("autoDetect"), cwd) {
+        ("absolute", _) | (_, None) => relative.to_string(),
+        ("relative", Some(cwd)) => Path::new(cwd).join(relative).to_string_lossy().to_string(),
+        (_, Some(cwd)) if !is_absolute => {
+            Path::new(cwd).join(relative).to_string_lossy().to_string()
+        }
+        _ => relative.to_string(),
+    }
+}
+
+pub fn strip_ansi(text: &str) -> String {
+    ANSI_RE.replace_all(text, "").to_string()
+}
+
+// 流式诊断收集器：按字节喂入输出，按行匹配
+pub struct DiagnosticCollector {
+    matchers: Vec<CompiledMatcher>,
+    cwd: Option<String>,
+    pending: Vec<u8>,
+    seen: HashSet<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl DiagnosticCollector {
+    pub fn new(cwd: Option<String>, names: Option<&[String]>) -> Self {
+        let matchers = active_matchers(names)
+            .into_iter()
+            .filter_map(|m| match CompiledMatcher::compile(m) {
+                Ok(compiled) => Some(compiled),
+                Err(e) => {
+                    eprintln!("[PROBLEM_MATCHER] {}", e);
+                    None
+                }
+            })
+            .collect();
+        Self {
+            matchers,
+            cwd,
+            pending: Vec::new(),
+            seen: HashSet::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    // 喂入一段原始输出，返回本次新增的诊断
+    pub fn feed(&mut self, data: &[u8]) -> Vec<Diagnostic> {
+        self.pending.extend_from_slice(data);
+        let mut found = Vec::new();
+        while let Some(pos) = self.pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=pos).collect();
+            let text = String::from_utf8_lossy(&line[..line.len() - 1]).to_string();
+            found.extend(self.process_line(&text));
+        }
+        found
+    }
+
+    // 处理缓冲区中剩余的最后一行
+    pub fn finish(&mut self) -> Vec<Diagnostic> {
+        if self.pending.is_empty() {
+            return Vec::new();
+        }
+        let text = String::from_utf8_lossy(&self.pending).to_string();
+        self.pending.clear();
+        self.process_line(&text)
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    fn process_line(&mut self, raw: &str) -> Vec<Diagnostic> {
+        let stripped = strip_ansi(raw);
+        // 进度条等通过 \r 覆盖的行只保留最后一段
+        let line = stripped
+            .trim_end_matches('\r')
+            .rsplit('\r')
+            .next()
+            .unwrap_or("");
+        let cwd = self.cwd.clone();
+        let mut found = Vec::new();
+        for matcher in &mut self.matchers {
+            if let Some(diagnostic) = matcher.process_line(line, cwd.as_deref()) {
+                let key = format!(
+                    "{}:{:?}:{:?}:{}",
+                    diagnostic.file, diagnostic.line, diagnostic.column, diagnostic.message
+                );
+                if self.seen.insert(key) {
+                    self.diagnostics.push(diagnostic.clone());
+                    found.push(diagnostic);
+                }
+            }
+        }
+        found
+    }
+}
+
+fn active_matchers(names: Option<&[String]>) -> Vec<ProblemMatcher> {
+    let mut all: Vec<ProblemMatcher> = BUILTIN_MATCHERS.clone();
+    if let Ok(user) = with_user_matchers(|user| user.clone()) {
+        all.extend(user);
+    }
+    match names {
+        Some(names) if !names.is_empty() => all
+            .into_iter()
+            .filter(|m| names.iter().any(|n| n == &m.name))
+            .collect(),
+        _ => all,
+    }
+}
+
+// 一次性解析完整输出
+pub fn parse_output(output: &str, cwd: Option<&str>, names: Option<&[String]>) -> Vec<Diagnostic> {
+    let mut collector = DiagnosticCollector::new(cwd.map(|s| s.to_string()), names);
+    collector.feed(output.as_bytes());
+    collector.finish();
+    collector.diagnostics().to_vec()
+}
+
+// 保存某次运行的诊断（追加）
+pub fn record_diagnostics(run_id: &str, diagnostics: &[Diagnostic]) {
+    if diagnostics.is_empty() {
+        return;
+    }
+    if let Ok(mut store) = RUN_DIAGNOSTICS.lock() {
+        store.record(run_id, diagnostics);
+    }
+}
+
+// 保存并推送给前端
+pub fn publish_diagnostics(app: &AppHandle, run_id: &str, diagnostics: &[Diagnostic]) {
+    if diagnostics.is_empty() {
+        return;
+    }
+    record_diagnostics(run_id, diagnostics);
+    let _ = app.emit(
+        "task-diagnostics",
+        serde_json::json!({ "runId": run_id, "diagnostics": diagnostics }),
+    );
+}
+
+pub fn clear_diagnostics(run_id: &str) {
+    if let Ok(mut store) = RUN_DIAGNOSTICS.lock() {
+        store.remove(run_id);
+    }
+}
+
+// ---------- Tauri 命令 ----------
+
+#[tauri::command]
+pub fn list_problem_matchers() -> Result<Vec<ProblemMatcher>, String> {
+    Ok(active_matchers(None))
+}
+
+// 覆盖用户自定义匹配器，保存前校验正则
+#[tauri::command]
+pub fn set_problem_matchers(matchers: Vec<ProblemMatcher>) -> Result<usize, String> {
+    let mut validated = Vec::with_capacity(matchers.len());
+    for mut matcher in matchers {
+        if BUILTIN_MATCHERS.iter().any(|b| b.name == matcher.name) {
+            return Err(i18n::tf(
+                "matcher.builtin_conflict",
+                &[("name", &matcher.name)],
+            ));
+        }
+        matcher.builtin = false;
+        CompiledMatcher::compile(matcher.clone())?;
+        validated.push(matcher);
+    }
+
+    let count = validated.len();
+    with_user_matchers(|user| *user = validated.clone())?;
+    storage::save(MATCHER_STORE, &validated)?;
+    Ok(count)
+}
+
+#[tauri::command]
+pub fn parse_task_output(
+    output: String,
+    cwd: Option<String>,
+    matchers: Option<Vec<String>>,
+) -> Result<Vec<Diagnostic>, String> {
+    Ok(parse_output(&output, cwd.as_deref(), matchers.as_deref()))
+}
+
+#[tauri::command]
+pub fn get_run_diagnostics(run_id: String) -> Result<Vec<Diagnostic>, String> {
+    let store = RUN_DIAGNOSTICS
+        .lock()
+        .map_err(|e| i18n::tf("common.lock_failed", &[("error", &e)]))?;
+    Ok(store.runs.get(&run_id).cloned().unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn clear_run_diagnostics(run_id: String) -> Result<(), String> {
+    clear_diagnostics(&run_id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(matcher: &str, output: &str) -> Vec<Diagnostic> {
+        parse_output(output, Some("/work"), Some(&[matcher.to_string()]))
+    }
+
+    fn location(diagnostic: &Diagnostic) -> (&str, Option<u32>, Option<u32>) {
+        (diagnostic.file.as_str(), diagnostic.line, diagnostic.column)
+    }
+
+    #[test]
+    fn tsc_matches_plain_and_pretty_output() {
+        let found = parse(
+            "tsc",
+            "src/a.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.\n\
+             \x1b[96msrc/b.ts\x1b[0m:10:2 - \x1b[91mwarning\x1b[0m TS6133: 'x' is declared but never used.\n",
+        );
+        assert_eq!(found.len(), 2);
+        assert_eq!(location(&found[0]), ("/work/src/a.ts", Some(3), Some(7)));
+        assert_eq!(found[0].code.as_deref(), Some("TS2322"));
+        assert_eq!(found[0].severity, "error");
+        assert_eq!(location(&found[1]), ("/work/src/b.ts", Some(10), Some(2)));
+        assert_eq!(found[1].severity, "warning");
+    }
+
+    #[test]
+    fn eslint_stylish_loops_over_file_problems() {
+        let found = parse(
+            "eslint-stylish",
+            "/work/src/app.js\n  \
+               1:10  error    'foo' is defined but never used  no-unused-vars\n  \
+               4:1   warning  Unexpected console statement     no-console\n\
+             \n\
+             ✖ 2 problems (1 error, 1 warning)\n",
+        );
+        assert_eq!(found.len(), 2);
+        assert_eq!(location(&found[0]), ("/work/src/app.js", Some(1), Some(10)));
+        assert_eq!(found[0].code.as_deref(), Some("no-unused-vars"));
+        assert_eq!(location(&found[1]), ("/work/src/app.js", Some(4), Some(1)));
+        assert_eq!(found[1].severity, "warning");
+        assert_eq!(found[1].message, "Unexpected console statement");
+    }
+
+    #[test]
+    fn eslint_unix_reads_rule_and_severity() {
+        let found = parse(
+            "eslint-unix",
+            "/work/src/app.js:1:10: 'foo' is defined but never used. [Error/no-unused-vars]\n",
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].code.as_deref(), Some("no-unused-vars"));
+        assert_eq!(found[0].severity, "error");
+    }
+
+    #[test]
+    fn vite_matches_with_and_without_plugin_line() {
+        let output = "12:00:00 [vite] Internal server error: Failed to resolve import \"./x\"\n  \
+                        Plugin: vite:import-analysis\n  \
+                        File: /work/src/main.ts:3:6\n\
+                      12:00:01 [vite] Internal server error: Unexpected token\n  \
+                        File: /work/src/other.ts:8:1\n";
+        let found = parse("vite", output);
+        assert_eq!(found.len(), 2);
+        assert_eq!(location(&found[0]), ("/work/src/main.ts", Some(3), Some(6)));
+        assert_eq!(found[0].message, "Failed to resolve import \"./x\"");
+        assert_eq!(
+            location(&found[1]),
+            ("/work/src/other.ts", Some(8), Some(1))
+        );
+        // 所有内置匹配器一起运行时不会重复报告
+        assert_eq!(parse_output(output, Some("/work"), None).len(), 2);
+    }
+
+    #[test]
+    fn vite_esbuild_skips_source_excerpt() {
+        let found = parse(
+            "vite-esbuild",
+            "✘ [ERROR] Expected \";\" but found \"x\"\n\
+             \n    \
+                 src/main.ts:3:6:\n      \
+                   3 │ let a x = 1\n",
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(location(&found[0]), ("/work/src/main.ts", Some(3), Some(6)));
+        assert_eq!(found[0].severity, "error");
+    }
+
+    #[test]
+    fn webpack_takes_message_from_next_line() {
+        let found = parse(
+            "webpack",
+            "ERROR in ./src/index.js 5:2-8\nModule not found: Error: Can't resolve './missing'\n",
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            location(&found[0]),
+            ("/work/src/index.js", Some(5), Some(2))
+        );
+        assert_eq!(
+            found[0].message,
+            "Module not found: Error: Can't resolve './missing'"
+        );
+    }
+
+    #[test]
+    fn jest_and_vitest_find_test_locations() {
+        let found = parse(
+            "jest",
+            "  ● math › adds\n\n    expect(received).toBe(expected)\n\n      \
+               at Object.<anonymous> (src/math.test.js:4:17)\n",
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            location(&found[0]),
+            ("/work/src/math.test.js", Some(4), Some(17))
+        );
+        assert_eq!(found[0].message, "math › adds");
+
+        let found = parse(
+            "vitest",
+            " FAIL  src/a.test.ts > suite > works\nAssertionError: expected 1 to be 2\n \
+              ❯ src/a.test.ts:5:17\n",
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            location(&found[0]),
+            ("/work/src/a.test.ts", Some(5), Some(17))
+        );
+        assert_eq!(found[0].message, "suite > works");
+    }
+
+    #[test]
+    fn rustc_reads_code_and_location() {
+        let found = parse(
+            "rustc",
+            "error[E0308]: mismatched types\n --> src/main.rs:4:18\n  |\n",
+        );
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            location(&found[0]),
+            ("/work/src/main.rs", Some(4), Some(18))
+        );
+        assert_eq!(found[0].code.as_deref(), Some("E0308"));
+    }
+
+    #[test]
+    fn run_diagnostics_are_capped() {
+        let diagnostic = parse("rustc", "warning: unused\n --> a.rs:1:1\n").remove(0);
+        let mut store = RunDiagnostics::default();
+        store.record("run", &vec![diagnostic.clone(); MAX_RUN_DIAGNOSTICS + 10]);
+        store.record("run", std::slice::from_ref(&diagnostic));
+        assert_eq!(store.runs["run"].len(), MAX_RUN_DIAGNOSTICS);
+
+        for index in 0..MAX_TRACKED_RUNS {
+            store.record(
+                &format!("later-{}", index),
+                std::slice::from_ref(&diagnostic),
+            );
+        }
+        assert!(!store.runs.contains_key("run"));
+        assert_eq!(store.runs.len(), MAX_TRACKED_RUNS);
+        store.remove("later-0");
+        assert_eq!(store.order.len(), MAX_TRACKED_RUNS - 1);
+    }
+}
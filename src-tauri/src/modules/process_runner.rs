@@ -0,0 +1,471 @@
+// 外部进程统一执行模块
+//
+// git、Node 版本管理器、编辑器 CLI 与项目命令都经由这里启动，避免调用方无限期阻塞：
+// - 按操作类型使用可配置的超时，0 表示不限时
+// - 以非交互方式运行：stdin 置空，禁止 git 及凭据管理器弹出交互式提示
+// - 前端传入 operation_id 时登记为进行中的操作，可通过 cancel_operation 按 id 取消
+// - 超时或取消时终止整个进程组
+// - 基准测试可丢弃输出运行，并取得用户态/内核态耗时与峰值内存
+use crate::modules::error::{lock_error, AppError, AppResult, ErrorCode};
+use crate::modules::kitty::core::get_current_timestamp;
+use crate::modules::storage;
+use crate::modules::task_runner::kill_process_tree;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+const STORE_NAME: &str = "timeouts";
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+// 子进程退出后，继承了输出管道的后台进程（如编辑器本体）可能一直不关闭管道，最多再等这么久
+const OUTPUT_GRACE: Duration = Duration::from_secs(2);
+
+// 调用方显式设置过的变量不会被覆盖
+const NON_INTERACTIVE_ENV: &[(&str, &str)] =
+    &[("GIT_TERMINAL_PROMPT", "0"), ("GCM_INTERACTIVE", "never")];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationKind {
+    Git,
+    NodeManager,
+    Editor,
+    Command,
+    Hook,
+    Terminal,
+}
+
+// 各类操作的超时秒数，0 表示不限时
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TimeoutSettings {
+    pub git_secs: u64,
+    // 安装 Node 版本需要下载，默认给得比较宽
+    pub node_manager_secs: u64,
+    pub editor_secs: u64,
+    // 项目命令可能是常驻的开发服务器，默认不限时，只能手动取消
+    pub command_secs: u64,
+    // 任务生命周期 Hook 中的 shell 片段
+    pub hook_secs: u64,
+    // tmux、WezTerm 等外部终端的命令行控制
+    pub terminal_secs: u64,
+}
+
+impl Default for TimeoutSettings {
+    fn default() -> Self {
+        Self {
+            git_secs: 120,
+            node_manager_secs: 600,
+            editor_secs: 30,
+            command_secs: 0,
+            hook_secs: 60,
+            terminal_secs: 15,
+        }
+    }
+}
+
+impl TimeoutSettings {
+    fn timeout_for(&self, kind: OperationKind) -> Option<Duration> {
+        let secs = match kind {
+            OperationKind::Git => self.git_secs,
+            OperationKind::NodeManager => self.node_manager_secs,
+            OperationKind::Editor => self.editor_secs,
+            OperationKind::Command => self.command_secs,
+            OperationKind::Hook => self.hook_secs,
+            OperationKind::Terminal => self.terminal_secs,
+        };
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+}
+
+#[derive(Debug)]
+pub enum RunError {
+    Spawn(io::Error),
+    TimedOut(Duration),
+    Cancelled,
+}
+
+impl RunError {
+    // 启动失败的归类因调用方而异，超时与取消统一处理
+    pub fn into_app_error(self, on_spawn: impl FnOnce(io::Error) -> AppError) -> AppError {
+        match self {
+            RunError::Spawn(err) => on_spawn(err),
+            RunError::TimedOut(timeout) => AppError::new(ErrorCode::Timeout, "runner.timed_out")
+                .arg("seconds", timeout.as_secs()),
+            RunError::Cancelled => AppError::new(ErrorCode::Cancelled, "runner.cancelled"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationInfo {
+    pub operation_id: String,
+    pub kind: OperationKind,
+    pub started_at: u64,
+}
+
+struct ActiveOperation {
+    info: OperationInfo,
+    token: CancellationToken,
+}
+
+lazy_static! {
+    static ref OPERATIONS: Arc<Mutex<HashMap<String, ActiveOperation>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref SETTINGS: Arc<Mutex<Option<TimeoutSettings>>> = Arc::new(Mutex::new(None));
+}
+
+fn with_settings<R>(f: impl FnOnce(&mut TimeoutSettings) -> R) -> AppResult<R> {
+    let mut guard = SETTINGS.lock().map_err(lock_error)?;
+    Ok(f(guard.get_or_insert_with(|| storage::load(STORE_NAME))))
+}
+
+pub fn timeout_for(kind: OperationKind) -> Option<Duration> {
+    with_settings(|settings| settings.timeout_for(kind))
+        .unwrap_or_else(|_| TimeoutSettings::default().timeout_for(kind))
+}
+
+// 一次可取消的操作，可能包含多个子进程（如先检测 nvm 再安装、切换版本）
+// 取消后尚未启动的步骤直接返回 Cancelled；drop 时从进行中列表移除
+pub struct Operation {
+    id: Option<String>,
+    token: CancellationToken,
+}
+
+impl Operation {
+    // id 为空时不登记，仍然受超时限制
+    pub fn begin(id: Option<String>, kind: OperationKind) -> Self {
+        let token = CancellationToken::default();
+        if let Some(id) = &id {
+            if let Ok(mut operations) = OPERATIONS.lock() {
+                operations.insert(
+                    id.clone(),
+                    ActiveOperation {
+                        info: OperationInfo {
+                            operation_id: id.clone(),
+                            kind,
+                            started_at: get_current_timestamp(),
+                        },
+                        token: token.clone(),
+                    },
+                );
+            }
+        }
+        Self { id, token }
+    }
+
+    pub fn untracked() -> Self {
+        Self {
+            id: None,
+            token: CancellationToken::default(),
+        }
+    }
+
+    // 运行命令并收集输出，退出码非 0 不视为错误，由调用方判断
+    pub fn run(&self, mut command: Command, kind: OperationKind) -> Result<Output, RunError> {
+        if self.token.is_cancelled() {
+            return Err(RunError::Cancelled);
+        }
+
+        command.stdout(Stdio::piped()).stderr(Stdio::piped());
+        prepare(&mut command);
+        let child = command.spawn().map_err(RunError::Spawn)?;
+        wait_with_output(child, kind, timeout_for(kind), &self.token)
+    }
+
+    // 丢弃输出运行并计量，退出码非 0 同样由调用方判断
+    pub fn measure(
+        &self,
+        mut command: Command,
+        kind: OperationKind,
+    ) -> Result<Measurement, RunError> {
+        if self.token.is_cancelled() {
+            return Err(RunError::Cancelled);
+        }
+
+        command.stdout(Stdio::null()).stderr(Stdio::null());
+        prepare(&mut command);
+        let started = Instant::now();
+        let child = command.spawn().map_err(RunError::Spawn)?;
+        wait_measured(child, timeout_for(kind), &self.token, started)
+    }
+}
+
+impl Drop for Operation {
+    fn drop(&mut self) {
+        if let Some(id) = &self.id {
+            if let Ok(mut operations) = OPERATIONS.lock() {
+                // 同一 id 可能已被新的操作复用，只移除自己登记的那一项
+                if operations
+                    .get(id)
+                    .is_some_and(|active| Arc::ptr_eq(&active.token.0, &self.token.0))
+                {
+                    operations.remove(id);
+                }
+            }
+        }
+    }
+}
+
+// 不需要取消的一次性调用
+pub fn run(command: Command, kind: OperationKind) -> Result<Output, RunError> {
+    Operation::untracked().run(command, kind)
+}
+
+fn prepare(command: &mut Command) {
+    command.stdin(Stdio::null());
+    // 让命令成为独立进程组的组长，超时或取消时整体终止
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    for (key, value) in NON_INTERACTIVE_ENV {
+        let overridden =
+            std::env::var_os(key).is_some() || command.get_envs().any(|(name, _)| name == *key);
+        if !overridden {
+            command.env(key, value);
+        }
+    }
+}
+
+struct Capture {
+    buffer: Arc<Mutex<Vec<u8>>>,
+    reader: JoinHandle<()>,
+}
+
+fn capture(mut reader: impl Read + Send + 'static) -> Capture {
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    let sink = buffer.clone();
+    let reader = thread::spawn(move || {
+        let mut chunk = [0u8; 8192];
+        loop {
+            let n = match reader.read(&mut chunk) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            if let Ok(mut buffer) = sink.lock() {
+                buffer.extend_from_slice(&chunk[..n]);
+            }
+        }
+    });
+    Capture { buffer, reader }
+}
+
+fn collect(capture: Option<Capture>, deadline: Instant) -> Vec<u8> {
+    let Some(capture) = capture else {
+        return Vec::new();
+    };
+    while !capture.reader.is_finished() && Instant::now() < deadline {
+        thread::sleep(POLL_INTERVAL);
+    }
+    capture
+        .buffer
+        .lock()
+        .map(|mut buffer| std::mem::take(&mut *buffer))
+        .unwrap_or_default()
+}
+
+fn wait_with_output(
+    mut child: Child,
+    kind: OperationKind,
+    timeout: Option<Duration>,
+    token: &CancellationToken,
+) -> Result<Output, RunError> {
+    let stdout = child.stdout.take().map(capture);
+    let stderr = child.stderr.take().map(capture);
+    let started = Instant::now();
+
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(RunError::Spawn)? {
+            break status;
+        }
+
+        if token.is_cancelled() {
+            terminate(child);
+            return Err(RunError::Cancelled);
+        }
+
+        if let Some(timeout) = timeout.filter(|timeout| started.elapsed() >= *timeout) {
+            // 首次启动的编辑器 CLI 可能一直前台运行到编辑器退出，超时后不再等待但保留进程
+            if kind == OperationKind::Editor {
+                thread::spawn(move || {
+                    let _ = child.wait();
+                });
+            } else {
+                terminate(child);
+            }
+            return Err(RunError::TimedOut(timeout));
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let deadline = Instant::now() + OUTPUT_GRACE;
+    Ok(Output {
+        status,
+        stdout: collect(stdout, deadline),
+        stderr: collect(stderr, deadline),
+    })
+}
+
+// 一次计量运行的结果；资源占用包含命令派生的所有已退出子进程，不支持的平台为 None
+#[derive(Debug, Clone)]
+pub struct Measurement {
+    pub status: ExitStatus,
+    pub wall: Duration,
+    pub user: Option<Duration>,
+    pub sys: Option<Duration>,
+    pub max_rss_kb: Option<u64>,
+}
+
+#[cfg(unix)]
+fn wait_measured(
+    child: Child,
+    timeout: Option<Duration>,
+    token: &CancellationToken,
+    started: Instant,
+) -> Result<Measurement, RunError> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let pid = child.id() as libc::pid_t;
+    loop {
+        let mut status = 0;
+        // SAFETY: rusage 是纯数据结构，全零是合法值，wait4 只写入传入的指针
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+        let waited = unsafe { libc::wait4(pid, &mut status, libc::WNOHANG, &mut usage) };
+        if waited == pid {
+            let to_duration = |tv: libc::timeval| {
+                Duration::from_secs(tv.tv_sec as u64) + Duration::from_micros(tv.tv_usec as u64)
+            };
+            // macOS 的 ru_maxrss 单位是字节，Linux 是 KB
+            let max_rss = usage.ru_maxrss as u64;
+            let max_rss_kb = if cfg!(target_os = "macos") {
+                max_rss / 1024
+            } else {
+                max_rss
+            };
+            return Ok(Measurement {
+                status: ExitStatus::from_raw(status),
+                wall: started.elapsed(),
+                user: Some(to_duration(usage.ru_utime)),
+                sys: Some(to_duration(usage.ru_stime)),
+                max_rss_kb: Some(max_rss_kb),
+            });
+        }
+        if waited < 0 {
+            return Err(RunError::Spawn(io::Error::last_os_error()));
+        }
+
+        if token.is_cancelled() {
+            terminate(child);
+            return Err(RunError::Cancelled);
+        }
+        if let Some(timeout) = timeout.filter(|timeout| started.elapsed() >= *timeout) {
+            terminate(child);
+            return Err(RunError::TimedOut(timeout));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(not(unix))]
+fn wait_measured(
+    mut child: Child,
+    timeout: Option<Duration>,
+    token: &CancellationToken,
+    started: Instant,
+) -> Result<Measurement, RunError> {
+    loop {
+        if let Some(status) = child.try_wait().map_err(RunError::Spawn)? {
+            return Ok(Measurement {
+                status,
+                wall: started.elapsed(),
+                user: None,
+                sys: None,
+                max_rss_kb: None,
+            });
+        }
+        if token.is_cancelled() {
+            terminate(child);
+            return Err(RunError::Cancelled);
+        }
+        if let Some(timeout) = timeout.filter(|timeout| started.elapsed() >= *timeout) {
+            terminate(child);
+            return Err(RunError::TimedOut(timeout));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+// 读取输出的线程在管道关闭后自行退出，这里不等待，避免被遗留的孙进程卡住
+fn terminate(mut child: Child) {
+    kill_process_tree(child.id());
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+// 取消进行中的操作，返回是否找到该操作
+pub fn cancel(operation_id: &str) -> AppResult<bool> {
+    let operations = OPERATIONS.lock().map_err(lock_error)?;
+    match operations.get(operation_id) {
+        Some(active) => {
+            active.token.cancel();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+// ---------- Tauri 命令 ----------
+
+#[tauri::command]
+pub fn cancel_operation(operation_id: String) -> AppResult<bool> {
+    cancel(&operation_id)
+}
+
+#[tauri::command]
+pub fn list_operations() -> AppResult<Vec<OperationInfo>> {
+    let operations = OPERATIONS.lock().map_err(lock_error)?;
+    let mut list: Vec<OperationInfo> = operations
+        .values()
+        .map(|active| active.info.clone())
+        .collect();
+    list.sort_by_key(|info| info.started_at);
+    Ok(list)
+}
+
+#[tauri::command]
+pub fn get_timeout_settings() -> AppResult<TimeoutSettings> {
+    with_settings(|settings| settings.clone())
+}
+
+#[tauri::command]
+pub fn set_timeout_settings(settings: TimeoutSettings) -> AppResult<TimeoutSettings> {
+    let snapshot = with_settings(|current| {
+        *current = settings;
+        current.clone()
+    })?;
+    storage::save(STORE_NAME, &snapshot)?;
+    Ok(snapshot)
+}
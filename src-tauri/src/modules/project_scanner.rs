@@ -1,3 +1,4 @@
+use crate::modules::error::{AppError, AppResult, ErrorCode};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -86,15 +87,23 @@ pub fn extract_scripts(package_json_content: &str) -> Vec<ProjectCommand> {
 }
 
 // 扫描workspace目录下的所有项目
-pub fn scan_workspace(workspace_path: &str) -> Result<Vec<Project>, String> {
+pub fn scan_workspace(workspace_path: &str) -> AppResult<Vec<Project>> {
     let workspace_dir = PathBuf::from(workspace_path);
 
     if !workspace_dir.exists() {
-        return Err(format!("Workspace路径不存在: {}", workspace_path));
+        return Err(
+            AppError::new(ErrorCode::WorkspaceNotFound, "scanner.workspace_not_found")
+                .arg("path", workspace_path)
+                .with_path(workspace_path),
+        );
     }
 
     if !workspace_dir.is_dir() {
-        return Err(format!("路径不是目录: {}", workspace_path));
+        return Err(
+            AppError::new(ErrorCode::NotADirectory, "scanner.not_a_directory")
+                .arg("path", workspace_path)
+                .with_path(workspace_path),
+        );
     }
 
     let mut projects = Vec::new();
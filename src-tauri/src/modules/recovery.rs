@@ -0,0 +1,539 @@
+// 启动恢复与退出清理
+//
+// 应用异常退出后，kitty 实例及其中的开发服务器、后台任务与内置终端启动的进程会继续运行，
+// 重启后的应用不再记得它们：
+// - 应用启动的子进程都带有 RUNPROJECT_OWNER_PID 环境变量，记录启动它的应用进程
+// - 按 socket 前缀查找 kitty 实例：Linux 读取 /proc/net/unix 中的抽象 socket，
+//   其他平台或设置了 socket 目录时查找目录下的 socket 文件，连接不上的文件直接删除
+// - 所属应用进程已经不存在的带标记进程视为遗留进程，位于存活 kitty 实例中的随实例处理
+// - 前端启动时调用 scan_orphans，由用户选择接管（登记到 KITTY_TAB_MANAGER）或终止
+// 应用退出时关闭所有 kitty 实例与内置终端会话，见 shutdown
+use crate::modules::error::{AppError, AppResult, ErrorCode};
+use crate::modules::kitty::capture;
+use crate::modules::kitty::config;
+use crate::modules::kitty::connection::{cleanup_stale_socket, stop_kitty_instance};
+use crate::modules::kitty::core::{
+    get_current_timestamp, KittyConfig, KittyTab, TabStatus, KITTY_TAB_MANAGER, PROCESS_MANAGER,
+};
+use crate::modules::kitty::process::shutdown_all_kitty_instances;
+use crate::modules::kitty::protocol::{OsWindow, Window};
+use crate::modules::kitty::session::launch_command;
+use crate::modules::kitty::tabs::{
+    list_kitty_tabs, register_kitty_tab, COMMAND_ID_ENV, COMMAND_NAME_ENV, PROJECT_NAME_ENV,
+};
+use crate::modules::ports;
+use crate::modules::terminal::pty_manager;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use tauri::{AppHandle, Emitter};
+
+// 子进程环境变量中的应用进程 pid
+pub const OWNER_ENV: &str = "RUNPROJECT_OWNER_PID";
+// 只保留这些前缀的环境变量用于识别
+const TAG_PREFIX: &str = "RUNPROJECT_";
+
+// 启动子进程时附加的归属标记
+pub fn owner_tag() -> (&'static str, String) {
+    (OWNER_ENV, std::process::id().to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanWindow {
+    pub window_id: u64,
+    // 应用创建的标签页才有命令 id，只有这些可以接管
+    pub command_id: Option<String>,
+    pub title: String,
+    pub cwd: String,
+    pub pid: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanInstance {
+    pub socket_path: String,
+    pub windows: Vec<OrphanWindow>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanProcess {
+    pub pid: u32,
+    // 启动它的应用进程
+    pub owner_pid: u32,
+    pub command_id: Option<String>,
+    pub command: String,
+    // 同时会被终止的子孙进程
+    pub descendants: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanReport {
+    pub instances: Vec<OrphanInstance>,
+    pub processes: Vec<OrphanProcess>,
+}
+
+struct ProcessEntry {
+    pid: u32,
+    ppid: u32,
+    // 仅 RUNPROJECT_ 开头的环境变量
+    env: HashMap<String, String>,
+    command: String,
+}
+
+fn tagged_env(entries: impl Iterator<Item = String>) -> HashMap<String, String> {
+    entries
+        .filter(|entry| entry.starts_with(TAG_PREFIX))
+        .filter_map(|entry| {
+            let (key, value) = entry.split_once('=')?;
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn list_processes() -> Vec<ProcessEntry> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    let split_nul = |bytes: Vec<u8>| -> Vec<String> {
+        bytes
+            .split(|byte| *byte == 0)
+            .filter(|part| !part.is_empty())
+            .map(|part| String::from_utf8_lossy(part).to_string())
+            .collect()
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid = entry.file_name().to_str()?.parse::<u32>().ok()?;
+            let dir = entry.path();
+            // 其他用户的进程没有权限读取 environ，直接跳过
+            let environ = fs::read(dir.join("environ")).ok()?;
+            // comm 中可能有空格与括号，ppid 是最后一个 ) 之后的第二项
+            let stat = fs::read_to_string(dir.join("stat")).ok()?;
+            let ppid = stat
+                .rsplit_once(')')?
+                .1
+                .split_whitespace()
+                .nth(1)?
+                .parse()
+                .ok()?;
+            let command = fs::read(dir.join("cmdline"))
+                .map(|cmdline| split_nul(cmdline).join(" "))
+                .unwrap_or_default();
+            Some(ProcessEntry {
+                pid,
+                ppid,
+                env: tagged_env(split_nul(environ).into_iter()),
+                command,
+            })
+        })
+        .collect()
+}
+
+// ps 附加的环境变量以空格分隔，值中也可能有空格：不是 NAME= 开头的片段属于上一个变量的值
+#[cfg(any(not(target_os = "linux"), test))]
+fn split_env_words(text: &str) -> Vec<String> {
+    let starts_entry = |word: &str| {
+        word.split_once('=').is_some_and(|(name, _)| {
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+    };
+    let mut entries: Vec<String> = Vec::new();
+    for word in text.split(' ') {
+        match entries.last_mut() {
+            Some(entry) if !starts_entry(word) => {
+                entry.push(' ');
+                entry.push_str(word);
+            }
+            _ if starts_entry(word) => entries.push(word.to_string()),
+            _ => {}
+        }
+    }
+    entries
+}
+
+#[cfg(not(target_os = "linux"))]
+fn list_processes() -> Vec<ProcessEntry> {
+    use std::process::Command;
+
+    let read = |args: &[&str]| {
+        Command::new("ps")
+            .args(args)
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+            .unwrap_or_default()
+    };
+    // BSD ps 的 e 选项会在命令后附加环境变量，展示用的命令行另外读取
+    let commands: HashMap<u32, String> = read(&["-axww", "-o", "pid=,command="])
+        .lines()
+        .filter_map(|line| {
+            let (pid, command) = line.trim().split_once(char::is_whitespace)?;
+            Some((pid.parse().ok()?, command.trim().to_string()))
+        })
+        .collect();
+    read(&["-axeww", "-o", "pid=,ppid=,command="])
+        .lines()
+        .filter_map(|line| {
+            let (pid, rest) = line.trim().split_once(char::is_whitespace)?;
+            let (ppid, rest) = rest.trim_start().split_once(char::is_whitespace)?;
+            let pid = pid.parse().ok()?;
+            let command = commands.get(&pid).cloned().unwrap_or_default();
+            // 环境变量在命令之后，先去掉命令本身
+            let rest = rest.trim_start();
+            let env = rest.strip_prefix(command.as_str()).unwrap_or(rest);
+            Some(ProcessEntry {
+                pid,
+                ppid: ppid.parse().ok()?,
+                env: tagged_env(split_env_words(env.trim()).into_iter()),
+                command,
+            })
+        })
+        .collect()
+}
+
+// socket 地址中的名称：抽象 socket 为 @ 之后的部分，socket 文件为文件名
+fn socket_name(socket_path: &str) -> Option<String> {
+    let address = socket_path.strip_prefix("unix:")?;
+    match address.strip_prefix('@') {
+        Some(name) => Some(name.to_string()),
+        None => Path::new(address)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string()),
+    }
+}
+
+// 前缀匹配的 kitty socket
+fn candidate_sockets(config: &KittyConfig) -> Vec<String> {
+    let prefix = format!("{}-", config.socket_prefix);
+
+    #[cfg(target_os = "linux")]
+    {
+        if config.socket_dir.is_none() {
+            // 最后一列为路径，抽象 socket 以 @ 开头，实例退出后自动消失
+            let mut sockets: Vec<String> = fs::read_to_string("/proc/net/unix")
+                .unwrap_or_default()
+                .lines()
+                .skip(1)
+                .filter_map(|line| line.split_whitespace().nth(7))
+                .filter(|path| {
+                    path.strip_prefix('@')
+                        .is_some_and(|name| name.starts_with(&prefix))
+                })
+                .map(|path| format!("unix:{}", path))
+                .collect();
+            sockets.sort();
+            sockets.dedup();
+            return sockets;
+        }
+    }
+
+    let dir = config.socket_dir.as_deref().unwrap_or("/tmp");
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut sockets: Vec<String> = entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.starts_with(&prefix) && name.ends_with(".sock")
+        })
+        .map(|entry| format!("unix:{}", entry.path().display()))
+        .collect();
+    sockets.sort();
+    sockets
+}
+
+fn windows_of(os_windows: Vec<OsWindow>) -> Vec<(String, Window)> {
+    os_windows
+        .into_iter()
+        .flat_map(|os_window| os_window.tabs)
+        .flat_map(|tab| {
+            let title = tab.title;
+            tab.windows
+                .into_iter()
+                .map(move |window| (title.clone(), window))
+        })
+        .collect()
+}
+
+fn managed_tabs() -> Vec<KittyTab> {
+    KITTY_TAB_MANAGER
+        .lock()
+        .map(|manager| manager.values().cloned().collect())
+        .unwrap_or_default()
+}
+
+fn children_map(processes: &[ProcessEntry]) -> HashMap<u32, Vec<u32>> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for process in processes {
+        children.entry(process.ppid).or_default().push(process.pid);
+    }
+    children
+}
+
+fn descendants(pid: u32, children: &HashMap<u32, Vec<u32>>) -> Vec<u32> {
+    let mut result = Vec::new();
+    let mut pending = vec![pid];
+    while let Some(current) = pending.pop() {
+        for child in children.get(&current).into_iter().flatten() {
+            result.push(*child);
+            pending.push(*child);
+        }
+    }
+    result
+}
+
+// 带标记且所属应用进程已经退出的进程，只返回每棵进程树的根，live_ids 中的命令由 kitty 实例处理
+fn orphan_processes(processes: &[ProcessEntry], live_ids: &HashSet<String>) -> Vec<OrphanProcess> {
+    let self_pid = std::process::id();
+    let owners: HashMap<u32, u32> = processes
+        .iter()
+        .filter_map(|process| {
+            let owner = process.env.get(OWNER_ENV)?.parse::<u32>().ok()?;
+            (owner != self_pid && !ports::is_pid_alive(owner)).then_some((process.pid, owner))
+        })
+        .collect();
+    let children = children_map(processes);
+
+    processes
+        .iter()
+        .filter(|process| process.pid != self_pid && !owners.contains_key(&process.ppid))
+        .filter(|process| {
+            process
+                .env
+                .get(COMMAND_ID_ENV)
+                .is_none_or(|id| !live_ids.contains(id))
+        })
+        .filter_map(|process| {
+            Some(OrphanProcess {
+                pid: process.pid,
+                owner_pid: *owners.get(&process.pid)?,
+                command_id: process.env.get(COMMAND_ID_ENV).cloned(),
+                command: process.command.clone(),
+                descendants: descendants(process.pid, &children),
+            })
+        })
+        .collect()
+}
+
+// 查找未登记的 kitty 实例与遗留进程
+pub fn scan() -> OrphanReport {
+    let tabs = managed_tabs();
+    let known_sockets: HashSet<String> = tabs.iter().map(|tab| tab.socket_path.clone()).collect();
+    let mut live_ids: HashSet<String> = tabs.into_iter().map(|tab| tab.id).collect();
+
+    let mut instances = Vec::new();
+    for socket_path in candidate_sockets(&config::load()) {
+        match list_kitty_tabs(&socket_path) {
+            Ok(os_windows) => {
+                let windows: Vec<OrphanWindow> = windows_of(os_windows)
+                    .into_iter()
+                    .map(|(title, window)| OrphanWindow {
+                        window_id: window.id,
+                        command_id: window.env.get(COMMAND_ID_ENV).cloned(),
+                        title: if window.title.is_empty() { title } else { window.title },
+                        cwd: window.cwd,
+                        pid: window.pid,
+                    })
+                    .collect();
+                live_ids.extend(windows.iter().filter_map(|window| window.command_id.clone()));
+                if !known_sockets.contains(&socket_path) {
+                    instances.push(OrphanInstance {
+                        socket_path,
+                        windows,
+                    });
+                }
+            }
+            // 实例已经退出，留下的 socket 文件没有用处
+            Err(err) if err.code == ErrorCode::KittyConnectionFailed => {
+                cleanup_stale_socket(&socket_path);
+            }
+            Err(err) => eprintln!("[恢复] 读取 {} 失败: {}", socket_path, err),
+        }
+    }
+
+    OrphanReport {
+        instances,
+        processes: orphan_processes(&list_processes(), &live_ids),
+    }
+}
+
+// 把实例中由应用创建的标签页登记到 KITTY_TAB_MANAGER，之后由状态同步线程接管
+fn adopt_instance(app: &AppHandle, socket_path: &str) -> AppResult<Vec<KittyTab>> {
+    let now = get_current_timestamp();
+    let mut adopted = Vec::new();
+    for (title, window) in windows_of(list_kitty_tabs(socket_path)?) {
+        let Some(command_id) = window.env.get(COMMAND_ID_ENV).cloned() else {
+            continue;
+        };
+        let known = KITTY_TAB_MANAGER
+            .lock()
+            .map(|manager| manager.contains_key(&command_id))
+            .unwrap_or(true);
+        if known {
+            continue;
+        }
+
+        // 命令仍在运行时 cmdline 为 TAB_SCRIPT，可以取回命令与目录
+        let (command, cwd) = launch_command(&window.cmdline).unwrap_or_default();
+        let working_dir = cwd.unwrap_or(window.cwd.clone());
+        let project_name = window
+            .env
+            .get(PROJECT_NAME_ENV)
+            .cloned()
+            .or_else(|| {
+                Path::new(&working_dir)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+            })
+            .unwrap_or_default();
+        let command_name = window
+            .env
+            .get(COMMAND_NAME_ENV)
+            .cloned()
+            .unwrap_or_else(|| command_id.clone());
+
+        register_kitty_tab(KittyTab {
+            id: command_id.clone(),
+            title,
+            project_name,
+            command_name,
+            working_dir,
+            command,
+            socket_path: socket_path.to_string(),
+            status: TabStatus::Running,
+            created_at: now,
+            pid: window.pid,
+            window_id: Some(window.id.to_string()),
+            foreground_pids: Vec::new(),
+            exit_code: None,
+            finished_at: None,
+            run_id: None,
+            // 接管的标签页无法得知启动时选择的环境配置
+            env_profile: None,
+        })?;
+        capture::track(app, &command_id);
+
+        // 与新建的标签页一样登记虚拟的进程记录，terminate_command 依赖它
+        if let Ok(mut manager) = PROCESS_MANAGER.lock() {
+            if let Ok(dummy_child) = std::process::Command::new("echo")
+                .arg("kitty managed")
+                .spawn()
+            {
+                manager.insert(command_id.clone(), dummy_child);
+            }
+        }
+
+        if let Some(tab) = KITTY_TAB_MANAGER
+            .lock()
+            .ok()
+            .and_then(|manager| manager.get(&command_id).cloned())
+        {
+            adopted.push(tab);
+        }
+    }
+    Ok(adopted)
+}
+
+// 应用退出时调用
+pub fn shutdown() {
+    let _ = shutdown_all_kitty_instances();
+    pty_manager::close_all_sessions();
+}
+
+// ---------- Tauri 命令 ----------
+
+#[tauri::command(async)]
+pub fn scan_orphans() -> AppResult<OrphanReport> {
+    Ok(scan())
+}
+
+// 接管实例中的标签页，返回登记后的记录，并以 kitty-tab-status 事件推送
+#[tauri::command(async)]
+pub fn adopt_orphan_instances(
+    app: AppHandle,
+    socket_paths: Vec<String>,
+) -> AppResult<Vec<KittyTab>> {
+    let mut adopted = Vec::new();
+    for socket_path in &socket_paths {
+        adopted.extend(adopt_instance(&app, socket_path)?);
+    }
+    for tab in &adopted {
+        let _ = app.emit("kitty-tab-status", tab);
+    }
+    Ok(adopted)
+}
+
+// 关闭 kitty 实例并终止遗留进程（连同子孙进程），返回终止的进程数
+// 只接受重新扫描后仍然是遗留进程的 pid，避免误杀无关进程
+#[tauri::command(async)]
+pub fn kill_orphans(socket_paths: Vec<String>, pids: Vec<u32>) -> AppResult<usize> {
+    let prefix = format!("{}-", config::load().socket_prefix);
+    for socket_path in &socket_paths {
+        if !socket_name(socket_path).is_some_and(|name| name.starts_with(&prefix)) {
+            return Err(
+                AppError::new(ErrorCode::InvalidArgument, "recovery.not_orphan")
+                    .arg("target", socket_path),
+            );
+        }
+        stop_kitty_instance(socket_path)?;
+    }
+
+    let report = scan();
+    let mut killed = 0;
+    for pid in pids {
+        let orphan = report
+            .processes
+            .iter()
+            .find(|process| process.pid == pid)
+            .ok_or_else(|| {
+                AppError::new(ErrorCode::NotFound, "recovery.not_orphan").arg("target", pid)
+            })?;
+        // 子孙进程先于根进程终止
+        for target in orphan.descendants.iter().rev().chain([&orphan.pid]) {
+            if ports::kill_pid(*target, "TERM") {
+                killed += 1;
+            }
+        }
+    }
+    Ok(killed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_values_keep_their_spaces() {
+        assert_eq!(
+            split_env_words("RUNPROJECT_COMMAND_ID=my app-dev-1 HOME=/Users/me PATH=/bin"),
+            [
+                "RUNPROJECT_COMMAND_ID=my app-dev-1",
+                "HOME=/Users/me",
+                "PATH=/bin"
+            ]
+        );
+        assert_eq!(split_env_words("stray A=1 =x 1B=2"), ["A=1 =x 1B=2"]);
+        assert!(split_env_words("").is_empty());
+    }
+
+    #[test]
+    fn socket_names_are_taken_from_the_address() {
+        assert_eq!(
+            socket_name("unix:@runproject-dev_1").as_deref(),
+            Some("runproject-dev_1")
+        );
+        assert_eq!(
+            socket_name("unix:/tmp/x-runproject-/other-1.sock").as_deref(),
+            Some("other-1.sock")
+        );
+        assert_eq!(socket_name("tcp:localhost:1"), None);
+    }
+}
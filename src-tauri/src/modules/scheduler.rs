@@ -0,0 +1,681 @@
+// 定时任务模块
+//
+// 应用运行期间按 cron 表达式定时执行项目命令（如每晚 pnpm audit、每周 git fetch）：
+// - 支持标准 5 段、带秒的 6/7 段表达式以及 @daily / @hourly 等别名，按本地时间计算
+// - 一个定时任务可以作用于多个项目，每个项目独立执行
+// - 与手动运行使用相同的命令解析、环境变量与参数模板，通过后台执行器运行
+// - 上一次运行尚未结束时跳过本次，每次运行（含跳过）都会记录结果
+// - 定时任务与运行记录持久化在应用数据目录
+use crate::modules::command_template;
+use crate::modules::env_profile;
+use crate::modules::i18n;
+use crate::modules::install_preflight;
+use crate::modules::kitty::core::get_current_timestamp;
+use crate::modules::kitty::executor;
+use crate::modules::nvm_manager;
+use crate::modules::project_scanner;
+use crate::modules::storage;
+use crate::modules::task_runner::{self, RunRecord, RunRequest, RunStatus};
+use chrono::Local;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const SCHEDULE_STORE: &str = "schedules";
+const RUN_LOG_STORE: &str = "schedule_runs";
+const MAX_RUN_LOG: usize = 50;
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schedule {
+    // 为空时保存时自动生成
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub cron: String,
+    pub project_paths: Vec<String>,
+    // 项目脚本名，通过包管理器执行；与 command 二选一
+    #[serde(default)]
+    pub command_name: Option<String>,
+    // 原始 shell 命令，如 git fetch --all
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub package_manager: Option<String>,
+    #[serde(default)]
+    pub node_version: Option<String>,
+    #[serde(default)]
+    pub env_profile: Option<String>,
+    #[serde(default)]
+    pub arg_values: Option<BTreeMap<String, serde_json::Value>>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub created_at: u64,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ScheduleOutcome {
+    Succeeded,
+    Failed,
+    Cancelled,
+    // 上一次运行尚未结束
+    Skipped,
+    // 命令解析或启动失败
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleRun {
+    pub schedule_id: String,
+    pub project_path: String,
+    #[serde(default)]
+    pub run_id: Option<String>,
+    pub outcome: ScheduleOutcome,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    pub started_at: u64,
+    #[serde(default)]
+    pub finished_at: Option<u64>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleInfo {
+    #[serde(flatten)]
+    pub schedule: Schedule,
+    pub next_run_at: Option<u64>,
+    pub running: Vec<String>,
+    pub last_run: Option<ScheduleRun>,
+}
+
+// key: 定时任务 id
+type RunLog = HashMap<String, Vec<ScheduleRun>>;
+
+lazy_static! {
+    static ref SCHEDULES: Arc<Mutex<Option<Vec<Schedule>>>> = Arc::new(Mutex::new(None));
+    static ref RUN_LOG: Arc<Mutex<Option<RunLog>>> = Arc::new(Mutex::new(None));
+    // 定时任务 id -> 下一次运行时间
+    static ref NEXT_RUNS: Arc<Mutex<HashMap<String, u64>>> = Arc::new(Mutex::new(HashMap::new()));
+    // 定时任务 id::项目路径 -> 运行中的 run_id
+    static ref ACTIVE_RUNS: Arc<Mutex<HashMap<String, String>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+}
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+fn with_schedules<R>(f: impl FnOnce(&mut Vec<Schedule>) -> R) -> Result<R, String> {
+    let mut guard = SCHEDULES
+        .lock()
+        .map_err(|e| i18n::tf("common.lock_failed", &[("error", &e)]))?;
+    Ok(f(guard.get_or_insert_with(|| storage::load(SCHEDULE_STORE))))
+}
+
+fn with_run_log<R>(f: impl FnOnce(&mut RunLog) -> R) -> Result<R, String> {
+    let mut guard = RUN_LOG
+        .lock()
+        .map_err(|e| i18n::tf("common.lock_failed", &[("error", &e)]))?;
+    Ok(f(guard.get_or_insert_with(|| storage::load(RUN_LOG_STORE))))
+}
+
+fn active_key(schedule_id: &str, project_path: &str) -> String {
+    format!("{}::{}", schedule_id, project_path)
+}
+
+const WEEKDAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+fn weekday_name(value: &str) -> Option<&'static str> {
+    weekday_number(value).map(|n| WEEKDAY_NAMES[n % 7])
+}
+
+fn weekday_number(value: &str) -> Option<usize> {
+    value.parse::<usize>().ok().filter(|n| *n <= 7)
+}
+
+// 带步长的数字写法按标准 cron 语义展开为星期列表：`a/n` 从 a 到 7，`*/n` 从 0 到 7
+fn expand_weekday_step(range: &str, step: &str) -> Option<Vec<&'static str>> {
+    let step = step.parse::<usize>().ok().filter(|n| *n > 0)?;
+    let (start, end) = match range.split_once('-') {
+        Some((start, end)) => (weekday_number(start)?, weekday_number(end)?),
+        None if range == "*" => (0, 7),
+        None => (weekday_number(range)?, 7),
+    };
+    if start > end {
+        return None;
+    }
+    Some(
+        (start..=end)
+            .step_by(step)
+            .map(|n| WEEKDAY_NAMES[n % 7])
+            .collect(),
+    )
+}
+
+// 标准 cron 的星期字段 0/7 表示周日，cron 库从 1（周日）开始计数，这里统一转换为英文缩写
+fn normalize_weekdays(field: &str) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for part in field.split(',') {
+        let converted = match part.split_once('/') {
+            // 步长只能作用于整个区间，展开后不再依赖 cron 库的计数方式
+            Some((range, step)) => expand_weekday_step(range, step)
+                .map(|days| days.into_iter().map(str::to_string).collect())
+                .unwrap_or_else(|| vec![part.to_string()]),
+            None => vec![match part.split_once('-') {
+                // 以 7（周日）结尾的区间拆成 a-SAT 与 SUN 两段
+                Some((start, "7")) => weekday_name(start)
+                    .map(|start| format!("{}-SAT,SUN", start))
+                    .unwrap_or_else(|| part.to_string()),
+                Some((start, end)) => match (weekday_name(start), weekday_name(end)) {
+                    (Some(start), Some(end)) => format!("{}-{}", start, end),
+                    _ => part.to_string(),
+                },
+                None => weekday_name(part)
+                    .map(|name| name.to_string())
+                    .unwrap_or_else(|| part.to_string()),
+            }],
+        };
+        for item in converted {
+            if !parts.contains(&item) {
+                parts.push(item);
+            }
+        }
+    }
+    parts.join(",")
+}
+
+// 5 段表达式补齐秒字段；5/6/7 段表达式的星期字段都按标准 cron 转换，别名原样解析
+pub fn parse_cron(expression: &str) -> Result<cron::Schedule, String> {
+    let expression = expression.trim();
+    let mut fields: Vec<String> = expression.split_whitespace().map(str::to_string).collect();
+    if fields.len() == 5 {
+        fields.insert(0, "0".to_string());
+    }
+    let normalized = if !expression.starts_with('@') && (6..=7).contains(&fields.len()) {
+        fields[5] = normalize_weekdays(&fields[5]);
+        fields.join(" ")
+    } else {
+        expression.to_string()
+    };
+    cron::Schedule::from_str(&normalized).map_err(|e| {
+        i18n::tf(
+            "schedule.invalid_cron",
+            &[("expression", &expression), ("error", &e)],
+        )
+    })
+}
+
+fn next_after_now(expression: &str) -> Option<u64> {
+    let schedule = parse_cron(expression).ok()?;
+    schedule
+        .after(&Local::now())
+        .next()
+        .map(|time| time.timestamp().max(0) as u64)
+}
+
+fn record_run(app: &AppHandle, run: ScheduleRun) {
+    let _ = app.emit("schedule-run", &run);
+    let snapshot = with_run_log(|log| {
+        let list = log.entry(run.schedule_id.clone()).or_default();
+        list.insert(0, run);
+        list.truncate(MAX_RUN_LOG);
+        log.clone()
+    });
+    if let Ok(snapshot) = snapshot {
+        if let Err(e) = storage::save(RUN_LOG_STORE, &snapshot) {
+            eprintln!("[SCHEDULE] 保存运行记录失败: {}", e);
+        }
+    }
+}
+
+// 解析出与手动运行一致的最终命令
+fn resolve_command(schedule: &Schedule, project_path: &str) -> Result<String, String> {
+    if let Some(command_name) = &schedule.command_name {
+        let package_manager = schedule
+            .package_manager
+            .clone()
+            .unwrap_or_else(|| project_scanner::detect_package_manager(Path::new(project_path)));
+        let extra_args = command_template::render_for_execution(
+            project_path,
+            command_name,
+            &package_manager,
+            schedule.arg_values.as_ref(),
+        )?;
+        // 无人值守运行，询问策略按仅提示处理
+        let preflight =
+            install_preflight::preflight(project_path, Some(&package_manager), None, true)?;
+        return executor::build_execution_command(
+            command_name,
+            schedule.node_version.as_deref(),
+            &package_manager,
+            Some(&extra_args),
+            preflight.install_command.as_deref(),
+        )
+        .map_err(String::from);
+    }
+
+    let command = schedule
+        .command
+        .as_ref()
+        .filter(|c| !c.trim().is_empty())
+        .ok_or_else(|| i18n::t("schedule.command_missing"))?;
+    match &schedule.node_version {
+        Some(version) => {
+            nvm_manager::wrap_command_with_node(version, command).map_err(String::from)
+        }
+        None => Ok(command.clone()),
+    }
+}
+
+fn run_for_project(app: &AppHandle, schedule: &Schedule, project_path: &str) {
+    let key = active_key(&schedule.id, project_path);
+    let previous = ACTIVE_RUNS
+        .lock()
+        .ok()
+        .and_then(|active| active.get(&key).cloned());
+    if let Some(run_id) = previous.filter(|id| task_runner::is_running(id)) {
+        eprintln!("[SCHEDULE] {} 上一次运行尚未结束，跳过", key);
+        record_run(
+            app,
+            ScheduleRun {
+                schedule_id: schedule.id.clone(),
+                project_path: project_path.to_string(),
+                run_id: None,
+                outcome: ScheduleOutcome::Skipped,
+                exit_code: None,
+                started_at: get_current_timestamp(),
+                finished_at: None,
+                message: Some(i18n::tf("schedule.previous_running", &[("run", &run_id)])),
+            },
+        );
+        return;
+    }
+
+    let prepared = resolve_command(schedule, project_path).and_then(|command| {
+        let env = env_profile::resolve_env_vars(
+            project_path,
+            schedule.command_name.as_deref(),
+            schedule.env_profile.as_deref(),
+        )?;
+        Ok((command, env))
+    });
+
+    let started_at = get_current_timestamp();
+    let result = prepared.and_then(|(command, env)| {
+        let callback_app = app.clone();
+        let schedule_id = schedule.id.clone();
+        let callback_key = key.clone();
+        task_runner::spawn_run(
+            app,
+            RunRequest {
+                command_id: format!("schedule:{}:{}", schedule.id, project_path),
+                project_path: project_path.to_string(),
+                command,
+                command_name: schedule.command_name.clone(),
+                env,
+                trigger: "schedule".to_string(),
+                hook_depth: 0,
+                priority: 0,
+                long_running: schedule
+                    .command_name
+                    .as_deref()
+                    .is_some_and(task_runner::is_long_running_script),
+            },
+            Some(Box::new(move |record: &RunRecord| {
+                if let Ok(mut active) = ACTIVE_RUNS.lock() {
+                    if active.get(&callback_key) == Some(&record.run_id) {
+                        active.remove(&callback_key);
+                    }
+                }
+                let outcome = match record.status {
+                    RunStatus::Succeeded => ScheduleOutcome::Succeeded,
+                    RunStatus::Cancelled => ScheduleOutcome::Cancelled,
+                    _ => ScheduleOutcome::Failed,
+                };
+                record_run(
+                    &callback_app,
+                    ScheduleRun {
+                        schedule_id,
+                        project_path: record.project_path.clone(),
+                        run_id: Some(record.run_id.clone()),
+                        outcome,
+                        exit_code: record.exit_code,
+                        started_at: record.started_at.unwrap_or(record.queued_at),
+                        finished_at: record.finished_at,
+                        message: None,
+                    },
+                );
+            })),
+        )
+        .map_err(String::from)
+    });
+
+    match result {
+        Ok(run_id) => {
+            if task_runner::is_running(&run_id) {
+                if let Ok(mut active) = ACTIVE_RUNS.lock() {
+                    active.insert(key, run_id);
+                }
+            }
+        }
+        Err(e) => {
+            eprintln!("[SCHEDULE] {} 启动失败: {}", key, e);
+            record_run(
+                app,
+                ScheduleRun {
+                    schedule_id: schedule.id.clone(),
+                    project_path: project_path.to_string(),
+                    run_id: None,
+                    outcome: ScheduleOutcome::Error,
+                    exit_code: None,
+                    started_at,
+                    finished_at: Some(get_current_timestamp()),
+                    message: Some(e),
+                },
+            );
+        }
+    }
+}
+
+fn fire(app: &AppHandle, schedule: &Schedule) {
+    eprintln!(
+        "[SCHEDULE] 触发定时任务: {} ({})",
+        schedule.name, schedule.id
+    );
+    for project_path in &schedule.project_paths {
+        run_for_project(app, schedule, project_path);
+    }
+}
+
+fn tick(app: &AppHandle) {
+    let Ok(schedules) = with_schedules(|schedules| schedules.clone()) else {
+        return;
+    };
+    let now = get_current_timestamp();
+    let mut due = Vec::new();
+
+    if let Ok(mut next_runs) = NEXT_RUNS.lock() {
+        next_runs.retain(|id, _| schedules.iter().any(|s| &s.id == id && s.enabled));
+        for schedule in schedules.iter().filter(|s| s.enabled) {
+            let Some(next) = next_runs
+                .get(&schedule.id)
+                .copied()
+                .or_else(|| next_after_now(&schedule.cron))
+            else {
+                continue;
+            };
+            if now >= next {
+                due.push(schedule.clone());
+                match next_after_now(&schedule.cron) {
+                    Some(following) => next_runs.insert(schedule.id.clone(), following),
+                    None => next_runs.remove(&schedule.id),
+                };
+            } else {
+                next_runs.insert(schedule.id.clone(), next);
+            }
+        }
+    }
+
+    for schedule in due {
+        fire(app, &schedule);
+    }
+}
+
+// 在 setup 中调用，启动调度线程（仅启动一次）
+pub fn start(app: AppHandle) {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(move || loop {
+        tick(&app);
+        std::thread::sleep(TICK_INTERVAL);
+    });
+}
+
+fn schedule_info(schedule: &Schedule) -> ScheduleInfo {
+    let next_run_at = if schedule.enabled {
+        NEXT_RUNS
+            .lock()
+            .ok()
+            .and_then(|next_runs| next_runs.get(&schedule.id).copied())
+            .or_else(|| next_after_now(&schedule.cron))
+    } else {
+        None
+    };
+    let running = ACTIVE_RUNS
+        .lock()
+        .map(|active| {
+            active
+                .iter()
+                .filter(|(key, run_id)| {
+                    key.starts_with(&format!("{}::", schedule.id))
+                        && task_runner::is_running(run_id)
+                })
+                .map(|(_, run_id)| run_id.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    let last_run = with_run_log(|log| log.get(&schedule.id).and_then(|runs| runs.first().cloned()))
+        .ok()
+        .flatten();
+
+    ScheduleInfo {
+        schedule: schedule.clone(),
+        next_run_at,
+        running,
+        last_run,
+    }
+}
+
+// ---------- Tauri 命令 ----------
+
+#[tauri::command]
+pub fn list_schedules() -> Result<Vec<ScheduleInfo>, String> {
+    let schedules = with_schedules(|schedules| schedules.clone())?;
+    Ok(schedules.iter().map(schedule_info).collect())
+}
+
+// 新建或更新定时任务
+#[tauri::command]
+pub fn save_schedule(mut schedule: Schedule) -> Result<ScheduleInfo, String> {
+    if schedule.name.trim().is_empty() {
+        return Err(i18n::t("schedule.name_required"));
+    }
+    if schedule.project_paths.is_empty() {
+        return Err(i18n::t("schedule.projects_required"));
+    }
+    let has_command = schedule
+        .command_name
+        .as_ref()
+        .or(schedule.command.as_ref())
+        .is_some_and(|c| !c.trim().is_empty());
+    if !has_command {
+        return Err(i18n::t("schedule.command_missing"));
+    }
+    parse_cron(&schedule.cron)?;
+
+    if schedule.id.is_empty() {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        schedule.id = format!("schedule-{:x}", nanos);
+    }
+    if schedule.created_at == 0 {
+        schedule.created_at = get_current_timestamp();
+    }
+
+    let snapshot = with_schedules(|schedules| {
+        match schedules.iter_mut().find(|s| s.id == schedule.id) {
+            Some(existing) => *existing = schedule.clone(),
+            None => schedules.push(schedule.clone()),
+        }
+        schedules.clone()
+    })?;
+    storage::save(SCHEDULE_STORE, &snapshot)?;
+
+    // 表达式可能已修改，重新计算下一次运行时间
+    if let Ok(mut next_runs) = NEXT_RUNS.lock() {
+        next_runs.remove(&schedule.id);
+    }
+    Ok(schedule_info(&schedule))
+}
+
+#[tauri::command]
+pub fn delete_schedule(schedule_id: String) -> Result<(), String> {
+    let snapshot = with_schedules(|schedules| {
+        schedules.retain(|s| s.id != schedule_id);
+        schedules.clone()
+    })?;
+    storage::save(SCHEDULE_STORE, &snapshot)?;
+
+    if let Ok(mut next_runs) = NEXT_RUNS.lock() {
+        next_runs.remove(&schedule_id);
+    }
+    let log_snapshot = with_run_log(|log| {
+        log.remove(&schedule_id);
+        log.clone()
+    })?;
+    storage::save(RUN_LOG_STORE, &log_snapshot)
+}
+
+#[tauri::command]
+pub fn set_schedule_enabled(schedule_id: String, enabled: bool) -> Result<ScheduleInfo, String> {
+    let (updated, snapshot) = with_schedules(|schedules| {
+        let updated = schedules.iter_mut().find(|s| s.id == schedule_id).map(|s| {
+            s.enabled = enabled;
+            s.clone()
+        });
+        (updated, schedules.clone())
+    })?;
+    let updated = updated.ok_or_else(|| i18n::tf("schedule.not_found", &[("id", &schedule_id)]))?;
+    storage::save(SCHEDULE_STORE, &snapshot)?;
+
+    if let Ok(mut next_runs) = NEXT_RUNS.lock() {
+        next_runs.remove(&schedule_id);
+    }
+    Ok(schedule_info(&updated))
+}
+
+// 立即执行一次，同样遵循跳过重叠运行的规则
+#[tauri::command]
+pub fn run_schedule_now(app: AppHandle, schedule_id: String) -> Result<(), String> {
+    let schedule =
+        with_schedules(|schedules| schedules.iter().find(|s| s.id == schedule_id).cloned())?
+            .ok_or_else(|| i18n::tf("schedule.not_found", &[("id", &schedule_id)]))?;
+    fire(&app, &schedule);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_schedule_runs(schedule_id: String) -> Result<Vec<ScheduleRun>, String> {
+    with_run_log(|log| log.get(&schedule_id).cloned().unwrap_or_default())
+}
+
+// 预览表达式接下来的若干次运行时间
+#[tauri::command]
+pub fn preview_cron(expression: String, count: Option<usize>) -> Result<Vec<u64>, String> {
+    let schedule = parse_cron(&expression)?;
+    Ok(schedule
+        .after(&Local::now())
+        .take(count.unwrap_or(5))
+        .map(|time| time.timestamp().max(0) as u64)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, Timelike, Weekday};
+
+    fn weekdays(expression: &str) -> Vec<Weekday> {
+        let mut days: Vec<Weekday> = parse_cron(expression)
+            .unwrap()
+            .upcoming(Local)
+            .take(14)
+            .map(|time| time.weekday())
+            .collect();
+        days.sort_by_key(|day| day.num_days_from_sunday());
+        days.dedup();
+        days
+    }
+
+    #[test]
+    fn zero_and_seven_both_mean_sunday() {
+        assert_eq!(normalize_weekdays("0"), "SUN");
+        assert_eq!(normalize_weekdays("7"), "SUN");
+        assert_eq!(weekdays("0 9 * * 0"), vec![Weekday::Sun]);
+        assert_eq!(weekdays("0 9 * * 7"), vec![Weekday::Sun]);
+    }
+
+    #[test]
+    fn ranges_lists_and_steps_are_converted() {
+        assert_eq!(normalize_weekdays("1-5"), "MON-FRI");
+        assert_eq!(normalize_weekdays("5-7"), "FRI-SAT,SUN");
+        assert_eq!(normalize_weekdays("0,3/2"), "SUN,WED,FRI");
+        assert_eq!(normalize_weekdays("*"), "*");
+        assert_eq!(normalize_weekdays("MON-FRI"), "MON-FRI");
+        assert_eq!(
+            weekdays("30 8 * * 1-5"),
+            vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri
+            ]
+        );
+        assert_eq!(
+            weekdays("30 8 * * 5-7"),
+            vec![Weekday::Sun, Weekday::Fri, Weekday::Sat]
+        );
+    }
+
+    #[test]
+    fn five_field_expressions_get_a_zero_second_field() {
+        let next = parse_cron("15 10 * * *")
+            .unwrap()
+            .upcoming(Local)
+            .next()
+            .unwrap();
+        assert_eq!((next.hour(), next.minute(), next.second()), (10, 15, 0));
+        assert!(parse_cron("@daily").is_ok());
+        assert!(parse_cron("not a cron").is_err());
+        assert!(parse_cron("0 0 * * 8").is_err());
+    }
+
+    #[test]
+    fn stepped_weekday_ranges_are_expanded() {
+        assert_eq!(normalize_weekdays("1-7/2"), "MON,WED,FRI,SUN");
+        assert_eq!(normalize_weekdays("*/3"), "SUN,WED,SAT");
+        assert_eq!(normalize_weekdays("MON-FRI/2"), "MON-FRI/2");
+        assert_eq!(
+            weekdays("0 9 * * 1-7/2"),
+            vec![Weekday::Sun, Weekday::Mon, Weekday::Wed, Weekday::Fri]
+        );
+        assert!(parse_cron("0 9 * * 5-2/2").is_err());
+        assert!(parse_cron("0 9 * * 1-5/0").is_err());
+    }
+
+    #[test]
+    fn six_and_seven_field_expressions_use_standard_weekdays() {
+        assert_eq!(weekdays("0 0 9 * * 1"), vec![Weekday::Mon]);
+        assert_eq!(weekdays("0 9 * * 1"), vec![Weekday::Mon]);
+        assert_eq!(weekdays("0 0 9 * * 0 *"), vec![Weekday::Sun]);
+        assert_eq!(weekdays("30 0 9 * * 7"), vec![Weekday::Sun]);
+    }
+}
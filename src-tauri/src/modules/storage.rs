@@ -0,0 +1,55 @@
+// 持久化存储模块 - 以 JSON 文件的形式保存在应用数据目录下
+//
+// 应用启动时在 setup 中调用 init 设置目录；未初始化时读取返回默认值、写入返回错误
+use crate::modules::i18n;
+use lazy_static::lazy_static;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+lazy_static! {
+    static ref DATA_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+pub fn init(dir: PathBuf) {
+    if let Err(e) = fs::create_dir_all(&dir) {
+        eprintln!("[STORAGE] 创建数据目录失败: {:?} {}", dir, e);
+    }
+    if let Ok(mut guard) = DATA_DIR.lock() {
+        *guard = Some(dir);
+    }
+}
+
+pub fn data_dir() -> Option<PathBuf> {
+    DATA_DIR.lock().ok().and_then(|guard| guard.clone())
+}
+
+fn file_path(name: &str) -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(format!("{}.json", name)))
+}
+
+// 读取 JSON 文件，不存在或解析失败时返回默认值
+pub fn load<T: DeserializeOwned + Default>(name: &str) -> T {
+    let Some(path) = file_path(name) else {
+        return T::default();
+    };
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            eprintln!("[STORAGE] 解析 {:?} 失败: {}", path, e);
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
+
+// 先写临时文件再重命名，避免写入中断导致文件损坏
+pub fn save<T: Serialize>(name: &str, value: &T) -> Result<(), String> {
+    let path = file_path(name).ok_or_else(|| i18n::t("common.storage_not_initialized"))?;
+    let content = serde_json::to_string_pretty(value)
+        .map_err(|e| i18n::tf("common.serialize_failed", &[("error", &e)]))?;
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content).map_err(|e| i18n::tf("common.write_failed", &[("error", &e)]))?;
+    fs::rename(&tmp_path, &path).map_err(|e| i18n::tf("common.write_failed", &[("error", &e)]))
+}
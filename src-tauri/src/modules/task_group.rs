@@ -0,0 +1,121 @@
+// 任务组
+//
+// 需要一起启动的一组命令（如前端、后端与数据库），按标签页或分屏排列：
+// - 可以从 kitty 会话文件导入，也可以导出为会话文件在应用外启动，见 kitty::session
+// - 任务组持久化在应用数据目录
+use crate::modules::error::{lock_error, AppError, AppResult, ErrorCode};
+use crate::modules::kitty::core::get_current_timestamp;
+use crate::modules::kitty::grouping::Layout;
+use crate::modules::storage;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+const STORE_NAME: &str = "task_groups";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupTask {
+    // 标签页或窗口标题
+    pub title: String,
+    pub cwd: String,
+    // 完整的 shell 命令
+    pub command: String,
+    // 额外设置的环境变量，如环境配置中的变量
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    // 分屏布局下所在标签页的标题；为空的任务合并到以任务组命名的标签页中
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tab: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskGroup {
+    // 为空时保存时自动生成
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub workspace_path: Option<String>,
+    #[serde(default)]
+    pub layout: Layout,
+    pub tasks: Vec<GroupTask>,
+    #[serde(default)]
+    pub created_at: u64,
+}
+
+lazy_static! {
+    static ref GROUPS: Arc<Mutex<Option<Vec<TaskGroup>>>> = Arc::new(Mutex::new(None));
+}
+
+fn with_groups<R>(f: impl FnOnce(&mut Vec<TaskGroup>) -> R) -> AppResult<R> {
+    let mut guard = GROUPS.lock().map_err(lock_error)?;
+    Ok(f(guard.get_or_insert_with(|| storage::load(STORE_NAME))))
+}
+
+pub fn get_group(group_id: &str) -> AppResult<TaskGroup> {
+    with_groups(|groups| groups.iter().find(|g| g.id == group_id).cloned())?.ok_or_else(|| {
+        AppError::new(ErrorCode::NotFound, "task_group.not_found").arg("id", group_id)
+    })
+}
+
+// 新建或更新任务组
+pub fn save_group(mut group: TaskGroup) -> AppResult<TaskGroup> {
+    if group.name.trim().is_empty() {
+        return Err(AppError::new(
+            ErrorCode::InvalidArgument,
+            "task_group.name_required",
+        ));
+    }
+    if group.tasks.is_empty() {
+        return Err(AppError::new(
+            ErrorCode::InvalidArgument,
+            "task_group.tasks_required",
+        ));
+    }
+
+    if group.id.is_empty() {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        group.id = format!("group-{:x}", nanos);
+    }
+    if group.created_at == 0 {
+        group.created_at = get_current_timestamp();
+    }
+
+    let snapshot = with_groups(|groups| {
+        match groups.iter_mut().find(|g| g.id == group.id) {
+            Some(existing) => *existing = group.clone(),
+            None => groups.push(group.clone()),
+        }
+        groups.clone()
+    })?;
+    storage::save(STORE_NAME, &snapshot)?;
+    Ok(group)
+}
+
+// ---------- Tauri 命令 ----------
+
+#[tauri::command]
+pub fn list_task_groups() -> AppResult<Vec<TaskGroup>> {
+    with_groups(|groups| groups.clone())
+}
+
+#[tauri::command]
+pub fn save_task_group(group: TaskGroup) -> AppResult<TaskGroup> {
+    save_group(group)
+}
+
+#[tauri::command]
+pub fn delete_task_group(group_id: String) -> AppResult<()> {
+    let snapshot = with_groups(|groups| {
+        groups.retain(|g| g.id != group_id);
+        groups.clone()
+    })?;
+    storage::save(STORE_NAME, &snapshot)?;
+    Ok(())
+}
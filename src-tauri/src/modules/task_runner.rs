@@ -0,0 +1,1228 @@
+// 后台任务执行模块
+//
+// 以非交互方式在后台运行项目命令，供监听重跑、定时任务等功能复用：
+// - 通过平台 shell 启动命令，输出以 task-output 事件流式推送
+// - 运行过程中同步解析诊断信息
+// - 支持按 run_id 取消（终止整个进程组）
+// - 记录每次运行的结果，状态变化通过 task-run-status 事件推送
+// - 构建、测试等一次性任务受全局与工作区并发上限约束，超出时排队等待：
+//   优先级高的先出队，同优先级先进先出，队列变化通过 task-queue-changed 事件推送
+// - 开发服务器等常驻任务不占用并发名额，也不排队
+// - 在 kitty、内置终端等交互式终端中运行的命令同样经过准入判断：有名额时立即打开，
+//   否则进入同一个队列，出队后再打开并推送 task-run-launched 事件；
+//   这类运行通过退出码文件、标签页状态等方式结束后释放名额
+// - 启动、结束以及就绪探测通过时触发对应的生命周期 Hook
+use crate::modules::command_template;
+use crate::modules::env_profile;
+use crate::modules::error::AppResult;
+use crate::modules::hooks::{self, HookEvent};
+use crate::modules::i18n;
+use crate::modules::install_preflight;
+use crate::modules::kitty::core::get_current_timestamp;
+use crate::modules::kitty::executor;
+use crate::modules::nvm_manager;
+use crate::modules::platform;
+use crate::modules::ports;
+use crate::modules::problem_matcher::{self, DiagnosticCollector};
+use crate::modules::project_scanner;
+use crate::modules::recovery;
+use crate::modules::storage;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+const MAX_HISTORY: usize = 200;
+const MAX_OUTPUT_TAIL: usize = 64 * 1024;
+const MAX_SEARCH_MATCHES: usize = 500;
+const QUEUE_STORE: &str = "task_queue";
+// 交互式终端中的命令结束后把退出码写入该环境变量指向的文件
+pub const EXIT_FILE_ENV: &str = "RUNPROJECT_EXIT_FILE";
+const EXIT_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+// 脚本名等于这些名称或以 "名称:" / "名称-" 开头时视为常驻任务
+const DEFAULT_LONG_RUNNING_SCRIPTS: &[&str] =
+    &["dev", "start", "serve", "watch", "preview", "storybook"];
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum RunStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunRecord {
+    pub run_id: String,
+    pub command_id: String,
+    pub project_path: String,
+    pub command: String,
+    // 项目脚本名，原始 shell 命令为 None
+    pub command_name: Option<String>,
+    // manual / watch / schedule / hook 等
+    pub trigger: String,
+    pub status: RunStatus,
+    pub exit_code: Option<i32>,
+    pub priority: i32,
+    pub long_running: bool,
+    pub queued_at: u64,
+    // 排队中的任务为 None
+    pub started_at: Option<u64>,
+    // 就绪探测通过的时间
+    pub ready_at: Option<u64>,
+    pub finished_at: Option<u64>,
+    pub diagnostics: usize,
+    pub output_tail: String,
+    // 由 Hook 触发的嵌套层数，用于防止 Hook 互相触发形成循环
+    #[serde(skip)]
+    pub hook_depth: u32,
+}
+
+// 输出搜索命中的一行
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputMatch {
+    pub run_id: String,
+    pub command_id: String,
+    pub command_name: Option<String>,
+    pub project_path: String,
+    // 在保存的输出末尾中的行号，从 1 开始
+    pub line_number: usize,
+    pub line: String,
+}
+
+pub struct RunRequest {
+    pub command_id: String,
+    pub project_path: String,
+    // 已经解析完成的 shell 命令
+    pub command: String,
+    pub command_name: Option<String>,
+    pub env: Vec<(String, String)>,
+    pub trigger: String,
+    pub hook_depth: u32,
+    // 数值越大越先出队
+    pub priority: i32,
+    // 常驻任务不受并发上限约束，可用 is_long_running_script 判断
+    pub long_running: bool,
+}
+
+pub type FinishCallback = Box<dyn FnOnce(&RunRecord) + Send + 'static>;
+
+// 打开交互式终端并启动命令，参数为 run_id，返回交给前端的启动结果
+pub type LaunchCallback = Box<dyn FnOnce(&str) -> AppResult<serde_json::Value> + Send + 'static>;
+
+// 交互式运行的准入结果
+pub enum Admission {
+    // 有名额，已经打开，附带启动结果
+    Launched(serde_json::Value),
+    // 没有名额，已进入队列，返回 run_id
+    Queued(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct QueueSettings {
+    // 同时运行的一次性任务上限，0 表示不限制
+    pub max_concurrent: usize,
+    // 工作区路径 -> 该工作区内同时运行的上限，项目按路径前缀最长的一项匹配
+    pub workspace_limits: HashMap<String, usize>,
+    // 视为常驻任务的脚本名
+    pub long_running_scripts: Vec<String>,
+}
+
+impl Default for QueueSettings {
+    fn default() -> Self {
+        let cpus = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(4);
+        Self {
+            max_concurrent: (cpus / 2).max(2),
+            workspace_limits: HashMap::new(),
+            long_running_scripts: DEFAULT_LONG_RUNNING_SCRIPTS
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl QueueSettings {
+    fn workspace_limit(&self, project_path: &str) -> Option<(&str, usize)> {
+        let project = Path::new(project_path);
+        self.workspace_limits
+            .iter()
+            .filter(|(workspace, limit)| **limit > 0 && project.starts_with(workspace))
+            .max_by_key(|(workspace, _)| workspace.len())
+            .map(|(workspace, limit)| (workspace.as_str(), *limit))
+    }
+}
+
+struct ActiveRun {
+    // 交互式终端中的运行没有可直接终止的进程
+    pid: Option<u32>,
+    cancelled: bool,
+    project_path: String,
+    long_running: bool,
+}
+
+enum Launch {
+    // 后台进程，结束（含取消、启动失败）后调用 on_finish
+    Background(Option<FinishCallback>),
+    // 交互式终端，出队后调用 launch 打开，结束时由调用方调用 finish_external_run
+    Interactive(LaunchCallback),
+}
+
+struct QueuedRun {
+    run_id: String,
+    request: RunRequest,
+    launch: Launch,
+    app: AppHandle,
+}
+
+lazy_static! {
+    static ref ACTIVE_RUNS: Arc<Mutex<HashMap<String, ActiveRun>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    static ref RUN_HISTORY: Arc<Mutex<VecDeque<RunRecord>>> = Arc::new(Mutex::new(VecDeque::new()));
+    // 按出队顺序排列；需要同时持有时先锁 QUEUE 再锁 ACTIVE_RUNS
+    static ref QUEUE: Arc<Mutex<Vec<QueuedRun>>> = Arc::new(Mutex::new(Vec::new()));
+    static ref QUEUE_SETTINGS: Arc<Mutex<Option<QueueSettings>>> = Arc::new(Mutex::new(None));
+    // 外部终端中运行的命令的诊断收集器，按 run_id 保存
+    static ref EXTERNAL_COLLECTORS: Arc<Mutex<HashMap<String, Arc<Mutex<DiagnosticCollector>>>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+}
+
+static RUN_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn next_run_id(command_id: &str) -> String {
+    let seq = RUN_COUNTER.fetch_add(1, Ordering::SeqCst);
+    format!("{}#{}-{}", command_id, get_current_timestamp(), seq)
+}
+
+fn update_record(run_id: &str, f: impl FnOnce(&mut RunRecord)) -> Option<RunRecord> {
+    let mut history = RUN_HISTORY.lock().ok()?;
+    let record = history.iter_mut().find(|r| r.run_id == run_id)?;
+    f(record);
+    Some(record.clone())
+}
+
+fn push_record(record: RunRecord) {
+    if let Ok(mut history) = RUN_HISTORY.lock() {
+        history.push_back(record);
+        while history.len() > MAX_HISTORY {
+            history.pop_front();
+        }
+    }
+}
+
+fn append_tail(tail: &mut String, chunk: &str) {
+    tail.push_str(chunk);
+    if tail.len() > MAX_OUTPUT_TAIL {
+        let mut cut = tail.len() - MAX_OUTPUT_TAIL;
+        while !tail.is_char_boundary(cut) {
+            cut += 1;
+        }
+        tail.drain(..cut);
+    }
+}
+
+fn with_queue_settings<R>(f: impl FnOnce(&mut QueueSettings) -> R) -> Result<R, String> {
+    let mut guard = QUEUE_SETTINGS
+        .lock()
+        .map_err(|e| i18n::tf("common.lock_failed", &[("error", &e)]))?;
+    Ok(f(guard.get_or_insert_with(|| storage::load(QUEUE_STORE))))
+}
+
+fn queue_settings() -> QueueSettings {
+    with_queue_settings(|settings| settings.clone()).unwrap_or_default()
+}
+
+// 判断脚本是否为开发服务器等常驻任务
+pub fn is_long_running_script(script: &str) -> bool {
+    let script = script.trim();
+    queue_settings().long_running_scripts.iter().any(|name| {
+        script == name
+            || script
+                .strip_prefix(name.as_str())
+                .is_some_and(|rest| rest.starts_with(':') || rest.starts_with('-'))
+    })
+}
+
+// 终止整个进程组，避免 shell 退出后留下子进程
+pub fn kill_process_tree(pid: u32) {
+    #[cfg(target_os = "windows")]
+    {
+        let _ = std::process::Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/T", "/F"])
+            .output();
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = std::process::Command::new("kill")
+            .args(["-TERM", "--", &format!("-{}", pid)])
+            .output();
+    }
+}
+
+fn spawn_child(request: &RunRequest) -> Result<Child, String> {
+    let (owner_key, owner_pid) = recovery::owner_tag();
+    let mut cmd = platform::build_shell_command(&request.command);
+    cmd.current_dir(&request.project_path)
+        .envs(request.env.iter().cloned())
+        .env(owner_key, owner_pid)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    // 让命令成为独立进程组的组长，便于整体取消
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+
+    cmd.spawn()
+        .map_err(|e| i18n::tf("task.spawn_failed", &[("error", &e)]))
+}
+
+// 记录一段输出：匹配诊断、追加到记录并推送给前端
+fn record_output(
+    app: &AppHandle,
+    run_id: &str,
+    stream: &str,
+    text: &str,
+    collector: &mut DiagnosticCollector,
+) {
+    let found = collector.feed(text.as_bytes());
+    problem_matcher::publish_diagnostics(app, run_id, &found);
+
+    update_record(run_id, |record| append_tail(&mut record.output_tail, text));
+    let _ = app.emit(
+        "task-output",
+        serde_json::json!({ "runId": run_id, "stream": stream, "data": text }),
+    );
+}
+
+// 取出缓冲区中完整的 UTF-8 文本，末尾被读取截断的多字节字符留到下一次
+fn take_utf8(buffer: &mut Vec<u8>) -> String {
+    let incomplete = (buffer.len().saturating_sub(3)..buffer.len()).find(|&start| {
+        matches!(
+            std::str::from_utf8(&buffer[start..]),
+            Err(e) if e.valid_up_to() == 0 && e.error_len().is_none()
+        )
+    });
+    let end = incomplete.unwrap_or(buffer.len());
+    let text = String::from_utf8_lossy(&buffer[..end]).to_string();
+    buffer.drain(..end);
+    text
+}
+
+// 每个输出流单独匹配诊断，避免 stdout 与 stderr 交错拼成一行；结束后返回该流的诊断收集器
+fn pump_output(
+    app: AppHandle,
+    run_id: String,
+    stream: &'static str,
+    mut reader: impl Read + Send + 'static,
+    project_path: String,
+) -> std::thread::JoinHandle<DiagnosticCollector> {
+    std::thread::spawn(move || {
+        let mut collector = DiagnosticCollector::new(Some(project_path), None);
+        let mut chunk = [0u8; 8192];
+        let mut pending = Vec::new();
+        loop {
+            let n = match reader.read(&mut chunk) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            pending.extend_from_slice(&chunk[..n]);
+            let text = take_utf8(&mut pending);
+            if !text.is_empty() {
+                record_output(&app, &run_id, stream, &text, &mut collector);
+            }
+        }
+        if !pending.is_empty() {
+            let text = String::from_utf8_lossy(&pending).to_string();
+            record_output(&app, &run_id, stream, &text, &mut collector);
+        }
+        collector
+    })
+}
+
+// 结束一条运行记录（含启动失败、排队中被取消），推送状态并调用 on_finish
+fn finish_record(
+    app: &AppHandle,
+    run_id: &str,
+    on_finish: Option<FinishCallback>,
+    f: impl FnOnce(&mut RunRecord),
+) {
+    let finished = update_record(run_id, |record| {
+        f(record);
+        record.finished_at = Some(get_current_timestamp());
+    });
+
+    if let Some(record) = finished {
+        let _ = app.emit("task-run-status", &record);
+        match record.status {
+            RunStatus::Succeeded => hooks::dispatch(app, &record, HookEvent::Succeeded),
+            RunStatus::Failed => hooks::dispatch(app, &record, HookEvent::Failed),
+            _ => {}
+        }
+        if let Some(callback) = on_finish {
+            callback(&record);
+        }
+    }
+}
+
+// 登记已启动的排队任务；调用方持有 QUEUE 锁，保证名额判断与登记之间不会插入其他任务
+fn start_run(
+    app: AppHandle,
+    run_id: String,
+    request: RunRequest,
+    on_finish: Option<FinishCallback>,
+    mut child: Child,
+) {
+    let pid = child.id();
+
+    if let Ok(mut active) = ACTIVE_RUNS.lock() {
+        active.insert(
+            run_id.clone(),
+            ActiveRun {
+                pid: Some(pid),
+                cancelled: false,
+                project_path: request.project_path.clone(),
+                long_running: request.long_running,
+            },
+        );
+    }
+    let started = update_record(&run_id, |record| {
+        record.status = RunStatus::Running;
+        record.started_at = Some(get_current_timestamp());
+    });
+    if let Some(record) = started {
+        let _ = app.emit("task-run-status", &record);
+        hooks::dispatch(&app, &record, HookEvent::Started);
+    }
+
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        readers.push(pump_output(
+            app.clone(),
+            run_id.clone(),
+            "stdout",
+            stdout,
+            request.project_path.clone(),
+        ));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        readers.push(pump_output(
+            app.clone(),
+            run_id.clone(),
+            "stderr",
+            stderr,
+            request.project_path.clone(),
+        ));
+    }
+
+    std::thread::spawn(move || {
+        let status = child.wait();
+        let mut diagnostics = 0;
+        for reader in readers {
+            let Ok(mut collector) = reader.join() else {
+                continue;
+            };
+            let remaining = collector.finish();
+            problem_matcher::publish_diagnostics(&app, &run_id, &remaining);
+            diagnostics += collector.diagnostics().len();
+        }
+
+        let cancelled = ACTIVE_RUNS
+            .lock()
+            .ok()
+            .and_then(|mut active| active.remove(&run_id))
+            .map(|run| run.cancelled)
+            .unwrap_or(false);
+        let exit_code = status.as_ref().ok().and_then(|s| s.code());
+        let run_status = if cancelled {
+            RunStatus::Cancelled
+        } else if status.map(|s| s.success()).unwrap_or(false) {
+            RunStatus::Succeeded
+        } else {
+            RunStatus::Failed
+        };
+
+        finish_record(&app, &run_id, on_finish, |record| {
+            record.status = run_status;
+            record.exit_code = exit_code;
+            record.diagnostics = diagnostics;
+        });
+
+        // 释放了名额，继续启动排队中的任务
+        start_queued(&app);
+    });
+}
+
+fn has_capacity(
+    settings: &QueueSettings,
+    active: &HashMap<String, ActiveRun>,
+    request: &RunRequest,
+) -> bool {
+    if request.long_running {
+        return true;
+    }
+
+    let counted: Vec<&ActiveRun> = active.values().filter(|run| !run.long_running).collect();
+    if settings.max_concurrent > 0 && counted.len() >= settings.max_concurrent {
+        return false;
+    }
+
+    match settings.workspace_limit(&request.project_path) {
+        Some((workspace, limit)) => {
+            counted
+                .iter()
+                .filter(|run| Path::new(&run.project_path).starts_with(workspace))
+                .count()
+                < limit
+        }
+        None => true,
+    }
+}
+
+fn queued_records(queue: &[QueuedRun]) -> Vec<RunRecord> {
+    queue
+        .iter()
+        .filter_map(|item| get_run(&item.run_id))
+        .collect()
+}
+
+fn emit_queue_changed(app: &AppHandle, queue: &[QueuedRun]) {
+    let _ = app.emit("task-queue-changed", queued_records(queue));
+}
+
+// 按队列顺序启动所有有名额的任务；某个工作区已满时跳过其任务，不阻塞其他工作区
+// 后台任务直接启动；交互式运行先登记占用名额，返回给调用方在释放队列锁后打开
+fn admit_queued(app: &AppHandle) -> Vec<(String, AppHandle, LaunchCallback)> {
+    let settings = queue_settings();
+    let mut failed = Vec::new();
+    let mut admitted = Vec::new();
+    {
+        let Ok(mut queue) = QUEUE.lock() else {
+            return admitted;
+        };
+        let mut changed = false;
+        let mut index = 0;
+        while index < queue.len() {
+            let fits = ACTIVE_RUNS
+                .lock()
+                .map(|active| has_capacity(&settings, &active, &queue[index].request))
+                .unwrap_or(false);
+            if !fits {
+                index += 1;
+                continue;
+            }
+
+            let QueuedRun {
+                run_id,
+                request,
+                launch,
+                app,
+            } = queue.remove(index);
+            changed = true;
+            match launch {
+                Launch::Background(on_finish) => match spawn_child(&request) {
+                    Ok(child) => start_run(app, run_id, request, on_finish, child),
+                    Err(e) => failed.push((app, run_id, on_finish, e)),
+                },
+                Launch::Interactive(launch) => {
+                    register_external(&app, &run_id, &request);
+                    admitted.push((run_id, app, launch));
+                }
+            }
+        }
+        if changed {
+            emit_queue_changed(app, &queue);
+        }
+    }
+
+    // 回调可能再次调用本模块，在释放队列锁之后执行
+    for (app, run_id, on_finish, error) in failed {
+        finish_record(&app, &run_id, on_finish, |record| {
+            record.status = RunStatus::Failed;
+            append_tail(&mut record.output_tail, &error);
+        });
+    }
+    admitted
+}
+
+// 打开已登记的交互式运行，失败时结束运行记录
+fn launch_interactive(
+    app: &AppHandle,
+    run_id: &str,
+    launch: LaunchCallback,
+) -> AppResult<serde_json::Value> {
+    let result = launch(run_id);
+    if let Err(error) = &result {
+        update_record(run_id, |record| {
+            append_tail(&mut record.output_tail, &error.to_string())
+        });
+        finish_external_run(app, run_id, RunStatus::Failed, None);
+    }
+    result
+}
+
+// 在后台线程中打开出队的交互式运行，结果通过 task-run-launched 事件推送给前端
+fn launch_in_background(app: AppHandle, run_id: String, launch: LaunchCallback) {
+    std::thread::spawn(move || {
+        let result = launch_interactive(&app, &run_id, launch);
+        let command_id = get_run(&run_id).map(|record| record.command_id);
+        let _ = app.emit(
+            "task-run-launched",
+            serde_json::json!({
+                "runId": run_id,
+                "commandId": command_id,
+                "result": result.as_ref().ok(),
+                "error": result.as_ref().err(),
+            }),
+        );
+    });
+}
+
+// 启动所有有名额的排队任务
+fn start_queued(app: &AppHandle) {
+    for (run_id, app, launch) in admit_queued(app) {
+        launch_in_background(app, run_id, launch);
+    }
+}
+
+// 生成排队中的运行记录并按优先级插入队列，返回 run_id
+fn enqueue(app: &AppHandle, request: RunRequest, launch: Launch) -> Result<String, String> {
+    let run_id = next_run_id(&request.command_id);
+
+    let record = RunRecord {
+        run_id: run_id.clone(),
+        command_id: request.command_id.clone(),
+        project_path: request.project_path.clone(),
+        command: request.command.clone(),
+        command_name: request.command_name.clone(),
+        trigger: request.trigger.clone(),
+        status: RunStatus::Queued,
+        exit_code: None,
+        priority: request.priority,
+        long_running: request.long_running,
+        queued_at: get_current_timestamp(),
+        started_at: None,
+        ready_at: None,
+        finished_at: None,
+        diagnostics: 0,
+        output_tail: String::new(),
+        hook_depth: request.hook_depth,
+    };
+    push_record(record.clone());
+    let _ = app.emit("task-run-status", &record);
+
+    {
+        let mut queue = QUEUE
+            .lock()
+            .map_err(|e| i18n::tf("common.lock_failed", &[("error", &e)]))?;
+        let position = queue
+            .iter()
+            .position(|item| item.request.priority < request.priority)
+            .unwrap_or(queue.len());
+        queue.insert(
+            position,
+            QueuedRun {
+                run_id: run_id.clone(),
+                request,
+                launch,
+                app: app.clone(),
+            },
+        );
+        emit_queue_changed(app, &queue);
+    }
+    Ok(run_id)
+}
+
+// 提交一次后台运行，返回 run_id；没有名额时进入队列
+// on_finish 在运行结束（含取消、启动失败）后调用
+pub fn spawn_run(
+    app: &AppHandle,
+    request: RunRequest,
+    on_finish: Option<FinishCallback>,
+) -> AppResult<String> {
+    ports::ensure_port_free(&request.project_path, request.command_name.as_deref())?;
+    let run_id = enqueue(app, request, Launch::Background(on_finish))?;
+    start_queued(app);
+    Ok(run_id)
+}
+
+// 提交一次交互式终端中的运行：有名额时在当前线程打开并返回启动结果，否则进入队列
+// request.env 不会被使用，环境变量由 launch 自行传给终端
+pub fn admit_interactive(
+    app: &AppHandle,
+    request: RunRequest,
+    launch: LaunchCallback,
+) -> AppResult<Admission> {
+    ports::ensure_port_free(&request.project_path, request.command_name.as_deref())?;
+    let run_id = enqueue(app, request, Launch::Interactive(launch))?;
+    let mut own = None;
+    for (admitted_id, admitted_app, launch) in admit_queued(app) {
+        if admitted_id == run_id {
+            own = Some(launch);
+            continue;
+        }
+        // 与本次一同出队的其他交互式运行仍在后台打开
+        launch_in_background(admitted_app, admitted_id, launch);
+    }
+    match own {
+        Some(launch) => launch_interactive(app, &run_id, launch).map(Admission::Launched),
+        None => Ok(Admission::Queued(run_id)),
+    }
+}
+
+// 交互式运行的命令结果，排队时附带 run_id 与 queued 标记
+pub fn admission_result(admission: Admission, command_id: &str) -> serde_json::Value {
+    match admission {
+        Admission::Launched(result) => result,
+        Admission::Queued(run_id) => serde_json::json!({
+            "success": true,
+            "queued": true,
+            "run_id": run_id,
+            "command_id": command_id,
+            "message": i18n::t("task.queued"),
+        }),
+    }
+}
+
+// 取消运行中或排队中的任务，返回是否找到该任务
+pub fn cancel_run(run_id: &str) -> bool {
+    let dequeued = QUEUE.lock().ok().and_then(|mut queue| {
+        let index = queue.iter().position(|item| item.run_id == run_id)?;
+        let item = queue.remove(index);
+        emit_queue_changed(&item.app, &queue);
+        Some(item)
+    });
+    if let Some(item) = dequeued {
+        let on_finish = match item.launch {
+            Launch::Background(on_finish) => on_finish,
+            Launch::Interactive(_) => None,
+        };
+        finish_record(&item.app, &item.run_id, on_finish, |record| {
+            record.status = RunStatus::Cancelled;
+        });
+        return true;
+    }
+
+    let pid = {
+        let Ok(mut active) = ACTIVE_RUNS.lock() else {
+            return false;
+        };
+        // 交互式终端中的运行由对应的标签页或会话关闭
+        match active.get_mut(run_id) {
+            Some(ActiveRun {
+                pid: Some(pid),
+                cancelled,
+                ..
+            }) => {
+                *cancelled = true;
+                *pid
+            }
+            _ => return false,
+        }
+    };
+    kill_process_tree(pid);
+    true
+}
+
+// 运行中或排队中
+pub fn is_running(run_id: &str) -> bool {
+    let queued = QUEUE
+        .lock()
+        .map(|queue| queue.iter().any(|item| item.run_id == run_id))
+        .unwrap_or(false);
+    queued
+        || ACTIVE_RUNS
+            .lock()
+            .map(|active| active.contains_key(run_id))
+            .unwrap_or(false)
+}
+
+// 标记运行中的任务已就绪，只记录第一次，返回更新后的记录
+pub fn mark_ready(app: &AppHandle, run_id: &str) -> Option<RunRecord> {
+    let mut first = false;
+    let record = update_record(run_id, |record| {
+        if record.status == RunStatus::Running && record.ready_at.is_none() {
+            record.ready_at = Some(get_current_timestamp());
+            first = true;
+        }
+    })?;
+    if !first {
+        return None;
+    }
+    let _ = app.emit("task-run-status", &record);
+    Some(record)
+}
+
+pub fn get_run(run_id: &str) -> Option<RunRecord> {
+    RUN_HISTORY
+        .lock()
+        .ok()?
+        .iter()
+        .find(|r| r.run_id == run_id)
+        .cloned()
+}
+
+// 调整排队中的任务，f 返回 false 表示未作修改
+fn modify_queue(
+    app: &AppHandle,
+    f: impl FnOnce(&mut Vec<QueuedRun>) -> bool,
+) -> Result<bool, String> {
+    let changed = {
+        let mut queue = QUEUE
+            .lock()
+            .map_err(|e| i18n::tf("common.lock_failed", &[("error", &e)]))?;
+        let changed = f(&mut queue);
+        if changed {
+            emit_queue_changed(app, &queue);
+        }
+        changed
+    };
+    if changed {
+        start_queued(app);
+    }
+    Ok(changed)
+}
+
+// 前端手动提交的后台运行
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManualRunRequest {
+    pub project_path: String,
+    pub command_name: String,
+    #[serde(default)]
+    pub package_manager: Option<String>,
+    #[serde(default)]
+    pub node_version: Option<String>,
+    #[serde(default)]
+    pub env_profile: Option<String>,
+    #[serde(default)]
+    pub arg_values: Option<BTreeMap<String, serde_json::Value>>,
+    #[serde(default)]
+    pub priority: i32,
+}
+
+// 解析为与手动运行一致的后台运行请求，trigger 标明来源
+pub fn resolve_script_run(request: &ManualRunRequest, trigger: &str) -> AppResult<RunRequest> {
+    resolve_script(request, trigger, true).map(|(run, _)| run)
+}
+
+// 同上，但依赖安装命令（已切换到指定的 Node 版本）单独返回，不串联在脚本前，
+// 供需要只执行一次安装的调用方（如基准测试）使用
+pub fn resolve_script_run_with_install(
+    request: &ManualRunRequest,
+    trigger: &str,
+) -> AppResult<(RunRequest, Option<String>)> {
+    resolve_script(request, trigger, false)
+}
+
+fn resolve_script(
+    request: &ManualRunRequest,
+    trigger: &str,
+    chain_install: bool,
+) -> AppResult<(RunRequest, Option<String>)> {
+    let package_manager = request.package_manager.clone().unwrap_or_else(|| {
+        project_scanner::detect_package_manager(Path::new(&request.project_path))
+    });
+    let extra_args = command_template::render_for_execution(
+        &request.project_path,
+        &request.command_name,
+        &package_manager,
+        request.arg_values.as_ref(),
+    )?;
+    // 后台运行无法询问，询问策略按仅提示处理
+    let preflight =
+        install_preflight::preflight(&request.project_path, Some(&package_manager), None, true)?;
+    let (chained, install_command) = if chain_install {
+        (preflight.install_command, None)
+    } else {
+        (None, preflight.install_command)
+    };
+    let command = executor::build_execution_command(
+        &request.command_name,
+        request.node_version.as_deref(),
+        &package_manager,
+        Some(&extra_args),
+        chained.as_deref(),
+    )?;
+    let install_command = match (install_command, request.node_version.as_deref()) {
+        (Some(install), Some(version)) => {
+            Some(nvm_manager::wrap_command_with_node(version, &install)?)
+        }
+        (install, _) => install,
+    };
+    let env = env_profile::resolve_env_vars(
+        &request.project_path,
+        Some(&request.command_name),
+        request.env_profile.as_deref(),
+    )?;
+
+    let run = RunRequest {
+        command_id: format!("{}::{}", request.project_path, request.command_name),
+        project_path: request.project_path.clone(),
+        command,
+        command_name: Some(request.command_name.clone()),
+        env,
+        trigger: trigger.to_string(),
+        hook_depth: 0,
+        priority: request.priority,
+        long_running: is_long_running_script(&request.command_name),
+    };
+    Ok((run, install_command))
+}
+
+// ---------- 外部终端中的运行 ----------
+
+fn add_external_collector(run_id: &str, project_path: &str) {
+    if let Ok(mut collectors) = EXTERNAL_COLLECTORS.lock() {
+        collectors.insert(
+            run_id.to_string(),
+            Arc::new(Mutex::new(DiagnosticCollector::new(
+                Some(project_path.to_string()),
+                None,
+            ))),
+        );
+    }
+}
+
+// 交互式运行出队时登记为运行中并占用名额；调用方持有 QUEUE 锁
+fn register_external(app: &AppHandle, run_id: &str, request: &RunRequest) {
+    if let Ok(mut active) = ACTIVE_RUNS.lock() {
+        active.insert(
+            run_id.to_string(),
+            ActiveRun {
+                pid: None,
+                cancelled: false,
+                project_path: request.project_path.clone(),
+                long_running: request.long_running,
+            },
+        );
+    }
+    add_external_collector(run_id, &request.project_path);
+    let started = update_record(run_id, |record| {
+        record.status = RunStatus::Running;
+        record.started_at = Some(get_current_timestamp());
+    });
+    if let Some(record) = started {
+        let _ = app.emit("task-run-status", &record);
+        hooks::dispatch(app, &record, HookEvent::Started);
+    }
+}
+
+// 交互式运行的退出码文件，通过 EXIT_FILE_ENV 传给终端中的命令
+pub fn exit_file(run_id: &str) -> PathBuf {
+    let name: String = run_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    std::env::temp_dir().join(format!("runproject-exit-{}", name))
+}
+
+// 轮询退出码文件结束运行记录；alive 返回 false（标签页或会话已关闭）时按取消处理
+pub fn watch_exit_file(app: &AppHandle, run_id: &str, alive: impl Fn() -> bool + Send + 'static) {
+    let app = app.clone();
+    let run_id = run_id.to_string();
+    let path = exit_file(&run_id);
+    std::thread::spawn(move || loop {
+        std::thread::sleep(EXIT_POLL_INTERVAL);
+        if !is_running(&run_id) {
+            let _ = std::fs::remove_file(&path);
+            return;
+        }
+        // 文件可能刚创建还未写入，内容为空时等下一轮
+        let content = std::fs::read_to_string(&path).unwrap_or_default();
+        if !content.trim().is_empty() {
+            let _ = std::fs::remove_file(&path);
+            let exit_code = content.trim().parse::<i32>().ok();
+            let status = if exit_code == Some(0) {
+                RunStatus::Succeeded
+            } else {
+                RunStatus::Failed
+            };
+            finish_external_run(&app, &run_id, status, exit_code);
+            return;
+        }
+        if !alive() {
+            finish_external_run(&app, &run_id, RunStatus::Cancelled, None);
+            return;
+        }
+    });
+}
+
+// 登记已经在外部终端中运行的命令（如启动时接管的 kitty 标签页），不经过排队，直接计入运行中
+// 输出由调用方抓取后通过 append_external_output 写入，结束时调用 finish_external_run
+pub fn begin_external_run(
+    app: &AppHandle,
+    command_id: &str,
+    project_path: &str,
+    command: &str,
+    command_name: Option<String>,
+    trigger: &str,
+) -> String {
+    let run_id = next_run_id(command_id);
+    let now = get_current_timestamp();
+    let record = RunRecord {
+        run_id: run_id.clone(),
+        command_id: command_id.to_string(),
+        project_path: project_path.to_string(),
+        command: command.to_string(),
+        long_running: command_name.as_deref().is_some_and(is_long_running_script),
+        command_name,
+        trigger: trigger.to_string(),
+        status: RunStatus::Running,
+        exit_code: None,
+        priority: 0,
+        queued_at: now,
+        started_at: Some(now),
+        ready_at: None,
+        finished_at: None,
+        diagnostics: 0,
+        output_tail: String::new(),
+        hook_depth: 0,
+    };
+    push_record(record.clone());
+    if let Ok(mut active) = ACTIVE_RUNS.lock() {
+        active.insert(
+            run_id.clone(),
+            ActiveRun {
+                pid: None,
+                cancelled: false,
+                project_path: record.project_path.clone(),
+                long_running: record.long_running,
+            },
+        );
+    }
+    add_external_collector(&run_id, project_path);
+    let _ = app.emit("task-run-status", &record);
+    hooks::dispatch(app, &record, HookEvent::Started);
+    run_id
+}
+
+pub fn append_external_output(app: &AppHandle, run_id: &str, stream: &str, text: &str) {
+    let collector = EXTERNAL_COLLECTORS
+        .lock()
+        .ok()
+        .and_then(|collectors| collectors.get(run_id).cloned());
+    if let Some(collector) = collector {
+        if let Ok(mut collector) = collector.lock() {
+            record_output(app, run_id, stream, text, &mut collector);
+        }
+    }
+}
+
+pub fn finish_external_run(
+    app: &AppHandle,
+    run_id: &str,
+    status: RunStatus,
+    exit_code: Option<i32>,
+) {
+    let Some(collector) = EXTERNAL_COLLECTORS
+        .lock()
+        .ok()
+        .and_then(|mut collectors| collectors.remove(run_id))
+    else {
+        return;
+    };
+    let remaining = collector.lock().map(|mut c| c.finish()).unwrap_or_default();
+    problem_matcher::publish_diagnostics(app, run_id, &remaining);
+    let diagnostics = collector.lock().map(|c| c.diagnostics().len()).unwrap_or(0);
+
+    finish_record(app, run_id, None, |record| {
+        record.status = status;
+        record.exit_code = exit_code;
+        record.diagnostics = diagnostics;
+    });
+
+    // 交互式运行结束后释放名额
+    let released = ACTIVE_RUNS
+        .lock()
+        .ok()
+        .and_then(|mut active| active.remove(run_id))
+        .is_some();
+    if released {
+        start_queued(app);
+    }
+}
+
+// ---------- Tauri 命令 ----------
+
+#[tauri::command]
+pub fn list_task_runs(command_id: Option<String>) -> Result<Vec<RunRecord>, String> {
+    let history = RUN_HISTORY
+        .lock()
+        .map_err(|e| i18n::tf("common.lock_failed", &[("error", &e)]))?;
+    Ok(history
+        .iter()
+        .rev()
+        .filter(|r| command_id.as_ref().is_none_or(|id| &r.command_id == id))
+        .cloned()
+        .collect())
+}
+
+// 在保存的输出中搜索（不区分大小写），后台任务与外部终端中的运行都包括在内，最近的运行在前
+#[tauri::command]
+pub fn search_task_output(
+    query: String,
+    project_path: Option<String>,
+) -> Result<Vec<OutputMatch>, String> {
+    let needle = query.trim().to_lowercase();
+    if needle.is_empty() {
+        return Ok(Vec::new());
+    }
+    let history = RUN_HISTORY
+        .lock()
+        .map_err(|e| i18n::tf("common.lock_failed", &[("error", &e)]))?;
+    Ok(history
+        .iter()
+        .rev()
+        .filter(|r| {
+            project_path
+                .as_ref()
+                .is_none_or(|path| &r.project_path == path)
+        })
+        .flat_map(|record| {
+            record
+                .output_tail
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| line.to_lowercase().contains(&needle))
+                .map(|(index, line)| OutputMatch {
+                    run_id: record.run_id.clone(),
+                    command_id: record.command_id.clone(),
+                    command_name: record.command_name.clone(),
+                    project_path: record.project_path.clone(),
+                    line_number: index + 1,
+                    line: line.to_string(),
+                })
+        })
+        .take(MAX_SEARCH_MATCHES)
+        .collect())
+}
+
+#[tauri::command]
+pub fn get_task_run(run_id: String) -> Result<Option<RunRecord>, String> {
+    Ok(get_run(&run_id))
+}
+
+#[tauri::command]
+pub fn cancel_task_run(run_id: String) -> Result<bool, String> {
+    Ok(cancel_run(&run_id))
+}
+
+// 以后台任务方式运行项目脚本，受并发上限约束
+#[tauri::command]
+pub fn queue_task_run(app: AppHandle, request: ManualRunRequest) -> AppResult<String> {
+    let request = resolve_script_run(&request, "manual")?;
+    spawn_run(&app, request, None)
+}
+
+// 按出队顺序返回排队中的任务
+#[tauri::command]
+pub fn list_task_queue() -> Result<Vec<RunRecord>, String> {
+    let queue = QUEUE
+        .lock()
+        .map_err(|e| i18n::tf("common.lock_failed", &[("error", &e)]))?;
+    Ok(queued_records(&queue))
+}
+
+// 将排队中的任务移动到指定位置，优先级不变
+#[tauri::command]
+pub fn move_queued_run(app: AppHandle, run_id: String, index: usize) -> Result<bool, String> {
+    modify_queue(&app, |queue| {
+        let Some(from) = queue.iter().position(|item| item.run_id == run_id) else {
+            return false;
+        };
+        let item = queue.remove(from);
+        let to = index.min(queue.len());
+        queue.insert(to, item);
+        from != to
+    })
+}
+
+// 修改优先级后按新优先级重新排在同优先级任务之后
+#[tauri::command]
+pub fn set_queued_run_priority(
+    app: AppHandle,
+    run_id: String,
+    priority: i32,
+) -> Result<bool, String> {
+    modify_queue(&app, |queue| {
+        let Some(from) = queue.iter().position(|item| item.run_id == run_id) else {
+            return false;
+        };
+        let mut item = queue.remove(from);
+        item.request.priority = priority;
+        update_record(&run_id, |record| record.priority = priority);
+        let to = queue
+            .iter()
+            .position(|other| other.request.priority < priority)
+            .unwrap_or(queue.len());
+        queue.insert(to, item);
+        true
+    })
+}
+
+#[tauri::command]
+pub fn get_queue_settings() -> Result<QueueSettings, String> {
+    with_queue_settings(|settings| settings.clone())
+}
+
+// 上限放宽后立即启动可以运行的排队任务
+#[tauri::command]
+pub fn set_queue_settings(
+    app: AppHandle,
+    settings: QueueSettings,
+) -> Result<QueueSettings, String> {
+    let snapshot = with_queue_settings(|current| {
+        *current = settings;
+        current.clone()
+    })?;
+    storage::save(QUEUE_STORE, &snapshot)?;
+    start_queued(&app);
+    Ok(snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_characters_wait_for_the_next_read() {
+        let bytes = "编译错误".as_bytes();
+        let mut pending = bytes[..4].to_vec();
+        assert_eq!(take_utf8(&mut pending), "编");
+        assert_eq!(pending, &bytes[3..4]);
+        pending.extend_from_slice(&bytes[4..]);
+        assert_eq!(take_utf8(&mut pending), "译错误");
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn invalid_bytes_are_not_held_back() {
+        let mut pending = b"ok \xff".to_vec();
+        assert_eq!(take_utf8(&mut pending), "ok \u{FFFD}");
+        assert!(pending.is_empty());
+    }
+}
@@ -0,0 +1,1368 @@
+// 终端模拟
+//
+// 在后端解析内置终端会话的 PTY 输出（VT/xterm 控制序列，由 vte 分词），维护屏幕网格与回滚区：
+// - 重新连接时返回屏幕快照与最近的回滚区，不再重放原始输出，htop 等全屏程序也能正确还原
+// - 回滚区按行保存，可以按纯文本搜索
+// - 只处理影响屏幕内容与输入模式的序列；需要应答的查询（DSR、DA 等）由前端的 xterm 回复，这里忽略
+// - 调整大小时不按宽度重排，屏幕上的行直接截断或补齐，回滚区的行保持原宽度
+use serde::Serialize;
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt::Write as _;
+use unicode_width::UnicodeWidthChar;
+use vte::{Params, Parser, Perform};
+
+// 回滚区保留的行数
+pub const SCROLLBACK_LIMIT: usize = 5000;
+const TAB_WIDTH: usize = 8;
+// 每个单元格组合字符的长度上限，超出部分丢弃
+const MAX_COMBINING_LEN: usize = 16;
+// 宽字符右半边占位
+const WIDE_SPACER: char = '\0';
+
+const BOLD: u8 = 1;
+const DIM: u8 = 1 << 1;
+const ITALIC: u8 = 1 << 2;
+const UNDERLINE: u8 = 1 << 3;
+const BLINK: u8 = 1 << 4;
+const INVERSE: u8 = 1 << 5;
+const HIDDEN: u8 = 1 << 6;
+const STRIKE: u8 = 1 << 7;
+const FLAG_CODES: [(u8, &str); 8] = [
+    (BOLD, "1"),
+    (DIM, "2"),
+    (ITALIC, "3"),
+    (UNDERLINE, "4"),
+    (BLINK, "5"),
+    (INVERSE, "7"),
+    (HIDDEN, "8"),
+    (STRIKE, "9"),
+];
+
+// 只记录开关状态、快照时原样恢复的 DEC 私有模式：光标键、鼠标上报、焦点事件与括号粘贴
+const TRACKED_MODES: &[u16] = &[1, 9, 1000, 1002, 1003, 1004, 1005, 1006, 1015, 2004];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Color {
+    #[default]
+    Default,
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Attrs {
+    fg: Color,
+    bg: Color,
+    flags: u8,
+}
+
+fn push_color(codes: &mut Vec<String>, color: Color, base: u8) {
+    match color {
+        Color::Default => {}
+        Color::Indexed(index) if index < 8 => codes.push((base + index).to_string()),
+        Color::Indexed(index) if index < 16 => codes.push((base + 60 + index - 8).to_string()),
+        Color::Indexed(index) => codes.push(format!("{};5;{}", base + 8, index)),
+        Color::Rgb(r, g, b) => codes.push(format!("{};2;{};{};{}", base + 8, r, g, b)),
+    }
+}
+
+impl Attrs {
+    // 从任意状态切换到该属性的 SGR
+    fn sgr(&self) -> String {
+        let mut codes = vec!["0".to_string()];
+        codes.extend(
+            FLAG_CODES
+                .iter()
+                .filter(|(flag, _)| self.flags & flag != 0)
+                .map(|(_, code)| code.to_string()),
+        );
+        push_color(&mut codes, self.fg, 30);
+        push_color(&mut codes, self.bg, 40);
+        format!("\x1b[{}m", codes.join(";"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cell {
+    ch: char,
+    attrs: Attrs,
+    // 附加在该字符上的组合字符，很少出现，单独分配以免每个单元格都带一个 String
+    combining: Option<Box<str>>,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            ch: ' ',
+            attrs: Attrs::default(),
+            combining: None,
+        }
+    }
+}
+
+impl Cell {
+    fn marks(&self) -> &str {
+        self.combining.as_deref().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Row {
+    cells: Vec<Cell>,
+    // 该行写满后自动换到下一行，纯文本中与下一行拼接
+    wrapped: bool,
+}
+
+impl Row {
+    fn new(cols: usize, cell: &Cell) -> Self {
+        Self {
+            cells: vec![cell.clone(); cols],
+            wrapped: false,
+        }
+    }
+
+    fn is_blank(&self) -> bool {
+        self.cells.iter().all(|cell| *cell == Cell::default())
+    }
+
+    fn text(&self) -> String {
+        let text: String = self
+            .cells
+            .iter()
+            .filter(|cell| cell.ch != WIDE_SPACER)
+            .flat_map(|cell| std::iter::once(cell.ch).chain(cell.marks().chars()))
+            .collect();
+        if self.wrapped {
+            text
+        } else {
+            text.trim_end().to_string()
+        }
+    }
+
+    // 行尾的宽字符缺少右半边时换成空白
+    fn fix_end(&mut self) {
+        if let Some(last) = self.cells.last_mut() {
+            if char_width(last.ch) == 2 {
+                *last = Cell {
+                    attrs: last.attrs,
+                    ..Cell::default()
+                };
+            }
+        }
+    }
+
+    fn resize(&mut self, cols: usize) {
+        self.cells.resize(cols, Cell::default());
+        self.fix_end();
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Cursor {
+    row: usize,
+    col: usize,
+    attrs: Attrs,
+    // 写满最后一列后，下一个字符才换行
+    pending_wrap: bool,
+}
+
+// 字符宽度：组合字符与零宽字符为 0，东亚宽字符与 emoji 为 2
+fn char_width(ch: char) -> usize {
+    ch.width().unwrap_or(0).min(2)
+}
+
+// 重新连接时写入空白 xterm 即可还原的内容
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenSnapshot {
+    // 回滚区、屏幕内容以及光标、属性与模式的控制序列
+    pub data: String,
+    pub cols: usize,
+    pub rows: usize,
+    // 包含的回滚区行数
+    pub scrollback_lines: usize,
+    // 是否处于备用屏幕（全屏程序）
+    pub alternate: bool,
+    // 快照包含到的输出序号，序号不大于它的 terminal-output 事件已在快照中
+    pub seq: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineMatch {
+    // 在回滚区与屏幕拼接后的行号，从 0 开始
+    pub line: usize,
+    pub text: String,
+}
+
+pub struct Emulator {
+    cols: usize,
+    rows: usize,
+    grid: Vec<Row>,
+    // 使用备用屏幕时保存的主屏幕与光标
+    primary: Option<(Vec<Row>, Cursor)>,
+    scrollback: VecDeque<Row>,
+    cursor: Cursor,
+    saved_cursor: Option<Cursor>,
+    scroll_top: usize,
+    scroll_bottom: usize,
+    autowrap: bool,
+    origin_mode: bool,
+    insert_mode: bool,
+    cursor_visible: bool,
+    app_keypad: bool,
+    modes: BTreeSet<u16>,
+    title: String,
+    // REP 重复的字符
+    last_char: Option<char>,
+    parser: Parser,
+    // 已处理的输出块数
+    seq: u64,
+}
+
+impl Emulator {
+    pub fn new(cols: usize, rows: usize) -> Self {
+        let (cols, rows) = (cols.max(1), rows.max(1));
+        Self {
+            cols,
+            rows,
+            grid: vec![Row::new(cols, &Cell::default()); rows],
+            primary: None,
+            scrollback: VecDeque::new(),
+            cursor: Cursor::default(),
+            saved_cursor: None,
+            scroll_top: 0,
+            scroll_bottom: rows - 1,
+            autowrap: true,
+            origin_mode: false,
+            insert_mode: false,
+            cursor_visible: true,
+            app_keypad: false,
+            modes: BTreeSet::new(),
+            title: String::new(),
+            last_char: None,
+            parser: Parser::new(),
+            seq: 0,
+        }
+    }
+
+    // 处理一块输出，返回该块的序号
+    pub fn feed(&mut self, data: &[u8]) -> u64 {
+        let mut parser = std::mem::take(&mut self.parser);
+        parser.advance(self, data);
+        self.parser = parser;
+        self.seq += 1;
+        self.seq
+    }
+
+    // ---------- 控制序列 ----------
+
+    fn execute_control(&mut self, byte: u8) {
+        match byte {
+            0x08 => {
+                self.cursor.col = self.cursor.col.saturating_sub(1);
+                self.cursor.pending_wrap = false;
+            }
+            0x09 => self.tab_forward(1),
+            0x0A..=0x0C => {
+                self.index();
+                self.cursor.pending_wrap = false;
+            }
+            0x0D => {
+                self.cursor.col = 0;
+                self.cursor.pending_wrap = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn dispatch_esc(&mut self, intermediates: &[u8], byte: u8) {
+        match (intermediates, byte) {
+            ([], b'7') => self.saved_cursor = Some(self.cursor),
+            ([], b'8') => self.restore_cursor(),
+            ([], b'D') => self.index(),
+            ([], b'E') => {
+                self.cursor.col = 0;
+                self.index();
+            }
+            ([], b'M') => self.reverse_index(),
+            ([], b'c') => self.reset(),
+            ([], b'=') => self.app_keypad = true,
+            ([], b'>') => self.app_keypad = false,
+            // DECALN：以 E 填满屏幕
+            ([b'#'], b'8') => {
+                for row in &mut self.grid {
+                    *row = Row::new(
+                        self.cols,
+                        &Cell {
+                            ch: 'E',
+                            ..Cell::default()
+                        },
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // 只处理窗口标题（OSC 0 / 2），标题中的 ; 被 vte 拆成多段，重新拼接
+    fn dispatch_osc(&mut self, params: &[&[u8]]) {
+        if let [kind, title @ ..] = params {
+            if matches!(*kind, b"0" | b"2") {
+                self.title = String::from_utf8_lossy(&title.join(&b';'))
+                    .chars()
+                    .filter(|c| !c.is_control())
+                    .collect();
+            }
+        }
+    }
+
+    fn dispatch_csi(&mut self, params: &[&[u16]], intermediates: &[u8], action: char) {
+        match intermediates {
+            [] => {}
+            [b'?'] => {
+                match action {
+                    'h' => self.set_private_modes(params, true),
+                    'l' => self.set_private_modes(params, false),
+                    'J' => self.erase_display(param_or(params, 0, 0)),
+                    'K' => self.erase_line(param_or(params, 0, 0)),
+                    _ => {}
+                }
+                return;
+            }
+            // DECSTR 软重置，其余（如光标样式）忽略
+            [b'!'] if action == 'p' => return self.soft_reset(),
+            _ => return,
+        }
+        let n = param_or(params, 0, 1);
+        match action {
+            '@' => self.insert_chars(n),
+            'A' => self.cursor_up(n),
+            'B' | 'e' => self.cursor_down(n),
+            'C' | 'a' => self.move_to(self.cursor.row, self.cursor.col + n),
+            'D' => self.move_to(self.cursor.row, self.cursor.col.saturating_sub(n)),
+            'E' => {
+                self.cursor_down(n);
+                self.cursor.col = 0;
+            }
+            'F' => {
+                self.cursor_up(n);
+                self.cursor.col = 0;
+            }
+            'G' | '`' => self.move_to(self.cursor.row, n - 1),
+            'H' | 'f' => {
+                let col = param_or(params, 1, 1) - 1;
+                self.move_to_origin(n - 1, col);
+            }
+            'I' => self.tab_forward(n),
+            'Z' => self.tab_backward(n),
+            'J' => self.erase_display(param_or(params, 0, 0)),
+            'K' => self.erase_line(param_or(params, 0, 0)),
+            'L' => self.insert_lines(n),
+            'M' => self.delete_lines(n),
+            'P' => self.delete_chars(n),
+            'S' => self.scroll_up(n),
+            'T' => self.scroll_down(n),
+            'X' => {
+                let (row, col) = (self.cursor.row, self.cursor.col);
+                self.fill(row, col, col + n);
+            }
+            'b' => {
+                if let Some(ch) = self.last_char {
+                    for _ in 0..n.min(self.cols * self.rows) {
+                        self.print_char(ch);
+                    }
+                }
+            }
+            'd' => self.move_to_origin(n - 1, self.cursor.col),
+            'h' | 'l' if params.iter().any(|param| param.first() == Some(&4)) => {
+                self.insert_mode = action == 'h';
+            }
+            'm' => self.sgr(params),
+            'r' => {
+                let top = param_or(params, 0, 1) - 1;
+                let bottom = param_or(params, 1, self.rows).min(self.rows) - 1;
+                if top < bottom {
+                    self.scroll_top = top;
+                    self.scroll_bottom = bottom;
+                    self.move_to_origin(0, 0);
+                }
+            }
+            's' => self.saved_cursor = Some(self.cursor),
+            'u' => self.restore_cursor(),
+            _ => {}
+        }
+    }
+
+    // 每个参数连同 : 分隔的子参数为一组
+    fn sgr(&mut self, params: &[&[u16]]) {
+        if params.is_empty() {
+            self.cursor.attrs = Attrs::default();
+            return;
+        }
+        let mut index = 0;
+        while index < params.len() {
+            let (code, subs) = match params[index] {
+                [code, subs @ ..] => (*code, subs),
+                [] => (0, &[][..]),
+            };
+            index += 1;
+
+            let attrs = &mut self.cursor.attrs;
+            match code {
+                0 => *attrs = Attrs::default(),
+                4 if subs.first() == Some(&0) => attrs.flags &= !UNDERLINE,
+                1..=9 => {
+                    let flag = match code {
+                        1 => BOLD,
+                        2 => DIM,
+                        3 => ITALIC,
+                        4 => UNDERLINE,
+                        5 | 6 => BLINK,
+                        7 => INVERSE,
+                        8 => HIDDEN,
+                        _ => STRIKE,
+                    };
+                    attrs.flags |= flag;
+                }
+                21 => attrs.flags |= UNDERLINE,
+                22 => attrs.flags &= !(BOLD | DIM),
+                23 => attrs.flags &= !ITALIC,
+                24 => attrs.flags &= !UNDERLINE,
+                25 => attrs.flags &= !BLINK,
+                27 => attrs.flags &= !INVERSE,
+                28 => attrs.flags &= !HIDDEN,
+                29 => attrs.flags &= !STRIKE,
+                30..=37 => attrs.fg = Color::Indexed((code - 30) as u8),
+                39 => attrs.fg = Color::Default,
+                40..=47 => attrs.bg = Color::Indexed((code - 40) as u8),
+                49 => attrs.bg = Color::Default,
+                90..=97 => attrs.fg = Color::Indexed((code - 90 + 8) as u8),
+                100..=107 => attrs.bg = Color::Indexed((code - 100 + 8) as u8),
+                38 | 48 => {
+                    let color = if subs.is_empty() {
+                        // ; 分隔的形式，颜色参数紧随其后
+                        let rest: Vec<u16> = params[index..]
+                            .iter()
+                            .map(|param| param.first().copied().unwrap_or(0))
+                            .collect();
+                        let (color, used) = extended_color(&rest);
+                        index += used;
+                        color
+                    } else {
+                        colon_color(subs)
+                    };
+                    if let Some(color) = color {
+                        if code == 38 {
+                            attrs.fg = color;
+                        } else {
+                            attrs.bg = color;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn set_private_modes(&mut self, params: &[&[u16]], on: bool) {
+        for mode in params.iter().filter_map(|param| param.first().copied()) {
+            match mode {
+                6 => {
+                    self.origin_mode = on;
+                    self.move_to_origin(0, 0);
+                }
+                7 => self.autowrap = on,
+                25 => self.cursor_visible = on,
+                47 | 1047 => self.set_alternate(on, false),
+                1048 => {
+                    if on {
+                        self.saved_cursor = Some(self.cursor);
+                    } else {
+                        self.restore_cursor();
+                    }
+                }
+                1049 => self.set_alternate(on, true),
+                mode if TRACKED_MODES.contains(&mode) => {
+                    if on {
+                        self.modes.insert(mode);
+                    } else {
+                        self.modes.remove(&mode);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // ---------- 屏幕操作 ----------
+
+    // 擦除后的空白单元格保留当前背景色
+    fn erase_cell(&self) -> Cell {
+        Cell {
+            attrs: Attrs {
+                bg: self.cursor.attrs.bg,
+                ..Attrs::default()
+            },
+            ..Cell::default()
+        }
+    }
+
+    fn blank_row(&self) -> Row {
+        Row::new(self.cols, &self.erase_cell())
+    }
+
+    fn print_char(&mut self, ch: char) {
+        if ('\u{80}'..='\u{9F}').contains(&ch) {
+            return;
+        }
+        let width = char_width(ch);
+        if width == 0 {
+            self.attach_combining(ch);
+            return;
+        }
+        if self.cursor.pending_wrap && self.autowrap {
+            self.wrap_line();
+        }
+        self.cursor.pending_wrap = false;
+        if width == 2 && self.cursor.col + 1 >= self.cols {
+            if self.cols < 2 {
+                return;
+            }
+            if self.autowrap {
+                let (row, col) = (self.cursor.row, self.cursor.col);
+                self.fill(row, col, col + 1);
+                self.wrap_line();
+            } else {
+                self.cursor.col = self.cols - 2;
+            }
+        }
+
+        let (row, col) = (self.cursor.row, self.cursor.col);
+        if self.insert_mode {
+            self.insert_blanks(row, col, width);
+        }
+        self.split_wide(row, col);
+        if width == 2 {
+            self.split_wide(row, col + 1);
+        }
+        let attrs = self.cursor.attrs;
+        let cells = &mut self.grid[row].cells;
+        cells[col] = Cell {
+            ch,
+            attrs,
+            combining: None,
+        };
+        if width == 2 {
+            cells[col + 1] = Cell {
+                ch: WIDE_SPACER,
+                attrs,
+                combining: None,
+            };
+        }
+        self.last_char = Some(ch);
+
+        if col + width >= self.cols {
+            self.cursor.col = self.cols - 1;
+            self.cursor.pending_wrap = self.autowrap;
+        } else {
+            self.cursor.col = col + width;
+        }
+    }
+
+    fn attach_combining(&mut self, ch: char) {
+        let row = self.cursor.row;
+        let mut col = if self.cursor.pending_wrap {
+            self.cursor.col
+        } else if self.cursor.col > 0 {
+            self.cursor.col - 1
+        } else {
+            return;
+        };
+        if self.grid[row].cells[col].ch == WIDE_SPACER && col > 0 {
+            col -= 1;
+        }
+        let cell = &mut self.grid[row].cells[col];
+        if cell.marks().len() + ch.len_utf8() <= MAX_COMBINING_LEN {
+            let mut marks = cell.marks().to_string();
+            marks.push(ch);
+            cell.combining = Some(marks.into_boxed_str());
+        }
+    }
+
+    fn wrap_line(&mut self) {
+        self.grid[self.cursor.row].wrapped = true;
+        self.cursor.col = 0;
+        self.index();
+    }
+
+    // 覆盖宽字符的一半时，另一半换成空白
+    fn split_wide(&mut self, row: usize, col: usize) {
+        let blank = self.erase_cell();
+        let cells = &mut self.grid[row].cells;
+        match cells.get(col).map(|cell| cell.ch) {
+            Some(WIDE_SPACER) if col > 0 => cells[col - 1] = blank,
+            Some(_)
+                if cells
+                    .get(col + 1)
+                    .is_some_and(|cell| cell.ch == WIDE_SPACER) =>
+            {
+                cells[col + 1] = blank;
+            }
+            _ => {}
+        }
+    }
+
+    // 擦除 [from, to) 列
+    fn fill(&mut self, row: usize, from: usize, to: usize) {
+        let to = to.min(self.cols);
+        if from >= to {
+            return;
+        }
+        self.split_wide(row, from);
+        self.split_wide(row, to - 1);
+        let blank = self.erase_cell();
+        let line = &mut self.grid[row];
+        line.cells[from..to].fill(blank);
+        if to == self.cols {
+            line.wrapped = false;
+        }
+    }
+
+    fn insert_blanks(&mut self, row: usize, col: usize, count: usize) {
+        self.split_wide(row, col);
+        let blank = self.erase_cell();
+        let count = count.min(self.cols - col);
+        let line = &mut self.grid[row];
+        line.cells
+            .splice(col..col, std::iter::repeat_n(blank, count));
+        line.cells.truncate(self.cols);
+        line.fix_end();
+    }
+
+    fn insert_chars(&mut self, count: usize) {
+        let (row, col) = (self.cursor.row, self.cursor.col);
+        self.insert_blanks(row, col, count);
+        self.cursor.pending_wrap = false;
+    }
+
+    fn delete_chars(&mut self, count: usize) {
+        let (row, col) = (self.cursor.row, self.cursor.col);
+        let count = count.min(self.cols - col);
+        self.split_wide(row, col);
+        if col + count < self.cols {
+            self.split_wide(row, col + count);
+        }
+        let blank = self.erase_cell();
+        let line = &mut self.grid[row];
+        line.cells.drain(col..col + count);
+        line.cells.resize(self.cols, blank);
+        self.cursor.pending_wrap = false;
+    }
+
+    fn erase_display(&mut self, mode: usize) {
+        let (row, col) = (self.cursor.row, self.cursor.col);
+        match mode {
+            0 => {
+                self.fill(row, col, self.cols);
+                for below in row + 1..self.rows {
+                    self.fill(below, 0, self.cols);
+                }
+            }
+            1 => {
+                for above in 0..row {
+                    self.fill(above, 0, self.cols);
+                }
+                self.fill(row, 0, col + 1);
+            }
+            2 => {
+                for any in 0..self.rows {
+                    self.fill(any, 0, self.cols);
+                }
+            }
+            3 => self.scrollback.clear(),
+            _ => {}
+        }
+    }
+
+    fn erase_line(&mut self, mode: usize) {
+        let (row, col) = (self.cursor.row, self.cursor.col);
+        match mode {
+            0 => self.fill(row, col, self.cols),
+            1 => self.fill(row, 0, col + 1),
+            2 => self.fill(row, 0, self.cols),
+            _ => {}
+        }
+    }
+
+    fn in_region(&self) -> bool {
+        (self.scroll_top..=self.scroll_bottom).contains(&self.cursor.row)
+    }
+
+    fn insert_lines(&mut self, count: usize) {
+        if !self.in_region() {
+            return;
+        }
+        let count = count.min(self.scroll_bottom - self.cursor.row + 1);
+        for _ in 0..count {
+            self.grid.remove(self.scroll_bottom);
+            self.grid.insert(self.cursor.row, self.blank_row());
+        }
+        self.cursor.col = 0;
+        self.cursor.pending_wrap = false;
+    }
+
+    fn delete_lines(&mut self, count: usize) {
+        if !self.in_region() {
+            return;
+        }
+        let count = count.min(self.scroll_bottom - self.cursor.row + 1);
+        for _ in 0..count {
+            self.grid.remove(self.cursor.row);
+            self.grid.insert(self.scroll_bottom, self.blank_row());
+        }
+        self.cursor.col = 0;
+        self.cursor.pending_wrap = false;
+    }
+
+    // 滚动区域上移，主屏幕从第一行开始滚动时移出的行进入回滚区
+    fn scroll_up(&mut self, count: usize) {
+        let count = count.min(self.scroll_bottom - self.scroll_top + 1);
+        for _ in 0..count {
+            let row = self.grid.remove(self.scroll_top);
+            self.grid.insert(self.scroll_bottom, self.blank_row());
+            if self.scroll_top == 0 && self.primary.is_none() {
+                self.push_scrollback(row);
+            }
+        }
+    }
+
+    fn scroll_down(&mut self, count: usize) {
+        let count = count.min(self.scroll_bottom - self.scroll_top + 1);
+        for _ in 0..count {
+            self.grid.remove(self.scroll_bottom);
+            self.grid.insert(self.scroll_top, self.blank_row());
+        }
+    }
+
+    fn push_scrollback(&mut self, row: Row) {
+        self.scrollback.push_back(row);
+        while self.scrollback.len() > SCROLLBACK_LIMIT {
+            self.scrollback.pop_front();
+        }
+    }
+
+    fn index(&mut self) {
+        if self.cursor.row == self.scroll_bottom {
+            self.scroll_up(1);
+        } else if self.cursor.row + 1 < self.rows {
+            self.cursor.row += 1;
+        }
+    }
+
+    fn reverse_index(&mut self) {
+        if self.cursor.row == self.scroll_top {
+            self.scroll_down(1);
+        } else if self.cursor.row > 0 {
+            self.cursor.row -= 1;
+        }
+    }
+
+    fn move_to(&mut self, row: usize, col: usize) {
+        self.cursor.row = row.min(self.rows - 1);
+        self.cursor.col = col.min(self.cols - 1);
+        self.cursor.pending_wrap = false;
+    }
+
+    // 绝对定位，原点模式下相对于滚动区域
+    fn move_to_origin(&mut self, row: usize, col: usize) {
+        if self.origin_mode {
+            let row = (self.scroll_top + row).min(self.scroll_bottom);
+            self.move_to(row, col);
+        } else {
+            self.move_to(row, col);
+        }
+    }
+
+    fn cursor_up(&mut self, count: usize) {
+        let limit = if self.cursor.row >= self.scroll_top {
+            self.scroll_top
+        } else {
+            0
+        };
+        let row = self.cursor.row.saturating_sub(count).max(limit);
+        self.move_to(row, self.cursor.col);
+    }
+
+    fn cursor_down(&mut self, count: usize) {
+        let limit = if self.cursor.row <= self.scroll_bottom {
+            self.scroll_bottom
+        } else {
+            self.rows - 1
+        };
+        let row = (self.cursor.row + count).min(limit);
+        self.move_to(row, self.cursor.col);
+    }
+
+    fn tab_forward(&mut self, count: usize) {
+        let col = (self.cursor.col / TAB_WIDTH + count.min(self.cols)) * TAB_WIDTH;
+        self.move_to(self.cursor.row, col);
+    }
+
+    fn tab_backward(&mut self, count: usize) {
+        let mut col = self.cursor.col;
+        for _ in 0..count.min(self.cols) {
+            col = col.saturating_sub(1) / TAB_WIDTH * TAB_WIDTH;
+        }
+        self.move_to(self.cursor.row, col);
+    }
+
+    fn restore_cursor(&mut self) {
+        if let Some(saved) = self.saved_cursor {
+            self.cursor = saved;
+            self.cursor.row = saved.row.min(self.rows - 1);
+            self.cursor.col = saved.col.min(self.cols - 1);
+        } else {
+            self.move_to(0, 0);
+        }
+    }
+
+    fn set_alternate(&mut self, on: bool, save_cursor: bool) {
+        if on && self.primary.is_none() {
+            let blank = vec![Row::new(self.cols, &Cell::default()); self.rows];
+            let grid = std::mem::replace(&mut self.grid, blank);
+            self.primary = Some((grid, self.cursor));
+        } else if !on {
+            if let Some((grid, cursor)) = self.primary.take() {
+                self.grid = grid;
+                if save_cursor {
+                    self.cursor = cursor;
+                }
+            }
+        }
+    }
+
+    fn soft_reset(&mut self) {
+        self.cursor.attrs = Attrs::default();
+        self.cursor.pending_wrap = false;
+        self.scroll_top = 0;
+        self.scroll_bottom = self.rows - 1;
+        self.autowrap = true;
+        self.origin_mode = false;
+        self.insert_mode = false;
+        self.cursor_visible = true;
+        self.app_keypad = false;
+        self.modes.clear();
+        self.saved_cursor = None;
+    }
+
+    // RIS：回滚区与输出序号保留
+    fn reset(&mut self) {
+        let scrollback = std::mem::take(&mut self.scrollback);
+        let seq = self.seq;
+        *self = Self::new(self.cols, self.rows);
+        self.scrollback = scrollback;
+        self.seq = seq;
+    }
+
+    // 行数减少时先去掉光标下方的空行，其余按原宽度从顶部移入回滚区，留下的行再调整宽度
+    fn resize_grid(
+        grid: &mut Vec<Row>,
+        cursor: &mut Cursor,
+        cols: usize,
+        rows: usize,
+        mut scrollback: Option<&mut VecDeque<Row>>,
+    ) {
+        while grid.len() > rows {
+            if grid.len() - 1 > cursor.row && grid.last().is_some_and(Row::is_blank) {
+                grid.pop();
+                continue;
+            }
+            let row = grid.remove(0);
+            if let Some(scrollback) = scrollback.as_deref_mut() {
+                scrollback.push_back(row);
+            }
+            cursor.row = cursor.row.saturating_sub(1);
+        }
+        for row in grid.iter_mut() {
+            row.resize(cols);
+        }
+        while grid.len() < rows {
+            grid.push(Row::new(cols, &Cell::default()));
+        }
+        cursor.row = cursor.row.min(rows - 1);
+        cursor.col = cursor.col.min(cols - 1);
+        cursor.pending_wrap = false;
+    }
+
+    pub fn resize(&mut self, cols: usize, rows: usize) {
+        let (cols, rows) = (cols.max(1), rows.max(1));
+        if (cols, rows) == (self.cols, self.rows) {
+            return;
+        }
+        let alternate = self.primary.is_some();
+        Self::resize_grid(
+            &mut self.grid,
+            &mut self.cursor,
+            cols,
+            rows,
+            (!alternate).then_some(&mut self.scrollback),
+        );
+        if let Some((grid, cursor)) = &mut self.primary {
+            Self::resize_grid(grid, cursor, cols, rows, Some(&mut self.scrollback));
+        }
+        while self.scrollback.len() > SCROLLBACK_LIMIT {
+            self.scrollback.pop_front();
+        }
+        self.cols = cols;
+        self.rows = rows;
+        self.scroll_top = 0;
+        self.scroll_bottom = rows - 1;
+        if let Some(saved) = &mut self.saved_cursor {
+            saved.row = saved.row.min(rows - 1);
+            saved.col = saved.col.min(cols - 1);
+        }
+    }
+
+    // ---------- 输出 ----------
+
+    // 一行的内容，trim 时省略末尾的默认空白；从默认属性开始，结束时恢复默认属性
+    fn render_row(row: &Row, trim: bool) -> String {
+        let mut out = String::new();
+        let mut pen = Attrs::default();
+        let end = if trim {
+            row.cells
+                .iter()
+                .rposition(|cell| *cell != Cell::default())
+                .map_or(0, |index| index + 1)
+        } else {
+            row.cells.len()
+        };
+        for cell in &row.cells[..end] {
+            if cell.ch == WIDE_SPACER {
+                continue;
+            }
+            if cell.attrs != pen {
+                out.push_str(&cell.attrs.sgr());
+                pen = cell.attrs;
+            }
+            out.push(cell.ch);
+            out.push_str(cell.marks());
+        }
+        if pen != Attrs::default() {
+            out.push_str("\x1b[0m");
+        }
+        out
+    }
+
+    fn cursor_position(&self, cursor: &Cursor) -> String {
+        let row = if self.origin_mode {
+            cursor.row.saturating_sub(self.scroll_top)
+        } else {
+            cursor.row
+        };
+        format!("\x1b[{};{}H", row + 1, cursor.col + 1)
+    }
+
+    // 快照：最近 scrollback_lines 行回滚区 + 主屏幕逐行输出，使回滚区进入 xterm 的历史，
+    // 处于备用屏幕时再切换过去按位置绘制，最后恢复光标、属性与模式
+    pub fn snapshot(&self, scrollback_lines: usize) -> ScreenSnapshot {
+        let mut out = String::new();
+        let (primary_grid, primary_cursor) = match &self.primary {
+            Some((grid, cursor)) => (grid, *cursor),
+            None => (&self.grid, self.cursor),
+        };
+        let skip = self.scrollback.len().saturating_sub(scrollback_lines);
+        let lines: Vec<&Row> = self
+            .scrollback
+            .iter()
+            .skip(skip)
+            .chain(primary_grid.iter())
+            .collect();
+
+        // 调整大小前进入回滚区的行宽度不同：回滚区内自动换行的行直接拼接，由 xterm 按当前宽度重新折行；
+        // 接续到屏幕的一段必须每行都是当前宽度，否则屏幕内容会错位，这时改为显式换行
+        let first_screen_row = lines.len() - primary_grid.len();
+        let mut aligned = true;
+        let joined: Vec<bool> = lines
+            .iter()
+            .enumerate()
+            .map(|(index, row)| {
+                aligned &= row.cells.len() == self.cols;
+                let joined = row.wrapped && (aligned || index + 1 < first_screen_row);
+                if !joined {
+                    aligned = true;
+                }
+                joined
+            })
+            .collect();
+        let rendered: Vec<String> = lines
+            .iter()
+            .zip(&joined)
+            .map(|(row, joined)| Self::render_row(row, !joined))
+            .collect();
+        for (index, text) in rendered.iter().enumerate() {
+            out.push_str(text);
+            let Some(next) = rendered.get(index + 1) else {
+                break;
+            };
+            // 自动换行的行写满后由下一个字符换行，下一行为空时仍需显式换行
+            if !joined[index] || next.is_empty() {
+                out.push_str("\r\n");
+            }
+        }
+
+        if self.primary.is_some() {
+            out.push_str(&self.cursor_position(&primary_cursor));
+            out.push_str("\x1b[?1049h");
+            for (index, row) in self.grid.iter().enumerate() {
+                let _ = write!(out, "\x1b[{};1H", index + 1);
+                out.push_str(&Self::render_row(row, true));
+            }
+        }
+
+        if self.scroll_top != 0 || self.scroll_bottom + 1 != self.rows {
+            let _ = write!(
+                out,
+                "\x1b[{};{}r",
+                self.scroll_top + 1,
+                self.scroll_bottom + 1
+            );
+        }
+        if self.origin_mode {
+            out.push_str("\x1b[?6h");
+        }
+        out.push_str(&self.cursor_position(&self.cursor));
+        if self.cursor.attrs != Attrs::default() {
+            out.push_str(&self.cursor.attrs.sgr());
+        }
+        for mode in &self.modes {
+            let _ = write!(out, "\x1b[?{}h", mode);
+        }
+        if self.app_keypad {
+            out.push_str("\x1b=");
+        }
+        if self.insert_mode {
+            out.push_str("\x1b[4h");
+        }
+        if !self.autowrap {
+            out.push_str("\x1b[?7l");
+        }
+        if !self.cursor_visible {
+            out.push_str("\x1b[?25l");
+        }
+        if !self.title.is_empty() {
+            let _ = write!(out, "\x1b]0;{}\x07", self.title);
+        }
+
+        ScreenSnapshot {
+            data: out,
+            cols: self.cols,
+            rows: self.rows,
+            scrollback_lines: self.scrollback.len() - skip,
+            alternate: self.primary.is_some(),
+            seq: self.seq,
+        }
+    }
+
+    // 当前屏幕的纯文本，每行去掉末尾空白
+    pub fn screen_text(&self) -> String {
+        self.grid
+            .iter()
+            .map(|row| row.text().trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // 回滚区与当前屏幕的纯文本，自动换行的行拼接为一行，末尾的空行去掉
+    pub fn text_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut current = String::new();
+        for row in self.scrollback.iter().chain(self.grid.iter()) {
+            current.push_str(&row.text());
+            if !row.wrapped {
+                lines.push(std::mem::take(&mut current));
+            }
+        }
+        if !current.is_empty() {
+            lines.push(current);
+        }
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        lines
+    }
+
+    pub fn search(&self, query: &str, case_sensitive: bool) -> Vec<LineMatch> {
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let query = if case_sensitive {
+            query.to_string()
+        } else {
+            query.to_lowercase()
+        };
+        self.text_lines()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, text)| {
+                if case_sensitive {
+                    text.contains(&query)
+                } else {
+                    text.to_lowercase().contains(&query)
+                }
+            })
+            .map(|(line, text)| LineMatch { line, text })
+            .collect()
+    }
+}
+
+impl Perform for Emulator {
+    fn print(&mut self, ch: char) {
+        self.print_char(ch);
+    }
+
+    fn execute(&mut self, byte: u8) {
+        self.execute_control(byte);
+    }
+
+    fn osc_dispatch(&mut self, params: &[&[u8]], _bell_terminated: bool) {
+        self.dispatch_osc(params);
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char) {
+        if !ignore {
+            let params: Vec<&[u16]> = params.iter().collect();
+            self.dispatch_csi(&params, intermediates, action);
+        }
+    }
+
+    // DCS、SOS、PM、APC 的内容由 vte 吞掉，这里不需要处理
+    fn esc_dispatch(&mut self, intermediates: &[u8], ignore: bool, byte: u8) {
+        if !ignore {
+            self.dispatch_esc(intermediates, byte);
+        }
+    }
+}
+
+// 第 index 个参数，缺省或为 0 时使用 default
+fn param_or(params: &[&[u16]], index: usize, default: usize) -> usize {
+    match params.get(index).and_then(|param| param.first()) {
+        Some(value) if *value != 0 => usize::from(*value),
+        _ => default,
+    }
+}
+
+// ; 分隔的 38/48 参数：5;n 或 2;r;g;b，返回颜色与使用的参数个数
+fn extended_color(rest: &[u16]) -> (Option<Color>, usize) {
+    let byte = |value: u16| value.min(255) as u8;
+    match rest {
+        [5, index, ..] => (Some(Color::Indexed(byte(*index))), 2),
+        [2, r, g, b, ..] => (Some(Color::Rgb(byte(*r), byte(*g), byte(*b))), 4),
+        [_, ..] => (None, rest.len()),
+        [] => (None, 0),
+    }
+}
+
+// : 分隔的形式：5:n、2:r:g:b 或带色彩空间的 2:cs:r:g:b
+fn colon_color(subs: &[u16]) -> Option<Color> {
+    let byte = |value: u16| value.min(255) as u8;
+    match subs {
+        [5, index, ..] => Some(Color::Indexed(byte(*index))),
+        [2, _, r, g, b, ..] | [2, r, g, b] => Some(Color::Rgb(byte(*r), byte(*g), byte(*b))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::QuickCheck;
+
+    // 终端输出片段，随机拼接成控制序列、宽字符与组合字符交错的输出
+    const TERMINAL_FRAGMENTS: &[&str] = &[
+        "\x1b[",
+        "\x1b]0;",
+        "\x1bP",
+        "\x1b\\",
+        "\x1b7",
+        "\x1b8",
+        "\x1bM",
+        "\x1bD",
+        "\x1bc",
+        "\x1b#8",
+        "?",
+        "1",
+        "4",
+        "7",
+        "25",
+        "47",
+        "1049",
+        "999999",
+        ";",
+        ":",
+        "2",
+        "5",
+        "38;2;1;2;3",
+        "H",
+        "J",
+        "K",
+        "A",
+        "B",
+        "C",
+        "D",
+        "r",
+        "m",
+        "h",
+        "l",
+        "@",
+        "P",
+        "L",
+        "M",
+        "S",
+        "T",
+        "X",
+        "b",
+        "\r",
+        "\n",
+        "\t",
+        "\x08",
+        "\x07",
+        "\x18",
+        "中",
+        "e\u{301}",
+        "\u{1F600}",
+        "\u{200D}",
+        "\u{fe0f}",
+        "abc",
+        " ",
+    ];
+
+    fn terminal_output(seed: &[u8]) -> Vec<u8> {
+        seed.iter()
+            .flat_map(|byte| match TERMINAL_FRAGMENTS.get(usize::from(*byte)) {
+                Some(fragment) => fragment.as_bytes().to_vec(),
+                None => vec![*byte],
+            })
+            .collect()
+    }
+
+    fn restore(screen: &Emulator) -> Emulator {
+        let snapshot = screen.snapshot(usize::MAX);
+        let mut restored = Emulator::new(snapshot.cols, snapshot.rows);
+        restored.feed(snapshot.data.as_bytes());
+        restored
+    }
+
+    fn same_cells(left: &[Row], right: &[Row]) -> bool {
+        left.iter()
+            .map(|row| &row.cells)
+            .eq(right.iter().map(|row| &row.cells))
+    }
+
+    // 任意输出与调整大小都不会让模拟器崩溃，快照写入空白终端后屏幕、属性、光标与备用屏幕一致
+    #[test]
+    fn terminal_snapshots_restore_the_screen() {
+        fn property(seed: Vec<u8>, first: (u8, u8), second: (u8, u8)) -> bool {
+            let size =
+                |(cols, rows): (u8, u8)| (usize::from(cols % 100) + 1, usize::from(rows % 40) + 1);
+            let ((cols, rows), (new_cols, new_rows)) = (size(first), size(second));
+            let output = terminal_output(&seed);
+            let (head, tail) = output.split_at(output.len() / 2);
+
+            let mut screen = Emulator::new(cols, rows);
+            screen.feed(head);
+            screen.resize(new_cols, new_rows);
+            screen.feed(tail);
+            let _ = screen.search("a", false);
+
+            let restored = restore(&screen);
+            let primary = match (&screen.primary, &restored.primary) {
+                (Some((grid, _)), Some((restored_grid, _))) => same_cells(grid, restored_grid),
+                (None, None) => true,
+                _ => false,
+            };
+            primary
+                && same_cells(&screen.grid, &restored.grid)
+                && (restored.cursor.row, restored.cursor.col)
+                    == (screen.cursor.row, screen.cursor.col)
+                && restored.cursor.attrs == screen.cursor.attrs
+                && restored.modes == screen.modes
+        }
+        QuickCheck::new()
+            .tests(100)
+            .quickcheck(property as fn(Vec<u8>, (u8, u8), (u8, u8)) -> bool);
+    }
+
+    #[test]
+    fn snapshots_carry_scrollback_with_attributes() {
+        let mut screen = Emulator::new(20, 3);
+        for line in 0..6 {
+            screen.feed(format!("\x1b[31mred\x1b[0m line {}\r\n", line).as_bytes());
+        }
+        let snapshot = screen.snapshot(usize::MAX);
+        assert_eq!(snapshot.scrollback_lines, 4);
+
+        let restored = restore(&screen);
+        assert_eq!(restored.text_lines(), screen.text_lines());
+        assert_eq!(restored.scrollback.len(), 4);
+        let first = &restored.scrollback[0].cells;
+        assert_eq!(first[0].attrs.fg, Color::Indexed(1));
+        assert_eq!(first[4].attrs, Attrs::default());
+        assert_eq!(screen.snapshot(2).scrollback_lines, 2);
+    }
+
+    #[test]
+    fn snapshots_return_to_the_alternate_screen() {
+        let mut screen = Emulator::new(20, 4);
+        screen.feed(b"shell prompt\r\n\x1b[?1049h\x1b[2;5H\x1b[1mtop\x1b[3;3H");
+
+        let mut restored = restore(&screen);
+        assert!(screen.snapshot(0).alternate);
+        assert!(restored.primary.is_some());
+        assert_eq!(restored.screen_text(), screen.screen_text());
+        assert_eq!((restored.cursor.row, restored.cursor.col), (2, 2));
+        assert_eq!(restored.cursor.attrs.flags, BOLD);
+        assert_eq!(restored.grid[1].cells[4].attrs.flags, BOLD);
+
+        screen.feed(b"\x1b[?1049l");
+        restored.feed(b"\x1b[?1049l");
+        assert_eq!(restored.text_lines(), ["shell prompt"]);
+        assert_eq!(restored.screen_text(), screen.screen_text());
+        assert_eq!((restored.cursor.row, restored.cursor.col), (1, 0));
+    }
+
+    #[test]
+    fn resize_leaves_scrollback_rows_alone() {
+        let mut screen = Emulator::new(10, 2);
+        screen.feed(b"0123456789abc\r\nline two\r\nthree");
+        screen.resize(6, 2);
+        assert!(screen.scrollback.iter().all(|row| row.cells.len() == 10));
+        assert!(screen.grid.iter().all(|row| row.cells.len() == 6));
+
+        let restored = restore(&screen);
+        assert_eq!(restored.screen_text(), screen.screen_text());
+        assert_eq!(restored.text_lines(), screen.text_lines());
+    }
+
+    #[test]
+    fn wide_and_combining_characters_use_unicode_widths() {
+        let mut screen = Emulator::new(10, 2);
+        screen.feed("中e\u{301}\u{1F600}a".as_bytes());
+        let cells = &screen.grid[0].cells;
+        assert_eq!(cells[0].ch, '中');
+        assert_eq!(cells[1].ch, WIDE_SPACER);
+        assert_eq!((cells[2].ch, cells[2].marks()), ('e', "\u{301}"));
+        assert_eq!(cells[5].ch, 'a');
+        assert_eq!(screen.cursor.col, 6);
+        assert_eq!(screen.text_lines(), ["中e\u{301}\u{1F600}a"]);
+    }
+
+    #[test]
+    fn snapshots_report_the_last_output_seq() {
+        let mut screen = Emulator::new(10, 2);
+        assert_eq!(screen.feed(b"a"), 1);
+        assert_eq!(screen.feed(b"\x1bc"), 2);
+        assert_eq!(screen.snapshot(0).seq, 2);
+    }
+}
@@ -1,3 +1,5 @@
 // 内置终端模块 - 基于 PTY (伪终端)
+// 解析 PTY 输出，维护屏幕与回滚区
+pub mod emulator;
 pub mod pty_manager;
 pub mod session;
@@ -1,4 +1,9 @@
+use super::emulator::{LineMatch, ScreenSnapshot};
 use super::session::{TerminalConfig, TerminalSession};
+use crate::modules::error::{lock_error, AppError, AppResult, ErrorCode};
+use crate::modules::platform;
+use crate::modules::problem_matcher::{self, DiagnosticCollector};
+use crate::modules::task_runner::{self, RunRequest};
 use base64::{engine::general_purpose, Engine as _};
 use lazy_static::lazy_static;
 use serde_json;
@@ -12,19 +17,30 @@ lazy_static! {
         Arc::new(Mutex::new(HashMap::new()));
 }
 
-const MAX_BUFFER_SIZE: usize = 1024 * 1024 * 2; // 2 MB
+// 重新连接时默认附带的回滚区行数
+const SNAPSHOT_SCROLLBACK_LINES: usize = 1000;
+
+fn session_not_found(session_id: &str) -> AppError {
+    AppError::new(
+        ErrorCode::TerminalSessionNotFound,
+        "terminal.session_not_found",
+    )
+    .arg("id", session_id)
+}
 
 #[tauri::command]
 pub fn create_terminal_session(
     app: AppHandle,
     session_id: String,
     config: TerminalConfig,
-) -> Result<String, String> {
+) -> AppResult<String> {
+    let cwd = config.cwd.clone();
     let session = TerminalSession::new(config)?;
+    problem_matcher::clear_diagnostics(&session_id);
 
     // 启动读取线程
     let master = session.master.clone();
-    let buffer_handle = session.buffer.clone();
+    let screen = session.screen.clone();
     let id = session_id.clone();
     let app_clone = app.clone();
 
@@ -40,6 +56,7 @@ pub fn create_terminal_session(
             }
         };
 
+        let mut collector = DiagnosticCollector::new(Some(cwd), None);
         let mut chunk = [0u8; 8192];
         loop {
             match reader.read(&mut chunk) {
@@ -48,27 +65,21 @@ pub fn create_terminal_session(
                     let data = &chunk[..n];
                     let encoded = general_purpose::STANDARD.encode(data);
 
+                    // 在屏幕锁内发送，快照与输出事件的先后顺序一致；前端按序号丢弃快照已包含的输出
                     {
-                        let data_to_store = if n > MAX_BUFFER_SIZE {
-                            &data[n - MAX_BUFFER_SIZE..]
-                        } else {
-                            data
-                        };
-                        let mut history = buffer_handle.lock().unwrap();
-                        let overflow = history
-                            .len()
-                            .saturating_add(data_to_store.len())
-                            .saturating_sub(MAX_BUFFER_SIZE);
-                        if overflow > 0 {
-                            history.drain(..overflow);
+                        let mut screen = screen.lock().unwrap();
+                        let seq = screen.feed(data);
+                        if let Err(e) = app_clone.emit(
+                            &format!("terminal-output-{}", id),
+                            serde_json::json!({ "seq": seq, "data": encoded }),
+                        ) {
+                            eprintln!("发送数据失败: {}", e);
+                            break;
                         }
-                        history.extend_from_slice(data_to_store);
                     }
 
-                    if let Err(e) = app_clone.emit(&format!("terminal-output-{}", id), encoded) {
-                        eprintln!("发送数据失败: {}", e);
-                        break;
-                    }
+                    let found = collector.feed(data);
+                    problem_matcher::publish_diagnostics(&app_clone, &id, &found);
                 }
                 Err(e) => {
                     eprintln!("读取失败: {}", e);
@@ -77,75 +88,205 @@ pub fn create_terminal_session(
             }
         }
 
+        problem_matcher::publish_diagnostics(&app_clone, &id, &collector.finish());
+
         let mut sessions = SESSIONS.lock().unwrap();
         sessions.remove(&id);
         let _ = app_clone.emit(&format!("terminal-closed-{}", id), ());
         let _ = app_clone.emit("terminal-closed", serde_json::json!({ "sessionId": id }));
     });
 
-    let mut sessions = SESSIONS.lock().map_err(|e| format!("获取锁失败: {}", e))?;
+    let mut sessions = SESSIONS.lock().map_err(lock_error)?;
     sessions.insert(session_id.clone(), session);
 
     Ok(session_id)
 }
 
 #[tauri::command]
-pub fn write_to_terminal(session_id: String, data: String) -> Result<(), String> {
-    let sessions = SESSIONS.lock().map_err(|e| format!("获取锁失败: {}", e))?;
+pub fn write_to_terminal(session_id: String, data: String) -> AppResult<()> {
+    let sessions = SESSIONS.lock().map_err(lock_error)?;
 
     let session = sessions
         .get(&session_id)
-        .ok_or_else(|| format!("会话不存在: {}", session_id))?;
+        .ok_or_else(|| session_not_found(&session_id))?;
 
-    let decoded = general_purpose::STANDARD
-        .decode(&data)
-        .map_err(|e| format!("解码失败: {}", e))?;
+    let decoded = general_purpose::STANDARD.decode(&data).map_err(|e| {
+        AppError::new(ErrorCode::InvalidArgument, "common.decode_failed").arg("error", e)
+    })?;
     session.write(&decoded)?;
 
     Ok(())
 }
 
+// 写入会话 shell 的命令行：命令结束后把退出码写入退出码文件，同一行执行以免被前台程序读走
+fn command_line(command: &str, run_id: &str) -> String {
+    #[cfg(unix)]
+    {
+        let exit_file = task_runner::exit_file(run_id);
+        format!(
+            "{}; printf %s \"$?\" > {}\n",
+            command,
+            platform::shell_quote(&exit_file.to_string_lossy())
+        )
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = run_id;
+        format!("{}\r\n", command)
+    }
+}
+
+// 在会话中运行项目命令，与外部终端一样经过并发上限的准入判断，没有名额时排队
+// 命令结束（unix 上通过退出码文件）或会话关闭后释放名额
+#[tauri::command]
+pub fn run_in_terminal_session(
+    app: AppHandle,
+    session_id: String,
+    project_path: String,
+    command_name: String,
+    command: String,
+) -> AppResult<serde_json::Value> {
+    if !SESSIONS
+        .lock()
+        .map_err(lock_error)?
+        .contains_key(&session_id)
+    {
+        return Err(session_not_found(&session_id));
+    }
+
+    let request = RunRequest {
+        command_id: session_id.clone(),
+        project_path,
+        command: command.clone(),
+        command_name: Some(command_name.clone()),
+        env: Vec::new(),
+        trigger: "terminal".to_string(),
+        hook_depth: 0,
+        priority: 0,
+        long_running: task_runner::is_long_running_script(&command_name),
+    };
+    let launch_app = app.clone();
+    let launch_session_id = session_id.clone();
+    let admission = task_runner::admit_interactive(
+        &app,
+        request,
+        Box::new(move |run_id| {
+            let session_id = launch_session_id;
+            {
+                let sessions = SESSIONS.lock().map_err(lock_error)?;
+                let session = sessions
+                    .get(&session_id)
+                    .ok_or_else(|| session_not_found(&session_id))?;
+                session.write(command_line(&command, run_id).as_bytes())?;
+            }
+            let alive_id = session_id.clone();
+            task_runner::watch_exit_file(&launch_app, run_id, move || {
+                SESSIONS
+                    .lock()
+                    .map(|sessions| sessions.contains_key(&alive_id))
+                    .unwrap_or(false)
+            });
+            Ok(serde_json::json!({
+                "success": true,
+                "command_id": session_id,
+                "command": command_name,
+                "run_id": run_id
+            }))
+        }),
+    )?;
+    Ok(task_runner::admission_result(admission, &session_id))
+}
+
 #[tauri::command]
-pub fn resize_terminal(session_id: String, cols: u16, rows: u16) -> Result<(), String> {
-    let sessions = SESSIONS.lock().map_err(|e| format!("获取锁失败: {}", e))?;
+pub fn resize_terminal(session_id: String, cols: u16, rows: u16) -> AppResult<()> {
+    let sessions = SESSIONS.lock().map_err(lock_error)?;
 
     let session = sessions
         .get(&session_id)
-        .ok_or_else(|| format!("会话不存在: {}", session_id))?;
+        .ok_or_else(|| session_not_found(&session_id))?;
 
     session.resize(cols, rows)?;
     Ok(())
 }
 
 #[tauri::command]
-pub fn close_terminal_session(session_id: String) -> Result<(), String> {
-    let mut sessions = SESSIONS.lock().map_err(|e| format!("获取锁失败: {}", e))?;
+pub fn close_terminal_session(session_id: String) -> AppResult<()> {
+    let mut sessions = SESSIONS.lock().map_err(lock_error)?;
     if let Some(session) = sessions.remove(&session_id) {
         let _ = session.terminate();
     }
     Ok(())
 }
 
+// 结束所有会话，应用退出时调用
+pub fn close_all_sessions() {
+    let sessions: Vec<TerminalSession> = match SESSIONS.lock() {
+        Ok(mut sessions) => sessions.drain().map(|(_, session)| session).collect(),
+        Err(_) => return,
+    };
+    for session in sessions {
+        let _ = session.terminate();
+    }
+}
+
 #[tauri::command]
-pub fn ping_terminal_session(session_id: String) -> Result<bool, String> {
-    let sessions = SESSIONS.lock().map_err(|e| format!("获取锁失败: {}", e))?;
+pub fn ping_terminal_session(session_id: String) -> AppResult<bool> {
+    let sessions = SESSIONS.lock().map_err(lock_error)?;
     Ok(sessions.contains_key(&session_id))
 }
 
+// 获取所有会话的 shell 进程 PID，供端口登记等模块反查归属
+pub fn session_pids() -> Vec<(String, u32)> {
+    let Ok(sessions) = SESSIONS.lock() else {
+        return Vec::new();
+    };
+    sessions
+        .iter()
+        .filter_map(|(id, session)| {
+            let child = session.child.lock().ok()?;
+            let pid = child.as_ref()?.process_id()?;
+            Some((id.clone(), pid))
+        })
+        .collect()
+}
+
+// 屏幕快照与最近的回滚区，重新连接时写入空白终端即可还原
 #[tauri::command]
-pub fn get_terminal_buffer(session_id: String) -> Result<Option<String>, String> {
-    let sessions = SESSIONS.lock().map_err(|e| format!("获取锁失败: {}", e))?;
-    if let Some(session) = sessions.get(&session_id) {
-        let buffer = session
-            .buffer
-            .lock()
-            .map_err(|e| format!("获取缓冲区失败: {}", e))?;
-        if buffer.is_empty() {
-            return Ok(None);
-        }
-        let encoded = general_purpose::STANDARD.encode(&*buffer);
-        Ok(Some(encoded))
-    } else {
-        Ok(None)
-    }
+pub fn get_terminal_snapshot(
+    session_id: String,
+    scrollback_lines: Option<usize>,
+) -> AppResult<Option<ScreenSnapshot>> {
+    let sessions = SESSIONS.lock().map_err(lock_error)?;
+    let Some(session) = sessions.get(&session_id) else {
+        return Ok(None);
+    };
+    let screen = session.screen.lock().map_err(lock_error)?;
+    Ok(Some(screen.snapshot(
+        scrollback_lines.unwrap_or(SNAPSHOT_SCROLLBACK_LINES),
+    )))
+}
+
+// 回滚区与屏幕的纯文本
+#[tauri::command]
+pub fn get_terminal_text(session_id: String) -> AppResult<Vec<String>> {
+    let sessions = SESSIONS.lock().map_err(lock_error)?;
+    let session = sessions
+        .get(&session_id)
+        .ok_or_else(|| session_not_found(&session_id))?;
+    let screen = session.screen.lock().map_err(lock_error)?;
+    Ok(screen.text_lines())
+}
+
+#[tauri::command]
+pub fn search_terminal_scrollback(
+    session_id: String,
+    query: String,
+    case_sensitive: Option<bool>,
+) -> AppResult<Vec<LineMatch>> {
+    let sessions = SESSIONS.lock().map_err(lock_error)?;
+    let session = sessions
+        .get(&session_id)
+        .ok_or_else(|| session_not_found(&session_id))?;
+    let screen = session.screen.lock().map_err(lock_error)?;
+    Ok(screen.search(&query, case_sensitive.unwrap_or(false)))
 }
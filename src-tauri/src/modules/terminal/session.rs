@@ -1,5 +1,10 @@
+use super::emulator::Emulator;
+use crate::modules::env_profile;
+use crate::modules::error::{lock_error, AppError, AppResult, ErrorCode};
+use crate::modules::recovery;
 use portable_pty::{CommandBuilder, MasterPty, PtySize};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::io::Write;
 use std::sync::{Arc, Mutex};
 use std::{env, path::Path};
@@ -9,17 +14,26 @@ pub struct TerminalConfig {
     pub cwd: String,
     pub cols: u16,
     pub rows: u16,
+    // 为该会话加载的环境配置（.env 与 profile）
+    #[serde(default)]
+    pub command_name: Option<String>,
+    #[serde(default)]
+    pub env_profile: Option<String>,
+    // 额外的环境变量，优先级最高
+    #[serde(default)]
+    pub env: HashMap<String, String>,
 }
 
 pub struct TerminalSession {
     pub master: Arc<Mutex<Box<dyn MasterPty + Send>>>,
     pub writer: Arc<Mutex<Box<dyn Write + Send>>>,
     pub child: Arc<Mutex<Option<Box<dyn portable_pty::Child + Send>>>>,
-    pub buffer: Arc<Mutex<Vec<u8>>>,
+    // 解析输出后的屏幕与回滚区，重新连接时用于还原
+    pub screen: Arc<Mutex<Emulator>>,
 }
 
 impl TerminalSession {
-    pub fn new(config: TerminalConfig) -> Result<Self, String> {
+    pub fn new(config: TerminalConfig) -> AppResult<Self> {
         let pty_system = portable_pty::native_pty_system();
 
         let pair = pty_system
@@ -29,49 +43,66 @@ impl TerminalSession {
                 pixel_width: 0,
                 pixel_height: 0,
             })
-            .map_err(|e| format!("创建 PTY 失败: {}", e))?;
+            .map_err(|e| {
+                AppError::new(ErrorCode::TerminalSpawnFailed, "terminal.create_pty_failed")
+                    .arg("error", e)
+            })?;
 
         let mut cmd = build_shell_command()?;
         cmd.cwd(&config.cwd);
         cmd.env("TERM", "xterm-256color");
         cmd.env("COLORTERM", "truecolor");
         cmd.env("LANG", "en_US.UTF-8");
+        // 标记归属，应用异常退出后可以找回遗留的进程
+        let (owner_key, owner_pid) = recovery::owner_tag();
+        cmd.env(owner_key, owner_pid);
+
+        let env_vars = env_profile::resolve_env_vars(
+            &config.cwd,
+            config.command_name.as_deref(),
+            config.env_profile.as_deref(),
+        )
+        .map_err(|e| AppError::plain(ErrorCode::InvalidArgument, e).with_path(&config.cwd))?;
+        for (key, value) in env_vars.into_iter().chain(config.env.clone()) {
+            cmd.env(key, value);
+        }
 
-        let child = pair
-            .slave
-            .spawn_command(cmd)
-            .map_err(|e| format!("启动 shell 失败: {}", e))?;
+        let child = pair.slave.spawn_command(cmd).map_err(|e| {
+            AppError::new(
+                ErrorCode::TerminalSpawnFailed,
+                "terminal.spawn_shell_failed",
+            )
+            .arg("error", e)
+        })?;
 
-        let writer = pair
-            .master
-            .take_writer()
-            .map_err(|e| format!("获取 writer 失败: {}", e))?;
+        let writer = pair.master.take_writer().map_err(|e| {
+            AppError::new(ErrorCode::TerminalIoFailed, "terminal.writer_failed").arg("error", e)
+        })?;
 
         Ok(Self {
             master: Arc::new(Mutex::new(pair.master)),
             writer: Arc::new(Mutex::new(writer)),
             child: Arc::new(Mutex::new(Some(child))),
-            buffer: Arc::new(Mutex::new(Vec::new())),
+            screen: Arc::new(Mutex::new(Emulator::new(
+                usize::from(config.cols),
+                usize::from(config.rows),
+            ))),
         })
     }
 
-    pub fn write(&self, data: &[u8]) -> Result<(), String> {
-        let mut writer = self
-            .writer
-            .lock()
-            .map_err(|e| format!("获取锁失败: {}", e))?;
-        writer
-            .write_all(data)
-            .map_err(|e| format!("写入失败: {}", e))?;
-        writer.flush().map_err(|e| format!("刷新失败: {}", e))?;
+    pub fn write(&self, data: &[u8]) -> AppResult<()> {
+        let mut writer = self.writer.lock().map_err(lock_error)?;
+        writer.write_all(data).map_err(|e| {
+            AppError::new(ErrorCode::TerminalIoFailed, "terminal.write_failed").arg("error", e)
+        })?;
+        writer.flush().map_err(|e| {
+            AppError::new(ErrorCode::TerminalIoFailed, "terminal.flush_failed").arg("error", e)
+        })?;
         Ok(())
     }
 
-    pub fn resize(&self, cols: u16, rows: u16) -> Result<(), String> {
-        let master = self
-            .master
-            .lock()
-            .map_err(|e| format!("获取锁失败: {}", e))?;
+    pub fn resize(&self, cols: u16, rows: u16) -> AppResult<()> {
+        let master = self.master.lock().map_err(lock_error)?;
         master
             .resize(PtySize {
                 rows,
@@ -79,25 +110,31 @@ impl TerminalSession {
                 pixel_width: 0,
                 pixel_height: 0,
             })
-            .map_err(|e| format!("调整大小失败: {}", e))?;
+            .map_err(|e| {
+                AppError::new(ErrorCode::TerminalIoFailed, "terminal.resize_failed").arg("error", e)
+            })?;
+        self.screen
+            .lock()
+            .map_err(lock_error)?
+            .resize(usize::from(cols), usize::from(rows));
         Ok(())
     }
 
-    pub fn terminate(&self) -> Result<(), String> {
-        let mut child_guard = self
-            .child
-            .lock()
-            .map_err(|e| format!("获取锁失败: {}", e))?;
+    pub fn terminate(&self) -> AppResult<()> {
+        let mut child_guard = self.child.lock().map_err(lock_error)?;
 
         if let Some(mut child) = child_guard.take() {
-            child.kill().map_err(|e| format!("终止失败: {}", e))?;
+            child.kill().map_err(|e| {
+                AppError::new(ErrorCode::TerminalIoFailed, "terminal.kill_failed").arg("error", e)
+            })?;
         }
 
         Ok(())
     }
 }
 
-fn build_shell_command() -> Result<CommandBuilder, String> {
+// 内置终端启动的交互式 shell
+pub fn build_shell_command() -> AppResult<CommandBuilder> {
     #[cfg(target_os = "windows")]
     {
         let mut cmd = CommandBuilder::new("powershell.exe");
@@ -113,7 +150,11 @@ fn build_shell_command() -> Result<CommandBuilder, String> {
 
     #[cfg(not(target_os = "windows"))]
     {
-        let default_shell = if cfg!(target_os = "macos") { "zsh" } else { "bash" };
+        let default_shell = if cfg!(target_os = "macos") {
+            "zsh"
+        } else {
+            "bash"
+        };
         let shell = env::var("SHELL").unwrap_or_else(|_| default_shell.to_string());
         let shell_name = Path::new(&shell)
             .file_name()
@@ -0,0 +1,69 @@
+// Alacritty 后端：没有远程控制接口，每条命令打开一个独立窗口，启动后不再跟踪
+use super::{unsupported, BackendKind, LaunchSpec, TerminalBackend, TerminalTab};
+use crate::modules::error::{AppError, AppResult, ErrorCode};
+use std::process::{Command, Stdio};
+
+pub struct AlacrittyBackend;
+
+impl TerminalBackend for AlacrittyBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Alacritty
+    }
+
+    fn is_available(&self) -> bool {
+        super::cli_available("alacritty", "--version")
+    }
+
+    fn supports_remote_control(&self) -> bool {
+        false
+    }
+
+    // 每个窗口都是独立进程，没有需要提前启动的实例
+    fn start_instance(&self, instance: &str, _working_dir: &str) -> AppResult<String> {
+        Ok(instance.to_string())
+    }
+
+    // 返回窗口进程的 pid，仅用于展示
+    fn open(&self, _instance: &str, spec: &LaunchSpec) -> AppResult<String> {
+        let mut child = Command::new("alacritty")
+            .arg(format!("--working-directory={}", spec.working_dir))
+            .arg(format!("--title={}", spec.title()))
+            .arg("--hold")
+            .arg("-e")
+            .args(spec.argv())
+            .envs(spec.env.iter().cloned())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| {
+                AppError::new(
+                    ErrorCode::TerminalBackendUnavailable,
+                    "terminal_backend.spawn_failed",
+                )
+                .arg("program", "alacritty")
+                .arg("error", e)
+            })?;
+        let pid = child.id();
+        std::thread::spawn(move || {
+            let _ = child.wait();
+        });
+        Ok(pid.to_string())
+    }
+
+    fn list(&self, _instance: &str) -> AppResult<Vec<TerminalTab>> {
+        Err(unsupported(self.kind(), "list"))
+    }
+
+    fn close(&self, _instance: &str, _tab_id: &str) -> AppResult<()> {
+        Err(unsupported(self.kind(), "close"))
+    }
+
+    fn send_text(&self, _instance: &str, _tab_id: &str, _text: &str) -> AppResult<()> {
+        Err(unsupported(self.kind(), "send_text"))
+    }
+
+    fn capture_text(&self, _instance: &str, _tab_id: &str) -> AppResult<String> {
+        Err(unsupported(self.kind(), "capture_text"))
+    }
+}
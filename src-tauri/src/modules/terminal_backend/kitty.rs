@@ -0,0 +1,98 @@
+// kitty 后端：通过远程控制协议直接连接 socket，每个实例对应一个监听 socket
+use super::{BackendKind, LaunchSpec, TerminalBackend, TerminalTab};
+use crate::modules::error::AppResult;
+use crate::modules::kitty::connection::{
+    check_kitty_installed, get_socket_path, start_kitty_instance, test_kitty_connection,
+};
+use crate::modules::kitty::core::KittyConfig;
+use crate::modules::kitty::protocol::{self, match_window, CloseWindow, GetText, SendText};
+use crate::modules::kitty::tabs::{create_kitty_tab, list_kitty_tabs};
+
+#[derive(Default)]
+pub struct KittyBackend {
+    pub config: KittyConfig,
+}
+
+impl KittyBackend {
+    pub fn socket_path(&self, instance: &str) -> String {
+        get_socket_path(instance, &self.config)
+    }
+
+    // 已经是 socket 地址时直接使用，否则按实例名生成
+    fn resolve_socket(&self, instance: &str) -> String {
+        if instance.starts_with("unix:") {
+            instance.to_string()
+        } else {
+            self.socket_path(instance)
+        }
+    }
+}
+
+impl TerminalBackend for KittyBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Kitty
+    }
+
+    fn is_available(&self) -> bool {
+        check_kitty_installed().unwrap_or(false)
+    }
+
+    fn start_instance(&self, instance: &str, working_dir: &str) -> AppResult<String> {
+        let socket_path = self.resolve_socket(instance);
+        if !test_kitty_connection(&socket_path)? {
+            start_kitty_instance(&socket_path, working_dir, &self.config)?;
+        }
+        Ok(socket_path)
+    }
+
+    fn open(&self, instance: &str, spec: &LaunchSpec) -> AppResult<String> {
+        create_kitty_tab(
+            &self.resolve_socket(instance),
+            &spec.command_id,
+            &spec.project_name,
+            &spec.command_name,
+            &spec.working_dir,
+            &spec.command,
+            &spec.env,
+        )
+    }
+
+    // kitty 的标签页可以包含多个窗口，这里按窗口列出，id 为窗口 id
+    fn list(&self, instance: &str) -> AppResult<Vec<TerminalTab>> {
+        let os_windows = list_kitty_tabs(&self.resolve_socket(instance))?;
+        let tabs = os_windows
+            .into_iter()
+            .flat_map(|os_window| os_window.tabs)
+            .flat_map(|tab| tab.windows)
+            .map(|window| TerminalTab {
+                id: window.id.to_string(),
+                title: window.title,
+                working_dir: Some(window.cwd).filter(|cwd| !cwd.is_empty()),
+                pid: window.pid,
+            })
+            .collect();
+        Ok(tabs)
+    }
+
+    fn close(&self, instance: &str, tab_id: &str) -> AppResult<()> {
+        let request = CloseWindow {
+            matches: match_window(tab_id),
+            ignore_no_match: false,
+        };
+        protocol::send(&self.resolve_socket(instance), &request)
+    }
+
+    fn send_text(&self, instance: &str, tab_id: &str, text: &str) -> AppResult<()> {
+        let request = SendText::text(match_window(tab_id), text);
+        protocol::send(&self.resolve_socket(instance), &request)
+    }
+
+    fn capture_text(&self, instance: &str, tab_id: &str) -> AppResult<String> {
+        let request = GetText {
+            matches: match_window(tab_id),
+            extent: "all".to_string(),
+            ansi: false,
+        };
+        protocol::send(&self.resolve_socket(instance), &request)
+    }
+}
@@ -0,0 +1,435 @@
+// 外部终端后端
+//
+// 把 kitty、tmux、WezTerm、Alacritty 统一到 TerminalBackend 接口之后：
+// - 实例：kitty 的远程控制 socket、tmux 会话、WezTerm 的 workspace，同一实例下可以打开多个标签页
+// - 标签页中的命令与工作目录都通过位置参数传给 TAB_SCRIPT，不拼接进脚本
+// - Alacritty 没有远程控制，只能打开窗口，其余操作返回 terminal_operation_unsupported
+// - 没有可用的外部终端时，可以回退到内置 PTY 终端，由前端创建会话执行命令
+pub mod alacritty;
+pub mod kitty;
+pub mod tmux;
+pub mod wezterm;
+
+use crate::modules::error::{lock_error, AppError, AppResult, ErrorCode};
+use crate::modules::i18n;
+use crate::modules::kitty::executor::{
+    launch_with_kitten, prepare_terminal_command, PreparedCommand,
+};
+use crate::modules::kitty::tabs::TAB_SCRIPT;
+use crate::modules::ports;
+use crate::modules::process_runner::{self, OperationKind};
+use crate::modules::storage;
+use crate::modules::task_runner::{self, RunRequest};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::process::{Command, Output};
+use std::sync::{Arc, Mutex};
+use tauri::AppHandle;
+
+const SETTINGS_STORE: &str = "terminal_backend";
+
+// 自动选择时的优先顺序
+const AUTO_ORDER: [BackendKind; 4] = [
+    BackendKind::Kitty,
+    BackendKind::Wezterm,
+    BackendKind::Tmux,
+    BackendKind::Alacritty,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BackendKind {
+    Kitty,
+    Tmux,
+    Wezterm,
+    Alacritty,
+}
+
+impl BackendKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BackendKind::Kitty => "kitty",
+            BackendKind::Tmux => "tmux",
+            BackendKind::Wezterm => "wezterm",
+            BackendKind::Alacritty => "alacritty",
+        }
+    }
+}
+
+// 在标签页中启动的命令
+#[derive(Debug, Clone)]
+pub struct LaunchSpec {
+    pub command_id: String,
+    pub project_name: String,
+    pub command_name: String,
+    pub working_dir: String,
+    // 已经解析完成的 shell 命令
+    pub command: String,
+    pub env: Vec<(String, String)>,
+}
+
+impl LaunchSpec {
+    pub fn title(&self) -> String {
+        format!(
+            "{} - {} ({})",
+            self.project_name, self.command_name, self.command_id
+        )
+    }
+
+    // 标签页中实际执行的程序与参数
+    pub fn argv(&self) -> Vec<String> {
+        vec![
+            "bash".to_string(),
+            "-lc".to_string(),
+            TAB_SCRIPT.to_string(),
+            "runproject".to_string(),
+            self.command.clone(),
+            self.working_dir.clone(),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalTab {
+    pub id: String,
+    pub title: String,
+    pub working_dir: Option<String>,
+    pub pid: Option<u32>,
+}
+
+pub trait TerminalBackend: Send + Sync {
+    fn kind(&self) -> BackendKind;
+
+    fn is_available(&self) -> bool;
+
+    // 为 false 时只支持 start_instance 与 open
+    fn supports_remote_control(&self) -> bool {
+        true
+    }
+
+    // 确保实例可用，返回实例的实际标识（socket 路径、会话名等）
+    fn start_instance(&self, instance: &str, working_dir: &str) -> AppResult<String>;
+
+    // 在实例中打开新的标签页执行命令，返回标签页 id
+    fn open(&self, instance: &str, spec: &LaunchSpec) -> AppResult<String>;
+
+    fn list(&self, instance: &str) -> AppResult<Vec<TerminalTab>>;
+
+    fn close(&self, instance: &str, tab_id: &str) -> AppResult<()>;
+
+    // 原样写入标签页，不追加换行
+    fn send_text(&self, instance: &str, tab_id: &str, text: &str) -> AppResult<()>;
+
+    // 读取标签页中的文本，包含回滚区
+    fn capture_text(&self, instance: &str, tab_id: &str) -> AppResult<String>;
+}
+
+pub fn backend_for(kind: BackendKind) -> Box<dyn TerminalBackend> {
+    match kind {
+        BackendKind::Kitty => Box::new(kitty::KittyBackend {
+            config: crate::modules::kitty::config::load(),
+        }),
+        BackendKind::Tmux => Box::new(tmux::TmuxBackend),
+        BackendKind::Wezterm => Box::new(wezterm::WeztermBackend),
+        BackendKind::Alacritty => Box::new(alacritty::AlacrittyBackend),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TerminalBackendSettings {
+    // 指定的或自动选择的外部终端都不可用时，改用内置终端
+    pub fallback_to_builtin: bool,
+}
+
+impl Default for TerminalBackendSettings {
+    fn default() -> Self {
+        Self {
+            fallback_to_builtin: true,
+        }
+    }
+}
+
+lazy_static! {
+    static ref SETTINGS: Arc<Mutex<Option<TerminalBackendSettings>>> = Arc::new(Mutex::new(None));
+}
+
+fn with_settings<R>(f: impl FnOnce(&mut TerminalBackendSettings) -> R) -> AppResult<R> {
+    let mut guard = SETTINGS.lock().map_err(lock_error)?;
+    Ok(f(guard.get_or_insert_with(|| storage::load(SETTINGS_STORE))))
+}
+
+// preferred 为 None 时按 AUTO_ORDER 自动选择；没有可用后端时返回 None
+pub fn resolve_backend(preferred: Option<BackendKind>) -> Option<Box<dyn TerminalBackend>> {
+    let candidates: Vec<BackendKind> = match preferred {
+        Some(kind) => vec![kind],
+        None => AUTO_ORDER.to_vec(),
+    };
+    candidates
+        .into_iter()
+        .map(backend_for)
+        .find(|backend| backend.is_available())
+}
+
+pub fn unsupported(kind: BackendKind, operation: &str) -> AppError {
+    AppError::new(
+        ErrorCode::TerminalOperationUnsupported,
+        "terminal_backend.unsupported",
+    )
+    .arg("backend", kind.as_str())
+    .arg("operation", operation)
+}
+
+// tmux、WezTerm 等命令行控制的统一调用，失败时附带 stderr
+pub fn run_cli(program: &str, args: &[String], action: &'static str) -> AppResult<Output> {
+    let mut command = Command::new(program);
+    command.args(args);
+    let output = process_runner::run(command, OperationKind::Terminal).map_err(|e| {
+        e.into_app_error(|e| {
+            AppError::new(
+                ErrorCode::TerminalBackendUnavailable,
+                "terminal_backend.spawn_failed",
+            )
+            .arg("program", program)
+            .arg("error", e)
+        })
+    })?;
+    if output.status.success() {
+        Ok(output)
+    } else {
+        Err(AppError::new(ErrorCode::TerminalBackendFailed, action).with_output(&output))
+    }
+}
+
+// 程序能否执行，用于检测是否安装
+pub fn cli_available(program: &str, version_arg: &str) -> bool {
+    let mut command = Command::new(program);
+    command.arg(version_arg);
+    process_runner::run(command, OperationKind::Terminal).is_ok_and(|o| o.status.success())
+}
+
+pub fn stdout_text(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendInfo {
+    pub kind: BackendKind,
+    pub available: bool,
+    pub remote_control: bool,
+}
+
+// ---------- Tauri 命令 ----------
+
+#[tauri::command(async)]
+pub fn list_terminal_backends() -> Vec<BackendInfo> {
+    AUTO_ORDER
+        .iter()
+        .map(|kind| {
+            let backend = backend_for(*kind);
+            BackendInfo {
+                kind: *kind,
+                available: backend.is_available(),
+                remote_control: backend.supports_remote_control(),
+            }
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn get_terminal_backend_settings() -> AppResult<TerminalBackendSettings> {
+    with_settings(|settings| settings.clone())
+}
+
+#[tauri::command]
+pub fn set_terminal_backend_settings(
+    settings: TerminalBackendSettings,
+) -> AppResult<TerminalBackendSettings> {
+    let snapshot = with_settings(|current| {
+        *current = settings;
+        current.clone()
+    })?;
+    storage::save(SETTINGS_STORE, &snapshot)?;
+    Ok(snapshot)
+}
+
+// 在外部终端中执行项目命令，instance 相同的命令会打开在同一个实例中（kitty 按分组设置选择实例）
+// 回退到内置终端时返回 backend = "builtin" 与最终命令，由前端创建会话执行
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
+pub fn execute_command_in_terminal(
+    app: AppHandle,
+    backend: Option<BackendKind>,
+    instance: String,
+    command_id: String,
+    working_dir: String,
+    command: String,
+    node_version: Option<String>,
+    project_name: String,
+    command_name: String,
+    package_manager: String,
+    env_profile: Option<String>,
+    arg_values: Option<BTreeMap<String, serde_json::Value>>,
+    install_dependencies: Option<bool>,
+    workspace_path: Option<String>,
+) -> AppResult<serde_json::Value> {
+    let prepared = prepare_terminal_command(
+        &working_dir,
+        &command,
+        node_version.as_deref(),
+        &project_name,
+        &command_name,
+        &package_manager,
+        env_profile.as_deref(),
+        arg_values.as_ref(),
+        install_dependencies,
+    )?;
+    let mut output = prepared.output;
+
+    let Some(terminal) = resolve_backend(backend) else {
+        if !with_settings(|settings| settings.fallback_to_builtin)? {
+            return Err(match backend {
+                Some(kind) => AppError::new(
+                    ErrorCode::TerminalBackendUnavailable,
+                    "terminal_backend.not_available",
+                )
+                .arg("backend", kind.as_str()),
+                None => AppError::new(
+                    ErrorCode::TerminalBackendUnavailable,
+                    "terminal_backend.none_available",
+                ),
+            });
+        }
+        i18n::push_line(&mut output, "terminal_backend.fallback_builtin", &[]);
+        return Ok(serde_json::json!({
+            "success": true,
+            "backend": "builtin",
+            "fallback": true,
+            "finalCommand": prepared.final_command,
+            "env": prepared.env.into_iter().collect::<BTreeMap<_, _>>(),
+            "output": output,
+            "command_id": command_id
+        }));
+    };
+
+    let kind = terminal.kind();
+    // kitty 交给远程控制执行器，按分组设置选择实例并登记标签页状态与输出采集
+    if kind == BackendKind::Kitty {
+        let mut result = launch_with_kitten(
+            &app,
+            command_id,
+            project_name,
+            command_name,
+            working_dir,
+            workspace_path.as_deref(),
+            PreparedCommand { output, ..prepared },
+        )?;
+        result["backend"] = serde_json::json!(kind);
+        return Ok(result);
+    }
+    let terminal: Arc<dyn TerminalBackend> = Arc::from(terminal);
+    let request = RunRequest {
+        command_id: command_id.clone(),
+        project_path: working_dir.clone(),
+        command: prepared.final_command.clone(),
+        command_name: Some(command_name.clone()),
+        env: Vec::new(),
+        trigger: kind.as_str().to_string(),
+        hook_depth: 0,
+        priority: 0,
+        long_running: task_runner::is_long_running_script(&command_name),
+    };
+    let launch_app = app.clone();
+    let launch_command_id = command_id.clone();
+    let admission = task_runner::admit_interactive(
+        &app,
+        request,
+        Box::new(move |run_id| {
+            let instance = terminal.start_instance(&instance, &working_dir)?;
+            // 标签页中的命令结束时写入退出码文件，据此结束运行记录并释放名额
+            let mut env = prepared.env;
+            env.push((
+                task_runner::EXIT_FILE_ENV.to_string(),
+                task_runner::exit_file(run_id).to_string_lossy().to_string(),
+            ));
+            let spec = LaunchSpec {
+                command_id: launch_command_id.clone(),
+                project_name: project_name.clone(),
+                command_name: command_name.clone(),
+                working_dir,
+                command: prepared.final_command,
+                env,
+            };
+            let tab_id = terminal.open(&instance, &spec)?;
+            i18n::push_line(
+                &mut output,
+                "terminal_backend.tab_opened",
+                &[("backend", &kind.as_str())],
+            );
+            task_runner::watch_exit_file(
+                &launch_app,
+                run_id,
+                tab_alive(terminal.clone(), instance.clone(), tab_id.clone()),
+            );
+
+            Ok(serde_json::json!({
+                "success": true,
+                "backend": kind,
+                "fallback": false,
+                "instance": instance,
+                "tabId": tab_id,
+                "output": output,
+                "project": project_name,
+                "command": command_name,
+                "command_id": launch_command_id,
+                "run_id": run_id
+            }))
+        }),
+    )?;
+    Ok(task_runner::admission_result(admission, &command_id))
+}
+
+// 标签页是否仍然存在；无法列出标签页的后端按窗口进程判断，实例已不存在时视为已关闭
+fn tab_alive(
+    terminal: Arc<dyn TerminalBackend>,
+    instance: String,
+    tab_id: String,
+) -> impl Fn() -> bool + Send + 'static {
+    move || match terminal.list(&instance) {
+        Ok(tabs) => tabs.iter().any(|tab| tab.id == tab_id),
+        Err(_) if terminal.supports_remote_control() => false,
+        Err(_) => tab_id.parse().map(ports::is_pid_alive).unwrap_or(true),
+    }
+}
+
+#[tauri::command(async)]
+pub fn list_terminal_tabs(backend: BackendKind, instance: String) -> AppResult<Vec<TerminalTab>> {
+    backend_for(backend).list(&instance)
+}
+
+#[tauri::command(async)]
+pub fn close_terminal_tab(backend: BackendKind, instance: String, tab_id: String) -> AppResult<()> {
+    backend_for(backend).close(&instance, &tab_id)
+}
+
+#[tauri::command(async)]
+pub fn send_terminal_text(
+    backend: BackendKind,
+    instance: String,
+    tab_id: String,
+    text: String,
+) -> AppResult<()> {
+    backend_for(backend).send_text(&instance, &tab_id, &text)
+}
+
+#[tauri::command(async)]
+pub fn capture_terminal_text(
+    backend: BackendKind,
+    instance: String,
+    tab_id: String,
+) -> AppResult<String> {
+    backend_for(backend).capture_text(&instance, &tab_id)
+}
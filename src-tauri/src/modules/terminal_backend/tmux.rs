@@ -0,0 +1,149 @@
+// tmux 后端：每个实例对应一个后台会话，命令在会话的新窗口中运行
+// 会话以 detached 方式创建，可以通过 tmux attach -t <会话名> 查看
+use super::{run_cli, stdout_text, BackendKind, LaunchSpec, TerminalBackend, TerminalTab};
+use crate::modules::error::AppResult;
+
+const SESSION_PREFIX: &str = "runproject";
+
+pub struct TmuxBackend;
+
+// tmux 会话名不能包含 . 与 :，统一替换为下划线；已经是会话名时原样返回
+pub fn session_name(instance: &str) -> String {
+    let sanitized: String = instance
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if sanitized.starts_with(&format!("{}-", SESSION_PREFIX)) {
+        sanitized
+    } else {
+        format!("{}-{}", SESSION_PREFIX, sanitized)
+    }
+}
+
+fn tmux(args: &[String], action: &'static str) -> AppResult<String> {
+    run_cli("tmux", args, action).map(|output| stdout_text(&output))
+}
+
+impl TerminalBackend for TmuxBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Tmux
+    }
+
+    fn is_available(&self) -> bool {
+        super::cli_available("tmux", "-V")
+    }
+
+    fn start_instance(&self, instance: &str, working_dir: &str) -> AppResult<String> {
+        let session = session_name(instance);
+        // = 前缀表示精确匹配会话名
+        let exists = tmux(
+            &["has-session".to_string(), format!("-t={}", session)],
+            "terminal_backend.tmux_failed",
+        )
+        .is_ok();
+        if !exists {
+            tmux(
+                &[
+                    "new-session".to_string(),
+                    "-d".to_string(),
+                    format!("-s{}", session),
+                    format!("-c{}", working_dir),
+                ],
+                "terminal_backend.tmux_failed",
+            )?;
+        }
+        Ok(session)
+    }
+
+    // 返回新窗口中 pane 的 id（如 %3）
+    fn open(&self, instance: &str, spec: &LaunchSpec) -> AppResult<String> {
+        let mut args = vec![
+            "new-window".to_string(),
+            "-d".to_string(),
+            "-P".to_string(),
+            "-F#{pane_id}".to_string(),
+            format!("-t={}:", session_name(instance)),
+            format!("-c{}", spec.working_dir),
+            format!("-n{}", spec.title()),
+        ];
+        args.extend(
+            spec.env
+                .iter()
+                .map(|(key, value)| format!("-e{}={}", key, value)),
+        );
+        args.extend(spec.argv());
+        Ok(tmux(&args, "terminal_backend.tmux_failed")?
+            .trim()
+            .to_string())
+    }
+
+    fn list(&self, instance: &str) -> AppResult<Vec<TerminalTab>> {
+        let output = tmux(
+            &[
+                "list-panes".to_string(),
+                "-s".to_string(),
+                format!("-t={}", session_name(instance)),
+                "-F#{pane_id}\t#{window_name}\t#{pane_pid}\t#{pane_current_path}".to_string(),
+            ],
+            "terminal_backend.tmux_failed",
+        )?;
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(4, '\t');
+                let id = fields.next()?.to_string();
+                let title = fields.next().unwrap_or_default().to_string();
+                let pid = fields.next().and_then(|pid| pid.parse().ok());
+                let working_dir = fields.next().map(str::to_string);
+                Some(TerminalTab {
+                    id,
+                    title,
+                    working_dir,
+                    pid,
+                })
+            })
+            .collect())
+    }
+
+    fn close(&self, _instance: &str, tab_id: &str) -> AppResult<()> {
+        tmux(
+            &["kill-pane".to_string(), format!("-t{}", tab_id)],
+            "terminal_backend.tmux_failed",
+        )
+        .map(|_| ())
+    }
+
+    // -l 按字面发送，不解析按键名
+    fn send_text(&self, _instance: &str, tab_id: &str, text: &str) -> AppResult<()> {
+        tmux(
+            &[
+                "send-keys".to_string(),
+                format!("-t{}", tab_id),
+                "-l".to_string(),
+                "--".to_string(),
+                text.to_string(),
+            ],
+            "terminal_backend.tmux_failed",
+        )
+        .map(|_| ())
+    }
+
+    fn capture_text(&self, _instance: &str, tab_id: &str) -> AppResult<String> {
+        tmux(
+            &[
+                "capture-pane".to_string(),
+                "-p".to_string(),
+                "-J".to_string(),
+                "-S-".to_string(),
+                format!("-t{}", tab_id),
+            ],
+            "terminal_backend.tmux_failed",
+        )
+    }
+}
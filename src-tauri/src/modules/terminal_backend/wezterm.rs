@@ -0,0 +1,180 @@
+// WezTerm 后端：通过 wezterm cli 控制正在运行的 WezTerm
+// 每个实例对应一个 workspace，第一次打开时新建窗口，之后的命令作为该窗口的新标签页
+use super::{run_cli, stdout_text, BackendKind, LaunchSpec, TerminalBackend, TerminalTab};
+use crate::modules::error::{AppError, AppResult, ErrorCode};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+const START_RETRIES: u32 = 20;
+const START_RETRY_DELAY: Duration = Duration::from_millis(500);
+// get-text 的起始行，负数表示回滚区，足够覆盖默认的回滚长度
+const CAPTURE_START_LINE: &str = "-100000";
+
+pub struct WeztermBackend;
+
+fn wezterm(args: &[String]) -> AppResult<String> {
+    run_cli("wezterm", args, "terminal_backend.wezterm_failed").map(|output| stdout_text(&output))
+}
+
+fn list_panes() -> AppResult<Vec<serde_json::Value>> {
+    let output = wezterm(&[
+        "cli".to_string(),
+        "list".to_string(),
+        "--format".to_string(),
+        "json".to_string(),
+    ])?;
+    serde_json::from_str(&output).map_err(|e| {
+        AppError::new(
+            ErrorCode::TerminalBackendFailed,
+            "terminal_backend.parse_failed",
+        )
+        .arg("error", e)
+    })
+}
+
+impl TerminalBackend for WeztermBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Wezterm
+    }
+
+    fn is_available(&self) -> bool {
+        super::cli_available("wezterm", "--version")
+    }
+
+    // wezterm cli 需要一个正在运行的 WezTerm，没有时启动一个并等待其就绪
+    fn start_instance(&self, instance: &str, working_dir: &str) -> AppResult<String> {
+        if list_panes().is_ok() {
+            return Ok(instance.to_string());
+        }
+
+        let mut child = Command::new("wezterm")
+            .args(["start", "--cwd", working_dir])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| {
+                AppError::new(
+                    ErrorCode::TerminalBackendUnavailable,
+                    "terminal_backend.spawn_failed",
+                )
+                .arg("program", "wezterm")
+                .arg("error", e)
+            })?;
+        std::thread::spawn(move || {
+            let _ = child.wait();
+        });
+
+        for _ in 0..START_RETRIES {
+            std::thread::sleep(START_RETRY_DELAY);
+            if list_panes().is_ok() {
+                return Ok(instance.to_string());
+            }
+        }
+        Err(AppError::new(
+            ErrorCode::TerminalBackendFailed,
+            "terminal_backend.start_timeout",
+        )
+        .arg("backend", "wezterm"))
+    }
+
+    // 返回新 pane 的 id；wezterm cli spawn 不支持设置环境变量，通过 env 传入
+    fn open(&self, instance: &str, spec: &LaunchSpec) -> AppResult<String> {
+        let window_id = list_panes()?
+            .iter()
+            .find(|pane| pane["workspace"].as_str() == Some(instance))
+            .and_then(|pane| pane["window_id"].as_u64());
+
+        let mut args = vec!["cli".to_string(), "spawn".to_string()];
+        match window_id {
+            Some(id) => args.extend(["--window-id".to_string(), id.to_string()]),
+            None => args.extend([
+                "--new-window".to_string(),
+                "--workspace".to_string(),
+                instance.to_string(),
+            ]),
+        }
+        args.extend([
+            "--cwd".to_string(),
+            spec.working_dir.clone(),
+            "--".to_string(),
+            "env".to_string(),
+            "--".to_string(),
+        ]);
+        args.extend(
+            spec.env
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value)),
+        );
+        args.extend(spec.argv());
+
+        let pane_id = wezterm(&args)?.trim().to_string();
+        // 标签页标题默认取自进程名，设置为与 kitty 一致的格式
+        let _ = wezterm(&[
+            "cli".to_string(),
+            "set-tab-title".to_string(),
+            "--pane-id".to_string(),
+            pane_id.clone(),
+            spec.title(),
+        ]);
+        Ok(pane_id)
+    }
+
+    fn list(&self, instance: &str) -> AppResult<Vec<TerminalTab>> {
+        Ok(list_panes()?
+            .iter()
+            .filter(|pane| pane["workspace"].as_str() == Some(instance))
+            .filter_map(|pane| {
+                let title = pane["tab_title"]
+                    .as_str()
+                    .filter(|title| !title.is_empty())
+                    .or(pane["title"].as_str())
+                    .unwrap_or_default();
+                Some(TerminalTab {
+                    id: pane["pane_id"].as_u64()?.to_string(),
+                    title: title.to_string(),
+                    // cwd 形如 file://host/path
+                    working_dir: pane["cwd"]
+                        .as_str()
+                        .and_then(|cwd| url::Url::parse(cwd).ok())
+                        .map(|url| url.path().to_string()),
+                    pid: None,
+                })
+            })
+            .collect())
+    }
+
+    fn close(&self, _instance: &str, tab_id: &str) -> AppResult<()> {
+        wezterm(&[
+            "cli".to_string(),
+            "kill-pane".to_string(),
+            "--pane-id".to_string(),
+            tab_id.to_string(),
+        ])
+        .map(|_| ())
+    }
+
+    // --no-paste 直接发送，不使用括号粘贴模式
+    fn send_text(&self, _instance: &str, tab_id: &str, text: &str) -> AppResult<()> {
+        wezterm(&[
+            "cli".to_string(),
+            "send-text".to_string(),
+            "--pane-id".to_string(),
+            tab_id.to_string(),
+            "--no-paste".to_string(),
+            "--".to_string(),
+            text.to_string(),
+        ])
+        .map(|_| ())
+    }
+
+    fn capture_text(&self, _instance: &str, tab_id: &str) -> AppResult<String> {
+        wezterm(&[
+            "cli".to_string(),
+            "get-text".to_string(),
+            "--pane-id".to_string(),
+            tab_id.to_string(),
+            format!("--start-line={}", CAPTURE_START_LINE),
+        ])
+    }
+}
@@ -0,0 +1,526 @@
+// 文件监听重跑模块
+//
+// 为任意项目命令提供「文件变化时重新运行」模式：
+// - 按 glob 过滤变化的文件，遵循项目到仓库根目录各级以及项目内各子目录的 .gitignore 与 .git/info/exclude，
+//   node_modules 始终排除（monorepo 中的子包通常没有自己的忽略规则）
+// - 变化事件经过防抖合并后触发一次运行，运行中的上一次任务会被取消
+// - 运行结果在通过/失败之间切换时推送 watch-status-changed 事件
+//
+// 注意：命令自身会写入被监听的文件时（如代码生成），需要在 ignore 中排除其输出目录
+use crate::modules::command_template;
+use crate::modules::env_profile;
+use crate::modules::i18n;
+use crate::modules::kitty::executor;
+use crate::modules::project_scanner;
+use crate::modules::task_runner::{self, RunRecord, RunRequest, RunStatus};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::WalkBuilder;
+use lazy_static::lazy_static;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+const DEFAULT_DEBOUNCE_MS: u64 = 300;
+// 取消上一次运行后，最多等待其退出的时间
+const CANCEL_WAIT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchConfig {
+    pub project_path: String,
+    pub command_name: String,
+    #[serde(default)]
+    pub package_manager: Option<String>,
+    #[serde(default)]
+    pub node_version: Option<String>,
+    // 为空时监听项目下所有文件
+    #[serde(default)]
+    pub globs: Vec<String>,
+    // 额外排除的 glob，在 .gitignore 之外生效
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    #[serde(default)]
+    pub debounce_ms: Option<u64>,
+    #[serde(default)]
+    pub env_profile: Option<String>,
+    #[serde(default)]
+    pub arg_values: Option<BTreeMap<String, serde_json::Value>>,
+    // 开始监听时立即运行一次
+    #[serde(default)]
+    pub run_on_start: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchInfo {
+    pub watch_id: String,
+    pub project_path: String,
+    pub command_name: String,
+    pub command: String,
+    pub globs: Vec<String>,
+    pub ignore: Vec<String>,
+    pub debounce_ms: u64,
+    pub last_status: Option<RunStatus>,
+    pub last_run_id: Option<String>,
+    pub current_run_id: Option<String>,
+    pub run_count: u32,
+}
+
+struct WatchHandle {
+    info: WatchInfo,
+    env_profile: Option<String>,
+    // 持有 watcher，移除时随之停止监听
+    _watcher: RecommendedWatcher,
+}
+
+lazy_static! {
+    static ref WATCHES: Arc<Mutex<HashMap<String, WatchHandle>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+}
+
+fn watch_id_for(project_path: &str, command_name: &str) -> String {
+    format!("watch:{}:{}", project_path, command_name)
+}
+
+fn build_globset(patterns: &[String]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|e| {
+            i18n::tf(
+                "watch.invalid_glob",
+                &[("pattern", &pattern), ("error", &e)],
+            )
+        })?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| i18n::tf("watch.build_glob_failed", &[("error", &e)]))
+}
+
+// 始终排除的目录名
+const ALWAYS_IGNORED: &[&str] = &["node_modules", ".git"];
+
+// 一个目录自己的忽略规则，仓库根目录还包含 .git/info/exclude（优先级低于 .gitignore）
+fn dir_gitignore(dir: &Path) -> Result<Option<Gitignore>, String> {
+    let files: Vec<PathBuf> = [dir.join(".git/info/exclude"), dir.join(".gitignore")]
+        .into_iter()
+        .filter(|file| file.is_file())
+        .collect();
+    if files.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GitignoreBuilder::new(dir);
+    for file in &files {
+        if let Some(e) = builder.add(file) {
+            eprintln!("[WATCH] 读取 {:?} 失败: {}", file, e);
+        }
+    }
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| i18n::tf("watch.parse_gitignore_failed", &[("error", &e)]))
+}
+
+// 各级忽略规则，由浅到深：项目位于 git 仓库中时从仓库根目录开始，再加上项目内含 .gitignore 的子目录
+// （已被忽略的目录与 node_modules 不再深入）
+fn gitignore_chain(root: &Path) -> Result<Vec<Gitignore>, String> {
+    let mut dirs: Vec<PathBuf> = match root.ancestors().position(|dir| dir.join(".git").exists()) {
+        Some(depth) => root
+            .ancestors()
+            .take(depth + 1)
+            .map(Path::to_path_buf)
+            .collect(),
+        None => vec![root.to_path_buf()],
+    };
+    dirs.extend(
+        WalkBuilder::new(root)
+            .hidden(false)
+            .filter_entry(|entry| {
+                !ALWAYS_IGNORED.contains(&entry.file_name().to_string_lossy().as_ref())
+            })
+            .build()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.depth() > 1 && entry.file_name() == ".gitignore")
+            .filter_map(|entry| entry.path().parent().map(Path::to_path_buf)),
+    );
+    dirs.sort_by_key(|dir| dir.components().count());
+    dirs.dedup();
+
+    let mut chain = Vec::new();
+    for dir in dirs {
+        chain.extend(dir_gitignore(&dir)?);
+    }
+    Ok(chain)
+}
+
+struct WatchFilter {
+    root: PathBuf,
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+    gitignores: Vec<Gitignore>,
+}
+
+impl WatchFilter {
+    fn new(root: &Path, globs: &[String], ignore: &[String]) -> Result<Self, String> {
+        let gitignores = gitignore_chain(root)?;
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            include: if globs.is_empty() {
+                None
+            } else {
+                Some(build_globset(globs)?)
+            },
+            exclude: build_globset(ignore)?,
+            gitignores,
+        })
+    }
+
+    // 与 git 一致，离文件最近的规则优先
+    fn is_gitignored(&self, path: &Path) -> bool {
+        let is_dir = path.is_dir();
+        self.gitignores
+            .iter()
+            .rev()
+            .filter(|gitignore| path.starts_with(gitignore.path()))
+            .map(|gitignore| gitignore.matched_path_or_any_parents(path, is_dir))
+            .find(|matched| !matched.is_none())
+            .is_some_and(|matched| matched.is_ignore())
+    }
+
+    // 返回相对项目根目录的路径，不需要触发时返回 None
+    fn matches(&self, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(&self.root).ok()?;
+        if relative.as_os_str().is_empty()
+            || relative
+                .components()
+                .any(|part| ALWAYS_IGNORED.contains(&part.as_os_str().to_string_lossy().as_ref()))
+        {
+            return None;
+        }
+        if self.is_gitignored(path) {
+            return None;
+        }
+        if self.exclude.is_match(relative) {
+            return None;
+        }
+        if let Some(include) = &self.include {
+            if !include.is_match(relative) {
+                return None;
+            }
+        }
+        Some(relative.to_string_lossy().replace('\\', "/"))
+    }
+}
+
+fn changed_paths(filter: &WatchFilter, event: notify::Result<notify::Event>) -> Vec<String> {
+    let Ok(event) = event else {
+        return Vec::new();
+    };
+    if !matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    ) {
+        return Vec::new();
+    }
+    event
+        .paths
+        .iter()
+        .filter_map(|path| filter.matches(path))
+        .collect()
+}
+
+// 运行结束后更新监听状态，通过/失败发生切换时推送事件
+fn on_run_finished(app: &AppHandle, watch_id: &str, record: &RunRecord) {
+    let Ok(mut watches) = WATCHES.lock() else {
+        return;
+    };
+    let Some(handle) = watches.get_mut(watch_id) else {
+        return;
+    };
+    if handle.info.current_run_id.as_deref() == Some(record.run_id.as_str()) {
+        handle.info.current_run_id = None;
+    }
+    // 被新一轮运行取消的结果不参与状态切换
+    if record.status == RunStatus::Cancelled {
+        return;
+    }
+
+    let previous = handle.info.last_status.replace(record.status.clone());
+    handle.info.last_run_id = Some(record.run_id.clone());
+    if previous.as_ref() != Some(&record.status) {
+        let _ = app.emit(
+            "watch-status-changed",
+            serde_json::json!({
+                "watchId": watch_id,
+                "runId": record.run_id,
+                "from": previous,
+                "to": record.status,
+                "exitCode": record.exit_code,
+            }),
+        );
+    }
+}
+
+fn trigger_run(app: &AppHandle, watch_id: &str, files: Vec<String>) -> Result<String, String> {
+    let (previous_run, project_path, command_name, command, env_profile) = {
+        let watches = WATCHES
+            .lock()
+            .map_err(|e| i18n::tf("common.lock_failed", &[("error", &e)]))?;
+        let handle = watches
+            .get(watch_id)
+            .ok_or_else(|| i18n::tf("watch.not_found", &[("id", &watch_id)]))?;
+        (
+            handle.info.current_run_id.clone(),
+            handle.info.project_path.clone(),
+            handle.info.command_name.clone(),
+            handle.info.command.clone(),
+            handle.env_profile.clone(),
+        )
+    };
+
+    if let Some(previous) = previous_run {
+        if task_runner::cancel_run(&previous) {
+            let deadline = Instant::now() + CANCEL_WAIT;
+            while task_runner::is_running(&previous) && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+        }
+    }
+
+    // 每次运行都重新读取环境，.env 的修改可以立即生效
+    let env =
+        env_profile::resolve_env_vars(&project_path, Some(&command_name), env_profile.as_deref())?;
+
+    let _ = app.emit(
+        "watch-triggered",
+        serde_json::json!({ "watchId": watch_id, "files": files }),
+    );
+
+    let callback_app = app.clone();
+    let callback_watch_id = watch_id.to_string();
+    let run_id = task_runner::spawn_run(
+        app,
+        RunRequest {
+            command_id: watch_id.to_string(),
+            project_path,
+            command,
+            command_name: Some(command_name.clone()),
+            env,
+            trigger: "watch".to_string(),
+            hook_depth: 0,
+            priority: 0,
+            long_running: task_runner::is_long_running_script(&command_name),
+        },
+        Some(Box::new(move |record| {
+            on_run_finished(&callback_app, &callback_watch_id, record)
+        })),
+    )?;
+
+    if let Ok(mut watches) = WATCHES.lock() {
+        if let Some(handle) = watches.get_mut(watch_id) {
+            handle.info.run_count += 1;
+            if task_runner::is_running(&run_id) {
+                handle.info.current_run_id = Some(run_id.clone());
+            }
+        }
+    }
+
+    Ok(run_id)
+}
+
+fn spawn_event_loop(
+    app: AppHandle,
+    watch_id: String,
+    filter: WatchFilter,
+    rx: mpsc::Receiver<notify::Result<notify::Event>>,
+    debounce: Duration,
+) {
+    std::thread::spawn(move || {
+        // watcher 被移除后通道断开，线程随之退出
+        while let Ok(first) = rx.recv() {
+            let mut files: BTreeSet<String> = changed_paths(&filter, first).into_iter().collect();
+
+            loop {
+                match rx.recv_timeout(debounce) {
+                    Ok(event) => files.extend(changed_paths(&filter, event)),
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+
+            if files.is_empty() {
+                continue;
+            }
+            if let Err(e) = trigger_run(&app, &watch_id, files.into_iter().collect()) {
+                eprintln!("[WATCH] {} 触发运行失败: {}", watch_id, e);
+                let _ = app.emit(
+                    "watch-error",
+                    serde_json::json!({ "watchId": watch_id, "error": e }),
+                );
+            }
+        }
+    });
+}
+
+fn stop(watch_id: &str) -> bool {
+    let removed = WATCHES
+        .lock()
+        .ok()
+        .and_then(|mut watches| watches.remove(watch_id));
+    match removed {
+        Some(handle) => {
+            if let Some(run_id) = handle.info.current_run_id {
+                task_runner::cancel_run(&run_id);
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+// ---------- Tauri 命令 ----------
+
+// 开始监听；同一项目命令重复调用时替换原有监听
+#[tauri::command]
+pub fn start_watch(app: AppHandle, config: WatchConfig) -> Result<WatchInfo, String> {
+    let root = Path::new(&config.project_path)
+        .canonicalize()
+        .map_err(|e| i18n::tf("watch.invalid_project_path", &[("error", &e)]))?;
+    let filter = WatchFilter::new(&root, &config.globs, &config.ignore)?;
+
+    let package_manager = config
+        .package_manager
+        .clone()
+        .unwrap_or_else(|| project_scanner::detect_package_manager(&root));
+    let extra_args = command_template::render_for_execution(
+        &config.project_path,
+        &config.command_name,
+        &package_manager,
+        config.arg_values.as_ref(),
+    )?;
+    let command = executor::build_execution_command(
+        &config.command_name,
+        config.node_version.as_deref(),
+        &package_manager,
+        Some(&extra_args),
+        None,
+    )?;
+    // 提前校验环境配置，避免监听启动后每次触发都失败
+    env_profile::resolve_env(
+        &config.project_path,
+        Some(&config.command_name),
+        config.env_profile.as_deref(),
+    )?;
+
+    let watch_id = watch_id_for(&config.project_path, &config.command_name);
+    stop(&watch_id);
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)
+        .map_err(|e| i18n::tf("watch.create_failed", &[("error", &e)]))?;
+    watcher
+        .watch(&root, RecursiveMode::Recursive)
+        .map_err(|e| i18n::tf("watch.watch_dir_failed", &[("error", &e)]))?;
+
+    let debounce_ms = config.debounce_ms.unwrap_or(DEFAULT_DEBOUNCE_MS);
+    let info = WatchInfo {
+        watch_id: watch_id.clone(),
+        project_path: config.project_path.clone(),
+        command_name: config.command_name.clone(),
+        command,
+        globs: config.globs.clone(),
+        ignore: config.ignore.clone(),
+        debounce_ms,
+        last_status: None,
+        last_run_id: None,
+        current_run_id: None,
+        run_count: 0,
+    };
+
+    WATCHES
+        .lock()
+        .map_err(|e| i18n::tf("common.lock_failed", &[("error", &e)]))?
+        .insert(
+            watch_id.clone(),
+            WatchHandle {
+                info: info.clone(),
+                env_profile: config.env_profile.clone(),
+                _watcher: watcher,
+            },
+        );
+    spawn_event_loop(
+        app.clone(),
+        watch_id.clone(),
+        filter,
+        rx,
+        Duration::from_millis(debounce_ms),
+    );
+    eprintln!("[WATCH] 开始监听: {} ({:?})", watch_id, root);
+
+    if config.run_on_start {
+        trigger_run(&app, &watch_id, Vec::new())?;
+    }
+
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn stop_watch(watch_id: String) -> Result<bool, String> {
+    let stopped = stop(&watch_id);
+    if stopped {
+        eprintln!("[WATCH] 停止监听: {}", watch_id);
+    }
+    Ok(stopped)
+}
+
+#[tauri::command]
+pub fn list_watches() -> Result<Vec<WatchInfo>, String> {
+    let watches = WATCHES
+        .lock()
+        .map_err(|e| i18n::tf("common.lock_failed", &[("error", &e)]))?;
+    let mut list: Vec<WatchInfo> = watches.values().map(|h| h.info.clone()).collect();
+    list.sort_by(|a, b| a.watch_id.cmp(&b.watch_id));
+    Ok(list)
+}
+
+// 手动触发一次运行（同样会取消运行中的任务）
+#[tauri::command]
+pub fn trigger_watch(app: AppHandle, watch_id: String) -> Result<String, String> {
+    trigger_run(&app, &watch_id, Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn monorepo_packages_follow_parent_and_nested_gitignores() {
+        let repo = std::env::temp_dir().join(format!("runproject-watch-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&repo);
+        let package = repo.join("packages/app");
+        fs::create_dir_all(package.join("src/generated")).unwrap();
+        fs::create_dir_all(repo.join(".git/info")).unwrap();
+        fs::write(repo.join(".gitignore"), "dist/\n*.log\n").unwrap();
+        fs::write(repo.join(".git/info/exclude"), "scratch.txt\n").unwrap();
+        fs::write(package.join("src/.gitignore"), "generated/\n").unwrap();
+
+        let filter = WatchFilter::new(&package, &[], &[]).unwrap();
+        let matches = |path: &str| filter.matches(&package.join(path));
+        assert_eq!(matches("src/main.ts").as_deref(), Some("src/main.ts"));
+        assert_eq!(matches("dist/index.js"), None);
+        assert_eq!(matches("debug.log"), None);
+        assert_eq!(matches("scratch.txt"), None);
+        assert_eq!(matches("src/generated/api.ts"), None);
+        assert_eq!(matches("node_modules/vite/index.js"), None);
+        let _ = fs::remove_dir_all(&repo);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::modules::i18n;
 use serde::Serialize;
 use std::collections::HashMap;
 use std::sync::Mutex;
@@ -52,7 +53,7 @@ pub fn create_child_webview(
 ) -> Result<String, String> {
     let parsed_url = url
         .parse::<url::Url>()
-        .map_err(|e| format!("Invalid URL: {}", e))?;
+        .map_err(|e| i18n::tf("webview.invalid_url", &[("error", &e)]))?;
 
     let (x, y) = apply_pos_correction(&label, x, y);
 
@@ -238,7 +239,7 @@ pub fn create_child_webview(
             tauri::LogicalPosition::new(x, y),
             tauri::LogicalSize::new(width, height),
         )
-        .map_err(|e| format!("Failed to create child webview: {}", e))?;
+        .map_err(|e| i18n::tf("webview.create_failed", &[("error", &e)]))?;
 
     if let (Ok(scale), Ok(actual_pos)) = (window.scale_factor(), webview.position()) {
         let actual = actual_pos.to_logical::<f64>(scale);
@@ -254,48 +255,48 @@ pub fn create_child_webview(
 pub fn navigate_webview(app: tauri::AppHandle, label: String, url: String) -> Result<(), String> {
     let webview = app
         .get_webview(&label)
-        .ok_or_else(|| format!("Webview not found: {}", label))?;
+        .ok_or_else(|| i18n::tf("webview.not_found", &[("label", &label)]))?;
 
     let parsed_url = url
         .parse::<url::Url>()
-        .map_err(|e| format!("Invalid URL: {}", e))?;
+        .map_err(|e| i18n::tf("webview.invalid_url", &[("error", &e)]))?;
 
     webview
         .navigate(parsed_url)
-        .map_err(|e| format!("Failed to navigate: {}", e))
+        .map_err(|e| i18n::tf("webview.navigate_failed", &[("error", &e)]))
 }
 
 #[tauri::command]
 pub fn close_webview(app: tauri::AppHandle, label: String) -> Result<(), String> {
     let webview = app
         .get_webview(&label)
-        .ok_or_else(|| format!("Webview not found: {}", label))?;
+        .ok_or_else(|| i18n::tf("webview.not_found", &[("label", &label)]))?;
 
     webview
         .close()
-        .map_err(|e| format!("Failed to close webview: {}", e))
+        .map_err(|e| i18n::tf("webview.close_failed", &[("error", &e)]))
 }
 
 #[tauri::command]
 pub fn hide_webview(app: tauri::AppHandle, label: String) -> Result<(), String> {
     let webview = app
         .get_webview(&label)
-        .ok_or_else(|| format!("Webview not found: {}", label))?;
+        .ok_or_else(|| i18n::tf("webview.not_found", &[("label", &label)]))?;
 
     webview
         .hide()
-        .map_err(|e| format!("Failed to hide webview: {}", e))
+        .map_err(|e| i18n::tf("webview.hide_failed", &[("error", &e)]))
 }
 
 #[tauri::command]
 pub fn show_webview(app: tauri::AppHandle, label: String) -> Result<(), String> {
     let webview = app
         .get_webview(&label)
-        .ok_or_else(|| format!("Webview not found: {}", label))?;
+        .ok_or_else(|| i18n::tf("webview.not_found", &[("label", &label)]))?;
 
     webview
         .show()
-        .map_err(|e| format!("Failed to show webview: {}", e))
+        .map_err(|e| i18n::tf("webview.show_failed", &[("error", &e)]))
 }
 
 #[tauri::command]
@@ -309,17 +310,17 @@ pub fn resize_webview(
 ) -> Result<(), String> {
     let webview = app
         .get_webview(&label)
-        .ok_or_else(|| format!("Webview not found: {}", label))?;
+        .ok_or_else(|| i18n::tf("webview.not_found", &[("label", &label)]))?;
 
     let (x, y) = apply_pos_correction(&label, x, y);
 
     webview
         .set_position(tauri::LogicalPosition::new(x, y))
-        .map_err(|e| format!("Failed to set position: {}", e))?;
+        .map_err(|e| i18n::tf("webview.set_position_failed", &[("error", &e)]))?;
 
     webview
         .set_size(tauri::LogicalSize::new(width, height))
-        .map_err(|e| format!("Failed to set size: {}", e))?;
+        .map_err(|e| i18n::tf("webview.set_size_failed", &[("error", &e)]))?;
 
     let window = webview.window();
     if let (Ok(scale), Ok(actual_pos)) = (window.scale_factor(), webview.position()) {
@@ -2,6 +2,8 @@ import { HashRouter as Router } from 'react-router-dom';
 import { Toaster } from './components/ui/toaster';
 import TabBar from './components/TabBar';
 import TitleBar from './components/TitleBar';
+import OrphanDialog from './components/OrphanDialog';
+import TaskQueuePanel from './components/TaskQueuePanel';
 import { AppRouter } from './router';
 
 function App() {
@@ -16,8 +18,13 @@ function App() {
 				{/* 主内容区域 */}
 				<div className='flex-1 overflow-hidden relative'>
 					<AppRouter />
+					{/* 超出并发上限而排队的命令 */}
+					<TaskQueuePanel />
 				</div>
 
+				{/* 上次异常退出后遗留的实例与进程 */}
+				<OrphanDialog />
+
 				{/* Toast提示 */}
 				<Toaster />
 			</div>
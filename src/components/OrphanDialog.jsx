@@ -0,0 +1,147 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import {
+	Dialog,
+	DialogContent,
+	DialogFooter,
+	DialogHeader,
+	DialogTitle
+} from './ui/dialog';
+import { Button } from './ui/button';
+import { useToast } from '@/hooks/use-toast';
+import { formatError } from '@/lib/utils';
+
+// 启动时检查上次异常退出后遗留的 kitty 实例与进程，由用户选择接管或终止
+function OrphanDialog() {
+	const { toast } = useToast();
+	const [report, setReport] = useState(null);
+	const [busy, setBusy] = useState(false);
+
+	useEffect(() => {
+		invoke('scan_orphans')
+			.then(result => {
+				if (result.instances.length > 0 || result.processes.length > 0) {
+					setReport(result);
+				}
+			})
+			.catch(error => console.error('检查遗留进程失败:', formatError(error)));
+	}, []);
+
+	if (!report) return null;
+
+	const adoptable = report.instances.filter(instance =>
+		instance.windows.some(window => window.commandId)
+	);
+
+	const close = () => setReport(null);
+
+	const handleAdopt = async () => {
+		setBusy(true);
+		try {
+			const tabs = await invoke('adopt_orphan_instances', {
+				socketPaths: adoptable.map(instance => instance.socketPath)
+			});
+			toast({
+				title: '已接管',
+				description: `${tabs.length} 个 kitty 标签页已重新纳入管理`
+			});
+			close();
+		} catch (error) {
+			toast({
+				title: '接管失败',
+				description: formatError(error),
+				variant: 'destructive'
+			});
+		} finally {
+			setBusy(false);
+		}
+	};
+
+	const handleKill = async () => {
+		setBusy(true);
+		try {
+			await invoke('kill_orphans', {
+				socketPaths: report.instances.map(instance => instance.socketPath),
+				pids: report.processes.map(process => process.pid)
+			});
+			toast({ title: '已清理遗留的实例与进程' });
+			close();
+		} catch (error) {
+			toast({
+				title: '清理失败',
+				description: formatError(error),
+				variant: 'destructive'
+			});
+		} finally {
+			setBusy(false);
+		}
+	};
+
+	return (
+		<Dialog open onOpenChange={open => !open && close()}>
+			<DialogContent className='max-w-xl'>
+				<DialogHeader>
+					<DialogTitle>发现上次未关闭的任务</DialogTitle>
+					<p className='text-sm text-gray-500 mt-1'>
+						应用上次退出时以下 kitty 实例或进程仍在运行
+					</p>
+				</DialogHeader>
+				<div className='p-4 space-y-4 max-h-96 overflow-y-auto'>
+					{report.instances.map(instance => (
+						<div key={instance.socketPath} className='space-y-1'>
+							<div className='text-sm font-medium text-gray-900'>
+								kitty 实例
+								<span className='ml-2 font-mono text-xs text-gray-500'>
+									{instance.socketPath}
+								</span>
+							</div>
+							{instance.windows.map(window => (
+								<div
+									key={window.windowId}
+									className='text-xs text-gray-600 pl-3 truncate'>
+									{window.title || window.cwd}
+									{!window.commandId && (
+										<span className='ml-2 text-gray-400'>（非应用创建）</span>
+									)}
+								</div>
+							))}
+						</div>
+					))}
+					{report.processes.length > 0 && (
+						<div className='space-y-1'>
+							<div className='text-sm font-medium text-gray-900'>进程</div>
+							{report.processes.map(process => (
+								<div
+									key={process.pid}
+									className='text-xs text-gray-600 pl-3 font-mono truncate'>
+									{process.pid} {process.command}
+									{process.descendants.length > 0 &&
+										` (+${process.descendants.length})`}
+								</div>
+							))}
+						</div>
+					)}
+				</div>
+				<DialogFooter>
+					<Button variant='ghost' size='sm' disabled={busy} onClick={close}>
+						忽略
+					</Button>
+					<Button
+						variant='destructive'
+						size='sm'
+						disabled={busy}
+						onClick={handleKill}>
+						全部终止
+					</Button>
+					{adoptable.length > 0 && (
+						<Button size='sm' disabled={busy} onClick={handleAdopt}>
+							接管 kitty 标签页
+						</Button>
+					)}
+				</DialogFooter>
+			</DialogContent>
+		</Dialog>
+	);
+}
+
+export default OrphanDialog;
@@ -1,4 +1,5 @@
 import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import { Play, Square, Tag } from 'lucide-react';
 import { Button } from '@/components/ui/button';
 import { Card, CardContent } from '@/components/ui/card';
@@ -15,6 +16,8 @@ import {
 	PopoverContent
 } from '@/components/ui/popover';
 import { Input } from '@/components/ui/input';
+import { useToast } from '@/hooks/use-toast';
+import { formatError } from '@/lib/utils';
 
 function CommandCard({
 	command,
@@ -24,10 +27,48 @@ function CommandCard({
 	onStopCommand,
 	tags = [],
 	onSetTags,
-	commandKey
+	commandKey,
+	taskId
 }) {
 	const [tagEditorOpen, setTagEditorOpen] = useState(false);
 	const [tagDraft, setTagDraft] = useState('');
+	const [taskActions, setTaskActions] = useState([]);
+	const { toast } = useToast();
+
+	// kitty 标签页中运行的命令可以发送快捷键，标签页刚创建时还没有窗口 id，稍后再取
+	useEffect(() => {
+		if (!taskId) {
+			setTaskActions([]);
+			return;
+		}
+		let cancelled = false;
+		const timer = setTimeout(() => {
+			invoke('list_task_actions', { commandId: taskId })
+				.then(actions => {
+					if (!cancelled) setTaskActions(actions);
+				})
+				.catch(() => {
+					if (!cancelled) setTaskActions([]);
+				});
+		}, 1000);
+		return () => {
+			cancelled = true;
+			clearTimeout(timer);
+		};
+	}, [taskId]);
+
+	const handleTaskAction = async (event, action) => {
+		event.stopPropagation();
+		try {
+			await invoke('run_task_action', { commandId: taskId, action: action.name });
+		} catch (error) {
+			toast({
+				title: '发送失败',
+				description: `${action.label}: ${formatError(error)}`,
+				variant: 'destructive'
+			});
+		}
+	};
 
 	useEffect(() => {
 		if (tagEditorOpen) {
@@ -156,6 +197,20 @@ function CommandCard({
 									)}
 								</Button>
 							</div>
+							{running && taskActions.length > 0 && (
+								<div className='flex flex-wrap gap-1.5 mt-3'>
+									{taskActions.map(action => (
+										<Button
+											key={action.name}
+											variant='outline'
+											size='sm'
+											className='h-7 px-2 text-xs'
+											onClick={event => handleTaskAction(event, action)}>
+											{action.label}
+										</Button>
+									))}
+								</div>
+							)}
 						</CardContent>
 					</Card>
 				</TooltipTrigger>
@@ -76,6 +76,11 @@ function CommandList({
 									command={command}
 									project={project}
 									running={isCommandRunning(command)}
+									taskId={
+										isCommandRunning(command) && runningCommand.kittyTab
+											? runningCommand.id
+											: null
+									}
 									onExecuteCommand={onExecuteCommand}
 									onStopCommand={onStopCommand}
 									tags={getCommandTags(command)}
@@ -4,8 +4,10 @@ import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card';
 import { Info } from 'lucide-react';
 import { useToast } from '@/hooks/use-toast';
 import { useAppStore } from '@/store/useAppStore';
+import { cancelOperation, createOperationId } from '@/lib/utils';
 import ProjectInfoCard from './ProjectInfoCard';
 import CommandList from './CommandList';
+import RunOutputPanel from './RunOutputPanel';
 
 const EDITORS_CACHE_TTL = 1000 * 60 * 60 * 24;
 const GIT_BRANCHES_CACHE_TTL = 1000 * 60 * 60 * 24;
@@ -119,7 +121,7 @@ function ProjectDetails({
 
 	const sortedCommands = project.commands || [];
 
-	const loadGitBranches = async ({ forceRefresh = false } = {}) => {
+	const loadGitBranches = async ({ forceRefresh = false, operationId } = {}) => {
 		if (!project?.path) return;
 		setIsLoadingBranches(true);
 		try {
@@ -135,7 +137,8 @@ function ProjectDetails({
 				branchList = cachedData.branches;
 			} else {
 				branchList = await invoke('list_branches', {
-					projectPath: project.path
+					projectPath: project.path,
+					operationId
 				});
 				setGitBranchesCache(project.path, branchList);
 			}
@@ -224,9 +227,11 @@ function ProjectDetails({
 	};
 
 	useEffect(() => {
+		// 切换项目或卸载时取消仍在进行的 git 查询
+		const branchOperationId = createOperationId('list-branches');
 		const loadAllData = async () => {
 			await Promise.all([
-				loadGitBranches(),
+				loadGitBranches({ operationId: branchOperationId }),
 				loadAvailableEditors(),
 				(async () => {
 					if (onGetInstalledVersions) {
@@ -267,6 +272,9 @@ function ProjectDetails({
 		};
 
 		loadAllData();
+		return () => {
+			cancelOperation(branchOperationId);
+		};
 	}, [
 		project.name,
 		project.path,
@@ -410,6 +418,8 @@ function ProjectDetails({
 					commandTags={commandTags}
 					onSetCommandTags={setCommandTags}
 				/>
+
+				<RunOutputPanel project={project} />
 			</div>
 		</div>
 	);
@@ -10,6 +10,7 @@ import PackageManagerBadge from './PackageManagerBadge';
 import EditorSelector from './EditorSelector';
 import GitWorktreeDialog from './GitWorktreeDialog';
 import { useToast } from '@/hooks/use-toast';
+import { formatError } from '@/lib/utils';
 
 function ProjectInfoCard({
 	project,
@@ -104,7 +105,7 @@ function ProjectInfoCard({
 			console.error('打开 Worktree 失败:', error);
 			toast({
 				title: '打开失败',
-				description: error.toString(),
+				description: formatError(error),
 				variant: 'destructive'
 			});
 		}
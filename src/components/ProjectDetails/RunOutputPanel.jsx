@@ -0,0 +1,178 @@
+import { useEffect, useRef, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { ScrollText, Search } from 'lucide-react';
+import { Badge } from '@/components/ui/badge';
+import { Card, CardContent } from '@/components/ui/card';
+import { Input } from '@/components/ui/input';
+import { formatError } from '@/lib/utils';
+
+// 与后端保存的输出末尾长度一致
+const MAX_OUTPUT_LENGTH = 64 * 1024;
+const SEARCH_DELAY_MS = 300;
+
+const STATUS_LABELS = {
+	queued: '排队中',
+	running: '运行中',
+	succeeded: '成功',
+	failed: '失败',
+	cancelled: '已取消'
+};
+
+const trimOutput = text =>
+	text.length > MAX_OUTPUT_LENGTH ? text.slice(-MAX_OUTPUT_LENGTH) : text;
+
+// 项目最近的运行记录与输出：后台任务和 kitty 标签页抓取的输出都会写入运行记录，可按关键字搜索
+function RunOutputPanel({ project }) {
+	const [runs, setRuns] = useState([]);
+	const [selectedRunId, setSelectedRunId] = useState(null);
+	const [output, setOutput] = useState('');
+	const [query, setQuery] = useState('');
+	const [matches, setMatches] = useState([]);
+	const selectedRunIdRef = useRef(null);
+	const outputRef = useRef(null);
+
+	useEffect(() => {
+		selectedRunIdRef.current = selectedRunId;
+	}, [selectedRunId]);
+
+	useEffect(() => {
+		let unlistenStatus = null;
+		let unlistenOutput = null;
+
+		invoke('list_task_runs')
+			.then(records =>
+				setRuns(records.filter(record => record.projectPath === project.path))
+			)
+			.catch(error => console.error('获取运行记录失败:', formatError(error)));
+
+		const setupListeners = async () => {
+			unlistenStatus = await listen('task-run-status', event => {
+				const record = event.payload;
+				if (record.projectPath !== project.path) return;
+				setRuns(current => [
+					record,
+					...current.filter(item => item.runId !== record.runId)
+				]);
+			});
+			unlistenOutput = await listen('task-output', event => {
+				const { runId, data } = event.payload;
+				if (runId !== selectedRunIdRef.current) return;
+				setOutput(current => trimOutput(current + data));
+			});
+		};
+		setupListeners();
+
+		return () => {
+			if (unlistenStatus) unlistenStatus();
+			if (unlistenOutput) unlistenOutput();
+		};
+	}, [project.path]);
+
+	useEffect(() => {
+		if (!selectedRunId) {
+			setOutput('');
+			return;
+		}
+		let cancelled = false;
+		invoke('get_task_run', { runId: selectedRunId })
+			.then(record => {
+				if (!cancelled) setOutput(record?.outputTail || '');
+			})
+			.catch(error => console.error('获取运行输出失败:', formatError(error)));
+		return () => {
+			cancelled = true;
+		};
+	}, [selectedRunId]);
+
+	useEffect(() => {
+		if (outputRef.current) {
+			outputRef.current.scrollTop = outputRef.current.scrollHeight;
+		}
+	}, [output]);
+
+	useEffect(() => {
+		if (!query.trim()) {
+			setMatches([]);
+			return;
+		}
+		let cancelled = false;
+		const timer = setTimeout(() => {
+			invoke('search_task_output', { query, projectPath: project.path })
+				.then(result => {
+					if (!cancelled) setMatches(result);
+				})
+				.catch(error => console.error('搜索输出失败:', formatError(error)));
+		}, SEARCH_DELAY_MS);
+		return () => {
+			cancelled = true;
+			clearTimeout(timer);
+		};
+	}, [query, project.path]);
+
+	if (runs.length === 0) return null;
+
+	return (
+		<div className='space-y-4'>
+			<h3 className='text-lg font-bold text-gray-800 flex items-center gap-2'>
+				<ScrollText className='w-5 h-5 text-gray-500' />
+				运行输出
+				<Badge variant='secondary' className='ml-2 text-xs font-normal'>
+					{runs.length}
+				</Badge>
+			</h3>
+			<Card>
+				<CardContent className='p-4 space-y-3'>
+					<div className='relative'>
+						<Search className='absolute left-2 top-2.5 h-4 w-4 text-gray-400' />
+						<Input
+							value={query}
+							onChange={event => setQuery(event.target.value)}
+							placeholder='搜索输出'
+							className='pl-8'
+						/>
+					</div>
+					{matches.length > 0 && (
+						<div className='max-h-40 overflow-y-auto rounded border border-gray-100 text-xs font-mono'>
+							{matches.map(match => (
+								<button
+									type='button'
+									key={`${match.runId}:${match.lineNumber}`}
+									className='flex w-full gap-2 px-2 py-1 text-left hover:bg-gray-50'
+									onClick={() => setSelectedRunId(match.runId)}>
+									<span className='shrink-0 text-gray-400'>
+										{match.commandName || match.commandId}:{match.lineNumber}
+									</span>
+									<span className='truncate text-gray-700'>{match.line}</span>
+								</button>
+							))}
+						</div>
+					)}
+					<div className='flex flex-wrap gap-1'>
+						{runs.map(run => (
+							<button
+								type='button'
+								key={run.runId}
+								className={`rounded px-2 py-0.5 text-xs ${run.runId === selectedRunId
+									? 'bg-blue-100 text-blue-700'
+									: 'bg-gray-100 text-gray-600 hover:bg-gray-200'
+									}`}
+								onClick={() => setSelectedRunId(run.runId)}>
+								{run.commandName || run.command} · {STATUS_LABELS[run.status]}
+							</button>
+						))}
+					</div>
+					{selectedRunId && (
+						<pre
+							ref={outputRef}
+							className='max-h-80 overflow-auto rounded bg-gray-900 p-3 text-xs text-gray-100 whitespace-pre-wrap'>
+							{output || '暂无输出'}
+						</pre>
+					)}
+				</CardContent>
+			</Card>
+		</div>
+	);
+}
+
+export default RunOutputPanel;
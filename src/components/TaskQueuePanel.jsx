@@ -0,0 +1,126 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { ChevronDown, ChevronUp, ListOrdered, X } from 'lucide-react';
+import { Button } from './ui/button';
+import { useToast } from '@/hooks/use-toast';
+import { formatError } from '@/lib/utils';
+
+const projectName = path => path.split(/[\\/]/).filter(Boolean).pop() || path;
+
+// 超出并发上限而排队的命令，可调整出队顺序或取消；队列为空时不显示
+function TaskQueuePanel() {
+	const { toast } = useToast();
+	const [queue, setQueue] = useState([]);
+	const [collapsed, setCollapsed] = useState(false);
+
+	useEffect(() => {
+		let unlistenQueue = null;
+		let unlistenLaunched = null;
+
+		invoke('list_task_queue')
+			.then(setQueue)
+			.catch(error => console.error('获取任务队列失败:', formatError(error)));
+
+		const setupListeners = async () => {
+			unlistenQueue = await listen('task-queue-changed', event => {
+				setQueue(event.payload);
+			});
+			// 出队后在终端中打开失败时提示，成功由各执行入口自行处理
+			unlistenLaunched = await listen('task-run-launched', event => {
+				if (event.payload.error) {
+					toast({
+						title: '排队的命令启动失败',
+						description: formatError(event.payload.error),
+						variant: 'destructive'
+					});
+				}
+			});
+		};
+		setupListeners();
+
+		return () => {
+			if (unlistenQueue) unlistenQueue();
+			if (unlistenLaunched) unlistenLaunched();
+		};
+	}, [toast]);
+
+	if (queue.length === 0) return null;
+
+	const run = async (command, args, failure) => {
+		try {
+			await invoke(command, args);
+		} catch (error) {
+			toast({
+				title: failure,
+				description: formatError(error),
+				variant: 'destructive'
+			});
+		}
+	};
+
+	const move = (runId, index) =>
+		run('move_queued_run', { runId, index }, '调整顺序失败');
+
+	const cancel = runId => run('cancel_task_run', { runId }, '取消失败');
+
+	return (
+		<div className='absolute bottom-4 right-4 z-40 w-80 rounded-lg border border-gray-200 bg-white shadow-lg'>
+			<button
+				type='button'
+				className='flex w-full items-center gap-2 px-3 py-2 text-sm font-medium text-gray-900'
+				onClick={() => setCollapsed(!collapsed)}>
+				<ListOrdered className='h-4 w-4 text-gray-500' />
+				排队中的命令
+				<span className='ml-auto text-xs text-gray-500'>{queue.length}</span>
+			</button>
+			{!collapsed && (
+				<div className='max-h-64 overflow-y-auto border-t border-gray-100'>
+					{queue.map((item, index) => (
+						<div
+							key={item.runId}
+							className='flex items-center gap-1 px-3 py-1.5 text-xs'>
+							<div className='min-w-0 flex-1'>
+								<div className='truncate text-gray-900'>
+									{item.commandName || item.command}
+								</div>
+								<div className='truncate text-gray-500'>
+									{projectName(item.projectPath)}
+									{item.priority !== 0 && ` · 优先级 ${item.priority}`}
+								</div>
+							</div>
+							<Button
+								variant='ghost'
+								size='icon'
+								className='h-6 w-6'
+								disabled={index === 0}
+								title='上移'
+								onClick={() => move(item.runId, index - 1)}>
+								<ChevronUp className='h-3 w-3' />
+							</Button>
+							<Button
+								variant='ghost'
+								size='icon'
+								className='h-6 w-6'
+								disabled={index === queue.length - 1}
+								title='下移'
+								onClick={() => move(item.runId, index + 1)}>
+								<ChevronDown className='h-3 w-3' />
+							</Button>
+							<Button
+								variant='ghost'
+								size='icon'
+								className='h-6 w-6'
+								title='取消'
+								onClick={() => cancel(item.runId)}>
+								<X className='h-3 w-3' />
+							</Button>
+						</div>
+					))}
+				</div>
+			)}
+		</div>
+	);
+}
+
+export default TaskQueuePanel;
@@ -3,6 +3,7 @@ import { Terminal } from 'xterm';
 import { FitAddon } from '@xterm/addon-fit';
 import { invoke } from '@tauri-apps/api/core';
 import { listen, emit } from '@tauri-apps/api/event';
+import { formatError } from '@/lib/utils';
 import 'xterm/css/xterm.css';
 
 const XtermTerminal = ({
@@ -34,8 +35,9 @@ const XtermTerminal = ({
 		let terminal = null;
 		let fitAddon = null;
 		let heartbeatTimer = null;
+		// 快照写入前收到的输出，带后端的输出序号
 		const pendingChunks = [];
-		let backlogLoaded = !existingSession;
+		let backlogLoaded = false;
 
 		const writeEncodedChunk = encoded => {
 			if (unmounted || !terminal) return;
@@ -71,6 +73,32 @@ const XtermTerminal = ({
 		terminalRef.current = terminal;
 		fitAddonRef.current = fitAddon;
 
+		// 写入后端保存的屏幕快照（最近的回滚区与光标、模式），再补上快照之后的输出
+		// 快照按生成时的尺寸绘制，写入前先调整到该尺寸，写完再按容器大小调整
+		const loadSnapshot = async ({ clear = false } = {}) => {
+			let snapshotSeq = 0;
+			try {
+				const snapshot = await invoke('get_terminal_snapshot', { sessionId });
+				if (unmounted) return;
+				if (snapshot) {
+					if (clear) terminal.reset();
+					terminal.resize(snapshot.cols, snapshot.rows);
+					terminal.write(snapshot.data);
+					snapshotSeq = snapshot.seq;
+				}
+			} catch (error) {
+				console.warn('获取终端历史失败:', error);
+			}
+
+			backlogLoaded = true;
+			while (pendingChunks.length > 0) {
+				const chunk = pendingChunks.shift();
+				// 序号不大于快照序号的输出已包含在快照中
+				if (chunk.seq > snapshotSeq) writeEncodedChunk(chunk.data);
+			}
+			handleResize();
+		};
+
 		// 初始化会话
 		const initSession = async () => {
 			try {
@@ -92,7 +120,7 @@ const XtermTerminal = ({
 						pendingChunks.push(event.payload);
 						return;
 					}
-					writeEncodedChunk(event.payload);
+					writeEncodedChunk(event.payload.data);
 				});
 
 				let sessionReady = false;
@@ -135,21 +163,8 @@ const XtermTerminal = ({
 					}
 				});
 
-				try {
-					const buffered = await invoke('get_terminal_buffer', { sessionId });
-					if (unmounted) return;
-					if (buffered) {
-						writeEncodedChunk(buffered);
-					}
-				} catch (error) {
-					console.warn('获取终端历史失败:', error);
-				}
-
-				backlogLoaded = true;
-				while (pendingChunks.length > 0) {
-					const chunk = pendingChunks.shift();
-					writeEncodedChunk(chunk);
-				}
+				await loadSnapshot();
+				if (unmounted) return;
 
 				unlistenClose = await listen(`terminal-closed-${sessionId}`, () => {
 					if (unmounted) return;
@@ -159,7 +174,7 @@ const XtermTerminal = ({
 				});
 			} catch (error) {
 				console.error('终端初始化失败:', error);
-				terminal.write(`\r\n\x1b[31m错误: ${error}\x1b[0m\r\n`);
+				terminal.write(`\r\n\x1b[31m错误: ${formatError(error)}\x1b[0m\r\n`);
 			}
 		};
 
@@ -177,25 +192,17 @@ const XtermTerminal = ({
 						terminal.write(
 							'\r\n\x1b[33m[连接已断开，正在尝试重连...]\x1b[0m\r\n'
 						);
+						// 新会话的输出先缓存，与快照按序号去重
+						backlogLoaded = false;
+						pendingChunks.length = 0;
 						const { cols, rows } = terminal;
 						await invoke('create_terminal_session', {
 							sessionId,
 							config: { cwd, cols, rows }
 						});
 
-						backlogLoaded = false;
-						pendingChunks.length = 0;
-						try {
-							const buffered = await invoke('get_terminal_buffer', {
-								sessionId
-							});
-							if (!unmounted && buffered) {
-								writeEncodedChunk(buffered);
-							}
-						} catch (bufferError) {
-							console.warn('获取终端历史失败:', bufferError);
-						}
-						backlogLoaded = true;
+						// 快照按屏幕位置绘制，先清空旧内容
+						await loadSnapshot({ clear: true });
 
 						terminal.write(
 							'\r\n\x1b[32m[重连成功]\x1b[0m\r\n'
@@ -1,6 +1,38 @@
+import { invoke } from '@tauri-apps/api/core'
 import { clsx } from 'clsx'
 import { twMerge } from 'tailwind-merge'
 
 export function cn(...inputs) {
   return twMerge(clsx(inputs))
-}
\ No newline at end of file
+}
+
+// 后端命令返回的错误为 { code, message, details }，也兼容旧的字符串错误
+export function formatError(error) {
+  if (!error) return ''
+  if (typeof error === 'string') return error
+  const stderr = error.details?.stderr
+  const message = error.message || String(error)
+  return stderr ? `${message}: ${stderr}` : message
+}
+
+// 工作区的依赖安装策略为“询问”时，后端返回 dependencies_outdated，确认后带上选择重新执行
+export async function withInstallPrompt(run) {
+  try {
+    return await run(null)
+  } catch (error) {
+    if (error?.code !== 'dependencies_outdated') throw error
+    return run(window.confirm(error.message))
+  }
+}
+
+// git、Node 版本切换等耗时调用可带上 operationId，之后可按 id 取消
+export function createOperationId(prefix) {
+  return `${prefix}-${Date.now()}-${Math.random().toString(36).slice(2, 8)}`
+}
+
+// 操作已结束时后端返回 false，这里不关心结果
+export function cancelOperation(operationId) {
+  return invoke('cancel_operation', { operationId }).catch(error =>
+    console.error('取消操作失败:', formatError(error))
+  )
+}
@@ -10,6 +10,7 @@ import MainContent from '@/components/MainContent';
 import CommandPalette from '@/components/CommandPalette';
 import { Toaster } from '@/components/ui/toaster';
 import { useToast } from '@/hooks/use-toast';
+import { formatError, withInstallPrompt } from '@/lib/utils';
 function ProjectPage() {
 	const {
 		workspaces,
@@ -184,6 +185,85 @@ function ProjectPage() {
 		};
 	}, [setRunningCommand, updateProjectTerminal]);
 
+	useEffect(() => {
+		let unlisten = null;
+
+		const setupKittyStatusListener = async () => {
+			unlisten = await listen('kitty-tab-status', event => {
+				const tab = event.payload;
+				const currentRunningCommand =
+					useAppStore.getState().runningCommand;
+				const currentProjectTerminals =
+					useAppStore.getState().projectTerminals;
+
+				if (
+					tab.status !== 'Running' &&
+					currentRunningCommand &&
+					tab.id === currentRunningCommand.id
+				) {
+					const projectName = currentRunningCommand.project.name;
+					const existingTerminal =
+						currentProjectTerminals[projectName];
+					if (existingTerminal) {
+						updateProjectTerminal(projectName, {
+							...existingTerminal,
+							isBusy: false,
+							currentCommand: null
+						});
+					}
+					setRunningCommand(null);
+				}
+			});
+		};
+
+		setupKittyStatusListener();
+
+		return () => {
+			if (unlisten) {
+				unlisten();
+			}
+		};
+	}, [setRunningCommand, updateProjectTerminal]);
+
+	// 排队的命令出队后才打开标签页，补上标签页信息；启动失败时释放运行状态
+	useEffect(() => {
+		let unlisten = null;
+
+		const setupQueuedLaunchListener = async () => {
+			unlisten = await listen('task-run-launched', event => {
+				const { commandId, result, error } = event.payload;
+				const currentRunningCommand =
+					useAppStore.getState().runningCommand;
+				if (
+					!currentRunningCommand ||
+					currentRunningCommand.id !== commandId
+				) {
+					return;
+				}
+				if (error) {
+					clearProjectTerminal(currentRunningCommand.project.name);
+					setRunningCommand(null);
+					return;
+				}
+				if (result?.tabId) {
+					setRunningCommand({
+						...currentRunningCommand,
+						instance: result.instance,
+						tabId: result.tabId
+					});
+				}
+			});
+		};
+
+		setupQueuedLaunchListener();
+
+		return () => {
+			if (unlisten) {
+				unlisten();
+			}
+		};
+	}, [setRunningCommand, clearProjectTerminal]);
+
 	const clearCacheAndRefresh = () => {
 		localStorage.removeItem('nodejs-workspaces');
 		setWorkspaces([]);
@@ -261,7 +341,7 @@ function ProjectPage() {
 		} catch (error) {
 			toast({
 				title: '添加失败',
-				description: `添加workspace失败: ${error}`,
+				description: `添加workspace失败: ${formatError(error)}`,
 				variant: 'destructive'
 			});
 		} finally {
@@ -320,7 +400,7 @@ function ProjectPage() {
 		} catch (error) {
 			toast({
 				title: '刷新失败',
-				description: `刷新workspace失败: ${error}`,
+				description: `刷新workspace失败: ${formatError(error)}`,
 				variant: 'destructive'
 			});
 		} finally {
@@ -335,7 +415,14 @@ function ProjectPage() {
 			const projectName = runningCommand.project.name;
 			let result;
 
-			if (terminalType === 'builtin') {
+			if (runningCommand.backend) {
+				await invoke('close_terminal_tab', {
+					backend: runningCommand.backend,
+					instance: runningCommand.instance,
+					tabId: runningCommand.tabId
+				});
+				result = '已关闭终端标签页';
+			} else if (terminalType === 'builtin') {
 				const ctrlC = '\x03';
 				const encoded = btoa(ctrlC);
 				await invoke('write_to_terminal', {
@@ -380,7 +467,7 @@ function ProjectPage() {
 			setRunningCommand(null);
 			toast({
 				title: '停止失败',
-				description: `停止命令失败: ${error}`,
+				description: `停止命令失败: ${formatError(error)}`,
 				variant: 'destructive'
 			});
 		}
@@ -444,7 +531,7 @@ function ProjectPage() {
 		return null;
 	};
 
-	const executeInBuiltinTerminal = async (project, command) => {
+	const executeInBuiltinTerminal = async (project, command, options = {}) => {
 		const projectName = project.name;
 		const existingTerminal = projectTerminals[projectName];
 		const packageManager =
@@ -496,14 +583,19 @@ function ProjectPage() {
 
 			let fullCommand = `${packageManager} run ${command.name}`;
 			try {
-				const built = await invoke('build_execution_command', {
-					command: command.name,
-					nodeVersion:
-						effectiveNodeVersion && effectiveNodeVersion !== 'system'
-							? effectiveNodeVersion
-							: null,
-					packageManager
-				});
+				const built = await withInstallPrompt(installDependencies =>
+					invoke('build_execution_command', {
+						command: command.name,
+						nodeVersion:
+							effectiveNodeVersion && effectiveNodeVersion !== 'system'
+								? effectiveNodeVersion
+								: null,
+						packageManager,
+						projectPath: project.path,
+						argValues: options.argValues,
+						installDependencies
+					})
+				);
 				if (built) {
 					fullCommand = built;
 				}
@@ -511,11 +603,13 @@ function ProjectPage() {
 				console.warn('构建命令失败，使用默认命令:', error);
 			}
 
-			fullCommand += '\n';
-			const encoded = btoa(
-				String.fromCharCode(...new TextEncoder().encode(fullCommand))
-			);
-			await invoke('write_to_terminal', { sessionId, data: encoded });
+			// 经过并发上限的准入判断，没有名额时排队，出队后再写入会话
+			const result = await invoke('run_in_terminal_session', {
+				sessionId,
+				projectPath: project.path,
+				commandName: command.name,
+				command: fullCommand
+			});
 
 			updateProjectTerminal(projectName, {
 				isBusy: true,
@@ -531,8 +625,10 @@ function ProjectPage() {
 			);
 
 			toast({
-				title: '命令已启动',
-				description: `在内置终端中执行: ${command.name}`,
+				title: result.queued ? '已加入队列' : '命令已启动',
+				description: result.queued
+					? `已达到并发上限，${command.name} 将在有空闲时在内置终端中执行`
+					: `在内置终端中执行: ${command.name}`,
 				variant: 'default'
 			});
 		} catch (error) {
@@ -540,15 +636,100 @@ function ProjectPage() {
 			console.error('执行命令失败:', error);
 			toast({
 				title: '执行失败',
-				description: `在内置终端中执行命令失败: ${error}`,
+				description: `在内置终端中执行命令失败: ${formatError(error)}`,
+				variant: 'destructive'
+			});
+		}
+	};
+
+	// 远程控制时按设置把命令分组到项目或工作区共用的 kitty 窗口中
+	const findWorkspacePath = project =>
+		workspaces.find(workspace =>
+			workspace.projects?.some(item => item.path === project.path)
+		)?.path;
+
+	// tmux / WezTerm / Alacritty 以及自动选择，统一走 execute_command_in_terminal
+	const executeInExternalTerminal = async (project, command, options = {}) => {
+		const projectName = project.name;
+		const packageManager =
+			project.packageManager || project.package_manager || 'npm';
+		const effectiveNodeVersion = getEffectiveNodeVersion(project);
+		const backend = terminalType === 'auto' ? null : terminalType;
+		const commandId = `${projectName}-${command.name}-${Date.now()}`;
+
+		try {
+			const result = await withInstallPrompt(installDependencies =>
+				invoke('execute_command_in_terminal', {
+					backend,
+					instance: `${projectName}-${terminalType}`,
+					commandId,
+					workingDir: project.path,
+					command: command.name,
+					nodeVersion: effectiveNodeVersion,
+					projectName,
+					commandName: command.name,
+					packageManager,
+					envProfile: options.envProfile,
+					argValues: options.argValues,
+					installDependencies,
+					workspacePath: findWorkspacePath(project)
+				})
+			);
+
+			if (result.backend === 'builtin') {
+				toast({
+					title: '外部终端不可用',
+					description: '已改用内置终端执行',
+					variant: 'default'
+				});
+				return executeInBuiltinTerminal(project, command, options);
+			}
+
+			// 自动选择到 kitty 时由远程控制执行器打开标签页，按命令 id 管理
+			const kittyTab = result.backend === 'kitty';
+			const runningId = result.command_id || commandId;
+			updateProjectTerminal(projectName, {
+				isBusy: true,
+				currentCommand: command.name,
+				lastCommandId: runningId,
+				createdAt: Date.now()
+			});
+			setRunningCommand(
+				kittyTab
+					? { project, command, id: runningId, kittyTab: true }
+					: {
+							project,
+							command,
+							id: commandId,
+							backend: result.backend,
+							instance: result.instance,
+							tabId: result.tabId
+						}
+			);
+			toast({
+				title: result.queued ? '已加入队列' : '启动成功',
+				description: result.queued
+					? `已达到并发上限，${command.name} 将在有空闲时启动`
+					: `已在 ${result.backend} 中启动命令: ${command.name}`,
+				variant: 'default'
+			});
+		} catch (error) {
+			console.error('执行命令失败:', error);
+			toast({
+				title: '执行失败',
+				description: `执行命令失败: ${formatError(error)}`,
 				variant: 'destructive'
 			});
 		}
 	};
 
-	const executeProjectCommand = async (project, command) => {
+	// options 可带 envProfile（环境变量配置名）与 argValues（参数模板的取值）
+	const executeProjectCommand = async (project, command, options = {}) => {
 		if (terminalType === 'builtin') {
-			return executeInBuiltinTerminal(project, command);
+			return executeInBuiltinTerminal(project, command, options);
+		}
+		if (terminalType !== 'kitty') {
+			return executeInExternalTerminal(project, command, options);
 		}
 
 		const projectName = project.name;
@@ -558,9 +739,8 @@ function ProjectPage() {
 		const existingTerminal = projectTerminals[projectName];
 		const shouldReuseTerminal =
 			Boolean(existingTerminal) && !useKittenRemote;
-		const commandId = useKittenRemote
-			? `${projectName}-kitty`
-			: `${projectName}-${command.name}-${Date.now()}`;
+		const commandId = `${projectName}-${command.name}-${Date.now()}`;
+		const workspacePath = findWorkspacePath(project);
 
 		console.log('执行命令的项目对象:', {
 			name: project.name,
@@ -571,15 +751,21 @@ function ProjectPage() {
 		});
 
 		const runBackendCommand = async commandFunction =>
-			invoke(commandFunction, {
-				commandId,
-				workingDir: project.path,
-				command: command.name,
-				nodeVersion: effectiveNodeVersion,
-				projectName: project.name,
-				commandName: command.name,
-				packageManager
-			});
+			withInstallPrompt(installDependencies =>
+				invoke(commandFunction, {
+					commandId,
+					workingDir: project.path,
+					command: command.name,
+					nodeVersion: effectiveNodeVersion,
+					projectName: project.name,
+					commandName: command.name,
+					packageManager,
+					envProfile: options.envProfile,
+					argValues: options.argValues,
+					installDependencies,
+					workspacePath
+				})
+			);
 
 		const releaseTerminalState = () => {
 			clearProjectTerminal(projectName);
@@ -611,8 +797,10 @@ function ProjectPage() {
 
 				if (result.success) {
 					toast({
-						title: '命令启动',
-						description: `在现有终端中启动命令: ${command.name}`,
+						title: result.queued ? '已加入队列' : '命令启动',
+						description: result.queued
+							? `已达到并发上限，${command.name} 将在有空闲时启动`
+							: `在现有终端中启动命令: ${command.name}`,
 						variant: 'default'
 					});
 					return;
@@ -630,7 +818,7 @@ function ProjectPage() {
 				console.error('在终端中执行命令失败:', error);
 				toast({
 					title: '执行失败',
-					description: `在终端中执行命令失败: ${error.message || error}`,
+					description: `在终端中执行命令失败: ${formatError(error)}`,
 					variant: 'destructive'
 				});
 				return;
@@ -651,7 +839,12 @@ function ProjectPage() {
 		};
 
 		updateProjectTerminal(projectName, newTerminal);
-		setRunningCommand({ project, command, id: commandId });
+		setRunningCommand({
+			project,
+			command,
+			id: commandId,
+			kittyTab: useKittenRemote
+		});
 
 		const commandFunction = useKittenRemote
 			? 'execute_command_with_kitten'
@@ -661,10 +854,31 @@ function ProjectPage() {
 			console.log('commandFunction', commandFunction);
 			const result = await runBackendCommand(commandFunction);
 
+			if (result.success && result.reused) {
+				updateProjectTerminal(projectName, {
+					...newTerminal,
+					lastCommandId: result.command_id
+				});
+				setRunningCommand({
+					project,
+					command,
+					id: result.command_id,
+					kittyTab: true
+				});
+				toast({
+					title: '命令仍在运行',
+					description: `已切换到命令 "${command.name}" 的 kitty 标签页`,
+					variant: 'default'
+				});
+				return;
+			}
+
 			if (result.success) {
 				toast({
-					title: '启动成功',
-					description: `已在新kitty终端中启动命令: ${command.name}`,
+					title: result.queued ? '已加入队列' : '启动成功',
+					description: result.queued
+						? `已达到并发上限，${command.name} 将在有空闲时启动`
+						: `已在新kitty终端中启动命令: ${command.name}`,
 					variant: 'default'
 				});
 				return;
@@ -676,7 +890,7 @@ function ProjectPage() {
 			console.error('执行命令失败:', error);
 			toast({
 				title: '执行失败',
-				description: `执行命令失败: ${error.message || error}`,
+				description: `执行命令失败: ${formatError(error)}`,
 				variant: 'destructive'
 			});
 		}
@@ -1,3 +1,5 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import { useAppStore } from '@/store/useAppStore';
 import { Button } from '@/components/ui/button';
 import {
@@ -9,6 +11,7 @@ import {
 } from '@/components/ui/card';
 import { Switch } from '@/components/ui/switch';
 import { Label } from '@/components/ui/label';
+import { Input } from '@/components/ui/input';
 import {
 	Select,
 	SelectContent,
@@ -16,6 +19,59 @@ import {
 	SelectTrigger,
 	SelectValue
 } from '@/components/ui/select';
+import { formatError } from '@/lib/utils';
+
+const INSTALL_POLICY_LABELS = {
+	warn: '仅提示',
+	prompt: '运行前询问',
+	auto: '自动安装'
+};
+
+const TIMEOUT_FIELDS = [
+	{ key: 'gitSecs', label: 'Git 操作', description: '获取分支、切换分支、管理 worktree' },
+	{ key: 'nodeManagerSecs', label: 'Node 版本管理', description: '通过 nvm / fnm 安装或切换版本' },
+	{ key: 'editorSecs', label: '打开编辑器', description: '等待编辑器命令行返回' },
+	{ key: 'commandSecs', label: '项目命令', description: '后台执行的项目脚本与依赖安装' },
+	{ key: 'hookSecs', label: '任务 Hook', description: '任务启动、结束或就绪时执行的 shell 片段' },
+	{ key: 'terminalSecs', label: '外部终端', description: '通过 tmux / WezTerm 命令行打开、关闭标签页' }
+];
+
+const KITTY_GROUPING_LABELS = {
+	perCommand: '每条命令一个窗口',
+	perProject: '每个项目一个窗口',
+	perWorkspace: '每个工作区一个窗口'
+};
+
+const KITTY_LAYOUT_LABELS = {
+	tabs: '标签页',
+	splits: '分屏'
+};
+
+// kitty 的 --config：NONE 不加载配置文件，default 使用自己的 kitty.conf
+const KITTY_CONFIG_FILE_LABELS = {
+	none: '不加载配置文件',
+	default: '使用我的 kitty.conf',
+	custom: '指定配置文件'
+};
+
+const KITTY_START_AS_LABELS = {
+	default: '跟随 kitty',
+	normal: '普通',
+	maximized: '最大化',
+	fullscreen: '全屏',
+	minimized: '最小化'
+};
+
+const kittyConfigFileMode = configFile => {
+	if (configFile === 'NONE') return 'none';
+	// 空字符串表示已选择指定文件但尚未填写路径
+	return configFile == null ? 'default' : 'custom';
+};
+
+const LOCALE_LABELS = {
+	'zh-CN': '简体中文',
+	en: 'English'
+};
 
 function SettingsPage() {
 	const {
@@ -24,9 +80,161 @@ function SettingsPage() {
 		terminalType,
 		setTerminalType,
 		tabs,
-		addTab
+		addTab,
+		workspaces
 	} = useAppStore();
 
+	// 后端消息语言，system 表示跟随系统
+	const [localeInfo, setLocaleInfo] = useState(null);
+
+	useEffect(() => {
+		invoke('get_locale')
+			.then(setLocaleInfo)
+			.catch(error => console.error('获取语言设置失败:', formatError(error)));
+	}, []);
+
+	const handleLocaleChange = value => {
+		invoke('set_locale', { locale: value === 'system' ? null : value })
+			.then(setLocaleInfo)
+			.catch(error => console.error('保存语言设置失败:', formatError(error)));
+	};
+
+	// 各工作区的依赖安装策略，未配置的工作区按“仅提示”处理
+	const [installPolicies, setInstallPolicies] = useState({});
+
+	useEffect(() => {
+		invoke('get_install_policies')
+			.then(setInstallPolicies)
+			.catch(error => console.error('获取依赖安装策略失败:', formatError(error)));
+	}, []);
+
+	const handleInstallPolicyChange = (workspacePath, policy) => {
+		invoke('set_install_policy', {
+			workspacePath,
+			policy: policy === 'warn' ? null : policy
+		})
+			.then(() =>
+				setInstallPolicies(prev => {
+					const next = { ...prev };
+					if (policy === 'warn') {
+						delete next[workspacePath];
+					} else {
+						next[workspacePath] = policy;
+					}
+					return next;
+				})
+			)
+			.catch(error => console.error('保存依赖安装策略失败:', formatError(error)));
+	};
+
+	// 外部命令的超时秒数，0 表示不限时
+	const [timeoutSettings, setTimeoutSettings] = useState(null);
+
+	useEffect(() => {
+		invoke('get_timeout_settings')
+			.then(setTimeoutSettings)
+			.catch(error => console.error('获取超时设置失败:', formatError(error)));
+	}, []);
+
+	const handleTimeoutChange = (key, value) => {
+		const seconds = Math.max(0, Number.parseInt(value, 10) || 0);
+		invoke('set_timeout_settings', {
+			settings: { ...timeoutSettings, [key]: seconds }
+		})
+			.then(setTimeoutSettings)
+			.catch(error => console.error('保存超时设置失败:', formatError(error)));
+	};
+
+	// 后台任务并发上限，0 表示不限制；常驻脚本（如 dev）不受限制
+	const [queueSettings, setQueueSettings] = useState(null);
+
+	useEffect(() => {
+		invoke('get_queue_settings')
+			.then(setQueueSettings)
+			.catch(error => console.error('获取并发设置失败:', formatError(error)));
+	}, []);
+
+	const saveQueueSettings = settings => {
+		invoke('set_queue_settings', { settings })
+			.then(setQueueSettings)
+			.catch(error => console.error('保存并发设置失败:', formatError(error)));
+	};
+
+	const handleMaxConcurrentChange = value => {
+		saveQueueSettings({
+			...queueSettings,
+			maxConcurrent: Math.max(0, Number.parseInt(value, 10) || 0)
+		});
+	};
+
+	const handleWorkspaceLimitChange = (workspacePath, value) => {
+		const limit = Math.max(0, Number.parseInt(value, 10) || 0);
+		const workspaceLimits = { ...queueSettings.workspaceLimits };
+		if (limit === 0) {
+			delete workspaceLimits[workspacePath];
+		} else {
+			workspaceLimits[workspacePath] = limit;
+		}
+		saveQueueSettings({ ...queueSettings, workspaceLimits });
+	};
+
+	// kitty 远程控制时命令所在的窗口分组与排布方式
+	const [kittyGrouping, setKittyGrouping] = useState(null);
+
+	useEffect(() => {
+		invoke('get_kitty_grouping_settings')
+			.then(setKittyGrouping)
+			.catch(error => console.error('获取 kitty 分组设置失败:', formatError(error)));
+	}, []);
+
+	const saveKittyGrouping = settings => {
+		invoke('set_kitty_grouping_settings', { settings })
+			.then(setKittyGrouping)
+			.catch(error => console.error('保存 kitty 分组设置失败:', formatError(error)));
+	};
+
+	const handleProjectLayoutChange = (projectPath, layout) => {
+		const projectLayouts = { ...kittyGrouping.projectLayouts };
+		if (layout === 'default') {
+			delete projectLayouts[projectPath];
+		} else {
+			projectLayouts[projectPath] = layout;
+		}
+		saveKittyGrouping({ ...kittyGrouping, projectLayouts });
+	};
+
+	// 启动 kitty 实例时的配置文件、override、窗口尺寸与重试
+	const [kittyConfig, setKittyConfig] = useState(null);
+
+	useEffect(() => {
+		invoke('get_kitty_config')
+			.then(setKittyConfig)
+			.catch(error => console.error('获取 kitty 配置失败:', formatError(error)));
+	}, []);
+
+	const saveKittyConfig = config => {
+		invoke('set_kitty_config', { config })
+			.then(setKittyConfig)
+			.catch(error => console.error('保存 kitty 配置失败:', formatError(error)));
+	};
+
+	const handleKittyConfigFileModeChange = mode => {
+		const configFile = { none: 'NONE', default: null, custom: '' }[mode];
+		// 切换到“指定配置文件”时等填写路径后再保存
+		if (mode === 'custom') {
+			setKittyConfig({ ...kittyConfig, configFile });
+			return;
+		}
+		saveKittyConfig({ ...kittyConfig, configFile });
+	};
+
+	const handleKittyWindowChange = (key, value) => {
+		saveKittyConfig({
+			...kittyConfig,
+			window: { ...kittyConfig.window, [key]: value || null }
+		});
+	};
+
 	const handleAddSettingsTab = () => {
 		if (!tabs.includes('settings')) {
 			addTab('settings');
@@ -69,6 +277,10 @@ function SettingsPage() {
 									<SelectItem value='kitty'>
 										Kitty 终端
 									</SelectItem>
+									<SelectItem value='tmux'>tmux</SelectItem>
+									<SelectItem value='wezterm'>WezTerm</SelectItem>
+									<SelectItem value='alacritty'>Alacritty</SelectItem>
+									<SelectItem value='auto'>自动选择</SelectItem>
 								</SelectContent>
 							</Select>
 						</div>
@@ -89,6 +301,460 @@ function SettingsPage() {
 								/>
 							</div>
 						)}
+
+						{terminalType === 'kitty' && useKittenRemote && kittyGrouping && (
+							<div className='space-y-4 pl-4 border-l-2 border-gray-200'>
+								<div className='flex items-center justify-between'>
+									<div className='space-y-0.5 flex-1'>
+										<Label className='text-base'>窗口分组</Label>
+										<p className='text-sm text-gray-500'>
+											共用窗口时，再次执行仍在运行的命令会切换到已有标签页
+										</p>
+									</div>
+									<Select
+										value={kittyGrouping.grouping}
+										onValueChange={grouping =>
+											saveKittyGrouping({ ...kittyGrouping, grouping })
+										}>
+										<SelectTrigger className='w-44'>
+											<SelectValue />
+										</SelectTrigger>
+										<SelectContent>
+											{Object.entries(KITTY_GROUPING_LABELS).map(
+												([value, label]) => (
+													<SelectItem key={value} value={value}>
+														{label}
+													</SelectItem>
+												)
+											)}
+										</SelectContent>
+									</Select>
+								</div>
+
+								{kittyGrouping.grouping !== 'perCommand' && (
+									<div className='flex items-center justify-between'>
+										<div className='space-y-0.5 flex-1'>
+											<Label className='text-base'>默认排布</Label>
+											<p className='text-sm text-gray-500'>
+												分屏模式下同一项目的命令放在同一个标签页中
+											</p>
+										</div>
+										<Select
+											value={kittyGrouping.layout}
+											onValueChange={layout =>
+												saveKittyGrouping({ ...kittyGrouping, layout })
+											}>
+											<SelectTrigger className='w-44'>
+												<SelectValue />
+											</SelectTrigger>
+											<SelectContent>
+												{Object.entries(KITTY_LAYOUT_LABELS).map(
+													([value, label]) => (
+														<SelectItem key={value} value={value}>
+															{label}
+														</SelectItem>
+													)
+												)}
+											</SelectContent>
+										</Select>
+									</div>
+								)}
+
+								{kittyGrouping.grouping !== 'perCommand' &&
+									workspaces.flatMap(workspace =>
+										(workspace.projects || []).map(project => (
+											<div
+												key={project.path}
+												className='flex items-center justify-between'>
+												<div className='space-y-0.5 flex-1 min-w-0'>
+													<Label className='text-sm'>{project.name}</Label>
+													<p className='text-xs text-gray-500 truncate'>
+														{project.path}
+													</p>
+												</div>
+												<Select
+													value={
+														kittyGrouping.projectLayouts[project.path] ||
+														'default'
+													}
+													onValueChange={layout =>
+														handleProjectLayoutChange(project.path, layout)
+													}>
+													<SelectTrigger className='w-44'>
+														<SelectValue />
+													</SelectTrigger>
+													<SelectContent>
+														<SelectItem value='default'>跟随默认</SelectItem>
+														{Object.entries(KITTY_LAYOUT_LABELS).map(
+															([value, label]) => (
+																<SelectItem key={value} value={value}>
+																	{label}
+																</SelectItem>
+															)
+														)}
+													</SelectContent>
+												</Select>
+											</div>
+										))
+									)}
+							</div>
+						)}
+
+						{terminalType === 'kitty' && kittyConfig && (
+							<div className='space-y-4 pl-4 border-l-2 border-gray-200'>
+								<div className='flex items-center justify-between'>
+									<div className='space-y-0.5 flex-1'>
+										<Label className='text-base'>kitty 配置文件</Label>
+										<p className='text-sm text-gray-500'>
+											使用自己的配置可以保留主题与字体，修改后对新启动的 kitty 生效
+										</p>
+									</div>
+									<Select
+										value={kittyConfigFileMode(kittyConfig.configFile)}
+										onValueChange={handleKittyConfigFileModeChange}>
+										<SelectTrigger className='w-44'>
+											<SelectValue />
+										</SelectTrigger>
+										<SelectContent>
+											{Object.entries(KITTY_CONFIG_FILE_LABELS).map(
+												([value, label]) => (
+													<SelectItem key={value} value={value}>
+														{label}
+													</SelectItem>
+												)
+											)}
+										</SelectContent>
+									</Select>
+								</div>
+
+								{kittyConfigFileMode(kittyConfig.configFile) === 'custom' && (
+									<Input
+										placeholder='~/.config/kitty/runproject.conf'
+										defaultValue={kittyConfig.configFile || ''}
+										onBlur={event =>
+											event.target.value.trim() &&
+											saveKittyConfig({
+												...kittyConfig,
+												configFile: event.target.value
+											})
+										}
+									/>
+								)}
+
+								<div className='space-y-2'>
+									<Label className='text-base'>额外的 override</Label>
+									<p className='text-sm text-gray-500'>
+										每行一项 key=value，如 font_size=13
+									</p>
+									<textarea
+										className='flex min-h-[72px] w-full rounded-md border border-input bg-transparent px-3 py-2 text-sm font-mono shadow-sm'
+										defaultValue={kittyConfig.overrides.join('\n')}
+										onBlur={event =>
+											saveKittyConfig({
+												...kittyConfig,
+												overrides: event.target.value.split('\n')
+											})
+										}
+									/>
+								</div>
+
+								<div className='flex items-center justify-between'>
+									<div className='space-y-0.5 flex-1'>
+										<Label className='text-base'>窗口尺寸</Label>
+										<p className='text-sm text-gray-500'>
+											像素或字符数（如 120c），留空使用 kitty 的设置
+										</p>
+									</div>
+									<div className='flex items-center gap-2'>
+										<Input
+											className='w-24'
+											placeholder='宽'
+											defaultValue={kittyConfig.window.width || ''}
+											onBlur={event =>
+												handleKittyWindowChange('width', event.target.value)
+											}
+										/>
+										<span className='text-sm text-gray-500'>×</span>
+										<Input
+											className='w-24'
+											placeholder='高'
+											defaultValue={kittyConfig.window.height || ''}
+											onBlur={event =>
+												handleKittyWindowChange('height', event.target.value)
+											}
+										/>
+									</div>
+								</div>
+
+								<div className='flex items-center justify-between'>
+									<div className='space-y-0.5 flex-1'>
+										<Label className='text-base'>窗口状态</Label>
+									</div>
+									<Select
+										value={kittyConfig.window.startAs || 'default'}
+										onValueChange={value =>
+											handleKittyWindowChange(
+												'startAs',
+												value === 'default' ? null : value
+											)
+										}>
+										<SelectTrigger className='w-44'>
+											<SelectValue />
+										</SelectTrigger>
+										<SelectContent>
+											{Object.entries(KITTY_START_AS_LABELS).map(
+												([value, label]) => (
+													<SelectItem key={value} value={value}>
+														{label}
+													</SelectItem>
+												)
+											)}
+										</SelectContent>
+									</Select>
+								</div>
+
+								<div className='flex items-center justify-between'>
+									<div className='space-y-0.5 flex-1'>
+										<Label className='text-base'>启动等待</Label>
+										<p className='text-sm text-gray-500'>
+											等待 kitty socket 就绪的重试次数与间隔
+										</p>
+									</div>
+									<div className='flex items-center gap-2'>
+										<Input
+											type='number'
+											min={1}
+											className='w-20'
+											defaultValue={kittyConfig.maxRetries}
+											onBlur={event =>
+												saveKittyConfig({
+													...kittyConfig,
+													maxRetries: Math.max(
+														1,
+														Number.parseInt(event.target.value, 10) || 1
+													)
+												})
+											}
+										/>
+										<span className='text-sm text-gray-500'>次 ×</span>
+										<Input
+											type='number'
+											min={50}
+											className='w-24'
+											defaultValue={kittyConfig.retryDelayMs}
+											onBlur={event =>
+												saveKittyConfig({
+													...kittyConfig,
+													retryDelayMs: Math.max(
+														50,
+														Number.parseInt(event.target.value, 10) || 50
+													)
+												})
+											}
+										/>
+										<span className='text-sm text-gray-500'>毫秒</span>
+									</div>
+								</div>
+
+								<div className='flex items-center justify-between'>
+									<div className='space-y-0.5 flex-1'>
+										<Label className='text-base'>socket 目录</Label>
+										<p className='text-sm text-gray-500'>
+											留空时 Linux 使用抽象 socket，macOS 使用 /tmp
+										</p>
+									</div>
+									<Input
+										className='w-64'
+										placeholder='/run/user/1000'
+										defaultValue={kittyConfig.socketDir || ''}
+										onBlur={event =>
+											saveKittyConfig({
+												...kittyConfig,
+												socketDir: event.target.value || null
+											})
+										}
+									/>
+								</div>
+							</div>
+						)}
+					</CardContent>
+				</Card>
+
+				<Card>
+					<CardHeader>
+						<CardTitle>依赖安装</CardTitle>
+						<CardDescription>
+							运行脚本前发现依赖缺失或 lockfile 有更新时的处理方式
+						</CardDescription>
+					</CardHeader>
+					<CardContent className='space-y-4'>
+						{workspaces.length === 0 && (
+							<p className='text-sm text-gray-500'>尚未添加工作区</p>
+						)}
+						{workspaces.map(workspace => (
+							<div
+								key={workspace.path}
+								className='flex items-center justify-between'>
+								<div className='space-y-0.5 flex-1 min-w-0'>
+									<Label className='text-base'>{workspace.name}</Label>
+									<p className='text-sm text-gray-500 truncate'>
+										{workspace.path}
+									</p>
+								</div>
+								<Select
+									value={installPolicies[workspace.path] ?? 'warn'}
+									onValueChange={policy =>
+										handleInstallPolicyChange(workspace.path, policy)
+									}>
+									<SelectTrigger className='w-40'>
+										<SelectValue />
+									</SelectTrigger>
+									<SelectContent>
+										{Object.entries(INSTALL_POLICY_LABELS).map(
+											([value, label]) => (
+												<SelectItem key={value} value={value}>
+													{label}
+												</SelectItem>
+											)
+										)}
+									</SelectContent>
+								</Select>
+							</div>
+						))}
+					</CardContent>
+				</Card>
+
+				<Card>
+					<CardHeader>
+						<CardTitle>任务并发</CardTitle>
+						<CardDescription>
+							后台运行的构建、测试等任务超过上限时排队执行，0 表示不限制
+						</CardDescription>
+					</CardHeader>
+					<CardContent className='space-y-4'>
+						<div className='flex items-center justify-between'>
+							<div className='space-y-0.5 flex-1'>
+								<Label className='text-base'>全局上限</Label>
+								<p className='text-sm text-gray-500'>
+									{queueSettings
+										? `${queueSettings.longRunningScripts.join('、')} 等常驻脚本不受限制`
+										: '常驻脚本不受限制'}
+								</p>
+							</div>
+							<Input
+								type='number'
+								min={0}
+								className='w-24'
+								disabled={!queueSettings}
+								defaultValue={queueSettings?.maxConcurrent}
+								key={queueSettings ? 'loaded' : 'loading'}
+								onBlur={event => handleMaxConcurrentChange(event.target.value)}
+							/>
+						</div>
+						{workspaces.map(workspace => (
+							<div
+								key={workspace.path}
+								className='flex items-center justify-between'>
+								<div className='space-y-0.5 flex-1 min-w-0'>
+									<Label className='text-base'>{workspace.name}</Label>
+									<p className='text-sm text-gray-500 truncate'>
+										{workspace.path}
+									</p>
+								</div>
+								<Input
+									type='number'
+									min={0}
+									className='w-24'
+									disabled={!queueSettings}
+									defaultValue={
+										queueSettings?.workspaceLimits[workspace.path] ?? 0
+									}
+									key={queueSettings ? 'loaded' : 'loading'}
+									onBlur={event =>
+										handleWorkspaceLimitChange(
+											workspace.path,
+											event.target.value
+										)
+									}
+								/>
+							</div>
+						))}
+					</CardContent>
+				</Card>
+
+				<Card>
+					<CardHeader>
+						<CardTitle>超时</CardTitle>
+						<CardDescription>
+							外部命令超过时限后会被终止，0 表示不限时
+						</CardDescription>
+					</CardHeader>
+					<CardContent className='space-y-4'>
+						{TIMEOUT_FIELDS.map(field => (
+							<div
+								key={field.key}
+								className='flex items-center justify-between'>
+								<div className='space-y-0.5 flex-1'>
+									<Label className='text-base'>{field.label}</Label>
+									<p className='text-sm text-gray-500'>
+										{field.description}
+									</p>
+								</div>
+								<div className='flex items-center gap-2'>
+									<Input
+										type='number'
+										min={0}
+										className='w-24'
+										disabled={!timeoutSettings}
+										defaultValue={timeoutSettings?.[field.key]}
+										key={timeoutSettings ? 'loaded' : 'loading'}
+										onBlur={event =>
+											handleTimeoutChange(field.key, event.target.value)
+										}
+									/>
+									<span className='text-sm text-gray-500'>秒</span>
+								</div>
+							</div>
+						))}
+					</CardContent>
+				</Card>
+
+				<Card>
+					<CardHeader>
+						<CardTitle>语言</CardTitle>
+						<CardDescription>
+							后端返回的提示与错误信息所使用的语言
+						</CardDescription>
+					</CardHeader>
+					<CardContent>
+						<div className='flex items-center justify-between'>
+							<div className='space-y-0.5 flex-1'>
+								<Label className='text-base'>消息语言</Label>
+								<p className='text-sm text-gray-500'>
+									跟随系统时使用{' '}
+									{localeInfo
+										? LOCALE_LABELS[localeInfo.system]
+										: '系统语言'}
+								</p>
+							</div>
+							<Select
+								value={localeInfo?.preference ?? 'system'}
+								onValueChange={handleLocaleChange}
+								disabled={!localeInfo}>
+								<SelectTrigger className='w-40'>
+									<SelectValue />
+								</SelectTrigger>
+								<SelectContent>
+									<SelectItem value='system'>跟随系统</SelectItem>
+									<SelectItem value='zh-CN'>
+										{LOCALE_LABELS['zh-CN']}
+									</SelectItem>
+									<SelectItem value='en'>
+										{LOCALE_LABELS.en}
+									</SelectItem>
+								</SelectContent>
+							</Select>
+						</div>
 					</CardContent>
 				</Card>
 
@@ -29,7 +29,7 @@ export const useAppStore = create(
 			gitBranchesCache: {},
 			collapsedWorkspaces: {},
 			useKittenRemote: true,
-			terminalType: 'builtin', // 'builtin' | 'kitty'
+			terminalType: 'builtin', // 'builtin' | 'kitty' | 'tmux' | 'wezterm' | 'alacritty' | 'auto'
 
 			// === 收藏夹状态 ===
 			bookmarks: [], // { id, url, title, favicon, createdAt, lastVisited }